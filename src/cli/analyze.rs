@@ -1,14 +1,19 @@
 //! # analyze 子命令 CLI 定义
 //!
-//! 分析功能统一入口，包含 DFT 状态扫描、DFT 后处理与 XRD 计算。
+//! 分析功能统一入口，包含 DFT 状态扫描、DFT 扫描缓存管理、DFT 后处理、收敛诊断、状态方程拟合、相变分析、资源用量统计与 XRD 计算；
+//! 共享的作业目录布局参数（`--max-depth`、`--name-template`）也定义于此。
 //!
 //! ## 依赖关系
 //! - 被 `cli/mod.rs` 使用
 //! - 参数传递给 `commands/analyze/` 相应模块
+//! - 复用 `cli/submit.rs` 的调度器类型与 `--scheduler-cmd` 解析
 
+use super::submit::{parse_scheduler_cmd, SchedulerType};
 use clap::{Args, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+use crate::dft::{CodeSelection, EosForm, JobLayout, NameTemplate, DEFAULT_NAME_TEMPLATE};
+use crate::error::QutilityError;
 use crate::models::DftCodeType;
 
 // ─────────────────────────────────────────────────────────────
@@ -33,6 +38,22 @@ pub enum AnalyzeCommands {
     #[command(name = "dft-postprocessing", visible_alias = "dft-pp")]
     DftPostprocessing(DftPostprocessingArgs),
 
+    /// Show or clear the DFT scan cache of a job root
+    #[command(name = "dft-cache")]
+    DftCache(DftCacheArgs),
+
+    /// Per-ionic-step convergence diagnostics and trend plots for a job (or all incomplete jobs)
+    Convergence(ConvergenceArgs),
+
+    /// Fit equations of state to E(V) data from a set of DFT jobs
+    Eos(EosArgs),
+
+    /// Locate pressure-induced phase transitions from H(P) across pressure subtrees
+    Transitions(TransitionsArgs),
+
+    /// Wall time and core-hour accounting per job, by status, code and structure size
+    Usage(UsageArgs),
+
     /// Calculate X-ray diffraction pattern from structure
     Xrd(XrdArgs),
 }
@@ -68,6 +89,66 @@ impl From<DftCode> for DftCodeType {
     }
 }
 
+/// 扫描类命令的代码选择，`auto` 按目录识别
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ScanCode {
+    /// VASP
+    Vasp,
+    /// CASTEP
+    Castep,
+    /// Detect per directory (INCAR/OUTCAR/OSZICAR vs *.param/*.castep)
+    Auto,
+}
+
+impl std::fmt::Display for ScanCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanCode::Vasp => write!(f, "vasp"),
+            ScanCode::Castep => write!(f, "castep"),
+            ScanCode::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl From<ScanCode> for CodeSelection {
+    fn from(code: ScanCode) -> Self {
+        match code {
+            ScanCode::Vasp => CodeSelection::Fixed(DftCodeType::Vasp),
+            ScanCode::Castep => CodeSelection::Fixed(DftCodeType::Castep),
+            ScanCode::Auto => CodeSelection::Auto,
+        }
+    }
+}
+
+/// 作业目录布局参数（递归深度与结构名模板）
+#[derive(Args, Debug, Clone)]
+pub struct JobLayoutArgs {
+    /// Search this many directory levels below the root for jobs (INCAR/OUTCAR or *.param/*.castep)
+    #[arg(long, default_value_t = 1)]
+    pub max_depth: usize,
+
+    /// Structure name template: {path}, {dir}, {parent}, {seed} or a path component index like {0}
+    #[arg(long, default_value = DEFAULT_NAME_TEMPLATE)]
+    pub name_template: String,
+}
+
+impl TryFrom<&JobLayoutArgs> for JobLayout {
+    type Error = QutilityError;
+
+    fn try_from(args: &JobLayoutArgs) -> Result<Self, Self::Error> {
+        if args.max_depth == 0 {
+            return Err(QutilityError::InvalidArgument(
+                "--max-depth must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(JobLayout {
+            max_depth: args.max_depth,
+            name_template: NameTemplate::parse(&args.name_template)?,
+        })
+    }
+}
+
 /// 重算名单输出格式
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
 pub enum RetryListFormat {
@@ -93,9 +174,20 @@ pub struct DftStatusArgs {
     #[arg(long)]
     pub job_dir: PathBuf,
 
-    /// Specify the DFT code used
+    /// Specify the DFT code used (`auto` detects it per job directory)
     #[arg(long, value_enum)]
-    pub code: DftCode,
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning and parsing (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
 
     /// Export retry list to file
     #[arg(long)]
@@ -108,18 +200,146 @@ pub struct DftStatusArgs {
     /// Only export explicitly failed jobs
     #[arg(long, default_value_t = false)]
     pub failed_only: bool,
+
+    /// Extra failure-pattern rules file (TOML); takes priority over user and project rules
+    #[arg(long)]
+    pub rules: Option<PathBuf>,
+
+    /// Query the scheduler queue and history to tell running and pending jobs from dead ones
+    #[arg(long, default_value_t = false)]
+    pub queue: bool,
+
+    /// Batch scheduler queried with --queue and used by --resubmit
+    #[arg(long, value_enum, default_value = "slurm")]
+    pub scheduler: SchedulerType,
+
+    /// Run another program in place of a scheduler command, e.g. 'squeue=./stub.sh' or 'qstat=/opt/pbs/bin/qstat' (repeatable)
+    #[arg(long, value_name = "NAME=PATH", value_parser = parse_scheduler_cmd)]
+    pub scheduler_cmd: Vec<(String, String)>,
+
+    /// Start time passed to `sacct --starttime` (Slurm only)
+    #[arg(long, default_value = "now-7days")]
+    pub sacct_since: String,
+
+    /// Only query jobs of this user (defaults to $USER)
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Apply automatic corrections to retry candidates (back up to attempt_N/, rewrite inputs);
+    /// requires --queue so that jobs still queued or running are never touched
+    #[arg(long, default_value_t = false, requires = "queue")]
+    pub fix: bool,
+
+    /// With --fix, only print the planned corrections without touching any files
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Stop correcting a job after this many recorded attempts
+    #[arg(long, default_value_t = 3)]
+    pub max_attempts: usize,
+
+    /// With --fix, resubmit corrected jobs to the scheduler
+    #[arg(long, default_value_t = false)]
+    pub resubmit: bool,
+}
+
+/// DFT 扫描缓存子命令参数
+#[derive(Args, Debug)]
+pub struct DftCacheArgs {
+    /// Path to the root directory containing DFT calculation folders
+    #[arg(long)]
+    pub job_dir: PathBuf,
+
+    /// Delete the cache so the next scan re-parses every job
+    #[arg(long, default_value_t = false)]
+    pub clear: bool,
+}
+
+/// 资源用量统计子命令参数
+#[derive(Args, Debug)]
+pub struct UsageArgs {
+    /// Path to the root directory containing DFT calculation folders
+    #[arg(long)]
+    pub job_dir: PathBuf,
+
+    /// Specify the DFT code used (`auto` detects it per job directory)
+    #[arg(long, value_enum)]
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning and parsing (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Extra failure-pattern rules file (TOML); takes priority over user and project rules
+    #[arg(long)]
+    pub rules: Option<PathBuf>,
+
+    /// Export per-job usage (elapsed, CPU and core-hours) to this CSV file
+    #[arg(long)]
+    pub output_csv: Option<PathBuf>,
+}
+
+/// DFT 后处理排序依据
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum RankMode {
+    /// Total enthalpy of the cell
+    Total,
+    /// Enthalpy per atom
+    PerAtom,
+    /// Enthalpy per reduced formula unit
+    PerFormulaUnit,
+}
+
+impl std::fmt::Display for RankMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RankMode::Total => write!(f, "total"),
+            RankMode::PerAtom => write!(f, "per-atom"),
+            RankMode::PerFormulaUnit => write!(f, "per-formula-unit"),
+        }
+    }
 }
 
 /// DFT 后处理子命令参数
 #[derive(Args, Debug)]
 pub struct DftPostprocessingArgs {
-    /// Path to the root directory containing DFT calculation folders
+    /// Path to the root directory containing DFT calculation folders, or an archive directory (manifest.json) written by `archive create`
     #[arg(long)]
     pub job_dir: PathBuf,
 
-    /// Specify the DFT code used
+    /// Specify the DFT code used (`auto` detects it per job directory)
     #[arg(long, value_enum)]
-    pub code: DftCode,
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning and parsing (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Quantity used to rank structures
+    #[arg(long, value_enum, default_value = "per-atom")]
+    pub rank_by: RankMode,
+
+    /// Rank each composition separately, one table per reduced formula
+    #[arg(long, default_value_t = false)]
+    pub group_by_composition: bool,
+
+    /// Rank geometry optimisations that did not converge, marking them in the output
+    #[arg(long, default_value_t = false)]
+    pub include_unconverged: bool,
 
     /// Range of top structures to plot (e.g., '1-10')
     #[arg(long)]
@@ -133,10 +353,186 @@ pub struct DftPostprocessingArgs {
     #[arg(long, default_value = "dft_ranked_results.csv")]
     pub output_csv: PathBuf,
 
-    /// Filename for the comparison plot (PNG format)
+    /// Filename for the comparison plot (PNG or SVG, by extension)
     #[arg(long, default_value = "eddp_vs_dft_comparison.png")]
     pub output_plot: PathBuf,
 
+    /// Original EDDP/AIRSS ranking as a .res file, concatenated .res file or directory of .res files
+    #[arg(long)]
+    pub eddp_res: Option<PathBuf>,
+
+    /// Filename for the EDDP vs DFT parity CSV (requires --eddp-res)
+    #[arg(long, default_value = "eddp_vs_dft_parity.csv")]
+    pub parity_csv: PathBuf,
+
+    /// Filename for the EDDP vs DFT per-atom enthalpy parity plot (PNG or SVG)
+    #[arg(long, default_value = "eddp_vs_dft_parity.png")]
+    pub parity_plot: PathBuf,
+
+    /// Filename for the EDDP rank vs DFT rank plot (PNG or SVG)
+    #[arg(long, default_value = "eddp_vs_dft_ranks.png")]
+    pub rank_plot: PathBuf,
+
+    /// Skip plot generation
+    #[arg(long, default_value_t = false)]
+    pub no_plot: bool,
+}
+
+// ─────────────────────────────────────────────────────────────
+// 状态方程拟合子命令
+// ─────────────────────────────────────────────────────────────
+
+/// 状态方程形式
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum EosModel {
+    /// Third-order Birch–Murnaghan
+    BirchMurnaghan,
+    /// Vinet (Rose–Vinet)
+    Vinet,
+    /// Murnaghan
+    Murnaghan,
+}
+
+impl From<EosModel> for EosForm {
+    fn from(model: EosModel) -> Self {
+        match model {
+            EosModel::BirchMurnaghan => EosForm::BirchMurnaghan,
+            EosModel::Vinet => EosForm::Vinet,
+            EosModel::Murnaghan => EosForm::Murnaghan,
+        }
+    }
+}
+
+/// 状态方程拟合子命令参数
+#[derive(Args, Debug)]
+pub struct EosArgs {
+    /// Path to the root directory containing the E(V) job folders
+    #[arg(long)]
+    pub job_dir: PathBuf,
+
+    /// Specify the DFT code used
+    #[arg(long, value_enum)]
+    pub code: DftCode,
+
+    /// EOS forms to fit (comma-separated, default: all)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "birch-murnaghan,vinet,murnaghan"
+    )]
+    pub eos: Vec<EosModel>,
+
+    /// Pressures (GPa, comma-separated) at which to report H(P) from the fit
+    #[arg(long, value_delimiter = ',')]
+    pub pressures: Vec<f64>,
+
+    /// Number of volume samples along the fitted curve
+    #[arg(long, default_value_t = 200)]
+    pub points: usize,
+
+    /// Filename for the fitted curve CSV (V, E, P, H per EOS form)
+    #[arg(long, default_value = "eos_fit.csv")]
+    pub output_csv: PathBuf,
+
+    /// Filename for the E(V) plot (PNG or SVG, by extension)
+    #[arg(long, default_value = "eos_fit.png")]
+    pub output_plot: PathBuf,
+
+    /// Skip plot generation
+    #[arg(long, default_value_t = false)]
+    pub no_plot: bool,
+}
+
+// ─────────────────────────────────────────────────────────────
+// 收敛诊断子命令
+// ─────────────────────────────────────────────────────────────
+
+/// 收敛诊断子命令参数
+#[derive(Args, Debug)]
+pub struct ConvergenceArgs {
+    /// Job directory (or, with --batch, the root containing job folders)
+    pub job_dir: PathBuf,
+
+    /// DFT code (`auto` or omitted detects it from OUTCAR/OSZICAR or *.castep, per job directory with --batch)
+    #[arg(long, value_enum)]
+    pub code: Option<ScanCode>,
+
+    /// Rank all unfinished (incomplete, unconverged or timed-out) jobs under job_dir by how badly they are stuck
+    #[arg(long, default_value_t = false)]
+    pub batch: bool,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning in batch mode (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Number of jobs listed in batch mode
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Flag force stagnation after this many steps without a new force minimum
+    #[arg(long, default_value_t = 5)]
+    pub stagnation_steps: usize,
+
+    /// Flag SCF trouble when this many trailing steps hit NELM / max_scf_cycles
+    #[arg(long, default_value_t = 3)]
+    pub scf_limit_steps: usize,
+
+    /// Number of recent energy changes checked for oscillation
+    #[arg(long, default_value_t = 6)]
+    pub oscillation_window: usize,
+
+    /// Optional CSV with per-step data (or the ranking in batch mode)
+    #[arg(long)]
+    pub output_csv: Option<PathBuf>,
+
+    /// Filename for the trend plot (PNG or SVG, by extension)
+    #[arg(long, default_value = "convergence.png")]
+    pub output_plot: PathBuf,
+
+    /// Skip plot generation
+    #[arg(long, default_value_t = false)]
+    pub no_plot: bool,
+}
+
+// ─────────────────────────────────────────────────────────────
+// 相变分析子命令
+// ─────────────────────────────────────────────────────────────
+
+/// 焓–压力相变分析子命令参数
+#[derive(Args, Debug)]
+pub struct TransitionsArgs {
+    /// Pressure subtrees to scan (e.g., --job-dir jobs/P050 jobs/P100)
+    #[arg(long = "job-dir", required = true, num_args = 1..)]
+    pub job_dirs: Vec<PathBuf>,
+
+    /// Specify the DFT code used
+    #[arg(long, value_enum)]
+    pub code: DftCode,
+
+    /// Reference phase for ΔH (default: most stable phase at the lowest pressure)
+    #[arg(long)]
+    pub reference: Option<String>,
+
+    /// Regex mapping structure names to phases (first capture group, or the whole match)
+    #[arg(long)]
+    pub phase_regex: Option<String>,
+
+    /// Filename for the ΔH(P) CSV output
+    #[arg(long, default_value = "enthalpy_pressure.csv")]
+    pub output_csv: PathBuf,
+
+    /// Filename for the ΔH(P) plot (PNG or SVG, by extension)
+    #[arg(long, default_value = "enthalpy_pressure.png")]
+    pub output_plot: PathBuf,
+
     /// Skip plot generation
     #[arg(long, default_value_t = false)]
     pub no_plot: bool,
@@ -0,0 +1,83 @@
+//! # archive 子命令 CLI 定义
+//!
+//! 将已结束的 DFT 作业目录打包为压缩归档并写出清单，或从归档中恢复选中的作业
+//!
+//! ## 依赖关系
+//! - 被 `cli/mod.rs` 使用
+//! - 复用 `cli/analyze.rs` 的代码类型与作业布局参数、`cli/clean.rs` 的作业状态类别
+//! - 参数传递给 `commands/archive.rs`
+
+use super::analyze::{JobLayoutArgs, ScanCode};
+use super::clean::CleanStatus;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+/// archive 主命令参数
+#[derive(Args, Debug)]
+pub struct ArchiveArgs {
+    #[command(subcommand)]
+    pub command: ArchiveCommands,
+}
+
+/// archive 子命令
+#[derive(Subcommand, Debug)]
+pub enum ArchiveCommands {
+    /// Pack finished job directories into .tar.gz archives with a JSON/CSV manifest
+    Create(ArchiveCreateArgs),
+
+    /// Restore selected jobs from an archive directory and verify their checksums
+    Extract(ArchiveExtractArgs),
+}
+
+/// archive create 参数
+#[derive(Args, Debug)]
+pub struct ArchiveCreateArgs {
+    /// Path to the root directory containing DFT calculation folders
+    pub job_dir: PathBuf,
+
+    /// Specify the DFT code used (`auto` detects it per job directory)
+    #[arg(long, value_enum)]
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning and compression (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Directory that receives the archives and manifest.json / manifest.csv
+    #[arg(short, long, default_value = "archive")]
+    pub output: PathBuf,
+
+    /// Job statuses to archive (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "completed")]
+    pub status: Vec<CleanStatus>,
+
+    /// Pack jobs into shards of at most this many GiB (uncompressed) instead of one archive per job
+    #[arg(long)]
+    pub shard_size: Option<f64>,
+}
+
+/// archive extract 参数
+#[derive(Args, Debug)]
+pub struct ArchiveExtractArgs {
+    /// Archive directory containing manifest.json
+    pub archive_dir: PathBuf,
+
+    /// Structure names or job paths to restore (comma-separated, glob patterns allowed; default: all)
+    #[arg(long, value_delimiter = ',')]
+    pub only: Vec<String>,
+
+    /// Directory the jobs are restored into (each job keeps its relative path)
+    #[arg(short, long, default_value = ".")]
+    pub dest: PathBuf,
+
+    /// Overwrite jobs that already exist in the destination
+    #[arg(long, default_value_t = false)]
+    pub overwrite: bool,
+}
@@ -0,0 +1,93 @@
+//! # clean 子命令 CLI 定义
+//!
+//! 清理已结束 DFT 作业中的大体积中间文件（WAVECAR、CHGCAR、.check 等）
+//!
+//! ## 依赖关系
+//! - 被 `cli/mod.rs` 使用
+//! - 复用 `cli/analyze.rs` 的代码类型与作业布局参数、`cli/submit.rs` 的调度器类型与 `--scheduler-cmd` 解析
+//! - 参数传递给 `commands/clean.rs`
+
+use super::analyze::{JobLayoutArgs, ScanCode};
+use super::submit::{parse_scheduler_cmd, SchedulerType};
+use crate::dft::CleanupTarget;
+use clap::{Args, ValueEnum};
+use std::path::PathBuf;
+
+/// 允许清理的作业状态
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum CleanStatus {
+    /// Finished and converged jobs
+    Completed,
+    /// Finished geometry optimisations that did not converge
+    Unconverged,
+    /// Failed, timed-out, node-fail and out-of-memory jobs
+    Failed,
+}
+
+impl From<CleanStatus> for CleanupTarget {
+    fn from(status: CleanStatus) -> Self {
+        match status {
+            CleanStatus::Completed => CleanupTarget::Completed,
+            CleanStatus::Unconverged => CleanupTarget::Unconverged,
+            CleanStatus::Failed => CleanupTarget::Failed,
+        }
+    }
+}
+
+/// clean 子命令参数
+#[derive(Args, Debug)]
+pub struct CleanArgs {
+    /// Path to the root directory containing DFT calculation folders
+    pub job_dir: PathBuf,
+
+    /// Specify the DFT code used (`auto` detects it per job directory)
+    #[arg(long, value_enum)]
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning and parsing (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Job statuses to clean (comma-separated)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "completed")]
+    pub status: Vec<CleanStatus>,
+
+    /// File name patterns to remove (comma-separated; default: WAVECAR,CHGCAR,CHG,vasprun.xml for VASP, *.check,*.castep_bin for CASTEP)
+    #[arg(long, value_delimiter = ',')]
+    pub pattern: Vec<String>,
+
+    /// File name patterns to keep even if they match --pattern (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub keep: Vec<String>,
+
+    /// Only remove files last modified at least this many days ago
+    #[arg(long)]
+    pub older_than: Option<f64>,
+
+    /// Only report the files and bytes that would be removed
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Do not query the scheduler for jobs that are still queued or running (unsafe)
+    #[arg(long, default_value_t = false)]
+    pub no_queue_check: bool,
+
+    /// Batch scheduler whose queue is checked
+    #[arg(long, value_enum, default_value = "slurm")]
+    pub scheduler: SchedulerType,
+
+    /// Run another program in place of a scheduler command, e.g. 'squeue=./stub.sh' or 'bjobs=/opt/lsf/bin/bjobs' (repeatable)
+    #[arg(long, value_name = "NAME=PATH", value_parser = parse_scheduler_cmd)]
+    pub scheduler_cmd: Vec<(String, String)>,
+
+    /// Only query jobs of this user (defaults to $USER)
+    #[arg(long)]
+    pub user: Option<String>,
+}
@@ -4,9 +4,10 @@
 //!
 //! ## 依赖关系
 //! - 被 `cli/mod.rs` 使用
+//! - 复用 `cli/analyze.rs` 的代码类型与作业布局参数
 //! - 参数传递给 `commands/collect.rs`
 
-use super::analyze::DftCode;
+use super::analyze::{JobLayoutArgs, ScanCode};
 use clap::Args;
 use std::path::PathBuf;
 
@@ -16,9 +17,20 @@ pub struct CollectArgs {
     /// Path to the root directory containing DFT calculation folders
     pub dft_dir: PathBuf,
 
-    /// Specify the DFT code used
+    /// Specify the DFT code used (`auto` detects it per job directory)
     #[arg(long, value_enum)]
-    pub code: DftCode,
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning, parsing and .res conversion (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the DFT directory
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
 
     /// Filename for the final concatenated .res file
     #[arg(long, default_value = "all_structures.res")]
@@ -1,17 +1,22 @@
 //! # CLI module
 //!
-//! Defines the clap-based command tree for convert, analyze, collect, and submit.
+//! Defines the clap-based command tree for convert, analyze, collect, clean, archive, report, and submit,
+//! plus the global `--output-format` option.
 //!
 //! ## Coupling
 //! - Used directly by `main.rs`
 //! - Hands parsed arguments to `commands/`
 
 pub mod analyze;
+pub mod archive;
+pub mod clean;
 pub mod collect;
 pub mod convert;
+pub mod report;
 pub mod submit;
 
-use clap::{Parser, Subcommand};
+use crate::utils::structured::RecordFormat;
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "qutility")]
@@ -22,6 +27,34 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Output format: human-readable tables, or machine-readable records on stdout (messages go to stderr)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output_format: OutputFormat,
+}
+
+/// Global output format
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable tables
+    Table,
+    /// A JSON array of records
+    Json,
+    /// One JSON record per line
+    Jsonl,
+    /// CSV with a header row
+    Csv,
+}
+
+impl From<OutputFormat> for Option<RecordFormat> {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Table => None,
+            OutputFormat::Json => Some(RecordFormat::Json),
+            OutputFormat::Jsonl => Some(RecordFormat::Jsonl),
+            OutputFormat::Csv => Some(RecordFormat::Csv),
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -35,6 +68,15 @@ pub enum Commands {
     /// Collect completed DFT results and convert to .res format
     Collect(collect::CollectArgs),
 
+    /// Remove large scratch files (WAVECAR, CHGCAR, .check, ...) from finished DFT jobs
+    Clean(clean::CleanArgs),
+
+    /// Pack finished DFT jobs into compressed archives with a manifest, or restore them
+    Archive(archive::ArchiveArgs),
+
+    /// Write a self-contained HTML report of a job root (status, ranking, plots, failures)
+    Report(report::ReportArgs),
+
     /// Submit batch jobs to Slurm scheduler
     Submit(submit::SubmitArgs),
 }
@@ -0,0 +1,66 @@
+//! # report 子命令 CLI 定义
+//!
+//! 扫描作业根目录（或归档目录），生成单个自包含的 HTML 计算批次报告
+//!
+//! ## 依赖关系
+//! - 被 `cli/mod.rs` 使用
+//! - 复用 `cli/analyze.rs` 的代码类型、作业布局参数与排序依据
+//! - 参数传递给 `commands/report.rs`
+
+use super::analyze::{JobLayoutArgs, RankMode, ScanCode};
+use clap::Args;
+use std::path::PathBuf;
+
+/// report 子命令参数
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// Path to the root directory containing DFT calculation folders, or an archive directory (manifest.json) written by `archive create`
+    pub job_dir: PathBuf,
+
+    /// Specify the DFT code used (`auto` detects it per job directory)
+    #[arg(long, value_enum)]
+    pub code: ScanCode,
+
+    #[command(flatten)]
+    pub layout: JobLayoutArgs,
+
+    /// Number of parallel jobs for scanning and parsing (0 = auto)
+    #[arg(short, long, default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Ignore and do not update the scan cache (.qutility-cache) in the job root
+    #[arg(long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Extra failure-pattern rules file (TOML); takes priority over user and project rules
+    #[arg(long)]
+    pub rules: Option<PathBuf>,
+
+    /// Quantity used to rank structures
+    #[arg(long, value_enum, default_value = "per-atom")]
+    pub rank_by: RankMode,
+
+    /// Rank geometry optimisations that did not converge, marking them in the report
+    #[arg(long, default_value_t = false)]
+    pub include_unconverged: bool,
+
+    /// Original EDDP/AIRSS ranking as a .res file, concatenated .res file or directory of .res files (adds the parity section)
+    #[arg(long)]
+    pub eddp_res: Option<PathBuf>,
+
+    /// Size of the top-N overlap reported in the parity section
+    #[arg(long, default_value_t = 10)]
+    pub top_n: usize,
+
+    /// Maximum number of structures per group drawn in the ranking plot
+    #[arg(long, default_value_t = 50)]
+    pub plot_top: usize,
+
+    /// Report title (default: the job directory name)
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Output HTML file
+    #[arg(short, long, default_value = "qutility_report.html")]
+    pub output: PathBuf,
+}
@@ -1,11 +1,16 @@
 //! # submit 子命令 CLI 定义
 //!
-//! 批量提交 CASTEP/VASP 作业到 Slurm
+//! 批量提交 CASTEP/VASP 作业到 Slurm、PBS/Torque、LSF 或 SGE；调度器、资源、模块与可执行文件参数未给出时取自 `--profile` 选定的集群配置档案。
+//! 调度器类型也被 dft-status 与 clean 复用
 //!
 //! ## 依赖关系
 //! - 被 `cli/mod.rs` 使用
 //! - 参数传递给 `commands/submit.rs`
+//! - `PotcarSet` 转换为 `dft/potcar.rs` 的 `PotcarPreset`；k 点网格选项转换为 `dft/kpoints.rs` 的类型
+//! - `SchedulerType` 与 `--scheduler-cmd` 的解析函数被 `cli/analyze.rs` 与 `cli/clean.rs` 复用；前者转换为 `utils/scheduler.rs` 的 `SchedulerKind`，后者按其 `SCHEDULER_COMMANDS` 校验命令名
 
+use crate::dft::{MeshCentering, MeshParity, PotcarPreset, SpacingUnit};
+use crate::utils::scheduler::{SchedulerKind, SCHEDULER_COMMANDS};
 use clap::{Args, ValueEnum};
 use std::path::PathBuf;
 
@@ -20,6 +25,129 @@ pub enum DftEngine {
     Vasp,
 }
 
+/// POTCAR 预设选择
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum PotcarSet {
+    /// VASP recommended potentials (e.g. Ti_sv, Ga_d, Nb_sv)
+    Recommended,
+    /// The bare element name for every species
+    Plain,
+}
+
+impl From<PotcarSet> for PotcarPreset {
+    fn from(preset: PotcarSet) -> Self {
+        match preset {
+            PotcarSet::Recommended => PotcarPreset::Recommended,
+            PotcarSet::Plain => PotcarPreset::Plain,
+        }
+    }
+}
+
+/// k 点间距单位
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum KpointUnit {
+    /// 2π/Å, as CASTEP KPOINTS_MP_SPACING (0.03-0.07 is typical)
+    #[value(name = "2pi/ang")]
+    TwoPiPerAngstrom,
+    /// 1/Å with the 2π included, as VASP KSPACING (0.2-0.5 is typical)
+    #[value(name = "1/ang")]
+    PerAngstrom,
+}
+
+/// k 点网格中心
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum KpointCentering {
+    /// Γ-centred mesh (safe for hexagonal cells)
+    Gamma,
+    /// Monkhorst–Pack mesh (shifted off Γ for even subdivisions)
+    #[value(name = "mp")]
+    MonkhorstPack,
+}
+
+/// k 点数奇偶约束
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum KpointParity {
+    /// Smallest subdivisions that meet the spacing
+    Any,
+    /// Round even subdivisions up to odd
+    Odd,
+    /// Round odd subdivisions up to even
+    Even,
+}
+
+impl From<KpointUnit> for SpacingUnit {
+    fn from(unit: KpointUnit) -> Self {
+        match unit {
+            KpointUnit::TwoPiPerAngstrom => SpacingUnit::TwoPiPerAngstrom,
+            KpointUnit::PerAngstrom => SpacingUnit::PerAngstrom,
+        }
+    }
+}
+
+impl From<KpointCentering> for MeshCentering {
+    fn from(centering: KpointCentering) -> Self {
+        match centering {
+            KpointCentering::Gamma => MeshCentering::Gamma,
+            KpointCentering::MonkhorstPack => MeshCentering::MonkhorstPack,
+        }
+    }
+}
+
+impl From<KpointParity> for MeshParity {
+    fn from(parity: KpointParity) -> Self {
+        match parity {
+            KpointParity::Any => MeshParity::Any,
+            KpointParity::Odd => MeshParity::Odd,
+            KpointParity::Even => MeshParity::Even,
+        }
+    }
+}
+
+/// 作业调度器选择
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum SchedulerType {
+    /// Slurm (sbatch, squeue, sacct)
+    Slurm,
+    /// PBS Pro or Torque (qsub, qstat)
+    #[value(alias = "torque")]
+    Pbs,
+    /// IBM Spectrum LSF (bsub, bjobs)
+    Lsf,
+    /// Sun/Univa/Son of Grid Engine (qsub, qstat)
+    Sge,
+}
+
+impl From<SchedulerType> for SchedulerKind {
+    fn from(scheduler: SchedulerType) -> Self {
+        match scheduler {
+            SchedulerType::Slurm => SchedulerKind::Slurm,
+            SchedulerType::Pbs => SchedulerKind::Pbs,
+            SchedulerType::Lsf => SchedulerKind::Lsf,
+            SchedulerType::Sge => SchedulerKind::Sge,
+        }
+    }
+}
+
+/// 解析 `--scheduler-cmd NAME=PATH`；NAME 须为某个调度器后端调用的命令
+pub fn parse_scheduler_cmd(input: &str) -> Result<(String, String), String> {
+    let (name, path) = input
+        .split_once('=')
+        .map(|(name, path)| (name.trim(), path.trim()))
+        .filter(|(name, path)| !name.is_empty() && !path.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "Invalid scheduler command '{input}'. Use NAME=PATH, e.g. qstat=/opt/pbs/bin/qstat"
+            )
+        })?;
+    if !SCHEDULER_COMMANDS.contains(&name) {
+        return Err(format!(
+            "Unknown scheduler command '{name}'. Available: {}",
+            SCHEDULER_COMMANDS.join(", ")
+        ));
+    }
+    Ok((name.to_string(), path.to_string()))
+}
+
 /// submit 子命令参数
 #[derive(Args, Debug)]
 pub struct SubmitArgs {
@@ -50,17 +178,17 @@ pub struct SubmitArgs {
     #[arg(long)]
     pub param_template: Option<PathBuf>,
 
-    /// CASTEP executable name
-    #[arg(long, default_value = "castep.mpi")]
-    pub castep_exec: String,
+    /// CASTEP executable name [profile or default: castep.mpi]
+    #[arg(long)]
+    pub castep_exec: Option<String>,
 
-    /// Number of MPI processes for CASTEP
-    #[arg(long, default_value_t = 32)]
-    pub castep_np: u32,
+    /// Number of MPI processes for CASTEP [profile or default: 32]
+    #[arg(long)]
+    pub castep_np: Option<u32>,
 
-    /// Module list for CASTEP (comma-separated)
-    #[arg(long, default_value = "airss/arm-v2/0.2,castep/arm-v2/25.12")]
-    pub castep_modules: String,
+    /// Module list for CASTEP (comma-separated) [profile or default: none]
+    #[arg(long)]
+    pub castep_modules: Option<String>,
 
     /// External pressure in GPa (for CASTEP %BLOCK EXTERNAL_PRESSURE)
     #[arg(long)]
@@ -74,55 +202,104 @@ pub struct SubmitArgs {
     pub incar_template: Option<PathBuf>,
 
     /// Optional VASP KPOINTS template file
-    #[arg(long)]
+    #[arg(long, conflicts_with = "kpoint_spacing")]
     pub kpoints_template: Option<PathBuf>,
 
-    /// VASP POTCAR library directory
+    /// Generate each VASP KPOINTS from this k-point spacing (unit set by --kpoint-unit) instead of a template
+    #[arg(long)]
+    pub kpoint_spacing: Option<f64>,
+
+    /// Unit of --kpoint-spacing
+    #[arg(
+        long,
+        value_enum,
+        default_value = "2pi/ang",
+        requires = "kpoint_spacing"
+    )]
+    pub kpoint_unit: KpointUnit,
+
+    /// Centring of the generated k-point mesh
+    #[arg(long, value_enum, default_value = "gamma", requires = "kpoint_spacing")]
+    pub kpoint_centering: KpointCentering,
+
+    /// Force odd or even subdivisions along every direction
+    #[arg(long, value_enum, default_value = "any", requires = "kpoint_spacing")]
+    pub kpoint_parity: KpointParity,
+
+    /// VASP POTCAR library directory (not a single POTCAR file) holding <Element><suffix>/POTCAR; the POTCAR of each job is assembled in POSCAR species order
     #[arg(long)]
     pub potcar_dir: Option<PathBuf>,
 
-    /// VASP executable name
-    #[arg(long, default_value = "vasp_std")]
-    pub vasp_exec: String,
+    /// POTCAR choices overriding the preset, e.g. 'Fe=Fe_pv,O=O_h' or 'Fe=_pv' (comma-separated)
+    #[arg(long, value_delimiter = ',', requires = "potcar_dir")]
+    pub potcar_map: Vec<String>,
+
+    /// POTCAR variant for elements not in --potcar-map
+    #[arg(long, value_enum, default_value = "recommended")]
+    pub potcar_preset: PotcarSet,
 
-    /// Number of MPI processes for VASP
-    #[arg(long, default_value_t = 32)]
-    pub vasp_np: u32,
+    /// VASP executable name [profile or default: vasp_std]
+    #[arg(long)]
+    pub vasp_exec: Option<String>,
 
-    /// Module list for VASP (comma-separated)
-    #[arg(long, default_value = "")]
-    pub vasp_modules: String,
+    /// Number of MPI processes for VASP [profile or default: 32]
+    #[arg(long)]
+    pub vasp_np: Option<u32>,
+
+    /// Module list for VASP (comma-separated) [profile or default: none]
+    #[arg(long)]
+    pub vasp_modules: Option<String>,
 
     // ─────────────────────────────────────────────────────────────
-    // Slurm options
+    // Scheduler options (each flag overrides the selected profile)
     // ─────────────────────────────────────────────────────────────
-    /// Slurm partition
-    #[arg(long, default_value = "arm")]
-    pub partition: String,
+    /// Cluster profile from ~/.config/qutility/config.toml or ./qutility.toml [default: the file's default_profile]
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Batch scheduler that job scripts are written for and submitted to [profile or default: slurm]
+    #[arg(long, value_enum)]
+    pub scheduler: Option<SchedulerType>,
+
+    /// Partition (Slurm) or queue (PBS/LSF/SGE) [profile or default: scheduler default queue]
+    #[arg(long)]
+    pub partition: Option<String>,
+
+    /// Node feature constraint (Slurm --constraint, PBS node property, LSF select[]) [profile or default: none]
+    #[arg(long)]
+    pub constraint: Option<String>,
 
-    /// Slurm constraint
-    #[arg(long, default_value = "neoverse_v2")]
-    pub constraint: String,
+    /// SGE parallel environment requested with `-pe <name> <cores>` [profile or default: mpi]
+    #[arg(long)]
+    pub sge_pe: Option<String>,
 
-    /// Number of nodes
-    #[arg(long, default_value_t = 1)]
-    pub nodes: u32,
+    /// Number of nodes [profile or default: 1]
+    #[arg(long)]
+    pub nodes: Option<u32>,
 
-    /// Number of tasks
-    #[arg(long, default_value_t = 32)]
-    pub ntasks: u32,
+    /// Number of tasks [profile or default: 32]
+    #[arg(long)]
+    pub ntasks: Option<u32>,
 
-    /// CPUs per task
-    #[arg(long, default_value_t = 1)]
-    pub cpus_per_task: u32,
+    /// CPUs per task [profile or default: 1]
+    #[arg(long)]
+    pub cpus_per_task: Option<u32>,
 
-    /// Memory per CPU
-    #[arg(long, default_value = "3G")]
-    pub mem_per_cpu: String,
+    /// Memory per CPU, e.g. '3G' [profile or default: not set]
+    #[arg(long)]
+    pub mem_per_cpu: Option<String>,
 
-    /// Time limit (e.g., '24:00:00')
-    #[arg(long, default_value = "24:00:00")]
-    pub time: String,
+    /// Time limit, e.g. '24:00:00' [profile or default: 24:00:00]
+    #[arg(long)]
+    pub time: Option<String>,
+
+    /// MPI launcher; `{np}` is replaced by the process count, e.g. 'srun' or 'mpiexec -n {np}' [profile or default: 'mpirun -np {np}']
+    #[arg(long)]
+    pub mpi_launcher: Option<String>,
+
+    /// Job script template with {{placeholder}} fields, e.g. {{job_name}}, {{workdir}}, {{exec_cmd}} [profile or default: built-in script]
+    #[arg(long)]
+    pub script_template: Option<PathBuf>,
 
     // ─────────────────────────────────────────────────────────────
     // Execution control
@@ -131,7 +308,19 @@ pub struct SubmitArgs {
     #[arg(long, default_value_t = false)]
     pub dry_run: bool,
 
-    /// Submit jobs to Slurm after generation
+    /// Submit jobs to the scheduler after generation
     #[arg(long, default_value_t = false)]
     pub submit: bool,
+
+    /// Run another program in place of a scheduler command, e.g. 'sbatch=./stub.sh' or 'qsub=/opt/pbs/bin/qsub' (repeatable)
+    #[arg(long, value_name = "NAME=PATH", value_parser = parse_scheduler_cmd)]
+    pub scheduler_cmd: Vec<(String, String)>,
+
+    /// Write one Slurm job-array script plus a task → job-directory map in the jobs root instead of one script per job
+    #[arg(long, default_value_t = false)]
+    pub array: bool,
+
+    /// Maximum number of array tasks running at once (Slurm `--array=1-N%LIMIT`)
+    #[arg(long, requires = "array")]
+    pub array_limit: Option<u32>,
 }
@@ -0,0 +1,611 @@
+//! # 收敛诊断子命令实现
+//!
+//! 输出单个作业的逐离子步 SCF 次数、能量变化、最大受力与应力，绘制趋势图并标记停滞/振荡；
+//! 批量模式下按作业布局（可选扫描缓存）扫描，对未完成、几何未收敛与超时的作业诊断并列出最严重者。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/analyze.rs` 定义的 ConvergenceArgs
+//! - 复用 `dft/` 扫描（失败规则取内置及用户/项目规则文件）、扫描缓存与收敛诊断模块
+//! - 使用 `utils/output.rs`、`utils/parallel.rs`
+//! - 使用 `plotters` 渲染趋势图，经 `report/plot.rs` 的 `save_plot` 按扩展名写为 SVG 或位图
+
+use crate::cli::analyze::{ConvergenceArgs, ScanCode};
+use crate::dft::{
+    diagnose, energy_changes, load_trace, scan_calculations_with_rules, ConvergenceCriteria,
+    ConvergenceDiagnosis, ConvergenceIssue, FailureRules, JobLayout, ScanCache,
+};
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationStatus, ConvergenceTrace, DftCodeType};
+use crate::report::plot::{save_plot, PlotFigure};
+use crate::utils::{output, parallel};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tabled::{Table, Tabled};
+
+#[derive(Debug, Clone, Tabled)]
+struct StepRow {
+    #[tabled(rename = "Step")]
+    step: usize,
+    #[tabled(rename = "SCF")]
+    scf: usize,
+    #[tabled(rename = "E (eV)")]
+    energy: String,
+    #[tabled(rename = "ΔE (meV)")]
+    delta: String,
+    #[tabled(rename = "Max |F| (eV/Å)")]
+    force: String,
+    #[tabled(rename = "Max σ (GPa)")]
+    stress: String,
+}
+
+#[derive(Debug, Clone, Tabled)]
+struct OffenderRow {
+    #[tabled(rename = "Structure")]
+    structure: String,
+    #[tabled(rename = "Steps")]
+    steps: usize,
+    #[tabled(rename = "Last SCF")]
+    last_scf: String,
+    #[tabled(rename = "Last |F| (eV/Å)")]
+    last_force: String,
+    #[tabled(rename = "Steps since F min")]
+    since_min: usize,
+    #[tabled(rename = "Issues")]
+    issues: String,
+}
+
+/// 批量模式中单个作业的诊断
+struct JobDiagnosis {
+    structure: String,
+    trace: ConvergenceTrace,
+    diagnosis: ConvergenceDiagnosis,
+}
+
+impl JobDiagnosis {
+    fn last_force(&self) -> Option<f64> {
+        self.trace
+            .steps
+            .iter()
+            .rev()
+            .find_map(|step| step.max_force_ev_per_a)
+    }
+}
+
+pub fn execute(args: ConvergenceArgs) -> Result<()> {
+    let criteria = ConvergenceCriteria {
+        stagnation_steps: args.stagnation_steps,
+        scf_limit_steps: args.scf_limit_steps,
+        oscillation_window: args.oscillation_window,
+    };
+
+    if args.batch {
+        execute_batch(&args, &criteria)
+    } else {
+        execute_single(&args, &criteria)
+    }
+}
+
+fn execute_single(args: &ConvergenceArgs, criteria: &ConvergenceCriteria) -> Result<()> {
+    output::print_header("Convergence Diagnostics");
+
+    if !args.job_dir.is_dir() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+
+    let (code, structure_name) = match args.code {
+        Some(ScanCode::Vasp) => (DftCodeType::Vasp, job_name(&args.job_dir)),
+        Some(ScanCode::Castep) => (DftCodeType::Castep, job_name(&args.job_dir)),
+        Some(ScanCode::Auto) | None => detect_job(&args.job_dir)?,
+    };
+    let trace = load_trace(&args.job_dir, &structure_name, code)?;
+    if trace.steps.is_empty() {
+        output::print_warning("No completed ionic steps found in the output yet.");
+        return Ok(());
+    }
+
+    output::print_info(&format!(
+        "{} ({}): {} ionic steps, SCF limit {}",
+        structure_name,
+        code,
+        trace.steps.len(),
+        trace
+            .scf_limit
+            .map(|limit| limit.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    ));
+
+    let deltas = energy_changes(&trace);
+    let rows: Vec<StepRow> = trace
+        .steps
+        .iter()
+        .zip(&deltas)
+        .map(|(step, delta)| StepRow {
+            step: step.step,
+            scf: step.scf_iterations,
+            energy: format_option(step.energy_ev, 6),
+            delta: format_option(delta.map(|d| d * 1000.0), 3),
+            force: format_option(step.max_force_ev_per_a, 4),
+            stress: format_option(step.max_stress_gpa, 4),
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let diagnosis = diagnose(&trace, criteria);
+    output::print_header("Diagnosis");
+    if diagnosis.issues.is_empty() {
+        output::print_success("No stagnation, SCF-limit or oscillation patterns detected");
+    }
+    for issue in &diagnosis.issues {
+        output::print_warning(&issue.to_string());
+    }
+
+    if let Some(csv_path) = args.output_csv.as_ref() {
+        write_steps_csv(csv_path, &trace, &deltas)?;
+        output::print_success(&format!("Step data saved to '{}'", csv_path.display()));
+    }
+
+    if !args.no_plot {
+        let plot = ConvergencePlot {
+            trace: &trace,
+            deltas: &deltas,
+            title: &structure_name,
+        };
+        save_plot(&plot, &args.output_plot)?;
+        output::print_success(&format!(
+            "Trend plot saved to '{}'",
+            args.output_plot.display()
+        ));
+    }
+
+    Ok(())
+}
+
+fn execute_batch(args: &ConvergenceArgs, criteria: &ConvergenceCriteria) -> Result<()> {
+    output::print_header("Convergence Diagnostics (batch)");
+
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let code = args.code.unwrap_or(ScanCode::Auto).into();
+    let rules = FailureRules::discover(&args.job_dir, None)?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let records =
+        scan_calculations_with_rules(&args.job_dir, code, &rules, &layout, cache.as_mut())?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+
+    let mut jobs: Vec<JobDiagnosis> = records
+        .iter()
+        .filter(|record| {
+            matches!(
+                record.status,
+                CalculationStatus::Incomplete
+                    | CalculationStatus::Unconverged
+                    | CalculationStatus::TimedOut
+            )
+        })
+        .filter_map(
+            |record| match load_trace(&record.job_dir, &record.seed, record.code) {
+                Ok(trace) => Some(JobDiagnosis {
+                    structure: record.structure_name.clone(),
+                    diagnosis: diagnose(&trace, criteria),
+                    trace,
+                }),
+                Err(err) => {
+                    output::print_warning(&format!("{}: {}", record.structure_name, err));
+                    None
+                }
+            },
+        )
+        .collect();
+
+    output::print_info(&format!("Diagnosed {} unfinished jobs", jobs.len()));
+    if jobs.is_empty() {
+        return Ok(());
+    }
+
+    // 问题越多、停滞越久、受力越大越靠前
+    jobs.sort_by(|a, b| {
+        b.diagnosis
+            .issues
+            .len()
+            .cmp(&a.diagnosis.issues.len())
+            .then(
+                b.diagnosis
+                    .steps_since_force_minimum
+                    .cmp(&a.diagnosis.steps_since_force_minimum),
+            )
+            .then(
+                b.last_force()
+                    .unwrap_or(0.0)
+                    .total_cmp(&a.last_force().unwrap_or(0.0)),
+            )
+            .then(a.structure.cmp(&b.structure))
+    });
+
+    let rows: Vec<OffenderRow> = jobs
+        .iter()
+        .take(args.top)
+        .map(|job| OffenderRow {
+            structure: job.structure.clone(),
+            steps: job.trace.steps.len(),
+            last_scf: match (job.trace.steps.last(), job.trace.scf_limit) {
+                (Some(step), Some(limit)) => format!("{}/{}", step.scf_iterations, limit),
+                (Some(step), None) => step.scf_iterations.to_string(),
+                _ => "-".to_string(),
+            },
+            last_force: format_option(job.last_force(), 4),
+            since_min: job.diagnosis.steps_since_force_minimum,
+            issues: issue_summary(&job.diagnosis),
+        })
+        .collect();
+
+    output::print_header("Worst Offenders");
+    println!("{}", Table::new(rows));
+
+    if let Some(csv_path) = args.output_csv.as_ref() {
+        write_ranking_csv(csv_path, &jobs)?;
+        output::print_success(&format!("Ranking saved to '{}'", csv_path.display()));
+    }
+
+    Ok(())
+}
+
+/// 从目录内容判断代码：OUTCAR/OSZICAR → VASP，*.castep → CASTEP（种子名取文件名）
+fn detect_job(job_dir: &Path) -> Result<(DftCodeType, String)> {
+    if job_dir.join("OUTCAR").exists() || job_dir.join("OSZICAR").exists() {
+        return Ok((DftCodeType::Vasp, job_name(job_dir)));
+    }
+
+    let preferred = job_dir.join(format!("{}.castep", job_name(job_dir)));
+    let castep = if preferred.exists() {
+        Some(preferred)
+    } else {
+        let mut candidates: Vec<PathBuf> = fs::read_dir(job_dir)
+            .map_err(|e| QutilityError::FileReadError {
+                path: job_dir.display().to_string(),
+                source: e,
+            })?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("castep"))
+            .collect();
+        candidates.sort();
+        candidates.into_iter().next()
+    };
+
+    match castep.and_then(|path| path.file_stem().map(|s| s.to_string_lossy().to_string())) {
+        Some(seed) => Ok((DftCodeType::Castep, seed)),
+        None => Err(QutilityError::InvalidArgument(format!(
+            "No OUTCAR, OSZICAR or .castep found in '{}'; pass --code",
+            job_dir.display()
+        ))),
+    }
+}
+
+fn job_name(job_dir: &Path) -> String {
+    job_dir
+        .canonicalize()
+        .unwrap_or_else(|_| job_dir.to_path_buf())
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 表格中使用的简短问题标签
+fn issue_summary(diagnosis: &ConvergenceDiagnosis) -> String {
+    if diagnosis.issues.is_empty() {
+        return "-".to_string();
+    }
+
+    diagnosis
+        .issues
+        .iter()
+        .map(|issue| match issue {
+            ConvergenceIssue::ForceStagnation { steps, .. } => {
+                format!("force stagnant {steps} steps")
+            }
+            ConvergenceIssue::ScfLimit { steps, .. } => format!("SCF at limit {steps} steps"),
+            ConvergenceIssue::EnergyOscillation {
+                sign_changes,
+                window,
+            } => format!("ΔE oscillating {sign_changes}/{window}"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_option(value: Option<f64>, precision: usize) -> String {
+    value
+        .map(|v| format!("{v:.precision$}"))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn write_steps_csv(
+    output_path: &Path,
+    trace: &ConvergenceTrace,
+    deltas: &[Option<f64>],
+) -> Result<()> {
+    let mut writer = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+    writer
+        .write_record([
+            "step",
+            "scf_iterations",
+            "energy_eV",
+            "delta_energy_meV",
+            "max_force_eV_per_A",
+            "max_stress_GPa",
+        ])
+        .map_err(QutilityError::CsvError)?;
+
+    let field = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    for (step, delta) in trace.steps.iter().zip(deltas) {
+        writer
+            .write_record([
+                step.step.to_string(),
+                step.scf_iterations.to_string(),
+                field(step.energy_ev),
+                field(delta.map(|d| d * 1000.0)),
+                field(step.max_force_ev_per_a),
+                field(step.max_stress_gpa),
+            ])
+            .map_err(QutilityError::CsvError)?;
+    }
+
+    writer.flush().map_err(|e| QutilityError::FileWriteError {
+        path: output_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(())
+}
+
+fn write_ranking_csv(output_path: &Path, jobs: &[JobDiagnosis]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+    writer
+        .write_record([
+            "structure",
+            "ionic_steps",
+            "last_scf_iterations",
+            "scf_limit",
+            "scf_limit_hits",
+            "last_max_force_eV_per_A",
+            "steps_since_force_minimum",
+            "issues",
+        ])
+        .map_err(QutilityError::CsvError)?;
+
+    for job in jobs {
+        writer
+            .write_record([
+                job.structure.clone(),
+                job.trace.steps.len().to_string(),
+                job.trace
+                    .steps
+                    .last()
+                    .map(|step| step.scf_iterations.to_string())
+                    .unwrap_or_default(),
+                job.trace
+                    .scf_limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default(),
+                job.diagnosis.scf_limit_hits.to_string(),
+                job.last_force().map(|f| f.to_string()).unwrap_or_default(),
+                job.diagnosis.steps_since_force_minimum.to_string(),
+                job.diagnosis
+                    .issues
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ])
+            .map_err(QutilityError::CsvError)?;
+    }
+
+    writer.flush().map_err(|e| QutilityError::FileWriteError {
+        path: output_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(())
+}
+
+/// 单个作业的收敛趋势图
+struct ConvergencePlot<'a> {
+    trace: &'a ConvergenceTrace,
+    deltas: &'a [Option<f64>],
+    title: &'a str,
+}
+
+impl PlotFigure for ConvergencePlot<'_> {
+    const SIZE: (u32, u32) = (900, 1000);
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        draw_convergence_chart(root, self.trace, self.deltas, self.title)
+    }
+}
+
+/// 四个上下排列的面板：SCF 次数、|ΔE|（对数）、最大受力、最大应力
+fn draw_convergence_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    trace: &ConvergenceTrace,
+    deltas: &[Option<f64>],
+    title: &str,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    let root = root
+        .titled(&format!("Convergence: {title}"), ("sans-serif", 24))
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    let panels = root.split_evenly((4, 1));
+
+    let steps: Vec<f64> = trace.steps.iter().map(|step| step.step as f64).collect();
+    let x_max = steps.iter().copied().fold(1.0, f64::max) + 0.5;
+    let x_range = 0.5..x_max;
+
+    let scf: Vec<(f64, f64)> = trace
+        .steps
+        .iter()
+        .map(|step| (step.step as f64, step.scf_iterations as f64))
+        .collect();
+    let scf_max = trace
+        .scf_limit
+        .map(|limit| limit as f64)
+        .into_iter()
+        .chain(scf.iter().map(|(_, y)| *y))
+        .fold(1.0, f64::max)
+        * 1.1;
+    let mut chart = ChartBuilder::on(&panels[0])
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(x_range.clone(), 0.0..scf_max)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .configure_mesh()
+        .y_desc("SCF iterations")
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .draw_series(
+            scf.iter()
+                .map(|(x, y)| Circle::new((*x, *y), 3, BLUE.filled())),
+        )
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .draw_series(LineSeries::new(scf.iter().copied(), BLUE.stroke_width(1)))
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    if let Some(limit) = trace.scf_limit {
+        chart
+            .draw_series(LineSeries::new(
+                vec![(x_range.start, limit as f64), (x_range.end, limit as f64)],
+                RED.stroke_width(1),
+            ))
+            .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    }
+
+    let delta_points: Vec<(f64, f64)> = trace
+        .steps
+        .iter()
+        .zip(deltas)
+        .filter_map(|(step, delta)| {
+            delta
+                .map(|d| (d * 1000.0).abs())
+                .filter(|d| *d > 0.0)
+                .map(|d| (step.step as f64, d))
+        })
+        .collect();
+    let (d_min, d_max) = delta_points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, d)| {
+            (lo.min(*d), hi.max(*d))
+        });
+    let (d_min, d_max) = if delta_points.is_empty() {
+        (1e-3, 1.0)
+    } else {
+        (d_min / 2.0, d_max * 2.0)
+    };
+    let mut chart = ChartBuilder::on(&panels[1])
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(x_range.clone(), (d_min..d_max).log_scale())
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .configure_mesh()
+        .y_desc("|ΔE| (meV)")
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .draw_series(LineSeries::new(
+            delta_points.iter().copied(),
+            RGBColor(0, 153, 76).stroke_width(2),
+        ))
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    let forces: Vec<(f64, f64)> = trace
+        .steps
+        .iter()
+        .filter_map(|step| step.max_force_ev_per_a.map(|f| (step.step as f64, f)))
+        .collect();
+    draw_linear_panel(
+        &panels[2],
+        x_range.clone(),
+        &forces,
+        "Max |F| (eV/Å)",
+        RGBColor(220, 50, 47),
+        None,
+    )?;
+
+    let stresses: Vec<(f64, f64)> = trace
+        .steps
+        .iter()
+        .filter_map(|step| step.max_stress_gpa.map(|s| (step.step as f64, s)))
+        .collect();
+    draw_linear_panel(
+        &panels[3],
+        x_range,
+        &stresses,
+        "Max σ (GPa)",
+        RGBColor(108, 52, 131),
+        Some("Ionic step"),
+    )?;
+
+    Ok(())
+}
+
+fn draw_linear_panel<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    x_range: std::ops::Range<f64>,
+    points: &[(f64, f64)],
+    y_desc: &str,
+    color: RGBColor,
+    x_desc: Option<&str>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    let y_max = points.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+    let y_max = if y_max > 0.0 { y_max * 1.1 } else { 1.0 };
+
+    let mut chart = ChartBuilder::on(area)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(x_range, 0.0..y_max)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .configure_mesh()
+        .x_desc(x_desc.unwrap_or(""))
+        .y_desc(y_desc)
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .draw_series(LineSeries::new(
+            points.iter().copied(),
+            color.stroke_width(2),
+        ))
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    chart
+        .draw_series(
+            points
+                .iter()
+                .map(|(x, y)| Circle::new((*x, *y), 3, color.filled())),
+        )
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    Ok(())
+}
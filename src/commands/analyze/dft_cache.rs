@@ -0,0 +1,53 @@
+//! # DFT 扫描缓存子命令实现
+//!
+//! 显示作业根目录下 `.qutility-cache` 的条目数与大小，或删除缓存使下次扫描重新解析全部作业。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/analyze.rs` 定义的参数
+//! - 复用 `dft/` 的扫描缓存与 `utils/output.rs`
+
+use crate::cli::analyze::DftCacheArgs;
+use crate::dft::{ScanCache, CACHE_FILE};
+use crate::error::{QutilityError, Result};
+use crate::utils::output;
+
+use std::fs;
+
+pub fn execute(args: DftCacheArgs) -> Result<()> {
+    output::print_header("DFT Scan Cache");
+
+    if !args.job_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+
+    let path = args.job_dir.join(CACHE_FILE);
+    if args.clear {
+        if ScanCache::clear(&args.job_dir)? {
+            output::print_success(&format!("Removed '{}'", path.display()));
+        } else {
+            output::print_info(&format!("No cache found at '{}'", path.display()));
+        }
+        return Ok(());
+    }
+
+    let Ok(metadata) = fs::metadata(&path) else {
+        output::print_info(&format!("No cache found at '{}'", path.display()));
+        return Ok(());
+    };
+
+    // 损坏或版本不符的缓存按空缓存处理，下次扫描时重建
+    let cache = ScanCache::load(&args.job_dir);
+    output::print_info(&format!(
+        "'{}': {} cached jobs, {:.1} KiB",
+        path.display(),
+        cache.len(),
+        metadata.len() as f64 / 1024.0
+    ));
+    if cache.is_empty() {
+        output::print_info("Only completed and unconverged jobs are cached");
+    }
+
+    Ok(())
+}
@@ -1,17 +1,34 @@
 //! # DFT 后处理子命令实现
 //!
-//! 对已完成并可解析的 DFT 结果进行排序、导出与可选绘图。
+//! 对已完成并可解析的 DFT 结果按总焓、每原子焓或每化学式单元焓排序（可按组成分组，混合代码时按代码分别排序），导出与可选绘图；
+//! 几何优化未收敛的结果默认排除，可选择纳入并标记；
+//! 提供原始 EDDP `.res` 集合时，按结构名配对并输出 EDDP–DFT 一致性统计与图像；
+//! `--job-dir` 指向 `archive create` 生成的归档目录时，不解压、直接从归档读取结果与结构；
+//! `--output-format json|jsonl|csv` 时输出完整排名记录。
 //!
 //! ## 依赖关系
 //! - 使用 `cli/analyze.rs` 定义的参数
-//! - 复用 `dft/` 扫描模块与 `utils/output.rs`
-
-use crate::cli::analyze::DftPostprocessingArgs;
-use crate::dft::scan_calculations;
+//! - 复用 `dft/` 扫描模块（失败规则取内置及用户/项目规则文件）、归档读取与一致性统计、`parsers/`（结构文件与 `.res` 集合）、`utils/output.rs`
+//! - 使用 `plotters` 渲染排序图、parity 图与秩对比图，经 `report/plot.rs` 的 `save_plot` 按扩展名写为 SVG 或位图
+//! - 使用 `rayon` 并行读取结构文件，`utils/parallel.rs` 配置 `-j/--jobs` 并行度，`utils/structured.rs` 输出记录
+
+use crate::cli::analyze::{DftPostprocessingArgs, RankMode};
+use crate::dft::{
+    parity_stats, read_archived_records, scan_calculations_with_rules, CodeSelection, FailureRules,
+    JobLayout, Manifest, ParityPair, ParityStats, ScanCache,
+};
 use crate::error::{QutilityError, Result};
-use crate::models::{CalculationStatus, DftCodeType, DftResult};
-use crate::utils::output;
-
+use crate::models::{CalculationScanRecord, CalculationStatus, Crystal, DftCodeType, DftResult};
+use crate::parsers::parse_structure_file;
+use crate::parsers::res::parse_res_collection;
+use crate::report::plot::{save_plot, PlotFigure};
+use crate::utils::{output, parallel, structured};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::Path;
 use tabled::{Table, Tabled};
 
@@ -22,26 +39,124 @@ struct ResultRow {
     rank: usize,
     #[tabled(rename = "Structure")]
     structure: String,
-    #[tabled(rename = "Enthalpy (eV)")]
+    #[tabled(rename = "Formula")]
+    formula: String,
+    #[tabled(rename = "Enthalpy")]
     enthalpy: String,
-    #[tabled(rename = "ΔH (eV)")]
+    #[tabled(rename = "ΔH")]
     delta_h: String,
+    #[tabled(rename = "Note")]
+    note: String,
+}
+
+/// 带组成信息的已完成结果
+#[derive(Debug, Clone)]
+struct RankedEntry {
+    result: DftResult,
+    /// 晶胞化学式
+    formula: Option<String>,
+    /// 约化化学式
+    composition: Option<String>,
+    /// 化学式单元数 Z
+    formula_units: Option<usize>,
+}
+
+impl RankedEntry {
+    /// 结构优先取已解析的晶体（归档中读取），否则解析作业目录中的结构文件
+    fn from_record(
+        result: DftResult,
+        structure_file: Option<&Path>,
+        crystal: Option<Crystal>,
+    ) -> Self {
+        let crystal =
+            crystal.or_else(|| structure_file.and_then(|path| parse_structure_file(path).ok()));
+        let mut result = result;
+        let (formula, composition, formula_units) = match crystal {
+            Some(crystal) if !crystal.atoms.is_empty() => {
+                result.num_atoms.get_or_insert(crystal.atoms.len());
+                let (reduced, z) = crystal.reduced_formula();
+                (Some(crystal.formula()), Some(reduced), Some(z))
+            }
+            _ => (None, None, None),
+        };
+
+        Self {
+            result,
+            formula,
+            composition,
+            formula_units,
+        }
+    }
+
+    /// 按排序模式取焓值
+    fn value(&self, mode: RankMode) -> Option<f64> {
+        match mode {
+            RankMode::Total => self.result.enthalpy_ev,
+            RankMode::PerAtom => self.result.enthalpy_per_atom(),
+            RankMode::PerFormulaUnit => self.enthalpy_per_formula_unit(),
+        }
+    }
+
+    fn enthalpy_per_formula_unit(&self) -> Option<f64> {
+        Some(self.result.enthalpy_ev? / self.formula_units? as f64)
+    }
+
+    fn volume_per_atom(&self) -> Option<f64> {
+        match (self.result.volume, self.result.num_atoms) {
+            (Some(v), Some(n)) if n > 0 => Some(v / n as f64),
+            _ => None,
+        }
+    }
+}
+
+/// `--output-format` 记录：完整排名中的每个结构一条，焓值为 eV、eV/atom 或 eV/f.u.
+#[derive(Debug, Serialize)]
+struct RankedRecord {
+    rank: usize,
+    /// 组成或代码分组标签；未分组时为空
+    group: Option<String>,
+    structure: String,
+    code: String,
+    ranked_by: String,
+    enthalpy_ev: Option<f64>,
+    enthalpy_per_atom_ev: Option<f64>,
+    enthalpy_per_fu_ev: Option<f64>,
+    /// 相对组内最低值的差，单位与 `ranked_by` 一致
+    delta_h: f64,
+    num_atoms: Option<usize>,
+    volume_per_atom_a3: Option<f64>,
+    pressure_gpa: Option<f64>,
+    formula: Option<String>,
+    composition: Option<String>,
+    geometry_converged: Option<bool>,
+    max_force_ev_per_a: Option<f64>,
+    max_stress_gpa: Option<f64>,
+}
+
+/// EDDP–DFT 一致性统计表
+#[derive(Debug, Clone, Tabled)]
+struct ParityRow {
+    #[tabled(rename = "Metric")]
+    metric: String,
+    #[tabled(rename = "Value")]
+    value: String,
 }
 
 pub fn execute(args: DftPostprocessingArgs) -> Result<()> {
     output::print_header("DFT Postprocessing");
 
-    let code: DftCodeType = args.code.into();
-    let records = scan_calculations(&args.job_dir, code)?;
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let records = load_records(&args, &layout)?;
 
     let parse_error_count = records
         .iter()
-        .filter(|record| record.status == CalculationStatus::ParseError)
+        .filter(|(record, _)| record.status == CalculationStatus::ParseError)
         .count();
 
     let completed_without_enthalpy = records
         .iter()
-        .filter(|record| {
+        .filter(|(record, _)| {
             record.status == CalculationStatus::Completed
                 && record
                     .parsed
@@ -51,31 +166,43 @@ pub fn execute(args: DftPostprocessingArgs) -> Result<()> {
         })
         .count();
 
-    let mut results: Vec<DftResult> = records
-        .into_iter()
-        .filter_map(|record| match (record.status, record.parsed) {
-            (CalculationStatus::Completed, Some(result)) if result.enthalpy_ev.is_some() => {
-                Some(result)
-            }
+    let unconverged_count = records
+        .iter()
+        .filter(|(record, _)| record.status == CalculationStatus::Unconverged)
+        .count();
+
+    let entries: Vec<RankedEntry> = records
+        .into_par_iter()
+        .filter(|(record, _)| {
+            record.status == CalculationStatus::Completed
+                || (args.include_unconverged && record.status == CalculationStatus::Unconverged)
+        })
+        .filter_map(|(record, crystal)| match record.parsed {
+            Some(result) if result.enthalpy_ev.is_some() => Some(RankedEntry::from_record(
+                result,
+                record.structure_file.as_deref(),
+                crystal,
+            )),
             _ => None,
         })
         .collect();
 
-    if results.is_empty() {
+    if entries.is_empty() {
         output::print_warning("No completed DFT calculations found with valid enthalpy.");
         return Ok(());
     }
 
-    results.sort_by(|a, b| {
-        a.enthalpy_ev
-            .partial_cmp(&b.enthalpy_ev)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-
     output::print_info(&format!(
         "Found {} completed calculations with valid enthalpy",
-        results.len()
+        entries.len()
     ));
+    let codes = codes_present(&entries);
+    if codes.len() > 1 {
+        for code in &codes {
+            let count = entries.iter().filter(|e| e.result.code == *code).count();
+            output::print_info(&format!("  {code}: {count}"));
+        }
+    }
 
     if completed_without_enthalpy > 0 {
         output::print_warning(&format!(
@@ -91,29 +218,81 @@ pub fn execute(args: DftPostprocessingArgs) -> Result<()> {
         ));
     }
 
-    let min_enthalpy = results[0].enthalpy_ev.expect("validated before sorting");
-    let table_rows: Vec<ResultRow> = results
+    if unconverged_count > 0 {
+        if args.include_unconverged {
+            output::print_warning(&format!(
+                "{} unconverged geometry optimisations are included and marked",
+                unconverged_count
+            ));
+        } else {
+            output::print_warning(&format!(
+                "{} unconverged geometry optimisations were skipped (use --include-unconverged to rank them)",
+                unconverged_count
+            ));
+        }
+    }
+
+    let mode = args.rank_by;
+    let (mut ranked, unrankable): (Vec<RankedEntry>, Vec<RankedEntry>) = entries
         .iter()
-        .take(args.top_n)
-        .enumerate()
-        .map(|(i, result)| {
-            let enthalpy = result.enthalpy_ev.expect("validated before sorting");
-            ResultRow {
-                rank: i + 1,
-                structure: result.structure_name.clone(),
-                enthalpy: format!("{enthalpy:.6}"),
-                delta_h: format!("{:.6}", enthalpy - min_enthalpy),
+        .cloned()
+        .partition(|entry| entry.value(mode).is_some());
+
+    if !unrankable.is_empty() {
+        output::print_warning(&format!(
+            "{} calculations were skipped because their {} enthalpy could not be determined (missing atom count or structure file)",
+            unrankable.len(),
+            mode
+        ));
+    }
+
+    if ranked.is_empty() {
+        output::print_warning(&format!(
+            "No calculations could be ranked by {mode} enthalpy."
+        ));
+        return Ok(());
+    }
+
+    ranked.sort_by(|a, b| {
+        let (a, b) = (a.value(mode), b.value(mode));
+        a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // 不同代码的焓值不可直接比较，混合作业树先按代码分组
+    let groups: Vec<(Option<String>, Vec<RankedEntry>)> = split_ranked_by_code(ranked)
+        .into_iter()
+        .flat_map(|(code, group)| {
+            if args.group_by_composition {
+                group_by_composition(group)
+                    .into_iter()
+                    .map(|(composition, group)| (group_label(composition, code), group))
+                    .collect()
+            } else {
+                vec![(group_label(None, code), group)]
             }
         })
         .collect();
 
-    output::print_header(&format!(
-        "Top {} Structures by DFT Enthalpy",
-        args.top_n.min(results.len())
-    ));
-    println!("{}", Table::new(&table_rows));
+    for (composition, group) in &groups {
+        let title = match composition {
+            Some(composition) => format!(
+                "Top {} {} Structures by DFT Enthalpy ({})",
+                args.top_n.min(group.len()),
+                composition,
+                mode
+            ),
+            None => format!(
+                "Top {} Structures by DFT Enthalpy ({})",
+                args.top_n.min(group.len()),
+                mode
+            ),
+        };
+        output::print_header(&title);
+        output::print_table(Table::new(result_rows(group, mode, args.top_n)));
+    }
 
-    save_results_csv(&results, &args.output_csv)?;
+    structured::emit(&ranked_records(&groups, mode))?;
+    save_results_csv(&groups, mode, &args.output_csv)?;
     output::print_success(&format!(
         "Full ranking saved to '{}'",
         args.output_csv.display()
@@ -121,7 +300,17 @@ pub fn execute(args: DftPostprocessingArgs) -> Result<()> {
 
     if !args.no_plot {
         if let Some(ref range) = args.plot_range {
-            generate_plot(&results, range, &args.output_plot)?;
+            let mut overall: Vec<&RankedEntry> =
+                groups.iter().flat_map(|(_, group)| group).collect();
+            overall.sort_by(|a, b| {
+                let (a, b) = (a.value(mode), b.value(mode));
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            let values: Vec<f64> = overall
+                .iter()
+                .map(|entry| entry.value(mode).expect("validated before sorting"))
+                .collect();
+            save_ranking_plot(&values, mode, range, &args.output_plot)?;
             output::print_success(&format!(
                 "Comparison plot saved to '{}'",
                 args.output_plot.display()
@@ -129,21 +318,369 @@ pub fn execute(args: DftPostprocessingArgs) -> Result<()> {
         }
     }
 
+    if let Some(ref eddp_res) = args.eddp_res {
+        let results: Vec<DftResult> = entries.into_iter().map(|entry| entry.result).collect();
+        run_parity_analysis(&results, eddp_res, &args)?;
+    }
+
     Ok(())
 }
 
-fn save_results_csv(results: &[DftResult], output_path: &Path) -> Result<()> {
-    let mut wtr = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+/// 读取作业记录：目录含归档清单时不解压、直接从归档读取，否则扫描作业目录。
+/// 归档读取时一并返回归档中已解析的结构
+fn load_records(
+    args: &DftPostprocessingArgs,
+    layout: &JobLayout,
+) -> Result<Vec<(CalculationScanRecord, Option<Crystal>)>> {
+    if Manifest::exists(&args.job_dir) {
+        let manifest = Manifest::load(&args.job_dir)?;
+        output::print_info(&format!(
+            "Reading {} archived jobs from '{}' without unpacking",
+            manifest.jobs.len(),
+            args.job_dir.display()
+        ));
+        let code: CodeSelection = args.code.into();
+        let records = read_archived_records(&args.job_dir, &manifest)?;
+        return Ok(records
+            .into_iter()
+            .filter(|(record, _)| match code {
+                CodeSelection::Fixed(code) => record.code == code,
+                CodeSelection::Auto => true,
+            })
+            .collect());
+    }
 
-    wtr.write_record(["dft_rank", "structure", "enthalpy_eV"])
-        .map_err(QutilityError::CsvError)?;
+    let rules = FailureRules::discover(&args.job_dir, None)?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let records = scan_calculations_with_rules(
+        &args.job_dir,
+        args.code.into(),
+        &rules,
+        layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+    Ok(records.into_iter().map(|record| (record, None)).collect())
+}
+
+/// 结果中出现的代码（VASP 在前）
+fn codes_present(entries: &[RankedEntry]) -> Vec<DftCodeType> {
+    [DftCodeType::Vasp, DftCodeType::Castep]
+        .into_iter()
+        .filter(|code| entries.iter().any(|entry| entry.result.code == *code))
+        .collect()
+}
+
+/// 按代码拆分已排序结果，组内保持排名顺序；只有一种代码时不加代码标签
+/// （与 `dft::group_by_code` 不同，拆分的是 `RankedEntry` 且单代码时不分组）
+fn split_ranked_by_code(ranked: Vec<RankedEntry>) -> Vec<(Option<DftCodeType>, Vec<RankedEntry>)> {
+    let codes = codes_present(&ranked);
+    if codes.len() <= 1 {
+        return vec![(None, ranked)];
+    }
+
+    let mut groups: Vec<(Option<DftCodeType>, Vec<RankedEntry>)> = codes
+        .into_iter()
+        .map(|code| (Some(code), Vec::new()))
+        .collect();
+    for entry in ranked {
+        if let Some((_, group)) = groups
+            .iter_mut()
+            .find(|(code, _)| *code == Some(entry.result.code))
+        {
+            group.push(entry);
+        }
+    }
+    groups
+}
+
+fn group_label(composition: Option<String>, code: Option<DftCodeType>) -> Option<String> {
+    match (composition, code) {
+        (Some(composition), Some(code)) => Some(format!("{composition} {code}")),
+        (composition, code) => composition.or_else(|| code.map(|code| code.to_string())),
+    }
+}
+
+/// 按约化化学式分组；组内保持已排好的顺序，组按最低焓排序，无法确定组成的结构归入最后一组
+fn group_by_composition(ranked: Vec<RankedEntry>) -> Vec<(Option<String>, Vec<RankedEntry>)> {
+    let mut groups: Vec<(Option<String>, Vec<RankedEntry>)> = Vec::new();
+
+    for entry in ranked {
+        let key = Some(
+            entry
+                .composition
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        );
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => group.push(entry),
+            None => groups.push((key, vec![entry])),
+        }
+    }
+
+    if let Some(pos) = groups
+        .iter()
+        .position(|(k, _)| k.as_deref() == Some("unknown"))
+    {
+        let unknown = groups.remove(pos);
+        groups.push(unknown);
+    }
+
+    groups
+}
+
+fn result_rows(group: &[RankedEntry], mode: RankMode, top_n: usize) -> Vec<ResultRow> {
+    let unit = enthalpy_unit(mode);
+    let min_value = group[0].value(mode).expect("validated before sorting");
+
+    group
+        .iter()
+        .take(top_n)
+        .enumerate()
+        .map(|(i, entry)| {
+            let value = entry.value(mode).expect("validated before sorting");
+            ResultRow {
+                rank: i + 1,
+                structure: entry.result.structure_name.clone(),
+                formula: entry.formula.clone().unwrap_or_else(|| "-".to_string()),
+                enthalpy: format!("{value:.6} {unit}"),
+                delta_h: format!("{:.6} {unit}", value - min_value),
+                note: if entry.result.geometry_converged == Some(false) {
+                    "unconverged".to_string()
+                } else {
+                    String::new()
+                },
+            }
+        })
+        .collect()
+}
+
+fn ranked_records(
+    groups: &[(Option<String>, Vec<RankedEntry>)],
+    mode: RankMode,
+) -> Vec<RankedRecord> {
+    groups
+        .iter()
+        .flat_map(|(label, group)| {
+            let min_value = group[0].value(mode).expect("validated before sorting");
+            group.iter().enumerate().map(move |(i, entry)| {
+                let result = &entry.result;
+                RankedRecord {
+                    rank: i + 1,
+                    group: label.clone(),
+                    structure: result.structure_name.clone(),
+                    code: result.code.to_string(),
+                    ranked_by: mode.to_string(),
+                    enthalpy_ev: result.enthalpy_ev,
+                    enthalpy_per_atom_ev: result.enthalpy_per_atom(),
+                    enthalpy_per_fu_ev: entry.enthalpy_per_formula_unit(),
+                    delta_h: entry.value(mode).expect("validated before sorting") - min_value,
+                    num_atoms: result.num_atoms,
+                    volume_per_atom_a3: entry.volume_per_atom(),
+                    pressure_gpa: result.pressure_kbar.map(|kbar| kbar * 0.1),
+                    formula: entry.formula.clone(),
+                    composition: entry.composition.clone(),
+                    geometry_converged: result.geometry_converged,
+                    max_force_ev_per_a: result.max_force_ev_per_a,
+                    max_stress_gpa: result.max_stress_gpa,
+                }
+            })
+        })
+        .collect()
+}
+
+fn enthalpy_unit(mode: RankMode) -> &'static str {
+    match mode {
+        RankMode::Total => "eV",
+        RankMode::PerAtom => "eV/atom",
+        RankMode::PerFormulaUnit => "eV/f.u.",
+    }
+}
+
+/// 读取 EDDP 集合并与 DFT 结果按结构名配对，输出统计、CSV 与图像
+fn run_parity_analysis(
+    results: &[DftResult],
+    eddp_res: &Path,
+    args: &DftPostprocessingArgs,
+) -> Result<()> {
+    output::print_header("EDDP vs DFT Parity");
+
+    let crystals = parse_res_collection(eddp_res)?;
+    let eddp: HashMap<String, f64> = crystals
+        .iter()
+        .filter_map(|crystal| Some((crystal.name.clone(), crystal.enthalpy_per_atom()?)))
+        .collect();
+
+    output::print_info(&format!(
+        "Loaded {} EDDP structures ({} with enthalpy) from '{}'",
+        crystals.len(),
+        eddp.len(),
+        eddp_res.display()
+    ));
 
-    for (i, result) in results.iter().enumerate() {
-        let enthalpy = result.enthalpy_ev.expect("validated before writing");
+    let mut pairs = Vec::new();
+    let mut unmatched = 0;
+    for result in results {
+        match (eddp.get(&result.structure_name), result.enthalpy_per_atom()) {
+            (Some(&eddp_per_atom), Some(dft_per_atom)) => pairs.push(ParityPair {
+                structure_name: result.structure_name.clone(),
+                eddp_per_atom,
+                dft_per_atom,
+            }),
+            _ => unmatched += 1,
+        }
+    }
+
+    if unmatched > 0 {
+        output::print_warning(&format!(
+            "{} DFT results have no matching EDDP structure or atom count and were skipped",
+            unmatched
+        ));
+    }
+
+    let Some(stats) = parity_stats(&pairs, args.top_n) else {
+        output::print_warning("At least 2 matched structures are required for parity analysis.");
+        return Ok(());
+    };
+
+    print_parity_stats(&stats);
+
+    let eddp_ranks = ranks_by(&pairs, |p| p.eddp_per_atom);
+    let dft_ranks = ranks_by(&pairs, |p| p.dft_per_atom);
+
+    save_parity_csv(&pairs, &eddp_ranks, &dft_ranks, &args.parity_csv)?;
+    output::print_success(&format!(
+        "Parity data saved to '{}'",
+        args.parity_csv.display()
+    ));
+
+    if !args.no_plot {
+        let points: Vec<(f64, f64)> = pairs
+            .iter()
+            .map(|p| (p.eddp_per_atom, p.dft_per_atom))
+            .collect();
+        save_plot(
+            &ScatterPlot {
+                points: &points,
+                labels: ScatterLabels {
+                    caption: "EDDP vs DFT Enthalpy",
+                    x_desc: "EDDP enthalpy (eV/atom)",
+                    y_desc: "DFT enthalpy (eV/atom)",
+                },
+            },
+            &args.parity_plot,
+        )?;
+        output::print_success(&format!(
+            "Parity plot saved to '{}'",
+            args.parity_plot.display()
+        ));
+
+        let rank_points: Vec<(f64, f64)> = eddp_ranks
+            .iter()
+            .zip(&dft_ranks)
+            .map(|(e, d)| (*e as f64, *d as f64))
+            .collect();
+        save_plot(
+            &ScatterPlot {
+                points: &rank_points,
+                labels: ScatterLabels {
+                    caption: "EDDP Rank vs DFT Rank",
+                    x_desc: "EDDP rank",
+                    y_desc: "DFT rank",
+                },
+            },
+            &args.rank_plot,
+        )?;
+        output::print_success(&format!(
+            "Rank comparison plot saved to '{}'",
+            args.rank_plot.display()
+        ));
+    }
+
+    Ok(())
+}
+
+fn print_parity_stats(stats: &ParityStats) {
+    let rows = vec![
+        ParityRow {
+            metric: "Matched structures".to_string(),
+            value: stats.count.to_string(),
+        },
+        ParityRow {
+            metric: "MAE (meV/atom)".to_string(),
+            value: format!("{:.3}", stats.mae * 1000.0),
+        },
+        ParityRow {
+            metric: "RMSE (meV/atom)".to_string(),
+            value: format!("{:.3}", stats.rmse * 1000.0),
+        },
+        ParityRow {
+            metric: "Mean error EDDP-DFT (meV/atom)".to_string(),
+            value: format!("{:.3}", stats.mean_error * 1000.0),
+        },
+        ParityRow {
+            metric: "Spearman ρ".to_string(),
+            value: format!("{:.4}", stats.spearman),
+        },
+        ParityRow {
+            metric: "Kendall τ".to_string(),
+            value: format!("{:.4}", stats.kendall),
+        },
+        ParityRow {
+            metric: format!("DFT top-{} ∩ EDDP top-{}", stats.top_n, stats.top_n),
+            value: format!("{}/{}", stats.top_n_hits, stats.top_n),
+        },
+    ];
+
+    output::print_table(Table::new(rows));
+}
+
+/// 按给定键升序排序后的名次（从 1 开始）
+fn ranks_by(pairs: &[ParityPair], key: impl Fn(&ParityPair) -> f64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..pairs.len()).collect();
+    order.sort_by(|&a, &b| key(&pairs[a]).total_cmp(&key(&pairs[b])));
+
+    let mut ranks = vec![0; pairs.len()];
+    for (rank, idx) in order.into_iter().enumerate() {
+        ranks[idx] = rank + 1;
+    }
+    ranks
+}
+
+fn save_parity_csv(
+    pairs: &[ParityPair],
+    eddp_ranks: &[usize],
+    dft_ranks: &[usize],
+    output_path: &Path,
+) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+
+    wtr.write_record([
+        "structure",
+        "eddp_enthalpy_per_atom_eV",
+        "dft_enthalpy_per_atom_eV",
+        "error_meV_per_atom",
+        "eddp_rank",
+        "dft_rank",
+    ])
+    .map_err(QutilityError::CsvError)?;
+
+    let mut order: Vec<usize> = (0..pairs.len()).collect();
+    order.sort_by_key(|&i| dft_ranks[i]);
+
+    for i in order {
+        let pair = &pairs[i];
         wtr.write_record([
-            (i + 1).to_string(),
-            result.structure_name.clone(),
-            format!("{enthalpy:.10}"),
+            pair.structure_name.clone(),
+            format!("{:.10}", pair.eddp_per_atom),
+            format!("{:.10}", pair.dft_per_atom),
+            format!("{:.4}", (pair.eddp_per_atom - pair.dft_per_atom) * 1000.0),
+            eddp_ranks[i].to_string(),
+            dft_ranks[i].to_string(),
         ])
         .map_err(QutilityError::CsvError)?;
     }
@@ -156,26 +693,182 @@ fn save_results_csv(results: &[DftResult], output_path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn generate_plot(results: &[DftResult], range: &str, output_path: &Path) -> Result<()> {
-    use plotters::prelude::*;
+/// 散点图坐标轴说明
+struct ScatterLabels {
+    caption: &'static str,
+    x_desc: &'static str,
+    y_desc: &'static str,
+}
+
+/// 带 y = x 参考线的散点图
+struct ScatterPlot<'a> {
+    points: &'a [(f64, f64)],
+    labels: ScatterLabels,
+}
+
+impl PlotFigure for ScatterPlot<'_> {
+    const SIZE: (u32, u32) = (800, 800);
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        draw_scatter_chart(root, self.points, &self.labels)
+    }
+}
+
+fn draw_scatter_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    points: &[(f64, f64)],
+    labels: &ScatterLabels,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    // 两轴共用同一范围，保证 y = x 为对角线
+    let (lo, hi) = points
+        .iter()
+        .flat_map(|(x, y)| [*x, *y])
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let margin = ((hi - lo).abs() * 0.05).max(1e-6);
+    let (lo, hi) = (lo - margin, hi + margin);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(labels.caption, ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(45)
+        .y_label_area_size(80)
+        .build_cartesian_2d(lo..hi, lo..hi)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
 
+    chart
+        .configure_mesh()
+        .x_desc(labels.x_desc)
+        .y_desc(labels.y_desc)
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    chart
+        .draw_series(LineSeries::new(
+            [(lo, lo), (hi, hi)],
+            BLACK.mix(0.5).stroke_width(1),
+        ))
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?
+        .label("y = x")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK.mix(0.5)));
+
+    chart
+        .draw_series(
+            points
+                .iter()
+                .map(|(x, y)| Circle::new((*x, *y), 4, BLUE.mix(0.7).filled())),
+        )
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?
+        .label("Structures")
+        .legend(|(x, y)| Circle::new((x + 10, y), 4, BLUE.filled()));
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    Ok(())
+}
+
+fn save_results_csv(
+    groups: &[(Option<String>, Vec<RankedEntry>)],
+    mode: RankMode,
+    output_path: &Path,
+) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+
+    wtr.write_record([
+        "dft_rank",
+        "structure",
+        "enthalpy_eV",
+        "enthalpy_per_atom_eV",
+        "enthalpy_per_fu_eV",
+        "ranked_by",
+        "natoms",
+        "volume_per_atom_A3",
+        "pressure_GPa",
+        "formula",
+        "composition",
+        "geometry_converged",
+        "max_force_eV_per_A",
+        "max_stress_GPa",
+        "code",
+    ])
+    .map_err(QutilityError::CsvError)?;
+
+    let fmt = |value: Option<f64>, precision: usize| {
+        value
+            .map(|v| format!("{v:.precision$}"))
+            .unwrap_or_default()
+    };
+
+    for (_, group) in groups {
+        for (i, entry) in group.iter().enumerate() {
+            let result = &entry.result;
+            wtr.write_record([
+                (i + 1).to_string(),
+                result.structure_name.clone(),
+                fmt(result.enthalpy_ev, 10),
+                fmt(result.enthalpy_per_atom(), 10),
+                fmt(entry.enthalpy_per_formula_unit(), 10),
+                mode.to_string(),
+                result.num_atoms.map(|n| n.to_string()).unwrap_or_default(),
+                fmt(entry.volume_per_atom(), 6),
+                fmt(result.pressure_kbar.map(|kbar| kbar * 0.1), 6),
+                entry.formula.clone().unwrap_or_default(),
+                entry.composition.clone().unwrap_or_default(),
+                result
+                    .geometry_converged
+                    .map(|converged| converged.to_string())
+                    .unwrap_or_default(),
+                fmt(result.max_force_ev_per_a, 6),
+                fmt(result.max_stress_gpa, 6),
+                result.code.to_string(),
+            ])
+            .map_err(QutilityError::CsvError)?;
+        }
+    }
+
+    wtr.flush().map_err(|e| QutilityError::FileWriteError {
+        path: output_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(())
+}
+
+/// 按名次绘制 `--plot-range` 范围内的焓，标出范围内最低者
+fn save_ranking_plot(
+    values: &[f64],
+    mode: RankMode,
+    range: &str,
+    output_path: &Path,
+) -> Result<()> {
     let (start, end) = parse_range(range)?;
     let start_idx = start.saturating_sub(1);
-    let end_idx = end.min(results.len());
+    let end_idx = end.min(values.len());
 
     if start_idx >= end_idx {
         return Err(QutilityError::InvalidRange(range.to_string()));
     }
 
-    let plot_data: Vec<(usize, f64)> = results[start_idx..end_idx]
+    let plot_data: Vec<(usize, f64)> = values[start_idx..end_idx]
         .iter()
         .enumerate()
-        .map(|(i, result)| {
-            (
-                start + i,
-                result.enthalpy_ev.expect("validated before plotting"),
-            )
-        })
+        .map(|(i, value)| (start + i, *value))
         .collect();
 
     let y_min = plot_data
@@ -188,72 +881,92 @@ fn generate_plot(results: &[DftResult], range: &str, output_path: &Path) -> Resu
         .fold(f64::NEG_INFINITY, f64::max);
     let y_margin = (y_max - y_min).abs() * 0.1;
 
-    let root = BitMapBackend::new(output_path, (800, 600)).into_drawing_area();
-    root.fill(&WHITE)
-        .map_err(|e| QutilityError::Other(e.to_string()))?;
+    let plot = RankingPlot {
+        points: plot_data,
+        x_range: (start as f64 - 0.5)..(end as f64 + 0.5),
+        y_range: (y_min - y_margin)..(y_max + y_margin),
+        mode,
+    };
+    save_plot(&plot, output_path)
+}
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption("DFT Enthalpy Comparison", ("sans-serif", 24))
-        .margin(20)
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(
-            (start as f64 - 0.5)..(end as f64 + 0.5),
-            (y_min - y_margin)..(y_max + y_margin),
-        )
-        .map_err(|e| QutilityError::Other(e.to_string()))?;
+/// 名次–焓对比图
+struct RankingPlot {
+    points: Vec<(usize, f64)>,
+    x_range: std::ops::Range<f64>,
+    y_range: std::ops::Range<f64>,
+    mode: RankMode,
+}
 
-    chart
-        .configure_mesh()
-        .x_desc("Rank")
-        .y_desc("Enthalpy (eV)")
-        .draw()
-        .map_err(|e| QutilityError::Other(e.to_string()))?;
+impl PlotFigure for RankingPlot {
+    const SIZE: (u32, u32) = (800, 600);
 
-    chart
-        .draw_series(
-            plot_data
-                .iter()
-                .map(|(x, y)| Circle::new((*x as f64, *y), 5, RED.filled())),
-        )
-        .map_err(|e| QutilityError::Other(e.to_string()))?
-        .label("DFT Enthalpy")
-        .legend(|(x, y)| Circle::new((x + 10, y), 5, RED.filled()));
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        let plot_data = &self.points;
 
-    chart
-        .draw_series(LineSeries::new(
-            plot_data.iter().map(|(x, y)| (*x as f64, *y)),
-            RED.stroke_width(2),
-        ))
-        .map_err(|e| QutilityError::Other(e.to_string()))?;
+        root.fill(&WHITE)
+            .map_err(|e| QutilityError::Other(e.to_string()))?;
+
+        let mut chart = ChartBuilder::on(root)
+            .caption("DFT Enthalpy Comparison", ("sans-serif", 24))
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(self.x_range.clone(), self.y_range.clone())
+            .map_err(|e| QutilityError::Other(e.to_string()))?;
 
-    if let Some((min_x, min_y)) = plot_data
-        .iter()
-        .min_by(|a, b| a.1.partial_cmp(&b.1).expect("finite enthalpy"))
-    {
         chart
-            .draw_series(std::iter::once(Circle::new(
-                (*min_x as f64, *min_y),
-                8,
-                GREEN.filled(),
-            )))
-            .map_err(|e| QutilityError::Other(e.to_string()))?
-            .label("Lowest in Range")
-            .legend(|(x, y)| Circle::new((x + 10, y), 5, GREEN.filled()));
-    }
+            .configure_mesh()
+            .x_desc("Rank")
+            .y_desc(format!("Enthalpy ({})", enthalpy_unit(self.mode)))
+            .draw()
+            .map_err(|e| QutilityError::Other(e.to_string()))?;
 
-    chart
-        .configure_series_labels()
-        .position(SeriesLabelPosition::UpperRight)
-        .background_style(WHITE.mix(0.8))
-        .border_style(BLACK)
-        .draw()
-        .map_err(|e| QutilityError::Other(e.to_string()))?;
+        chart
+            .draw_series(
+                plot_data
+                    .iter()
+                    .map(|(x, y)| Circle::new((*x as f64, *y), 5, RED.filled())),
+            )
+            .map_err(|e| QutilityError::Other(e.to_string()))?
+            .label("DFT Enthalpy")
+            .legend(|(x, y)| Circle::new((x + 10, y), 5, RED.filled()));
 
-    root.present()
-        .map_err(|e| QutilityError::Other(e.to_string()))?;
+        chart
+            .draw_series(LineSeries::new(
+                plot_data.iter().map(|(x, y)| (*x as f64, *y)),
+                RED.stroke_width(2),
+            ))
+            .map_err(|e| QutilityError::Other(e.to_string()))?;
+
+        if let Some((min_x, min_y)) = plot_data
+            .iter()
+            .min_by(|a, b| a.1.partial_cmp(&b.1).expect("finite enthalpy"))
+        {
+            chart
+                .draw_series(std::iter::once(Circle::new(
+                    (*min_x as f64, *min_y),
+                    8,
+                    GREEN.filled(),
+                )))
+                .map_err(|e| QutilityError::Other(e.to_string()))?
+                .label("Lowest in Range")
+                .legend(|(x, y)| Circle::new((x + 10, y), 5, GREEN.filled()));
+        }
 
-    Ok(())
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(|e| QutilityError::Other(e.to_string()))?;
+
+        Ok(())
+    }
 }
 
 fn parse_range(range: &str) -> Result<(usize, usize)> {
@@ -1,17 +1,25 @@
 //! # DFT 状态扫描子命令实现
 //!
-//! 扫描 VASP/CASTEP 作业状态，并输出可重算结构清单。
+//! 扫描 VASP/CASTEP 作业状态（失败原因来自可配置规则文件），可选查询调度器（Slurm、PBS/Torque、LSF、SGE）队列区分运行中与已终止的作业；
+//! `--code auto` 时按目录识别代码并分代码汇总；`--output-format json|jsonl|csv` 时逐作业输出状态记录。输出可重算结构清单，并可对重算候选自动纠错、备份旧文件后重新提交。
 //!
 //! ## 依赖关系
 //! - 使用 `cli/analyze.rs` 定义的参数
-//! - 复用 `dft/` 扫描模块与 `utils/output.rs`
+//! - 复用 `dft/` 扫描、状态细化与纠错模块、`utils/scheduler.rs` 队列查询与提交、`utils/command.rs`、`utils/output.rs`、`utils/parallel.rs`、`utils/structured.rs`
 
 use crate::cli::analyze::{DftStatusArgs, RetryListFormat};
-use crate::dft::{retry_candidates, scan_calculations, RetryScope};
+use crate::dft::{
+    apply_correction, apply_scheduler_states, apply_slurm_log_states, find_queued_job,
+    group_by_code, plan_correction, retry_candidates, scan_calculations_with_rules,
+    CorrectionDecision, CorrectionHistory, FailureRules, JobLayout, RetryScope, ScanCache,
+};
 use crate::error::{QutilityError, Result};
-use crate::models::{CalculationScanRecord, CalculationStatus, DftCodeType};
-use crate::utils::output;
+use crate::models::{CalculationScanRecord, CalculationStatus};
+use crate::utils::command::{CommandRunner, SystemCommandRunner};
+use crate::utils::scheduler::{submit_job_dir, Scheduler, SchedulerJob, SchedulerKind};
+use crate::utils::{output, parallel, structured};
 
+use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
@@ -25,21 +33,93 @@ struct StatusRow {
     count: usize,
 }
 
+#[derive(Debug, Clone, Tabled)]
+struct CorrectionRow {
+    #[tabled(rename = "Structure")]
+    structure: String,
+    #[tabled(rename = "Error")]
+    error: String,
+    #[tabled(rename = "Actions")]
+    actions: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
 #[derive(Debug, Clone, Tabled)]
 struct RetryRow {
     #[tabled(rename = "Structure")]
     structure: String,
+    #[tabled(rename = "Code")]
+    code: String,
     #[tabled(rename = "Status")]
     status: String,
+    #[tabled(rename = "Job ID")]
+    job_id: String,
     #[tabled(rename = "Reason")]
     reason: String,
+    #[tabled(rename = "Fix")]
+    fix: String,
+}
+
+/// `--output-format` 记录：每个作业目录一条
+#[derive(Debug, Serialize)]
+struct StatusRecord {
+    structure: String,
+    job_dir: String,
+    code: String,
+    status: String,
+    reason: Option<String>,
+    job_id: Option<String>,
+    suggested_fix: Option<String>,
+    retry: bool,
+    enthalpy_ev: Option<f64>,
+    energy_ev: Option<f64>,
+    num_atoms: Option<usize>,
+    geometry_converged: Option<bool>,
 }
 
 pub fn execute(args: DftStatusArgs) -> Result<()> {
     output::print_header("DFT Job Status");
 
-    let code: DftCodeType = args.code.into();
-    let records = scan_calculations(&args.job_dir, code)?;
+    if !args.job_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let rules = FailureRules::discover(&args.job_dir, args.rules.as_deref())?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let mut records = scan_calculations_with_rules(
+        &args.job_dir,
+        args.code.into(),
+        &rules,
+        &layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+
+    let scheduler = SchedulerKind::from(args.scheduler).scheduler();
+    let mut queued = Vec::new();
+    if args.queue {
+        let runner = SystemCommandRunner::new().with_overrides(args.scheduler_cmd.iter().cloned());
+        let user = args.user.clone().or_else(|| std::env::var("USER").ok());
+        let (live, history) = query_scheduler(
+            scheduler,
+            &runner,
+            user.as_deref(),
+            &args.sacct_since,
+            args.fix,
+        )?;
+        apply_scheduler_states(&mut records, &live, &history);
+        queued = live;
+    }
+    apply_slurm_log_states(&mut records, &queued);
+
     let retry_scope = if args.failed_only {
         RetryScope::FailedOnly
     } else {
@@ -50,29 +130,226 @@ pub fn execute(args: DftStatusArgs) -> Result<()> {
     output::print_info(&format!("Scanned {} job directories", records.len()));
     print_status_summary(&records);
     print_retry_candidates(&retry_records);
+    structured::emit(&status_records(&records, retry_scope))?;
 
     if let Some(output_path) = args.output.as_ref() {
         write_retry_list(output_path, &retry_records, args.format)?;
         output::print_success(&format!("Retry list saved to '{}'", output_path.display()));
     }
 
+    if args.fix {
+        let runner = SystemCommandRunner::new().with_overrides(args.scheduler_cmd.iter().cloned());
+        let submitter: Option<(&dyn Scheduler, &dyn CommandRunner)> =
+            args.resubmit.then_some((scheduler, &runner));
+        let rows = run_corrections(
+            &retry_records,
+            &queued,
+            args.max_attempts,
+            args.dry_run,
+            submitter,
+        );
+        print_corrections(&rows, args.dry_run);
+    }
+
     Ok(())
 }
 
+fn status_records(records: &[CalculationScanRecord], retry_scope: RetryScope) -> Vec<StatusRecord> {
+    records
+        .iter()
+        .map(|record| {
+            let parsed = record.parsed.as_ref();
+            StatusRecord {
+                structure: record.structure_name.clone(),
+                job_dir: record.job_dir.display().to_string(),
+                code: record.code.to_string(),
+                status: record.status.to_string(),
+                reason: record.reason.clone(),
+                job_id: record.job_id.clone(),
+                suggested_fix: record.suggested_fix.clone(),
+                retry: retry_scope.matches(record.status),
+                enthalpy_ev: parsed.and_then(|result| result.enthalpy_ev),
+                energy_ev: parsed.and_then(|result| result.energy_ev),
+                num_atoms: parsed.and_then(|result| result.num_atoms),
+                geometry_converged: parsed.and_then(|result| result.geometry_converged),
+            }
+        })
+        .collect()
+}
+
+/// 为重算候选规划并（非 dry-run 时）执行纠错，可选重新提交；仍在队列中的作业跳过
+fn run_corrections(
+    records: &[&CalculationScanRecord],
+    queued: &[SchedulerJob],
+    max_attempts: usize,
+    dry_run: bool,
+    submitter: Option<(&dyn Scheduler, &dyn CommandRunner)>,
+) -> Vec<CorrectionRow> {
+    records
+        .iter()
+        .map(|record| {
+            let mut row = CorrectionRow {
+                structure: record.structure_name.clone(),
+                error: String::new(),
+                actions: String::new(),
+                result: String::new(),
+            };
+
+            if let Some(job) = find_queued_job(record, queued) {
+                row.result = format!(
+                    "skipped: job {} is still {}",
+                    job.id,
+                    job.state.to_ascii_lowercase()
+                );
+                return row;
+            }
+
+            let history = match CorrectionHistory::load(&record.job_dir) {
+                Ok(history) => history,
+                Err(err) => {
+                    row.result = format!("error: {err}");
+                    return row;
+                }
+            };
+
+            let plan = match plan_correction(record, &history, max_attempts) {
+                CorrectionDecision::Apply(plan) => plan,
+                CorrectionDecision::Skip(reason) => {
+                    row.result = format!("skipped: {reason}");
+                    return row;
+                }
+            };
+
+            row.error = format!("{} (stage {})", plan.error, plan.stage);
+            row.actions = plan
+                .actions
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ");
+
+            if dry_run {
+                row.result = "planned".to_string();
+                return row;
+            }
+
+            row.result = match apply_correction(record, &plan) {
+                Ok(entry) => format!("applied, old files in {}", entry.backup),
+                Err(err) => format!("error: {err}"),
+            };
+
+            if let (Some((scheduler, runner)), true) =
+                (submitter, row.result.starts_with("applied"))
+            {
+                row.result = match submit_job_dir(scheduler, runner, &record.job_dir) {
+                    Ok(job_id) => format!("{}; resubmitted as {job_id}", row.result),
+                    Err(err) => format!("{}; resubmit failed: {err}", row.result),
+                };
+            }
+
+            row
+        })
+        .collect()
+}
+
+fn print_corrections(rows: &[CorrectionRow], dry_run: bool) {
+    output::print_header(if dry_run {
+        "Planned Corrections (dry run)"
+    } else {
+        "Corrections"
+    });
+
+    if rows.is_empty() {
+        output::print_info("No jobs to correct.");
+        return;
+    }
+
+    output::print_table(Table::new(rows));
+}
+
+/// 查询调度器队列与历史记录；任一查询失败时给出警告并视为无记录。
+///
+/// `require_queue` 时（`--fix`）队列查询失败直接报错，以免改写仍在运行的作业
+fn query_scheduler(
+    scheduler: &dyn Scheduler,
+    runner: &dyn CommandRunner,
+    user: Option<&str>,
+    since: &str,
+    require_queue: bool,
+) -> Result<(Vec<SchedulerJob>, Vec<SchedulerJob>)> {
+    let queued = match scheduler.query_queue(runner, user) {
+        Ok(queued) => queued,
+        Err(err) if require_queue => {
+            return Err(QutilityError::Other(format!(
+                "Cannot query the {} queue ({err}); refusing to apply corrections",
+                scheduler.name()
+            )));
+        }
+        Err(err) => {
+            output::print_warning(&format!("{} queue query failed: {err}", scheduler.name()));
+            Vec::new()
+        }
+    };
+    let history = scheduler
+        .query_history(runner, user, since)
+        .unwrap_or_else(|err| {
+            output::print_warning(&format!("{} history query failed: {err}", scheduler.name()));
+            Vec::new()
+        });
+
+    output::print_info(&format!(
+        "{} reports {} queued/running jobs and {} accounting records",
+        scheduler.name(),
+        queued.len(),
+        history.len()
+    ));
+
+    Ok((queued, history))
+}
+
+/// 打印状态汇总；混合代码的作业树按代码分别汇总
 fn print_status_summary(records: &[CalculationScanRecord]) {
-    let rows = vec![
+    let groups = group_by_code(records);
+    if groups.len() <= 1 {
+        let all: Vec<_> = records.iter().collect();
+        print_status_table("Status Summary", &all);
+        return;
+    }
+
+    for (code, group) in &groups {
+        print_status_table(&format!("Status Summary ({code})"), group);
+    }
+}
+
+fn print_status_table(title: &str, records: &[&CalculationScanRecord]) {
+    let mut rows = vec![
         status_row(records, CalculationStatus::Completed),
+        status_row(records, CalculationStatus::Unconverged),
         status_row(records, CalculationStatus::Failed),
         status_row(records, CalculationStatus::Incomplete),
         status_row(records, CalculationStatus::MissingOutput),
         status_row(records, CalculationStatus::ParseError),
     ];
 
-    output::print_header("Status Summary");
-    println!("{}", Table::new(rows));
+    // 调度器相关状态仅在出现时列出
+    rows.extend(
+        [
+            CalculationStatus::Running,
+            CalculationStatus::Pending,
+            CalculationStatus::TimedOut,
+            CalculationStatus::NodeFail,
+            CalculationStatus::OutOfMemory,
+        ]
+        .into_iter()
+        .map(|status| status_row(records, status))
+        .filter(|row| row.count > 0),
+    );
+
+    output::print_header(title);
+    output::print_table(Table::new(rows));
 }
 
-fn status_row(records: &[CalculationScanRecord], status: CalculationStatus) -> StatusRow {
+fn status_row(records: &[&CalculationScanRecord], status: CalculationStatus) -> StatusRow {
     StatusRow {
         status: status.to_string(),
         count: records
@@ -94,12 +371,15 @@ fn print_retry_candidates(records: &[&CalculationScanRecord]) {
         .iter()
         .map(|record| RetryRow {
             structure: record.structure_name.clone(),
+            code: record.code.to_string(),
             status: record.status.to_string(),
+            job_id: record.job_id.clone().unwrap_or_default(),
             reason: record.reason.clone().unwrap_or_default(),
+            fix: record.suggested_fix.clone().unwrap_or_default(),
         })
         .collect();
 
-    println!("{}", Table::new(rows));
+    output::print_table(Table::new(rows));
 }
 
 fn write_retry_list(
@@ -152,17 +432,12 @@ fn write_retry_list_csv(output_path: &Path, records: &[&CalculationScanRecord])
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::DftCodeType;
+    use crate::utils::scheduler::JOB_ID_FILE;
+    use crate::utils::slurm::Slurm;
+    use crate::utils::test_support::unique_test_dir;
     use std::fs;
     use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    fn unique_test_dir(name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time before UNIX_EPOCH")
-            .as_nanos();
-        std::env::temp_dir().join(format!("qutility-dft-status-{name}-{nanos}"))
-    }
 
     fn retry_records() -> Vec<CalculationScanRecord> {
         vec![
@@ -183,7 +458,7 @@ mod tests {
 
     #[test]
     fn writes_text_retry_list() {
-        let root = unique_test_dir("text");
+        let root = unique_test_dir("dft-status-text");
         fs::create_dir_all(&root).expect("create root");
         let output_path = root.join("retry.txt");
         let records = retry_records();
@@ -199,7 +474,7 @@ mod tests {
 
     #[test]
     fn writes_csv_retry_list() {
-        let root = unique_test_dir("csv");
+        let root = unique_test_dir("dft-status-csv");
         fs::create_dir_all(&root).expect("create root");
         let output_path = root.join("retry.csv");
         let records = retry_records();
@@ -212,4 +487,129 @@ mod tests {
 
         fs::remove_dir_all(&root).expect("cleanup");
     }
+
+    #[cfg(unix)]
+    fn write_stub(path: &Path, stdout: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::write(path, format!("#!/bin/sh\ncat <<'EOF'\n{stdout}EOF\n")).expect("write stub");
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stub_scheduler_commands_refine_incomplete_jobs() {
+        let root = unique_test_dir("dft-status-queue");
+        fs::create_dir_all(root.join("bin")).expect("create root");
+        let squeue = root.join("bin").join("squeue");
+        let sacct = root.join("bin").join("sacct");
+        write_stub(&squeue, "11|beta|RUNNING\n");
+        write_stub(
+            &sacct,
+            "10|alpha|TIMEOUT\n10.batch|batch|CANCELLED\n11|beta|RUNNING\n",
+        );
+
+        let runner = SystemCommandRunner::new()
+            .with_override("squeue", squeue.display().to_string())
+            .with_override("sacct", sacct.display().to_string());
+        let (queued, accounted) =
+            query_scheduler(&Slurm, &runner, None, "now-7days", true).expect("query");
+
+        let mut records = vec![
+            CalculationScanRecord::new(
+                "alpha",
+                root.join("alpha"),
+                DftCodeType::Vasp,
+                CalculationStatus::Incomplete,
+            ),
+            CalculationScanRecord::new(
+                "beta",
+                root.join("beta"),
+                DftCodeType::Vasp,
+                CalculationStatus::Incomplete,
+            ),
+        ];
+        apply_scheduler_states(&mut records, &queued, &accounted);
+
+        assert_eq!(records[0].status, CalculationStatus::TimedOut);
+        assert_eq!(records[0].job_id.as_deref(), Some("10"));
+        assert_eq!(records[1].status, CalculationStatus::Running);
+        assert_eq!(retry_candidates(&records, RetryScope::FailedOnly).len(), 1);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fix_corrects_and_resubmits_failed_jobs() {
+        let root = unique_test_dir("dft-status-fix");
+        let jobs = root.join("jobs");
+        let job_dir = jobs.join("alpha");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::create_dir_all(root.join("bin")).expect("create bin");
+        fs::write(job_dir.join("INCAR"), "IBRION = 2\n").expect("write INCAR");
+        fs::write(job_dir.join("POSCAR"), "initial\n").expect("write POSCAR");
+        fs::write(job_dir.join("CONTCAR"), "relaxed\n").expect("write CONTCAR");
+        fs::write(
+            job_dir.join("OUTCAR"),
+            "ZBRENT: fatal error in bracketing\n",
+        )
+        .expect("write OUTCAR");
+        fs::write(job_dir.join("submit.sbatch"), "#!/bin/bash\n").expect("write script");
+        let sbatch = root.join("bin").join("sbatch");
+        write_stub(&sbatch, "Submitted batch job 77\n");
+
+        let records = scan_calculations_with_rules(
+            &jobs,
+            DftCodeType::Vasp.into(),
+            &FailureRules::builtin(),
+            &JobLayout::default(),
+            None,
+        )
+        .expect("scan");
+        let candidates = retry_candidates(&records, RetryScope::FailedOnly);
+
+        // 队列中仍有同名作业时不动其目录
+        let running = vec![SchedulerJob {
+            id: "76".to_string(),
+            name: "alpha".to_string(),
+            state: "RUNNING".to_string(),
+        }];
+        let skipped = run_corrections(&candidates, &running, 3, false, None);
+        assert_eq!(skipped[0].result, "skipped: job 76 is still running");
+        assert!(job_dir.join("OUTCAR").exists());
+
+        let planned = run_corrections(&candidates, &[], 3, true, None);
+        assert_eq!(planned[0].result, "planned");
+        assert!(job_dir.join("OUTCAR").exists());
+
+        let runner =
+            SystemCommandRunner::new().with_override("sbatch", sbatch.display().to_string());
+        let rows = run_corrections(&candidates, &[], 3, false, Some((&Slurm, &runner)));
+        assert!(
+            rows[0].result.contains("resubmitted as 77"),
+            "{}",
+            rows[0].result
+        );
+        assert_eq!(
+            fs::read_to_string(job_dir.join(JOB_ID_FILE)).expect("read job id"),
+            "77\n"
+        );
+        assert_eq!(
+            fs::read_to_string(job_dir.join("POSCAR")).expect("read POSCAR"),
+            "relaxed\n"
+        );
+
+        let rescanned = scan_calculations_with_rules(
+            &jobs,
+            DftCodeType::Vasp.into(),
+            &FailureRules::builtin(),
+            &JobLayout::default(),
+            None,
+        )
+        .expect("rescan");
+        assert_eq!(rescanned[0].status, CalculationStatus::MissingOutput);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
 }
@@ -0,0 +1,374 @@
+//! # 状态方程拟合子命令实现
+//!
+//! 从一组 DFT 作业收集 E(V) 数据点，拟合状态方程并导出拟合曲线、H(P) 与图像。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/analyze.rs` 定义的 EosArgs
+//! - 复用 `dft/` 扫描模块与状态方程拟合、`utils/output.rs`
+//! - 使用 `plotters` 渲染 E(V) 图像，经 `report/plot.rs` 的 `save_plot` 按扩展名写为 SVG 或位图
+
+use crate::cli::analyze::EosArgs;
+use crate::dft::{fit_eos, scan_calculations, EosFit, EosForm, EV_PER_A3_TO_GPA};
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationStatus, DftCodeType};
+use crate::report::plot::{save_plot, PlotFigure};
+use crate::utils::output;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+use tabled::{Table, Tabled};
+
+/// E(V) 数据点
+#[derive(Debug, Clone)]
+struct EvPoint {
+    structure: String,
+    volume: f64,
+    energy: f64,
+    pressure_gpa: Option<f64>,
+}
+
+#[derive(Debug, Clone, Tabled)]
+struct PointRow {
+    #[tabled(rename = "Structure")]
+    structure: String,
+    #[tabled(rename = "V (Å³)")]
+    volume: String,
+    #[tabled(rename = "E (eV)")]
+    energy: String,
+    #[tabled(rename = "P (GPa)")]
+    pressure: String,
+}
+
+#[derive(Debug, Clone, Tabled)]
+struct FitRow {
+    #[tabled(rename = "EOS")]
+    form: String,
+    #[tabled(rename = "E0 (eV)")]
+    e0: String,
+    #[tabled(rename = "V0 (Å³)")]
+    v0: String,
+    #[tabled(rename = "B0 (GPa)")]
+    b0: String,
+    #[tabled(rename = "B0'")]
+    b0_prime: String,
+    #[tabled(rename = "RMS (meV)")]
+    rms: String,
+    #[tabled(rename = "Max |res| (meV)")]
+    max_res: String,
+}
+
+#[derive(Debug, Clone, Tabled)]
+struct EnthalpyRow {
+    #[tabled(rename = "EOS")]
+    form: String,
+    #[tabled(rename = "P (GPa)")]
+    pressure: String,
+    #[tabled(rename = "V (Å³)")]
+    volume: String,
+    #[tabled(rename = "H (eV)")]
+    enthalpy: String,
+}
+
+pub fn execute(args: EosArgs) -> Result<()> {
+    output::print_header("Equation of State Fitting");
+
+    let code: DftCodeType = args.code.into();
+    let records = scan_calculations(&args.job_dir, code)?;
+
+    let mut points: Vec<EvPoint> = records
+        .into_iter()
+        .filter(|record| record.status == CalculationStatus::Completed)
+        .filter_map(|record| {
+            let parsed = record.parsed?;
+            Some(EvPoint {
+                structure: record.structure_name,
+                volume: parsed.volume?,
+                energy: parsed.energy_ev?,
+                pressure_gpa: parsed.pressure_kbar.map(|kbar| kbar * 0.1),
+            })
+        })
+        .collect();
+
+    points.sort_by(|a, b| a.volume.total_cmp(&b.volume));
+
+    if points.len() < 4 {
+        return Err(QutilityError::InvalidArgument(format!(
+            "EOS fitting needs at least 4 completed jobs with energy and volume, found {}",
+            points.len()
+        )));
+    }
+
+    output::print_info(&format!("Collected {} E(V) points", points.len()));
+    print_points(&points);
+
+    let data: Vec<(f64, f64)> = points.iter().map(|p| (p.volume, p.energy)).collect();
+    let mut fits = Vec::new();
+    for model in &args.eos {
+        let form: EosForm = (*model).into();
+        match fit_eos(form, &data) {
+            Ok(fit) => fits.push(fit),
+            Err(err) => output::print_warning(&format!("{form} fit failed: {err}")),
+        }
+    }
+
+    if fits.is_empty() {
+        return Err(QutilityError::Other("All EOS fits failed".to_string()));
+    }
+
+    print_fits(&fits);
+
+    let v_min = points.first().map(|p| p.volume).expect("at least 4 points");
+    let v_max = points.last().map(|p| p.volume).expect("at least 4 points");
+
+    if !args.pressures.is_empty() {
+        print_enthalpies(&fits, &args.pressures, v_min, v_max);
+    }
+
+    let (curve_min, curve_max) = curve_range(v_min, v_max);
+    save_curve_csv(&fits, curve_min, curve_max, args.points, &args.output_csv)?;
+    output::print_success(&format!(
+        "Fitted curves saved to '{}'",
+        args.output_csv.display()
+    ));
+
+    if !args.no_plot {
+        let plot = EosPlot {
+            data: &data,
+            fits: &fits,
+            v_min: curve_min,
+            v_max: curve_max,
+        };
+        save_plot(&plot, &args.output_plot)?;
+        output::print_success(&format!(
+            "EOS plot saved to '{}'",
+            args.output_plot.display()
+        ));
+    }
+
+    Ok(())
+}
+
+fn print_points(points: &[EvPoint]) {
+    let rows: Vec<PointRow> = points
+        .iter()
+        .map(|p| PointRow {
+            structure: p.structure.clone(),
+            volume: format!("{:.4}", p.volume),
+            energy: format!("{:.6}", p.energy),
+            pressure: p
+                .pressure_gpa
+                .map(|gpa| format!("{gpa:.3}"))
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+
+    output::print_header("E(V) Data");
+    println!("{}", Table::new(rows));
+}
+
+fn print_fits(fits: &[EosFit]) {
+    let rows: Vec<FitRow> = fits
+        .iter()
+        .map(|fit| FitRow {
+            form: fit.form.to_string(),
+            e0: format!("{:.6}", fit.params.e0),
+            v0: format!("{:.4}", fit.params.v0),
+            b0: format!("{:.2}", fit.params.b0_gpa()),
+            b0_prime: format!("{:.3}", fit.params.b0_prime),
+            rms: format!("{:.3}", fit.rms_residual() * 1000.0),
+            max_res: format!("{:.3}", fit.max_abs_residual() * 1000.0),
+        })
+        .collect();
+
+    output::print_header("EOS Fit Parameters");
+    println!("{}", Table::new(rows));
+}
+
+fn print_enthalpies(fits: &[EosFit], pressures: &[f64], v_min: f64, v_max: f64) {
+    let mut rows = Vec::new();
+
+    for fit in fits {
+        for &pressure in pressures {
+            let row = match fit.volume_at_pressure(pressure, 0.5 * v_min, 1.5 * v_max) {
+                Some(volume) => EnthalpyRow {
+                    form: fit.form.to_string(),
+                    pressure: format!("{pressure:.3}"),
+                    volume: format!("{volume:.4}"),
+                    enthalpy: format!("{:.6}", fit.form.enthalpy(&fit.params, volume)),
+                },
+                None => EnthalpyRow {
+                    form: fit.form.to_string(),
+                    pressure: format!("{pressure:.3}"),
+                    volume: "-".to_string(),
+                    enthalpy: "out of range".to_string(),
+                },
+            };
+            rows.push(row);
+        }
+    }
+
+    output::print_header("Enthalpy from EOS Fit");
+    println!("{}", Table::new(rows));
+}
+
+/// 在数据范围两侧各外延 5% 作为曲线范围
+fn curve_range(v_min: f64, v_max: f64) -> (f64, f64) {
+    let margin = (v_max - v_min) * 0.05;
+    (v_min - margin, v_max + margin)
+}
+
+fn sample_volumes(v_min: f64, v_max: f64, n: usize) -> Vec<f64> {
+    let n = n.max(2);
+    (0..n)
+        .map(|i| v_min + (v_max - v_min) * i as f64 / (n - 1) as f64)
+        .collect()
+}
+
+fn save_curve_csv(
+    fits: &[EosFit],
+    v_min: f64,
+    v_max: f64,
+    n: usize,
+    output_path: &Path,
+) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+
+    wtr.write_record([
+        "eos",
+        "volume_A3",
+        "energy_eV",
+        "pressure_GPa",
+        "enthalpy_eV",
+    ])
+    .map_err(QutilityError::CsvError)?;
+
+    for fit in fits {
+        for volume in sample_volumes(v_min, v_max, n) {
+            let energy = fit.form.energy(&fit.params, volume);
+            let pressure = fit.form.pressure(&fit.params, volume);
+            wtr.write_record([
+                fit.form.to_string(),
+                format!("{volume:.6}"),
+                format!("{energy:.10}"),
+                format!("{:.6}", pressure * EV_PER_A3_TO_GPA),
+                format!("{:.10}", energy + pressure * volume),
+            ])
+            .map_err(QutilityError::CsvError)?;
+        }
+    }
+
+    wtr.flush().map_err(|e| QutilityError::FileWriteError {
+        path: output_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(())
+}
+
+/// E(V) 数据点与各形式的拟合曲线
+struct EosPlot<'a> {
+    data: &'a [(f64, f64)],
+    fits: &'a [EosFit],
+    v_min: f64,
+    v_max: f64,
+}
+
+impl PlotFigure for EosPlot<'_> {
+    const SIZE: (u32, u32) = (900, 650);
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        draw_eos_chart(root, self.data, self.fits, self.v_min, self.v_max)
+    }
+}
+
+fn draw_eos_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    data: &[(f64, f64)],
+    fits: &[EosFit],
+    v_min: f64,
+    v_max: f64,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    let curves: Vec<(EosForm, Vec<(f64, f64)>)> = fits
+        .iter()
+        .map(|fit| {
+            let curve = sample_volumes(v_min, v_max, 200)
+                .into_iter()
+                .map(|v| (v, fit.form.energy(&fit.params, v)))
+                .collect();
+            (fit.form, curve)
+        })
+        .collect();
+
+    let all_energies = data
+        .iter()
+        .map(|(_, e)| *e)
+        .chain(curves.iter().flat_map(|(_, c)| c.iter().map(|(_, e)| *e)));
+    let (e_min, e_max) = all_energies.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), e| {
+        (lo.min(e), hi.max(e))
+    });
+    let e_margin = ((e_max - e_min).abs() * 0.1).max(1e-6);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption("Equation of State Fit", ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(45)
+        .y_label_area_size(80)
+        .build_cartesian_2d(v_min..v_max, (e_min - e_margin)..(e_max + e_margin))
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Volume (Å³)")
+        .y_desc("Energy (eV)")
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    let palette = [
+        RGBColor(0, 102, 204),
+        RGBColor(220, 50, 47),
+        RGBColor(0, 153, 76),
+    ];
+
+    for (i, (form, curve)) in curves.iter().enumerate() {
+        let color = palette[i % palette.len()];
+        chart
+            .draw_series(LineSeries::new(
+                curve.iter().copied(),
+                color.stroke_width(2),
+            ))
+            .map_err(|e| QutilityError::Other(format!("{:?}", e)))?
+            .label(form.to_string())
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+    }
+
+    chart
+        .draw_series(
+            data.iter()
+                .map(|(v, e)| Circle::new((*v, *e), 5, BLACK.filled())),
+        )
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?
+        .label("DFT")
+        .legend(|(x, y)| Circle::new((x + 10, y), 5, BLACK.filled()));
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    Ok(())
+}
@@ -1,13 +1,18 @@
 //! # analyze 命令实现
 //!
-//! 分析功能统一入口，协调 DFT 状态扫描、DFT 后处理与 XRD 计算。
+//! 分析功能统一入口，协调 DFT 状态扫描、DFT 扫描缓存管理、DFT 后处理、收敛诊断、状态方程拟合、相变分析、资源用量统计与 XRD 计算。
 //!
 //! ## 依赖关系
 //! - 使用 `cli/analyze.rs` 定义的参数
-//! - 子模块: dft_status, dft_postprocessing, xrd
+//! - 子模块: dft_status, dft_cache, dft_postprocessing, convergence, eos, transitions, usage, xrd
 
+pub mod convergence;
+pub mod dft_cache;
 pub mod dft_postprocessing;
 pub mod dft_status;
+pub mod eos;
+pub mod transitions;
+pub mod usage;
 pub mod xrd;
 
 use crate::cli::analyze::{AnalyzeArgs, AnalyzeCommands};
@@ -18,6 +23,11 @@ pub fn execute(args: AnalyzeArgs) -> Result<()> {
     match args.command {
         AnalyzeCommands::DftStatus(status_args) => dft_status::execute(status_args),
         AnalyzeCommands::DftPostprocessing(post_args) => dft_postprocessing::execute(post_args),
+        AnalyzeCommands::DftCache(cache_args) => dft_cache::execute(cache_args),
+        AnalyzeCommands::Convergence(convergence_args) => convergence::execute(convergence_args),
+        AnalyzeCommands::Eos(eos_args) => eos::execute(eos_args),
+        AnalyzeCommands::Transitions(transitions_args) => transitions::execute(transitions_args),
+        AnalyzeCommands::Usage(usage_args) => usage::execute(usage_args),
         AnalyzeCommands::Xrd(xrd_args) => xrd::execute(xrd_args),
     }
 }
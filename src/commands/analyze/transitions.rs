@@ -0,0 +1,433 @@
+//! # 焓–压力相变分析子命令实现
+//!
+//! 扫描多个压力子目录，按结构名或正则匹配对齐各相，输出相对参考相的 ΔH(P) 与插值相变压力。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/analyze.rs` 定义的 TransitionsArgs
+//! - 复用 `dft/` 扫描模块与相变分析、`utils/output.rs`
+//! - 使用 `plotters` 渲染 ΔH(P) 图像，经 `report/plot.rs` 的 `save_plot` 按扩展名写为 SVG 或位图
+
+use crate::cli::analyze::TransitionsArgs;
+use crate::dft::{
+    find_crossings, relative_enthalpies, scan_calculations, stable_phase_transitions,
+    EnthalpyCrossing, PhaseSeries,
+};
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationStatus, DftCodeType};
+use crate::report::plot::{save_plot, PlotFigure};
+use crate::utils::output;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tabled::{Table, Tabled};
+
+/// 单个 H(P) 数据点
+#[derive(Debug, Clone)]
+struct PhasePoint {
+    phase: String,
+    structure: String,
+    pressure_gpa: f64,
+    enthalpy_per_atom: f64,
+}
+
+#[derive(Debug, Clone, Tabled)]
+struct DeltaRow {
+    #[tabled(rename = "Phase")]
+    phase: String,
+    #[tabled(rename = "P (GPa)")]
+    pressure: String,
+    #[tabled(rename = "H (eV/atom)")]
+    enthalpy: String,
+    #[tabled(rename = "ΔH (meV/atom)")]
+    delta_h: String,
+}
+
+#[derive(Debug, Clone, Tabled)]
+struct CrossingRow {
+    #[tabled(rename = "Low-P phase")]
+    low: String,
+    #[tabled(rename = "High-P phase")]
+    high: String,
+    #[tabled(rename = "P_t (GPa)")]
+    pressure: String,
+}
+
+pub fn execute(args: TransitionsArgs) -> Result<()> {
+    output::print_header("Enthalpy–Pressure Phase Transitions");
+
+    let code: DftCodeType = args.code.into();
+    let matcher = args
+        .phase_regex
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| QutilityError::InvalidArgument(format!("Invalid --phase-regex: {e}")))?;
+
+    let mut points = Vec::new();
+    let mut missing_pressure = 0;
+    let mut unmatched = 0;
+
+    for job_dir in &args.job_dirs {
+        let records = scan_calculations(job_dir, code)?;
+        output::print_info(&format!(
+            "Scanned {} job directories in '{}'",
+            records.len(),
+            job_dir.display()
+        ));
+
+        for record in records {
+            if record.status != CalculationStatus::Completed {
+                continue;
+            }
+            let Some(parsed) = record.parsed.as_ref() else {
+                continue;
+            };
+            let Some(enthalpy_per_atom) = parsed.enthalpy_per_atom() else {
+                continue;
+            };
+            let Some(pressure_kbar) = parsed.pressure_kbar else {
+                missing_pressure += 1;
+                continue;
+            };
+            let Some(phase) = phase_name(&record.structure_name, matcher.as_ref()) else {
+                unmatched += 1;
+                continue;
+            };
+
+            points.push(PhasePoint {
+                phase,
+                structure: record.structure_name,
+                pressure_gpa: pressure_kbar * 0.1,
+                enthalpy_per_atom,
+            });
+        }
+    }
+
+    if missing_pressure > 0 {
+        output::print_warning(&format!(
+            "{missing_pressure} completed calculations were skipped because pressure could not be extracted"
+        ));
+    }
+
+    if unmatched > 0 {
+        output::print_warning(&format!(
+            "{unmatched} structures did not match --phase-regex and were skipped"
+        ));
+    }
+
+    let series = build_series(&points);
+    if series.len() < 2 {
+        return Err(QutilityError::InvalidArgument(format!(
+            "Phase transition analysis needs at least 2 phases, found {}",
+            series.len()
+        )));
+    }
+
+    let reference = select_reference(&series, args.reference.as_deref())?;
+    output::print_info(&format!(
+        "Found {} phases over {} data points, reference phase: {}",
+        series.len(),
+        points.len(),
+        reference.phase
+    ));
+
+    print_delta_table(&series, reference);
+
+    let reference_crossings: Vec<EnthalpyCrossing> = series
+        .iter()
+        .filter(|s| s.phase != reference.phase)
+        .flat_map(|s| find_crossings(reference, s))
+        .collect();
+    print_crossings(
+        &format!("Crossings with Reference '{}'", reference.phase),
+        &reference_crossings,
+    );
+
+    let stable = stable_phase_transitions(&series);
+    print_crossings("Stable Phase Sequence", &stable);
+
+    save_results_csv(&points, reference, &args.output_csv)?;
+    output::print_success(&format!(
+        "ΔH(P) data saved to '{}'",
+        args.output_csv.display()
+    ));
+
+    if !args.no_plot {
+        save_plot(
+            &TransitionPlot {
+                series: &series,
+                reference,
+            },
+            &args.output_plot,
+        )?;
+        output::print_success(&format!(
+            "ΔH(P) plot saved to '{}'",
+            args.output_plot.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// 由结构名推断相名称；无正则时直接使用结构名
+fn phase_name(structure_name: &str, matcher: Option<&Regex>) -> Option<String> {
+    let Some(regex) = matcher else {
+        return Some(structure_name.to_string());
+    };
+
+    let captures = regex.captures(structure_name)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|m| m.as_str().to_string())
+}
+
+fn build_series(points: &[PhasePoint]) -> Vec<PhaseSeries> {
+    let mut grouped: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+    for point in points {
+        grouped
+            .entry(point.phase.as_str())
+            .or_default()
+            .push((point.pressure_gpa, point.enthalpy_per_atom));
+    }
+
+    grouped
+        .into_iter()
+        .map(|(phase, data)| PhaseSeries::new(phase, data))
+        .collect()
+}
+
+/// 选择参考相：显式指定，或取最低压力点上最稳定的相
+fn select_reference<'a>(
+    series: &'a [PhaseSeries],
+    requested: Option<&str>,
+) -> Result<&'a PhaseSeries> {
+    if let Some(name) = requested {
+        return series.iter().find(|s| s.phase == name).ok_or_else(|| {
+            QutilityError::InvalidArgument(format!("Reference phase '{name}' not found"))
+        });
+    }
+
+    // 同一压力子目录中的实际压力略有差异，1 GPa 内视为同一压力点
+    let lowest_pressure = series
+        .iter()
+        .filter_map(|s| s.points.first().map(|(p, _)| *p))
+        .fold(f64::INFINITY, f64::min);
+
+    series
+        .iter()
+        .filter_map(|s| s.points.first().map(|first| (s, *first)))
+        .filter(|(_, (p, _))| *p - lowest_pressure < 1.0)
+        .min_by(|(_, a), (_, b)| a.1.total_cmp(&b.1))
+        .map(|(s, _)| s)
+        .ok_or_else(|| QutilityError::Other("No phase has enthalpy data".to_string()))
+}
+
+fn print_delta_table(series: &[PhaseSeries], reference: &PhaseSeries) {
+    let rows: Vec<DeltaRow> = series
+        .iter()
+        .flat_map(|s| {
+            let deltas = relative_enthalpies(s, reference);
+            s.points.iter().map(move |&(p, h)| {
+                let delta = deltas
+                    .iter()
+                    .find(|(dp, _)| *dp == p)
+                    .map(|(_, d)| format!("{:.2}", d * 1000.0))
+                    .unwrap_or_else(|| "-".to_string());
+                DeltaRow {
+                    phase: s.phase.clone(),
+                    pressure: format!("{p:.2}"),
+                    enthalpy: format!("{h:.6}"),
+                    delta_h: delta,
+                }
+            })
+        })
+        .collect();
+
+    output::print_header("Enthalpy Relative to Reference");
+    println!("{}", Table::new(rows));
+}
+
+fn print_crossings(title: &str, crossings: &[EnthalpyCrossing]) {
+    output::print_header(title);
+
+    if crossings.is_empty() {
+        output::print_info("No enthalpy crossings found in the sampled pressure range.");
+        return;
+    }
+
+    let rows: Vec<CrossingRow> = crossings
+        .iter()
+        .map(|c| CrossingRow {
+            low: c.low_pressure_phase.clone(),
+            high: c.high_pressure_phase.clone(),
+            pressure: format!("{:.2}", c.pressure_gpa),
+        })
+        .collect();
+
+    println!("{}", Table::new(rows));
+}
+
+fn save_results_csv(
+    points: &[PhasePoint],
+    reference: &PhaseSeries,
+    output_path: &Path,
+) -> Result<()> {
+    let mut wtr = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+
+    wtr.write_record([
+        "phase",
+        "structure",
+        "pressure_GPa",
+        "enthalpy_per_atom_eV",
+        "delta_h_meV_per_atom",
+    ])
+    .map_err(QutilityError::CsvError)?;
+
+    let mut sorted: Vec<&PhasePoint> = points.iter().collect();
+    sorted.sort_by(|a, b| {
+        a.phase
+            .cmp(&b.phase)
+            .then(a.pressure_gpa.total_cmp(&b.pressure_gpa))
+            .then(a.structure.cmp(&b.structure))
+    });
+
+    for point in sorted {
+        let delta = reference
+            .enthalpy_at(point.pressure_gpa)
+            .map(|h_ref| format!("{:.4}", (point.enthalpy_per_atom - h_ref) * 1000.0))
+            .unwrap_or_default();
+        wtr.write_record([
+            point.phase.clone(),
+            point.structure.clone(),
+            format!("{:.4}", point.pressure_gpa),
+            format!("{:.10}", point.enthalpy_per_atom),
+            delta,
+        ])
+        .map_err(QutilityError::CsvError)?;
+    }
+
+    wtr.flush().map_err(|e| QutilityError::FileWriteError {
+        path: output_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(())
+}
+
+/// 各相相对参考相的 ΔH(P) 曲线
+struct TransitionPlot<'a> {
+    series: &'a [PhaseSeries],
+    reference: &'a PhaseSeries,
+}
+
+impl PlotFigure for TransitionPlot<'_> {
+    const SIZE: (u32, u32) = (900, 650);
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        draw_transition_chart(root, self.series, self.reference)
+    }
+}
+
+fn draw_transition_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    series: &[PhaseSeries],
+    reference: &PhaseSeries,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    let curves: Vec<(&str, Vec<(f64, f64)>)> = series
+        .iter()
+        .map(|s| {
+            let data = relative_enthalpies(s, reference)
+                .into_iter()
+                .map(|(p, d)| (p, d * 1000.0))
+                .collect();
+            (s.phase.as_str(), data)
+        })
+        .filter(|(_, data): &(&str, Vec<(f64, f64)>)| !data.is_empty())
+        .collect();
+
+    let all = curves.iter().flat_map(|(_, c)| c.iter().copied());
+    let (p_min, p_max, d_min, d_max) = all.fold(
+        (f64::INFINITY, f64::NEG_INFINITY, 0.0_f64, 0.0_f64),
+        |(p_lo, p_hi, d_lo, d_hi), (p, d)| (p_lo.min(p), p_hi.max(p), d_lo.min(d), d_hi.max(d)),
+    );
+    let p_margin = ((p_max - p_min) * 0.05).max(1.0);
+    let d_margin = ((d_max - d_min) * 0.1).max(1.0);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            format!("Enthalpy relative to {}", reference.phase),
+            ("sans-serif", 24),
+        )
+        .margin(20)
+        .x_label_area_size(45)
+        .y_label_area_size(70)
+        .build_cartesian_2d(
+            (p_min - p_margin)..(p_max + p_margin),
+            (d_min - d_margin)..(d_max + d_margin),
+        )
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Pressure (GPa)")
+        .y_desc("ΔH (meV/atom)")
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    for (i, (phase, data)) in curves.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(data.iter().copied(), color.stroke_width(2)))
+            .map_err(|e| QutilityError::Other(format!("{:?}", e)))?
+            .label(phase.to_string())
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+        chart
+            .draw_series(
+                data.iter()
+                    .map(|&(p, d)| Circle::new((p, d), 4, color.filled())),
+            )
+            .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .map_err(|e| QutilityError::Other(format!("{:?}", e)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_name_uses_first_capture_group() {
+        let regex = Regex::new(r"^([^_]+)_P\d+").expect("regex");
+
+        assert_eq!(
+            phase_name("Fm-3m_P050", Some(&regex)).as_deref(),
+            Some("Fm-3m")
+        );
+        assert_eq!(phase_name("unrelated", Some(&regex)), None);
+        assert_eq!(phase_name("plain", None).as_deref(), Some("plain"));
+    }
+}
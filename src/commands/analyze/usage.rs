@@ -0,0 +1,259 @@
+//! # 资源用量子命令实现
+//!
+//! 扫描作业目录，读取每个作业的墙钟时间与核数并估算核时，按状态、代码与结构大小（原子数分档）汇总，
+//! 失败作业消耗的核时单独列为浪费；可导出逐作业 CSV。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/analyze.rs` 定义的 UsageArgs
+//! - 复用 `dft/` 扫描、Slurm 日志状态细化与用量估算（`dft/usage.rs`）、`utils/output.rs`、`utils/parallel.rs`
+
+use crate::cli::analyze::UsageArgs;
+use crate::dft::{
+    apply_slurm_log_states, is_wasted, job_atoms, job_usage, scan_calculations_with_rules,
+    size_bin, FailureRules, JobLayout, JobUsage, ScanCache,
+};
+use crate::error::{QutilityError, Result};
+use crate::models::CalculationScanRecord;
+use crate::utils::{output, parallel};
+
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tabled::{Table, Tabled};
+
+#[derive(Debug, Clone, Tabled)]
+struct UsageRow {
+    #[tabled(rename = "Group")]
+    group: String,
+    #[tabled(rename = "Jobs")]
+    jobs: usize,
+    #[tabled(rename = "Timed")]
+    timed: usize,
+    #[tabled(rename = "Wall (h)")]
+    wall_hours: String,
+    #[tabled(rename = "Core-hours")]
+    core_hours: String,
+    #[tabled(rename = "Share")]
+    share: String,
+}
+
+/// 单个作业的用量
+struct UsageEntry<'a> {
+    record: &'a CalculationScanRecord,
+    usage: JobUsage,
+    atoms: Option<usize>,
+}
+
+/// 一组作业的用量合计
+#[derive(Default)]
+struct UsageTotals {
+    jobs: usize,
+    timed: usize,
+    wall_hours: f64,
+    core_hours: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &JobUsage) {
+        self.jobs += 1;
+        if let Some(elapsed) = usage.elapsed_sec {
+            self.timed += 1;
+            self.wall_hours += elapsed / 3600.0;
+        }
+        self.core_hours += usage.core_hours.unwrap_or(0.0);
+    }
+}
+
+pub fn execute(args: UsageArgs) -> Result<()> {
+    output::print_header("DFT Resource Usage");
+
+    if !args.job_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let rules = FailureRules::discover(&args.job_dir, args.rules.as_deref())?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let mut records = scan_calculations_with_rules(
+        &args.job_dir,
+        args.code.into(),
+        &rules,
+        &layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+    apply_slurm_log_states(&mut records, &[]);
+
+    let entries: Vec<UsageEntry> = records
+        .par_iter()
+        .map(|record| UsageEntry {
+            record,
+            usage: job_usage(record),
+            atoms: job_atoms(record),
+        })
+        .collect();
+
+    let mut overall = UsageTotals::default();
+    let mut wasted = UsageTotals::default();
+    for entry in &entries {
+        overall.add(&entry.usage);
+        if is_wasted(entry.record.status) {
+            wasted.add(&entry.usage);
+        }
+    }
+    output::print_info(&format!(
+        "Scanned {} job directories, {} with timing information",
+        overall.jobs, overall.timed
+    ));
+
+    print_usage_table(
+        "Usage by Status",
+        totals(&entries, |entry| entry.record.status.to_string()),
+        overall.core_hours,
+    );
+    print_usage_table(
+        "Usage by Code",
+        totals(&entries, |entry| entry.record.code.to_string()),
+        overall.core_hours,
+    );
+    // 按分档上限排序，而非按标签字典序
+    let by_size = totals(&entries, |entry| {
+        let order = entry
+            .atoms
+            .map_or(usize::MAX, |n| n.next_power_of_two().clamp(8, 512));
+        (order, size_bin(entry.atoms))
+    });
+    print_usage_table(
+        "Usage by Size (atoms)",
+        by_size
+            .into_iter()
+            .map(|((_, label), totals)| (label, totals)),
+        overall.core_hours,
+    );
+
+    output::print_separator();
+    output::print_info(&format!(
+        "Total: {:.1} wall-hours, {:.1} core-hours",
+        overall.wall_hours, overall.core_hours
+    ));
+
+    if wasted.jobs > 0 {
+        output::print_warning(&format!(
+            "Wasted on failed jobs: {:.1} core-hours ({}) across {} jobs",
+            wasted.core_hours,
+            share(wasted.core_hours, overall.core_hours),
+            wasted.jobs
+        ));
+    } else {
+        output::print_success("No core-hours wasted on failed jobs");
+    }
+
+    let untimed = overall.jobs - overall.timed;
+    if untimed > 0 {
+        output::print_warning(&format!(
+            "{untimed} jobs have no timing information in their output or Slurm logs"
+        ));
+    }
+
+    if let Some(output_path) = args.output_csv.as_ref() {
+        write_usage_csv(output_path, &entries)?;
+        output::print_success(&format!("Usage saved to '{}'", output_path.display()));
+    }
+
+    Ok(())
+}
+
+/// 按分组键合计；`BTreeMap` 使表格行顺序稳定
+fn totals<K: Ord>(
+    entries: &[UsageEntry],
+    key: impl Fn(&UsageEntry) -> K,
+) -> BTreeMap<K, UsageTotals> {
+    let mut groups: BTreeMap<K, UsageTotals> = BTreeMap::new();
+    for entry in entries {
+        groups.entry(key(entry)).or_default().add(&entry.usage);
+    }
+    groups
+}
+
+fn print_usage_table(
+    title: &str,
+    groups: impl IntoIterator<Item = (String, UsageTotals)>,
+    total_core_hours: f64,
+) {
+    let rows: Vec<UsageRow> = groups
+        .into_iter()
+        .map(|(group, totals)| UsageRow {
+            group,
+            jobs: totals.jobs,
+            timed: totals.timed,
+            wall_hours: format!("{:.1}", totals.wall_hours),
+            core_hours: format!("{:.1}", totals.core_hours),
+            share: share(totals.core_hours, total_core_hours),
+        })
+        .collect();
+
+    output::print_header(title);
+    println!("{}", Table::new(rows));
+}
+
+fn share(part: f64, total: f64) -> String {
+    if total > 0.0 {
+        format!("{:.1}%", part / total * 100.0)
+    } else {
+        "-".to_string()
+    }
+}
+
+fn write_usage_csv(output_path: &Path, entries: &[UsageEntry]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(output_path).map_err(QutilityError::CsvError)?;
+    writer
+        .write_record([
+            "structure",
+            "code",
+            "status",
+            "atoms",
+            "cores",
+            "elapsed_hours",
+            "cpu_hours",
+            "core_hours",
+            "source",
+            "wasted",
+        ])
+        .map_err(QutilityError::CsvError)?;
+
+    let hours = |seconds: Option<f64>| {
+        seconds
+            .map(|seconds| (seconds / 3600.0).to_string())
+            .unwrap_or_default()
+    };
+    for entry in entries {
+        let usage = &entry.usage;
+        writer
+            .write_record([
+                entry.record.structure_name.clone(),
+                entry.record.code.to_string(),
+                entry.record.status.to_string(),
+                entry.atoms.map(|n| n.to_string()).unwrap_or_default(),
+                usage.cores.map(|n| n.to_string()).unwrap_or_default(),
+                hours(usage.elapsed_sec),
+                hours(usage.cpu_sec),
+                usage.core_hours.map(|h| h.to_string()).unwrap_or_default(),
+                usage.source.map(|s| s.to_string()).unwrap_or_default(),
+                is_wasted(entry.record.status).to_string(),
+            ])
+            .map_err(QutilityError::CsvError)?;
+    }
+
+    writer.flush().map_err(|e| QutilityError::FileWriteError {
+        path: output_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(())
+}
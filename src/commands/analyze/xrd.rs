@@ -8,23 +8,25 @@
 //! - 可选展宽（Gaussian/Lorentzian/Pseudo-Voigt）
 //! - 输出高质量图像 (PNG/SVG)
 //! - 导出数据文件 (CSV/XY)
+//! - `--output-format json|jsonl|csv` 时输出全部峰位记录
 //!
 //! ## 依赖关系
 //! - 使用 `cli/analyze.rs` 定义的 XrdArgs
 //! - 使用 `batch/` 模块进行批量处理
 //! - 使用 `xrd/` 模块进行计算
-//! - 使用 `parsers/` 读取结构
+//! - 使用 `parsers/` 读取结构，`utils/structured.rs` 输出峰位记录
 
 use crate::batch::{BatchRunner, FileCollector, ProcessResult};
 use crate::cli::analyze::{parse_wavelength, BroadeningType, XrdArgs, XrdOutputFormat};
 use crate::error::{QutilityError, Result};
 use crate::parsers;
-use crate::utils::output;
+use crate::utils::{output, structured};
 use crate::xrd::{self, XrdCalculator};
 
+use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// 执行 XRD 分析
 pub fn execute(args: XrdArgs) -> Result<()> {
@@ -114,9 +116,14 @@ fn execute_batch(args: &XrdArgs) -> Result<()> {
         overwrite: args.overwrite,
     });
 
-    // 并行处理
+    // 并行处理；结构化输出时收集各结构的峰位
+    let peaks = Mutex::new(Vec::new());
     let runner = BatchRunner::new(args.jobs);
-    let result = runner.run(files, |file| process_batch_file(file, &config));
+    let result = runner.run(files, |file| process_batch_file(file, &config, &peaks));
+
+    let mut peaks = peaks.into_inner().unwrap_or_else(|e| e.into_inner());
+    peaks.sort_by(|a: &PeakRecord, b| a.input.cmp(&b.input));
+    structured::emit(&peaks)?;
 
     // 打印统计
     output::print_separator();
@@ -156,7 +163,11 @@ struct BatchXrdConfig {
 }
 
 /// 处理批量模式中的单个文件
-fn process_batch_file(input: &PathBuf, config: &Arc<BatchXrdConfig>) -> ProcessResult {
+fn process_batch_file(
+    input: &PathBuf,
+    config: &Arc<BatchXrdConfig>,
+    peaks: &Mutex<Vec<PeakRecord>>,
+) -> ProcessResult {
     // 构造输出文件名
     let stem = input
         .file_stem()
@@ -182,7 +193,14 @@ fn process_batch_file(input: &PathBuf, config: &Arc<BatchXrdConfig>) -> ProcessR
 
     // 创建临时 args 来复用单文件处理逻辑
     match process_single_structure_with_config(input, &output_file, config) {
-        Ok(_) => {
+        Ok(pattern) => {
+            if structured::format().is_some() {
+                let records = peak_records(input, &pattern);
+                peaks
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .extend(records);
+            }
             ProcessResult::Success(format!("{} -> {}", input.display(), output_file.display()))
         }
         Err(e) => ProcessResult::Failed(input.display().to_string(), e.to_string()),
@@ -194,7 +212,7 @@ fn process_single_structure_with_config(
     input: &Path,
     output: &Path,
     config: &BatchXrdConfig,
-) -> Result<()> {
+) -> Result<xrd::XrdPattern> {
     // 读取结构
     let crystal = parsers::parse_structure_file(input)?;
 
@@ -270,7 +288,7 @@ fn process_single_structure_with_config(
         }
     }
 
-    Ok(())
+    Ok(pattern)
 }
 
 /// 处理单个结构文件（完整参数版本）
@@ -386,6 +404,9 @@ fn process_single_structure(input: &Path, output: &Path, args: &XrdArgs) -> Proc
         Ok(_) => {
             // 显示主要峰位
             print_peak_table(&pattern.peaks, 10);
+            if let Err(e) = structured::emit(&peak_records(input, &pattern)) {
+                return ProcessResult::Failed(input.display().to_string(), e.to_string());
+            }
             ProcessResult::Success(format!("XRD saved to '{}'", output.display()))
         }
         Err(e) => ProcessResult::Failed(input.display().to_string(), e.to_string()),
@@ -485,6 +506,36 @@ fn parse_range(range: &str) -> Result<(f64, f64)> {
     Ok((min, max))
 }
 
+/// `--output-format` 记录：每个衍射峰一条，按结构内强度降序
+#[derive(Debug, Serialize)]
+struct PeakRecord {
+    structure: String,
+    input: String,
+    two_theta: f64,
+    d_spacing: f64,
+    intensity: f64,
+    h: i32,
+    k: i32,
+    l: i32,
+}
+
+fn peak_records(input: &Path, pattern: &xrd::XrdPattern) -> Vec<PeakRecord> {
+    pattern
+        .peaks
+        .iter()
+        .map(|peak| PeakRecord {
+            structure: pattern.structure_name.clone(),
+            input: input.display().to_string(),
+            two_theta: peak.two_theta,
+            d_spacing: peak.d_spacing,
+            intensity: peak.intensity,
+            h: peak.h,
+            k: peak.k,
+            l: peak.l,
+        })
+        .collect()
+}
+
 /// 打印峰位表格
 fn print_peak_table(peaks: &[xrd::Peak], count: usize) {
     use tabled::{Table, Tabled};
@@ -514,7 +565,6 @@ fn print_peak_table(peaks: &[xrd::Peak], count: usize) {
 
     if !rows.is_empty() {
         output::print_header(&format!("Top {} XRD Peaks", rows.len()));
-        let table = Table::new(&rows);
-        println!("{}", table);
+        output::print_table(Table::new(&rows));
     }
 }
@@ -0,0 +1,182 @@
+//! # archive 命令实现
+//!
+//! `create` 扫描作业根目录，把选定状态（默认只有 completed）的作业目录打包为 `.tar.gz`
+//! （每个作业一个归档或按大小分片），并写出含焓值、文件列表与校验和的 JSON/CSV 清单；原目录保持不变。
+//! `extract` 按结构名或作业路径（支持 glob）从清单中选择作业，解压恢复并校验 SHA-256。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/archive.rs` 定义的参数
+//! - 复用 `dft/` 扫描（失败规则取内置及用户/项目规则文件）、Slurm 日志状态细化、状态类别与归档模块
+//! - 使用 `utils/output.rs`、`utils/parallel.rs`
+
+use crate::cli::archive::{ArchiveArgs, ArchiveCommands, ArchiveCreateArgs, ArchiveExtractArgs};
+use crate::dft::{
+    apply_slurm_log_states, create_archives, extract_jobs, scan_calculations_with_rules,
+    ArchiveLayout, ArchivedJob, CleanupTarget, FailureRules, JobLayout, Manifest, ScanCache,
+};
+use crate::error::{QutilityError, Result};
+use crate::utils::{output, parallel};
+
+use glob::Pattern;
+use std::collections::BTreeSet;
+use std::fs;
+
+pub fn execute(args: ArchiveArgs) -> Result<()> {
+    match args.command {
+        ArchiveCommands::Create(create_args) => create(create_args),
+        ArchiveCommands::Extract(extract_args) => extract(extract_args),
+    }
+}
+
+fn create(args: ArchiveCreateArgs) -> Result<()> {
+    output::print_header("Archiving DFT Jobs");
+
+    if !args.job_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+
+    let layout = match args.shard_size {
+        Some(gib) if !gib.is_finite() || gib <= 0.0 => {
+            return Err(QutilityError::InvalidArgument(format!(
+                "--shard-size must be a positive number of GiB, got {gib}"
+            )));
+        }
+        Some(gib) => ArchiveLayout::Sharded {
+            max_bytes: (gib * 1024.0 * 1024.0 * 1024.0) as u64,
+        },
+        None => ArchiveLayout::PerJob,
+    };
+    if Manifest::exists(&args.output) {
+        return Err(QutilityError::InvalidArgument(format!(
+            "'{}' already contains an archive manifest; choose another --output",
+            args.output.display()
+        )));
+    }
+    let targets: Vec<CleanupTarget> = args.status.iter().map(|&s| s.into()).collect();
+
+    parallel::configure_threads(args.jobs);
+    let job_layout = JobLayout::try_from(&args.layout)?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let rules = FailureRules::discover(&args.job_dir, None)?;
+    let mut records = scan_calculations_with_rules(
+        &args.job_dir,
+        args.code.into(),
+        &rules,
+        &job_layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+    apply_slurm_log_states(&mut records, &[]);
+
+    let selected: Vec<_> = records
+        .iter()
+        .filter(|record| targets.iter().any(|target| target.matches(record.status)))
+        .collect();
+    output::print_info(&format!(
+        "Scanned {} job directories, {} match the selected statuses",
+        records.len(),
+        selected.len()
+    ));
+    if selected.is_empty() {
+        output::print_warning("Nothing to archive");
+        return Ok(());
+    }
+
+    let manifest = create_archives(&selected, &args.job_dir, &args.output, layout)?;
+
+    let archives: BTreeSet<&str> = manifest
+        .jobs
+        .iter()
+        .map(|job| job.archive.as_str())
+        .collect();
+    let files: usize = manifest.jobs.iter().map(|job| job.files.len()).sum();
+    let bytes: u64 = manifest.jobs.iter().map(ArchivedJob::bytes).sum();
+    let compressed: u64 = archives
+        .iter()
+        .filter_map(|name| fs::metadata(args.output.join(name)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    output::print_done(&format!(
+        "Archived {} jobs ({} files, {}) into {} archives ({} compressed)",
+        manifest.jobs.len(),
+        files,
+        output::format_bytes(bytes),
+        archives.len(),
+        output::format_bytes(compressed)
+    ));
+    output::print_success(&format!(
+        "Manifest saved to '{}' (manifest.json, manifest.csv)",
+        args.output.display()
+    ));
+    output::print_info(
+        "Job directories were left in place; remove them once the archives are verified",
+    );
+    Ok(())
+}
+
+fn extract(args: ArchiveExtractArgs) -> Result<()> {
+    output::print_header("Extracting Archived DFT Jobs");
+
+    if !args.archive_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.archive_dir.display().to_string(),
+        });
+    }
+    let manifest = Manifest::load(&args.archive_dir)?;
+
+    let patterns = args
+        .only
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|e| {
+                QutilityError::InvalidArgument(format!("Invalid pattern '{pattern}': {e}"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let matches = |job: &ArchivedJob, pattern: &Pattern| {
+        pattern.matches(&job.structure) || pattern.matches(&job.job)
+    };
+
+    for (pattern, text) in patterns.iter().zip(&args.only) {
+        if !manifest.jobs.iter().any(|job| matches(job, pattern)) {
+            output::print_warning(&format!("No archived job matches '{text}'"));
+        }
+    }
+
+    let mut selected = Vec::new();
+    for job in &manifest.jobs {
+        if !patterns.is_empty() && !patterns.iter().any(|pattern| matches(job, pattern)) {
+            continue;
+        }
+        if !args.overwrite && args.dest.join(&job.job).exists() {
+            output::print_skip(&format!(
+                "{}: '{}' already exists (use --overwrite)",
+                job.structure,
+                args.dest.join(&job.job).display()
+            ));
+            continue;
+        }
+        selected.push(job);
+    }
+
+    if selected.is_empty() {
+        output::print_info("Nothing to extract");
+        return Ok(());
+    }
+
+    let restored = extract_jobs(&args.archive_dir, &selected, &args.dest)?;
+    output::print_done(&format!(
+        "Restored {} jobs ({} files, checksums verified) into '{}'",
+        selected.len(),
+        restored,
+        args.dest.display()
+    ));
+    Ok(())
+}
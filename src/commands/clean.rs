@@ -0,0 +1,189 @@
+//! # clean 命令实现
+//!
+//! 按扫描状态（默认只处理 completed）删除作业目录中的大体积中间文件，支持自定义删除/保留模式与最小文件年龄；
+//! dry-run 时按模式汇总将释放的字节数。仍在调度器队列中排队或运行的作业目录一律跳过，
+//! 无法查询队列时拒绝执行（除非显式 `--no-queue-check`）。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/clean.rs` 定义的参数
+//! - 复用 `dft/` 扫描（失败规则取内置及用户/项目规则文件）、Slurm 日志状态细化、队列匹配与清理策略
+//! - 使用 `utils/scheduler.rs` 查询队列、`utils/command.rs`、`utils/output.rs`、`utils/parallel.rs`
+
+use crate::cli::clean::CleanArgs;
+use crate::dft::{
+    apply_slurm_log_states, find_queued_job, scan_calculations_with_rules, CleanupCandidate,
+    CleanupPolicy, CleanupTarget, FailureRules, JobLayout, ScanCache,
+};
+use crate::error::{QutilityError, Result};
+use crate::utils::command::SystemCommandRunner;
+use crate::utils::scheduler::{SchedulerJob, SchedulerKind};
+use crate::utils::{output, parallel};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tabled::{Table, Tabled};
+
+#[derive(Debug, Clone, Tabled)]
+struct PatternRow {
+    #[tabled(rename = "Pattern")]
+    pattern: String,
+    #[tabled(rename = "Files")]
+    files: usize,
+    #[tabled(rename = "Size")]
+    size: String,
+}
+
+pub fn execute(args: CleanArgs) -> Result<()> {
+    output::print_header("Cleaning Finished DFT Jobs");
+
+    if !args.job_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+
+    let min_age = match args.older_than {
+        Some(days) if !days.is_finite() || days < 0.0 => {
+            return Err(QutilityError::InvalidArgument(format!(
+                "--older-than must be a non-negative number of days, got {days}"
+            )));
+        }
+        Some(days) => Some(Duration::from_secs_f64(days * 86400.0)),
+        None => None,
+    };
+    let policy = CleanupPolicy::new(&args.pattern, &args.keep, min_age)?;
+    let targets: Vec<CleanupTarget> = args.status.iter().map(|&s| s.into()).collect();
+
+    // 先确认队列可查询，避免扫描后才发现无法保证安全
+    let queued = if args.no_queue_check {
+        output::print_warning(
+            "Skipping the queue check: directories of running jobs may be cleaned",
+        );
+        Vec::new()
+    } else {
+        let runner = SystemCommandRunner::new().with_overrides(args.scheduler_cmd.iter().cloned());
+        let user = args.user.clone().or_else(|| std::env::var("USER").ok());
+        let scheduler = SchedulerKind::from(args.scheduler).scheduler();
+        scheduler.query_queue(&runner, user.as_deref()).map_err(|err| {
+            QutilityError::Other(format!(
+                "Cannot query the queue ({err}); refusing to clean. Use --no-queue-check to override"
+            ))
+        })?
+    };
+
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let rules = FailureRules::discover(&args.job_dir, None)?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let mut records = scan_calculations_with_rules(
+        &args.job_dir,
+        args.code.into(),
+        &rules,
+        &layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+    apply_slurm_log_states(&mut records, &queued);
+
+    let selected: Vec<_> = records
+        .iter()
+        .filter(|record| targets.iter().any(|target| target.matches(record.status)))
+        .collect();
+    output::print_info(&format!(
+        "Scanned {} job directories, {} match the selected statuses",
+        records.len(),
+        selected.len()
+    ));
+
+    let now = SystemTime::now();
+    let mut candidates: Vec<CleanupCandidate> = Vec::new();
+    let mut in_queue: Vec<(&str, &SchedulerJob)> = Vec::new();
+    let mut cleaned_jobs = 0;
+    for record in selected {
+        if let Some(job) = find_queued_job(record, &queued) {
+            in_queue.push((&record.structure_name, job));
+            continue;
+        }
+
+        let planned = policy.plan(&record.job_dir, record.code, now)?;
+        if !planned.is_empty() {
+            cleaned_jobs += 1;
+        }
+        candidates.extend(planned);
+    }
+
+    for (structure, job) in &in_queue {
+        output::print_skip(&format!(
+            "{structure}: job {} is still {}",
+            job.id,
+            job.state.to_ascii_lowercase()
+        ));
+    }
+
+    if candidates.is_empty() {
+        output::print_info("Nothing to clean");
+        return Ok(());
+    }
+    print_pattern_table(&candidates);
+
+    let total: u64 = candidates.iter().map(|candidate| candidate.bytes).sum();
+    if args.dry_run {
+        output::print_done(&format!(
+            "[DRY] Would free {} from {} files in {} jobs",
+            output::format_bytes(total),
+            candidates.len(),
+            cleaned_jobs
+        ));
+        return Ok(());
+    }
+
+    let mut freed = 0;
+    let mut removed = 0;
+    for candidate in &candidates {
+        match fs::remove_file(&candidate.path) {
+            Ok(()) => {
+                freed += candidate.bytes;
+                removed += 1;
+            }
+            Err(err) => output::print_warning(&format!(
+                "Failed to remove {}: {err}",
+                candidate.path.display()
+            )),
+        }
+    }
+
+    output::print_done(&format!(
+        "Freed {} from {} files in {} jobs",
+        output::format_bytes(freed),
+        removed,
+        cleaned_jobs
+    ));
+    Ok(())
+}
+
+/// 按删除模式汇总文件数与字节数
+fn print_pattern_table(candidates: &[CleanupCandidate]) {
+    let mut groups: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+    for candidate in candidates {
+        let group = groups.entry(candidate.pattern.as_str()).or_default();
+        group.0 += 1;
+        group.1 += candidate.bytes;
+    }
+
+    let rows: Vec<PatternRow> = groups
+        .into_iter()
+        .map(|(pattern, (files, bytes))| PatternRow {
+            pattern: pattern.to_string(),
+            files,
+            size: output::format_bytes(bytes),
+        })
+        .collect();
+
+    output::print_header("Space by Pattern");
+    output::print_table(Table::new(rows));
+}
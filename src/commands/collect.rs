@@ -1,20 +1,23 @@
 //! # collect 命令实现
 //!
-//! 收集已完成的 DFT 结构，并转换为单个 `.res` 文件。
+//! 收集已完成的 DFT 结构，并行转换为单个 `.res` 文件（顺序与扫描顺序一致）；`--code auto` 时按目录识别代码并分代码汇总；
+//! `--output-format json|jsonl|csv` 时逐结构输出收集结果记录。
 //!
 //! ## 依赖关系
 //! - 使用 `cli/collect.rs` 定义的参数
-//! - 复用 `dft/` 扫描模块和 `parsers/`
+//! - 复用 `dft/` 扫描模块（失败规则取内置及用户/项目规则文件）和 `parsers/`
+//! - 使用 `utils/parallel.rs` 配置 `-j/--jobs` 并行度，`utils/structured.rs` 输出记录
 
-use crate::cli::analyze::DftCode;
 use crate::cli::collect::CollectArgs;
-use crate::dft::scan_calculations;
+use crate::dft::{group_by_code, scan_calculations_with_rules, FailureRules, JobLayout, ScanCache};
 use crate::error::{QutilityError, Result};
-use crate::models::{CalculationStatus, DftCodeType, DftResult};
+use crate::models::{CalculationScanRecord, CalculationStatus, DftCodeType, DftResult};
 use crate::parsers;
 use crate::parsers::res::to_res_string;
-use crate::utils::{output, progress};
+use crate::utils::{output, parallel, progress, structured};
 
+use rayon::prelude::*;
+use serde::Serialize;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
@@ -23,8 +26,22 @@ use std::process::Command;
 pub fn execute(args: CollectArgs) -> Result<()> {
     output::print_header("Collecting DFT Results");
 
-    let code: DftCodeType = args.code.into();
-    let records = scan_calculations(&args.dft_dir, code)?;
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let rules = FailureRules::discover(&args.dft_dir, None)?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.dft_dir));
+    let records = scan_calculations_with_rules(
+        &args.dft_dir,
+        args.code.into(),
+        &rules,
+        &layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.dft_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
     let completed_records: Vec<_> = records
         .into_iter()
         .filter(|record| record.status == CalculationStatus::Completed)
@@ -34,46 +51,60 @@ pub fn execute(args: CollectArgs) -> Result<()> {
         "Found {} completed calculations",
         completed_records.len()
     ));
+    let groups = group_by_code(&completed_records);
+    if groups.len() > 1 {
+        for (code, group) in &groups {
+            output::print_info(&format!("  {code}: {}", group.len()));
+        }
+    }
 
     let pb = progress::create_progress_bar(completed_records.len() as u64, "Converting to .res");
 
-    let mut collected_res = Vec::new();
-    let mut success_count = 0;
-    let mut missing_structure_count = 0;
-
-    for record in completed_records {
-        let Some(structure_file) = record.structure_file.as_deref() else {
-            missing_structure_count += 1;
+    // 并行转换；有序 collect 保持扫描顺序，输出文件内容确定
+    let converted: Vec<std::result::Result<String, String>> = completed_records
+        .par_iter()
+        .map(|record| {
             pb.inc(1);
-            continue;
-        };
-
-        let res_content = if args.use_cabal {
-            convert_to_res_cabal(structure_file, &args.code)
-        } else {
-            convert_to_res_native(structure_file, &record.structure_name, record.parsed.as_ref())
-        };
-
-        match res_content {
-            Ok(content) => {
-                collected_res.push(content);
-                success_count += 1;
-            }
-            Err(err) => {
+            let structure_file = record
+                .structure_file
+                .as_deref()
+                .ok_or_else(|| "no structure file found".to_string())?;
+            let res_content = if args.use_cabal {
+                convert_to_res_cabal(structure_file, record.code)
+            } else {
+                convert_to_res_native(
+                    structure_file,
+                    &record.structure_name,
+                    record.parsed.as_ref(),
+                )
+            };
+
+            res_content.map_err(|err| {
                 pb.suspend(|| {
                     output::print_warning(&format!(
                         "Failed to convert {}: {}",
                         record.structure_name, err
                     ));
                 });
-            }
-        }
-
-        pb.inc(1);
-    }
+                err.to_string()
+            })
+        })
+        .collect();
 
     pb.finish_and_clear();
 
+    let missing_structure_count = completed_records
+        .iter()
+        .filter(|record| record.structure_file.is_none())
+        .count();
+    structured::emit(&collect_records(
+        &completed_records,
+        &converted,
+        &args.output,
+    ))?;
+    let collected_res: Vec<String> = converted.into_iter().flatten().collect();
+    let success_count = collected_res.len();
+
     if collected_res.is_empty() {
         output::print_warning("No completed calculations found to collect.");
         return Ok(());
@@ -116,6 +147,50 @@ pub fn execute(args: CollectArgs) -> Result<()> {
     Ok(())
 }
 
+/// `--output-format` 记录：每个已完成作业一条
+#[derive(Debug, Serialize)]
+struct CollectRecord {
+    structure: String,
+    code: String,
+    job_dir: String,
+    structure_file: Option<String>,
+    collected: bool,
+    /// 未收集的原因
+    reason: Option<String>,
+    enthalpy_ev: Option<f64>,
+    num_atoms: Option<usize>,
+    /// 收集到的 `.res` 文件
+    output: Option<String>,
+}
+
+fn collect_records(
+    records: &[CalculationScanRecord],
+    converted: &[std::result::Result<String, String>],
+    output_path: &Path,
+) -> Vec<CollectRecord> {
+    records
+        .iter()
+        .zip(converted)
+        .map(|(record, converted)| {
+            let parsed = record.parsed.as_ref();
+            CollectRecord {
+                structure: record.structure_name.clone(),
+                code: record.code.to_string(),
+                job_dir: record.job_dir.display().to_string(),
+                structure_file: record
+                    .structure_file
+                    .as_ref()
+                    .map(|path| path.display().to_string()),
+                collected: converted.is_ok(),
+                reason: converted.as_ref().err().cloned(),
+                enthalpy_ev: parsed.and_then(|result| result.enthalpy_ev),
+                num_atoms: parsed.and_then(|result| result.num_atoms),
+                output: converted.is_ok().then(|| output_path.display().to_string()),
+            }
+        })
+        .collect()
+}
+
 fn convert_to_res_native(
     struct_file: &Path,
     structure_name: &str,
@@ -143,7 +218,7 @@ fn convert_to_res_native(
     Ok(to_res_string(&crystal))
 }
 
-fn convert_to_res_cabal(struct_file: &Path, code: &DftCode) -> Result<String> {
+fn convert_to_res_cabal(struct_file: &Path, code: DftCodeType) -> Result<String> {
     let input_content =
         fs::read_to_string(struct_file).map_err(|e| QutilityError::FileReadError {
             path: struct_file.display().to_string(),
@@ -151,8 +226,8 @@ fn convert_to_res_cabal(struct_file: &Path, code: &DftCode) -> Result<String> {
         })?;
 
     let input_format = match code {
-        DftCode::Vasp => "poscar",
-        DftCode::Castep => "cell",
+        DftCodeType::Vasp => "poscar",
+        DftCodeType::Castep => "cell",
     };
 
     let mut child = Command::new("cabal")
@@ -7,11 +7,12 @@
 //! - 转换为 `.res/.cell/.cif/.xyz/.xtl/POSCAR` 格式
 //! - 支持并行处理
 //! - 可选使用外部 `cabal` 命令作为 fallback
+//! - `--output-format json|jsonl|csv` 时逐文件输出转换结果记录
 //!
 //! ## 依赖关系
 //! - 使用 `cli/convert.rs` 定义的参数
 //! - 使用 `parsers/`, `models/`
-//! - 使用 `utils/output.rs`, `utils/progress.rs`
+//! - 使用 `utils/output.rs`, `utils/parallel.rs`, `utils/progress.rs`, `utils/structured.rs`
 
 use crate::cli::convert::{ConvertArgs, OutputFormat};
 use crate::error::{QutilityError, Result};
@@ -19,9 +20,10 @@ use crate::parsers;
 use crate::parsers::cell::to_cell_string;
 use crate::parsers::poscar::to_poscar_string;
 use crate::parsers::res::to_res_string;
-use crate::utils::{output, progress};
+use crate::utils::{output, parallel, progress, structured};
 
 use rayon::prelude::*;
+use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -65,52 +67,51 @@ pub fn execute(args: ConvertArgs) -> Result<()> {
     }
 
     // 设置并行度
-    let num_threads = if args.jobs == 0 {
-        num_cpus::get()
-    } else {
-        args.jobs
-    };
-
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .build_global()
-        .ok();
+    parallel::configure_threads(args.jobs);
 
     let pb = progress::create_progress_bar(files.len() as u64, "Converting");
     let success_count = AtomicUsize::new(0);
     let skip_count = AtomicUsize::new(0);
 
-    // 并行处理
-    files.par_iter().for_each(|input_path| {
-        let result = if args.use_cabal {
-            convert_with_cabal(
-                input_path,
-                &args.output,
-                args.target,
-                args.niggli,
-                args.overwrite,
-            )
-        } else {
-            convert_native(input_path, &args.output, args.target, args.overwrite)
-        };
-
-        match result {
-            Ok(ConvertStatus::Success) => {
-                success_count.fetch_add(1, Ordering::SeqCst);
-            }
-            Ok(ConvertStatus::Skipped) => {
-                skip_count.fetch_add(1, Ordering::SeqCst);
-            }
-            Err(e) => {
-                pb.suspend(|| {
-                    output::print_error(&format!("{}: {}", input_path.display(), e));
-                });
-            }
-        }
-        pb.inc(1);
-    });
+    // 并行处理；有序 collect 使记录顺序与输入文件顺序一致
+    let records: Vec<ConvertRecord> = files
+        .par_iter()
+        .map(|input_path| {
+            let result = if args.use_cabal {
+                convert_with_cabal(
+                    input_path,
+                    &args.output,
+                    args.target,
+                    args.niggli,
+                    args.overwrite,
+                )
+            } else {
+                convert_native(input_path, &args.output, args.target, args.overwrite)
+            };
+
+            let record = match result {
+                Ok(ConvertStatus::Success(output_path)) => {
+                    success_count.fetch_add(1, Ordering::SeqCst);
+                    ConvertRecord::new(input_path, "converted", Some(&output_path), None)
+                }
+                Ok(ConvertStatus::Skipped(output_path)) => {
+                    skip_count.fetch_add(1, Ordering::SeqCst);
+                    ConvertRecord::new(input_path, "skipped", Some(&output_path), None)
+                }
+                Err(e) => {
+                    pb.suspend(|| {
+                        output::print_error(&format!("{}: {}", input_path.display(), e));
+                    });
+                    ConvertRecord::new(input_path, "failed", None, Some(e.to_string()))
+                }
+            };
+            pb.inc(1);
+            record
+        })
+        .collect();
 
     pb.finish_with_message("Done");
+    structured::emit(&records)?;
 
     output::print_done(&format!(
         "Converted {} file(s) to '{}' in '{}' ({} skipped)",
@@ -123,9 +124,36 @@ pub fn execute(args: ConvertArgs) -> Result<()> {
     Ok(())
 }
 
+/// 转换结果，附带目标文件路径
 enum ConvertStatus {
-    Success,
-    Skipped,
+    Success(PathBuf),
+    Skipped(PathBuf),
+}
+
+/// `--output-format` 记录：每个输入文件一条
+#[derive(Debug, Serialize)]
+struct ConvertRecord {
+    input: String,
+    output: Option<String>,
+    /// converted / skipped / failed
+    status: &'static str,
+    error: Option<String>,
+}
+
+impl ConvertRecord {
+    fn new(
+        input: &Path,
+        status: &'static str,
+        output: Option<&Path>,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            input: input.display().to_string(),
+            output: output.map(|path| path.display().to_string()),
+            status,
+            error,
+        }
+    }
 }
 
 /// 收集输入文件
@@ -179,7 +207,7 @@ fn convert_native(
 
     // 检查是否需要跳过
     if output_path.exists() && !overwrite {
-        return Ok(ConvertStatus::Skipped);
+        return Ok(ConvertStatus::Skipped(output_path));
     }
 
     // 解析输入文件
@@ -201,7 +229,7 @@ fn convert_native(
         source: e,
     })?;
 
-    Ok(ConvertStatus::Success)
+    Ok(ConvertStatus::Success(output_path))
 }
 
 fn infer_cabal_format(input_path: &Path) -> Result<&'static str> {
@@ -260,7 +288,7 @@ fn convert_with_cabal(
     };
 
     if output_path.exists() && !overwrite {
-        return Ok(ConvertStatus::Skipped);
+        return Ok(ConvertStatus::Skipped(output_path));
     }
 
     // 读取输入文件
@@ -296,7 +324,7 @@ fn convert_with_cabal(
         source: e,
     })?;
 
-    Ok(ConvertStatus::Success)
+    Ok(ConvertStatus::Success(output_path))
 }
 
 /// 调用 cabal 命令
@@ -4,21 +4,50 @@
 //!
 //! ## Coupling
 //! - Invoked by `main.rs`
-//! - Uses `cli/`, `dft/`, `parsers/`, `models/`, and `utils/`
+//! - Uses `cli/`, `dft/`, `parsers/`, `models/`, `report/`, and `utils/`
+//! - Rejects `--output-format` for commands without a record schema
 
 pub mod analyze;
+pub mod archive;
+pub mod clean;
 pub mod collect;
 pub mod convert;
+pub mod report;
 pub mod submit;
 
+use crate::cli::analyze::AnalyzeCommands;
 use crate::cli::Commands;
-use crate::error::Result;
+use crate::error::{QutilityError, Result};
+use crate::utils::structured;
 
 pub fn run(cmd: Commands) -> Result<()> {
+    if structured::format().is_some() && !supports_structured_output(&cmd) {
+        return Err(QutilityError::InvalidArgument(
+            "--output-format json/jsonl/csv is supported by analyze dft-status, analyze dft-pp, analyze xrd, collect and convert".to_string(),
+        ));
+    }
+
     match cmd {
         Commands::Convert(args) => convert::execute(args),
         Commands::Analyze(args) => analyze::execute(args),
         Commands::Collect(args) => collect::execute(args),
+        Commands::Clean(args) => clean::execute(args),
+        Commands::Archive(args) => archive::execute(args),
+        Commands::Report(args) => report::execute(args),
         Commands::Submit(args) => submit::execute(args),
     }
 }
+
+/// Commands that emit machine-readable records under `--output-format`
+fn supports_structured_output(cmd: &Commands) -> bool {
+    match cmd {
+        Commands::Convert(_) | Commands::Collect(_) => true,
+        Commands::Analyze(args) => matches!(
+            args.command,
+            AnalyzeCommands::DftStatus(_)
+                | AnalyzeCommands::DftPostprocessing(_)
+                | AnalyzeCommands::Xrd(_)
+        ),
+        _ => false,
+    }
+}
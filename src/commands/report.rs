@@ -0,0 +1,462 @@
+//! # report 命令实现
+//!
+//! 扫描作业根目录（失败原因来自可配置规则文件），汇总状态分布、按焓排名（混合代码时按代码分组）、
+//! 可选的 EDDP–DFT 一致性统计、最终残余受力与未完成作业的收敛诊断、失败原因及每个结构的晶格参数，
+//! 写出单个自包含的 HTML 报告；`job_dir` 为 `archive create` 生成的归档目录时直接从归档读取。
+//!
+//! ## 依赖关系
+//! - 使用 `cli/report.rs` 定义的参数
+//! - 复用 `dft/` 扫描、规则、Slurm 日志状态细化、归档读取、收敛诊断与一致性统计，`parsers/`（结构文件与 `.res` 集合）
+//! - 使用 `report/` 渲染页面，`utils/output.rs`、`utils/parallel.rs`
+
+use crate::cli::analyze::RankMode;
+use crate::cli::report::ReportArgs;
+use crate::dft::{
+    apply_slurm_log_states, diagnose, group_by_code, load_trace, parity_stats,
+    read_archived_records, scan_calculations_with_rules, CodeSelection, ConvergenceCriteria,
+    FailureRules, JobLayout, Manifest, ParityPair, ScanCache,
+};
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationScanRecord, CalculationStatus, Crystal, DftCodeType, DftResult};
+use crate::parsers::parse_structure_file;
+use crate::parsers::res::parse_res_collection;
+use crate::report::{
+    self, FailedJob, InProgressJob, ParitySection, RankedStructure, Report, StatusGroup,
+};
+use crate::utils::{output, parallel};
+
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+
+/// 状态分布中始终列出的状态；调度器相关状态仅在出现时列出
+const BASE_STATUSES: [CalculationStatus; 6] = [
+    CalculationStatus::Completed,
+    CalculationStatus::Unconverged,
+    CalculationStatus::Failed,
+    CalculationStatus::Incomplete,
+    CalculationStatus::MissingOutput,
+    CalculationStatus::ParseError,
+];
+const SCHEDULER_STATUSES: [CalculationStatus; 5] = [
+    CalculationStatus::Running,
+    CalculationStatus::Pending,
+    CalculationStatus::TimedOut,
+    CalculationStatus::NodeFail,
+    CalculationStatus::OutOfMemory,
+];
+
+/// 已解析结果与其结构
+struct Candidate {
+    result: DftResult,
+    crystal: Option<Crystal>,
+}
+
+impl Candidate {
+    fn value(&self, mode: RankMode) -> Option<f64> {
+        match mode {
+            RankMode::Total => self.result.enthalpy_ev,
+            RankMode::PerAtom => self.result.enthalpy_per_atom(),
+            RankMode::PerFormulaUnit => {
+                let crystal = self.crystal.as_ref().filter(|c| !c.atoms.is_empty())?;
+                Some(self.result.enthalpy_ev? / crystal.reduced_formula().1 as f64)
+            }
+        }
+    }
+}
+
+pub fn execute(args: ReportArgs) -> Result<()> {
+    output::print_header("Campaign Report");
+
+    if !args.job_dir.exists() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: args.job_dir.display().to_string(),
+        });
+    }
+
+    parallel::configure_threads(args.jobs);
+    let layout = JobLayout::try_from(&args.layout)?;
+    let archived = Manifest::exists(&args.job_dir);
+    let (records, crystals): (Vec<CalculationScanRecord>, Vec<Option<Crystal>>) =
+        load_records(&args, &layout)?.into_iter().unzip();
+    output::print_info(&format!("Scanned {} job directories", records.len()));
+
+    let status = status_groups(&records);
+    let failures = failed_jobs(&records);
+    // 归档中没有未完成作业的输出
+    let in_progress = if archived {
+        Vec::new()
+    } else {
+        in_progress_jobs(&records)
+    };
+
+    let mode = args.rank_by;
+    let candidates: Vec<Candidate> = records
+        .par_iter()
+        .zip(crystals)
+        .filter(|(record, _)| {
+            record.status == CalculationStatus::Completed
+                || (args.include_unconverged && record.status == CalculationStatus::Unconverged)
+        })
+        .filter_map(|(record, crystal)| {
+            let result = record
+                .parsed
+                .clone()
+                .filter(|result| result.enthalpy_ev.is_some())?;
+            let crystal = crystal.or_else(|| {
+                record
+                    .structure_file
+                    .as_deref()
+                    .and_then(|path| parse_structure_file(path).ok())
+            });
+            Some(Candidate { result, crystal })
+        })
+        .collect();
+
+    let eddp = match &args.eddp_res {
+        Some(path) => {
+            let crystals = parse_res_collection(path)?;
+            output::print_info(&format!(
+                "Loaded {} EDDP structures from '{}'",
+                crystals.len(),
+                path.display()
+            ));
+            Some(crystals)
+        }
+        None => None,
+    };
+    let eddp_by_name: HashMap<&str, &Crystal> = eddp
+        .iter()
+        .flatten()
+        .map(|crystal| (crystal.name.as_str(), crystal))
+        .collect();
+
+    let ranking = rank(candidates, mode, &eddp_by_name);
+    output::print_info(&format!(
+        "Ranked {} structures by {} enthalpy",
+        ranking.len(),
+        mode
+    ));
+
+    let parity = args
+        .eddp_res
+        .as_ref()
+        .and_then(|path| parity_section(&ranking, &eddp_by_name, args.top_n, path));
+    if args.eddp_res.is_some() && parity.is_none() {
+        output::print_warning("At least 2 matched structures are required for the parity section.");
+    }
+
+    let title = args.title.clone().unwrap_or_else(|| {
+        let name = std::path::absolute(&args.job_dir)
+            .ok()
+            .and_then(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| args.job_dir.display().to_string());
+        format!("{name} DFT Report")
+    });
+    let report = Report {
+        title,
+        source: args.job_dir.display().to_string(),
+        ranked_by: mode.to_string(),
+        unit: enthalpy_unit(mode).to_string(),
+        plot_top: args.plot_top.max(1),
+        status,
+        ranking,
+        parity,
+        in_progress,
+        failures,
+    };
+
+    let html = report::render(&report)?;
+    fs::write(&args.output, html).map_err(|e| QutilityError::FileWriteError {
+        path: args.output.display().to_string(),
+        source: e,
+    })?;
+
+    output::print_done(&format!(
+        "{} ranked structures, {} jobs in progress, {} jobs need attention",
+        report.ranking.len(),
+        report.in_progress.len(),
+        report.failures.len()
+    ));
+    output::print_success(&format!("Report saved to '{}'", args.output.display()));
+    Ok(())
+}
+
+/// 读取作业记录：目录含归档清单时直接从归档读取（附带已解析的结构），否则按规则扫描作业目录
+fn load_records(
+    args: &ReportArgs,
+    layout: &JobLayout,
+) -> Result<Vec<(CalculationScanRecord, Option<Crystal>)>> {
+    if Manifest::exists(&args.job_dir) {
+        let manifest = Manifest::load(&args.job_dir)?;
+        output::print_info(&format!(
+            "Reading {} archived jobs from '{}' without unpacking",
+            manifest.jobs.len(),
+            args.job_dir.display()
+        ));
+        let code: CodeSelection = args.code.into();
+        let records = read_archived_records(&args.job_dir, &manifest)?;
+        return Ok(records
+            .into_iter()
+            .filter(|(record, _)| match code {
+                CodeSelection::Fixed(code) => record.code == code,
+                CodeSelection::Auto => true,
+            })
+            .collect());
+    }
+
+    let rules = FailureRules::discover(&args.job_dir, args.rules.as_deref())?;
+    let mut cache = (!args.no_cache).then(|| ScanCache::load(&args.job_dir));
+    let mut records = scan_calculations_with_rules(
+        &args.job_dir,
+        args.code.into(),
+        &rules,
+        layout,
+        cache.as_mut(),
+    )?;
+    if let Some(cache) = &cache {
+        if let Err(err) = cache.save(&args.job_dir) {
+            output::print_warning(&format!("Scan cache not saved: {err}"));
+        }
+    }
+    apply_slurm_log_states(&mut records, &[]);
+    Ok(records.into_iter().map(|record| (record, None)).collect())
+}
+
+/// 状态分布；混合代码的作业树按代码分别统计
+fn status_groups(records: &[CalculationScanRecord]) -> Vec<StatusGroup> {
+    let groups = group_by_code(records);
+    let labelled = groups.len() > 1;
+    groups
+        .into_iter()
+        .map(|(code, group)| {
+            let count =
+                |status: CalculationStatus| group.iter().filter(|r| r.status == status).count();
+            let mut counts: Vec<(String, usize)> = BASE_STATUSES
+                .into_iter()
+                .map(|status| (status.to_string(), count(status)))
+                .collect();
+            counts.extend(
+                SCHEDULER_STATUSES
+                    .into_iter()
+                    .map(|status| (status.to_string(), count(status)))
+                    .filter(|(_, count)| *count > 0),
+            );
+            StatusGroup {
+                label: labelled.then(|| code.to_string()),
+                counts,
+            }
+        })
+        .collect()
+}
+
+/// 已结束但未正常完成的作业（含几何优化未收敛）
+fn failed_jobs(records: &[CalculationScanRecord]) -> Vec<FailedJob> {
+    records
+        .iter()
+        .filter(|record| {
+            !matches!(
+                record.status,
+                CalculationStatus::Completed
+                    | CalculationStatus::Incomplete
+                    | CalculationStatus::Running
+                    | CalculationStatus::Pending
+            )
+        })
+        .map(|record| FailedJob {
+            structure: record.structure_name.clone(),
+            code: record.code.to_string(),
+            status: record.status.to_string(),
+            job_id: record.job_id.clone(),
+            reason: record.reason.clone(),
+            suggested_fix: record.suggested_fix.clone(),
+        })
+        .collect()
+}
+
+/// 对未完成与运行中的作业读取逐离子步轨迹并诊断，问题多者在前
+fn in_progress_jobs(records: &[CalculationScanRecord]) -> Vec<InProgressJob> {
+    let criteria = ConvergenceCriteria::default();
+    let mut jobs: Vec<InProgressJob> = records
+        .par_iter()
+        .filter(|record| {
+            matches!(
+                record.status,
+                CalculationStatus::Incomplete | CalculationStatus::Running
+            )
+        })
+        .filter_map(|record| {
+            let trace = load_trace(&record.job_dir, &record.seed, record.code).ok()?;
+            if trace.steps.is_empty() {
+                return None;
+            }
+            let diagnosis = diagnose(&trace, &criteria);
+            Some(InProgressJob {
+                structure: record.structure_name.clone(),
+                code: record.code.to_string(),
+                status: record.status.to_string(),
+                steps: trace.steps.len(),
+                last_force_ev_per_a: trace
+                    .steps
+                    .iter()
+                    .rev()
+                    .find_map(|step| step.max_force_ev_per_a),
+                steps_since_force_minimum: diagnosis.steps_since_force_minimum,
+                issues: diagnosis.issues.iter().map(ToString::to_string).collect(),
+            })
+        })
+        .collect();
+
+    jobs.sort_by(|a, b| {
+        b.issues
+            .len()
+            .cmp(&a.issues.len())
+            .then(
+                b.steps_since_force_minimum
+                    .cmp(&a.steps_since_force_minimum),
+            )
+            .then_with(|| a.structure.cmp(&b.structure))
+    });
+    jobs
+}
+
+/// 按排序量升序排名；不同代码的焓值不可直接比较，混合作业树按代码分组
+fn rank(
+    candidates: Vec<Candidate>,
+    mode: RankMode,
+    eddp: &HashMap<&str, &Crystal>,
+) -> Vec<RankedStructure> {
+    let codes: Vec<DftCodeType> = [DftCodeType::Vasp, DftCodeType::Castep]
+        .into_iter()
+        .filter(|code| candidates.iter().any(|c| c.result.code == *code))
+        .collect();
+    let labelled = codes.len() > 1;
+
+    let mut ranking = Vec::new();
+    for code in codes {
+        let mut group: Vec<(f64, &Candidate)> = candidates
+            .iter()
+            .filter(|c| c.result.code == code)
+            .filter_map(|c| Some((c.value(mode)?, c)))
+            .collect();
+        group.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let Some(&(min_value, _)) = group.first() else {
+            continue;
+        };
+
+        for (i, (value, candidate)) in group.into_iter().enumerate() {
+            let result = &candidate.result;
+            let crystal = candidate.crystal.as_ref().filter(|c| !c.atoms.is_empty());
+            let num_atoms = result.num_atoms.or(crystal.map(|c| c.atoms.len()));
+            let volume = result.volume.or(crystal.map(|c| c.lattice.volume()));
+            ranking.push(RankedStructure {
+                rank: i + 1,
+                group: labelled.then(|| code.to_string()),
+                structure: result.structure_name.clone(),
+                code: code.to_string(),
+                formula: crystal.map(Crystal::formula),
+                space_group: crystal.and_then(|c| c.space_group.clone()).or_else(|| {
+                    eddp.get(result.structure_name.as_str())
+                        .and_then(|c| c.space_group.clone())
+                }),
+                value,
+                delta: value - min_value,
+                enthalpy_ev: result.enthalpy_ev,
+                num_atoms,
+                volume_per_atom: match (volume, num_atoms) {
+                    (Some(v), Some(n)) if n > 0 => Some(v / n as f64),
+                    _ => None,
+                },
+                pressure_gpa: result.pressure_kbar.map(|kbar| kbar * 0.1),
+                max_force_ev_per_a: result.max_force_ev_per_a,
+                geometry_converged: result.geometry_converged,
+                lattice: crystal.map(|c| c.lattice.parameters()),
+            });
+        }
+    }
+    ranking
+}
+
+/// 按结构名把排名与 EDDP 每原子焓配对并计算一致性统计；配对不足 2 个时返回 `None`
+fn parity_section(
+    ranking: &[RankedStructure],
+    eddp: &HashMap<&str, &Crystal>,
+    top_n: usize,
+    source: &std::path::Path,
+) -> Option<ParitySection> {
+    let mut pairs = Vec::new();
+    let mut unmatched = 0;
+    for entry in ranking {
+        let dft_per_atom = match (entry.enthalpy_ev, entry.num_atoms) {
+            (Some(h), Some(n)) if n > 0 => Some(h / n as f64),
+            _ => None,
+        };
+        let eddp_per_atom = eddp
+            .get(entry.structure.as_str())
+            .and_then(|crystal| crystal.enthalpy_per_atom());
+        match (eddp_per_atom, dft_per_atom) {
+            (Some(eddp_per_atom), Some(dft_per_atom)) => pairs.push(ParityPair {
+                structure_name: entry.structure.clone(),
+                eddp_per_atom,
+                dft_per_atom,
+            }),
+            _ => unmatched += 1,
+        }
+    }
+
+    let stats = parity_stats(&pairs, top_n)?;
+    Some(ParitySection {
+        source: source.display().to_string(),
+        stats,
+        pairs,
+        unmatched,
+    })
+}
+
+fn enthalpy_unit(mode: RankMode) -> &'static str {
+    match mode {
+        RankMode::Total => "eV",
+        RankMode::PerAtom => "eV/atom",
+        RankMode::PerFormulaUnit => "eV/f.u.",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, code: DftCodeType, enthalpy: f64, atoms: usize) -> Candidate {
+        let mut result = DftResult::new(name, code);
+        result.enthalpy_ev = Some(enthalpy);
+        result.num_atoms = Some(atoms);
+        Candidate {
+            result,
+            crystal: None,
+        }
+    }
+
+    #[test]
+    fn ranks_each_code_separately_relative_to_its_minimum() {
+        let candidates = vec![
+            candidate("a", DftCodeType::Vasp, -10.0, 2),
+            candidate("b", DftCodeType::Vasp, -12.0, 2),
+            candidate("c", DftCodeType::Castep, -500.0, 4),
+            // 缺少原子数时无法按每原子焓排序
+            candidate("d", DftCodeType::Vasp, -20.0, 0),
+        ];
+
+        let ranking = rank(candidates, RankMode::PerAtom, &HashMap::new());
+        let summary: Vec<(usize, Option<&str>, &str, f64)> = ranking
+            .iter()
+            .map(|r| (r.rank, r.group.as_deref(), r.structure.as_str(), r.delta))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, Some("VASP"), "b", 0.0),
+                (2, Some("VASP"), "a", 1.0),
+                (1, Some("CASTEP"), "c", 0.0),
+            ]
+        );
+    }
+}
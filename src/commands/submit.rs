@@ -1,26 +1,56 @@
 //! # submit 命令实现
 //!
-//! 批量提交 CASTEP/VASP 作业到 Slurm。
+//! 批量提交 CASTEP/VASP 作业到 Slurm、PBS/Torque、LSF 或 SGE。
 //!
 //! ## 功能
 //! - 读取结构列表 CSV
-//! - 生成作业目录和输入文件
-//! - 生成 sbatch 脚本
-//! - 可选自动提交
+//! - 生成作业目录和输入文件；VASP 的 KPOINTS 可按 `--kpoint-spacing` 由倒格矢逐结构生成，POTCAR 按 POSCAR 元素顺序从 `--potcar-dir` 库拼接，缺失的元素在生成任何作业前报错
+//! - 合并命令行参数、`--profile` 集群配置档案与内置默认值（命令行优先）
+//! - 按所选调度器与作业脚本模板（`--script-template`，默认内置模板）生成作业脚本（submit.sbatch / submit.pbs / submit.lsf / submit.sge）；
+//!   模板中的未知占位符在生成任何作业之前报错
+//! - 在作业目录写出作业元数据 `.qutility_job.toml`（结构名、代码与生成的 k 点网格）
+//! - 可选自动提交，并在作业目录记录作业 ID
+//! - `--array`：另在作业根目录按 DFT 代码各写出一个 Slurm 数组脚本（申请的 `-n` 为该代码的 MPI 进程数）
+//!   与共用的任务映射文件，每个代码一次提交，各作业目录记录 `<作业ID>_<任务号>`；
+//!   各作业目录仍保留与数组资源一致的单作业脚本，供 `dft-status --resubmit` 单独重提
 //!
 //! ## 依赖关系
 //! - 使用 `cli/submit.rs` 定义的参数
-//! - 使用 `utils/slurm.rs`, `utils/output.rs`
+//! - 使用 `parsers/` 读取模板所需的结构元数据（化学式、原子数、元素、体积）与 POSCAR 元素顺序
+//! - 使用 `dft/potcar.rs` 组装 POTCAR，`dft/kpoints.rs` 生成 k 点网格
+//! - 使用 `utils/config.rs`（集群配置档案）, `utils/template.rs`（作业脚本模板）, `utils/scheduler.rs`, `utils/slurm.rs`（数组作业与外压块）, `utils/command.rs`, `utils/output.rs`
 
 use crate::cli::submit::{DftEngine, SubmitArgs};
+use crate::dft::{poscar_species, KpointSpacing, PotcarLibrary};
 use crate::error::{QutilityError, Result};
+use crate::parsers::{cell::parse_cell_file, poscar::parse_poscar_file};
+use crate::utils::command::{CommandRunner, SystemCommandRunner};
+use crate::utils::config::{load_profile, ClusterProfile};
 use crate::utils::output;
-use crate::utils::slurm::{generate_sbatch_script, upsert_external_pressure_block, SlurmConfig};
-
+use crate::utils::scheduler::{
+    generate_job_script, submit_job_dir, JobConfig, Scheduler, SchedulerKind, JOB_ID_FILE, LOG_DIR,
+    SCRIPT_PLACEHOLDERS,
+};
+use crate::utils::slurm::{
+    array_map, array_script_name, generate_array_script, upsert_external_pressure_block, ArrayTask,
+    Slurm, ARRAY_MAP_FILE,
+};
+use crate::utils::template::ScriptTemplate;
+
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+
+/// 作业元数据文件（位于作业目录）
+pub const JOB_METADATA_FILE: &str = ".qutility_job.toml";
+
+/// 作业脚本模板中由 submit 提供的占位符（调度器与资源相关的见 `SCRIPT_PLACEHOLDERS`）
+const JOB_PLACEHOLDERS: &[&str] = &["structure", "seed", "dft", "np", "pressure"];
+
+/// 需要读取结构文件的元数据占位符
+const STRUCTURE_PLACEHOLDERS: &[&str] = &["formula", "natoms", "elements", "volume"];
 
 /// 执行 submit 命令
 pub fn execute(args: SubmitArgs) -> Result<()> {
@@ -58,8 +88,74 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
         args.range
     ));
 
+    // 合并集群配置档案
+    let cwd = std::env::current_dir().map_err(|e| QutilityError::FileReadError {
+        path: ".".to_string(),
+        source: e,
+    })?;
+    let profile = match load_profile(args.profile.as_deref(), &cwd)? {
+        Some((name, profile)) => {
+            output::print_info(&format!("Using cluster profile '{name}'"));
+            profile
+        }
+        None => ClusterProfile::default(),
+    };
+    let settings = JobSettings::resolve(&args, profile);
+
+    if args.array && settings.scheduler != SchedulerKind::Slurm {
+        return Err(QutilityError::InvalidArgument(
+            "--array is only supported with the Slurm scheduler".to_string(),
+        ));
+    }
+
+    // 作业脚本模板：先检查占位符，避免生成一半作业后才报错
+    let template = match &settings.script_template {
+        Some(path) => {
+            output::print_info(&format!("Using job script template {}", path.display()));
+            ScriptTemplate::from_file(path)?
+        }
+        None => ScriptTemplate::default(),
+    };
+    template.check(
+        SCRIPT_PLACEHOLDERS
+            .iter()
+            .chain(JOB_PLACEHOLDERS)
+            .chain(STRUCTURE_PLACEHOLDERS)
+            .copied(),
+    )?;
+    if args.array && settings.script_template.is_some() {
+        output::print_warning(
+            "--array writes the built-in array script; the job script template only applies to the per-job scripts used for resubmission",
+        );
+    }
+    let needs_metadata = template
+        .placeholders()
+        .any(|name| STRUCTURE_PLACEHOLDERS.contains(&name));
+
+    if let Some(spacing) = args.kpoint_spacing {
+        if !(spacing > 0.0 && spacing.is_finite()) {
+            return Err(QutilityError::InvalidArgument(format!(
+                "--kpoint-spacing must be positive, got {spacing}"
+            )));
+        }
+        if args.dft == DftEngine::Castep {
+            output::print_warning(
+                "--kpoint-spacing only applies to VASP jobs; set KPOINTS_MP_SPACING in the CASTEP .param",
+            );
+        }
+    }
+
+    // 先为所有 VASP 结构载入并检查 POTCAR
+    let mut potcars = match &args.potcar_dir {
+        Some(dir) => Some(load_potcar_library(&args, dir, &structures, &indices)?),
+        None => None,
+    };
+
+    let scheduler = settings.scheduler.scheduler();
+    let runner = SystemCommandRunner::new().with_overrides(args.scheduler_cmd.iter().cloned());
     let mut submitted = Vec::new();
     let mut generated = Vec::new();
+    let mut array_groups: Vec<ArrayGroup> = Vec::new();
 
     for idx in &indices {
         let i = *idx;
@@ -78,21 +174,13 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
         let (cell_path, poscar_path) = find_structure_files(&args.struct_dir, structure_name);
 
         // 决定使用哪个 DFT 代码
-        let chosen_dft = match args.dft {
-            DftEngine::Auto => {
-                if cell_path.is_some() {
-                    DftEngine::Castep
-                } else if poscar_path.is_some() {
-                    DftEngine::Vasp
-                } else {
-                    output::print_warning(&format!(
-                        "No .cell or POSCAR found for '{}', skipping",
-                        structure_name
-                    ));
-                    continue;
-                }
-            }
-            other => other,
+        let Some(chosen_dft) = choose_dft(args.dft, cell_path.is_some(), poscar_path.is_some())
+        else {
+            output::print_warning(&format!(
+                "No .cell or POSCAR found for '{}', skipping",
+                structure_name
+            ));
+            continue;
         };
 
         // 创建作业目录
@@ -102,14 +190,14 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
             source: e,
         })?;
 
-        // 创建 slurm_logs 目录
-        fs::create_dir_all(job_dir.join("slurm_logs")).ok();
+        // 创建作业日志目录
+        fs::create_dir_all(job_dir.join(LOG_DIR)).ok();
 
         // 根据 DFT 代码生成输入
-        let sbatch_path = match chosen_dft {
+        let prepared = match chosen_dft {
             DftEngine::Castep => {
                 if let Some(cell_src) = cell_path {
-                    prepare_castep_job(&args, &job_dir, structure_name, &cell_src)?
+                    prepare_castep_job(&args, &settings, &job_dir, structure_name, &cell_src)?
                 } else {
                     output::print_warning(&format!("No .cell file for CASTEP: {}", structure_name));
                     continue;
@@ -117,7 +205,14 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
             }
             DftEngine::Vasp => {
                 if let Some(poscar_src) = poscar_path {
-                    prepare_vasp_job(&args, &job_dir, structure_name, &poscar_src)?
+                    prepare_vasp_job(
+                        &args,
+                        &settings,
+                        potcars.as_mut(),
+                        &job_dir,
+                        structure_name,
+                        &poscar_src,
+                    )?
                 } else {
                     output::print_warning(&format!("No POSCAR for VASP: {}", structure_name));
                     continue;
@@ -126,35 +221,77 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
             DftEngine::Auto => unreachable!(),
         };
 
+        write_job_metadata(
+            &job_dir,
+            structure_name,
+            &prepared,
+            args.kpoint_spacing.map(|_| kpoint_spacing(&args)),
+        )?;
         generated.push(structure_name.clone());
 
-        // 提交作业
+        // 数组模式下也写出单作业脚本，供 dft-status --resubmit 单独重提
+        let variables = job_variables(
+            structure_name,
+            &prepared,
+            args.external_pressure,
+            needs_metadata,
+        )?;
+        write_job_script(
+            &settings,
+            &template,
+            &job_dir,
+            structure_name,
+            &prepared,
+            &variables,
+            args.array,
+        )?;
+
+        // 数组模式只按代码收集任务，循环结束后统一写出数组脚本
+        if args.array {
+            let index = match array_groups.iter().position(|g| g.dft == prepared.dft) {
+                Some(index) => index,
+                None => {
+                    array_groups.push(ArrayGroup {
+                        dft: prepared.dft,
+                        np: prepared.np,
+                        modules: Vec::new(),
+                        tasks: Vec::new(),
+                    });
+                    array_groups.len() - 1
+                }
+            };
+            let group = &mut array_groups[index];
+            for module in prepared.modules {
+                if !group.modules.contains(module) {
+                    group.modules.push(module.clone());
+                }
+            }
+            group.tasks.push(ArrayTask {
+                structure: structure_name.clone(),
+                job_dir,
+                exec_cmd: prepared.exec_cmd,
+            });
+            continue;
+        }
+
+        // 提交作业；作业 ID 记录在作业目录中，供 dft-status --queue 按 ID 匹配
         if args.submit && !args.dry_run {
-            let sbatch_arg = submit_script_arg(&sbatch_path);
-            match Command::new("sbatch")
-                .arg(&sbatch_arg)
-                .current_dir(&job_dir)
-                .output()
-            {
-                Ok(out) if out.status.success() => {
+            match submit_job_dir(scheduler, &runner, &job_dir) {
+                Ok(job_id) => {
                     output::print_success(&format!(
-                        "Submitted: {} - {}",
+                        "Submitted: {} - {} job {}",
                         structure_name,
-                        String::from_utf8_lossy(&out.stdout).trim()
+                        scheduler.name(),
+                        job_id
                     ));
                     submitted.push(structure_name.clone());
                 }
-                Ok(out) => {
-                    output::print_error(&format!(
-                        "sbatch failed for {}: {}",
-                        structure_name,
-                        String::from_utf8_lossy(&out.stderr)
-                    ));
-                }
                 Err(e) => {
                     output::print_error(&format!(
-                        "Failed to run sbatch for {}: {}",
-                        structure_name, e
+                        "{} submission failed for {}: {}",
+                        scheduler.name(),
+                        structure_name,
+                        e
                     ));
                 }
             }
@@ -163,6 +300,10 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
         }
     }
 
+    if !array_groups.is_empty() {
+        submitted.extend(write_array_jobs(&args, &settings, &runner, &array_groups)?);
+    }
+
     output::print_separator();
     output::print_done(&format!(
         "Processed {} entries, generated {} jobs, submitted {} jobs",
@@ -174,6 +315,78 @@ pub fn execute(args: SubmitArgs) -> Result<()> {
     Ok(())
 }
 
+/// 同一 DFT 代码的数组任务，作为一个数组作业提交
+struct ArrayGroup {
+    /// "castep" 或 "vasp"
+    dft: &'static str,
+    /// 每个任务启动的 MPI 进程数，也是数组申请的 `-n`
+    np: u32,
+    modules: Vec<String>,
+    tasks: Vec<ArrayTask>,
+}
+
+/// 已准备好输入文件的作业：运行命令、所需模块与模板变量所需的信息
+struct PreparedJob<'a> {
+    exec_cmd: String,
+    modules: &'a [String],
+    /// "castep" 或 "vasp"
+    dft: &'static str,
+    seed: String,
+    np: u32,
+    /// 作业目录中的结构输入文件（`<seed>.cell` 或 POSCAR）
+    input: PathBuf,
+    /// 按间距生成的 k 点网格
+    kpoint_mesh: Option<[u32; 3]>,
+}
+
+/// 写入作业目录的元数据
+#[derive(Serialize)]
+struct JobMetadata<'a> {
+    structure: &'a str,
+    dft: &'a str,
+    kpoints: Option<KpointMetadata>,
+}
+
+#[derive(Serialize)]
+struct KpointMetadata {
+    #[serde(flatten)]
+    settings: KpointSpacing,
+    mesh: [u32; 3],
+}
+
+/// 写出作业元数据；生成了 k 点网格时连同间距设置一并记录
+fn write_job_metadata(
+    job_dir: &Path,
+    structure_name: &str,
+    prepared: &PreparedJob,
+    kpoints: Option<KpointSpacing>,
+) -> Result<()> {
+    let metadata = JobMetadata {
+        structure: structure_name,
+        dft: prepared.dft,
+        kpoints: kpoints
+            .zip(prepared.kpoint_mesh)
+            .map(|(settings, mesh)| KpointMetadata { settings, mesh }),
+    };
+    let path = job_dir.join(JOB_METADATA_FILE);
+    let content = toml::to_string(&metadata)
+        .map_err(|e| QutilityError::Other(format!("Cannot serialize job metadata: {e}")))?;
+    fs::write(&path, content).map_err(|e| QutilityError::FileWriteError {
+        path: path.display().to_string(),
+        source: e,
+    })
+}
+
+/// 由 `--kpoint-*` 参数组成的 k 点间距设置
+fn kpoint_spacing(args: &SubmitArgs) -> KpointSpacing {
+    KpointSpacing {
+        spacing: args.kpoint_spacing.unwrap_or_default(),
+        unit: args.kpoint_unit.into(),
+        centering: args.kpoint_centering.into(),
+        parity: args.kpoint_parity.into(),
+    }
+}
+
 /// 读取 CSV 中的结构名称列表
 fn read_csv_structures(path: &Path) -> Result<Vec<String>> {
     let file = File::open(path).map_err(|e| QutilityError::FileReadError {
@@ -254,6 +467,90 @@ fn parse_range(expr: &str) -> Result<Vec<usize>> {
     Ok(items)
 }
 
+/// 决定结构使用的 DFT 代码；`auto` 时有 .cell 用 CASTEP，否则有 POSCAR 用 VASP
+fn choose_dft(engine: DftEngine, has_cell: bool, has_poscar: bool) -> Option<DftEngine> {
+    match engine {
+        DftEngine::Auto if has_cell => Some(DftEngine::Castep),
+        DftEngine::Auto if has_poscar => Some(DftEngine::Vasp),
+        DftEngine::Auto => None,
+        other => Some(other),
+    }
+}
+
+/// 解析 `--potcar-map` 的 `元素=目录名` 或 `元素=_后缀` 项
+fn parse_potcar_map(entries: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut mapping = BTreeMap::new();
+    for entry in entries.iter().map(|e| e.trim()).filter(|e| !e.is_empty()) {
+        match entry.split_once('=') {
+            Some((element, variant))
+                if !element.trim().is_empty() && !variant.trim().is_empty() =>
+            {
+                mapping.insert(element.trim().to_string(), variant.trim().to_string());
+            }
+            _ => {
+                return Err(QutilityError::InvalidArgument(format!(
+                    "Invalid --potcar-map entry '{entry}' (expected e.g. 'Fe=Fe_pv' or 'Fe=_pv')"
+                )))
+            }
+        }
+    }
+    Ok(mapping)
+}
+
+/// 读取所有选中 VASP 结构的 POSCAR 元素，载入并检查对应 POTCAR；缺失的元素在生成任何作业前一次性报错
+fn load_potcar_library(
+    args: &SubmitArgs,
+    dir: &Path,
+    structures: &[String],
+    indices: &[usize],
+) -> Result<PotcarLibrary> {
+    if dir.is_file() {
+        return Err(QutilityError::InvalidArgument(format!(
+            "--potcar-dir {} is a file; it expects a POTCAR library directory holding <Element><suffix>/POTCAR",
+            dir.display()
+        )));
+    }
+    if !dir.is_dir() {
+        return Err(QutilityError::DirectoryNotFound {
+            path: dir.display().to_string(),
+        });
+    }
+    let mut library = PotcarLibrary::new(
+        dir,
+        parse_potcar_map(&args.potcar_map)?,
+        args.potcar_preset.into(),
+    );
+
+    let mut elements: Vec<String> = Vec::new();
+    for name in indices
+        .iter()
+        .filter_map(|&i| structures.get(i.wrapping_sub(1)))
+        .filter(|name| !name.is_empty())
+    {
+        let (cell, poscar) = find_structure_files(&args.struct_dir, name);
+        let Some(poscar) = poscar else { continue };
+        if choose_dft(args.dft, cell.is_some(), true) != Some(DftEngine::Vasp) {
+            continue;
+        }
+        for element in poscar_species(&parse_poscar_file(&poscar)?)? {
+            if !elements.contains(&element) {
+                elements.push(element);
+            }
+        }
+    }
+
+    library.load(&elements)?;
+    for element in &elements {
+        if let Some(entry) = library.entry(element) {
+            output::print_info(&format!(
+                "POTCAR {element} -> {}: {} (ZVAL {})",
+                entry.variant, entry.titel, entry.zval
+            ));
+        }
+    }
+    Ok(library)
+}
+
 /// 查找结构文件
 fn find_structure_files(
     struct_dir: &Path,
@@ -275,12 +572,13 @@ fn find_structure_files(
 }
 
 /// 准备 CASTEP 作业
-fn prepare_castep_job(
+fn prepare_castep_job<'a>(
     args: &SubmitArgs,
+    settings: &'a JobSettings,
     job_dir: &Path,
     structure_name: &str,
     cell_src: &Path,
-) -> Result<PathBuf> {
+) -> Result<PreparedJob<'a>> {
     // 检查 param 模板
     let param_template = args.param_template.as_ref().ok_or_else(|| {
         QutilityError::InvalidArgument("CASTEP requires --param-template".to_string())
@@ -313,49 +611,31 @@ fn prepare_castep_job(
         source: std::io::Error::new(std::io::ErrorKind::Other, e.to_string()),
     })?;
 
-    // 生成 sbatch 脚本
-    let modules: Vec<String> = args
-        .castep_modules
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    let config = SlurmConfig {
-        job_name: seed.to_string(),
-        partition: args.partition.clone(),
-        constraint: args.constraint.clone(),
-        nodes: args.nodes,
-        ntasks: args.ntasks,
-        cpus_per_task: args.cpus_per_task,
-        mem_per_cpu: args.mem_per_cpu.clone(),
-        time_limit: args.time.clone(),
-        modules,
-    };
-
-    let exec_cmd = format!(
-        "mpirun -np {} {} \"{}\"",
-        args.castep_np, args.castep_exec, seed
-    );
-
-    let sbatch_content = generate_sbatch_script(&config, job_dir, &exec_cmd);
-    let sbatch_path = job_dir.join("submit.sbatch");
-
-    fs::write(&sbatch_path, sbatch_content).map_err(|e| QutilityError::FileWriteError {
-        path: sbatch_path.display().to_string(),
-        source: e,
-    })?;
-
-    Ok(sbatch_path)
+    Ok(PreparedJob {
+        exec_cmd: format!(
+            "{} {} \"{}\"",
+            settings.launcher(settings.castep_np),
+            settings.castep_exec,
+            seed
+        ),
+        modules: &settings.castep_modules,
+        dft: "castep",
+        seed: seed.to_string(),
+        np: settings.castep_np,
+        input: dest_cell,
+        kpoint_mesh: None,
+    })
 }
 
 /// 准备 VASP 作业
-fn prepare_vasp_job(
+fn prepare_vasp_job<'a>(
     args: &SubmitArgs,
+    settings: &'a JobSettings,
+    potcars: Option<&mut PotcarLibrary>,
     job_dir: &Path,
     structure_name: &str,
     poscar_src: &Path,
-) -> Result<PathBuf> {
+) -> Result<PreparedJob<'a>> {
     // 检查必需的模板
     let incar_template = args.incar_template.as_ref().ok_or_else(|| {
         QutilityError::InvalidArgument("VASP requires --incar-template".to_string())
@@ -372,7 +652,21 @@ fn prepare_vasp_job(
         source: std::io::Error::new(std::io::ErrorKind::Other, e.to_string()),
     })?;
 
-    if let Some(kpoints_template) = args.kpoints_template.as_ref() {
+    let mut kpoint_mesh = None;
+    if args.kpoint_spacing.is_some() {
+        // 按目标间距由 POSCAR 的倒格矢生成网格
+        let spacing = kpoint_spacing(args);
+        let crystal = parse_poscar_file(&job_dir.join("POSCAR"))?;
+        let mesh = spacing.mesh(&crystal.lattice);
+        let kpoints_dst = job_dir.join("KPOINTS");
+        fs::write(&kpoints_dst, spacing.kpoints_file(mesh)).map_err(|e| {
+            QutilityError::FileWriteError {
+                path: kpoints_dst.display().to_string(),
+                source: e,
+            }
+        })?;
+        kpoint_mesh = Some(mesh);
+    } else if let Some(kpoints_template) = args.kpoints_template.as_ref() {
         fs::copy(kpoints_template, job_dir.join("KPOINTS")).map_err(|e| {
             QutilityError::FileWriteError {
                 path: job_dir.join("KPOINTS").display().to_string(),
@@ -386,81 +680,333 @@ fn prepare_vasp_job(
         ));
     }
 
-    // POTCAR 处理
+    // POTCAR：按 POSCAR 的元素顺序从库中拼接
     let potcar_dst = job_dir.join("POTCAR");
-    if !potcar_dst.exists() {
-        if let Some(ref potcar_dir) = args.potcar_dir {
-            let potcar_src = potcar_dir.join("POTCAR");
-            if potcar_src.exists() {
-                fs::copy(&potcar_src, &potcar_dst).map_err(|e| QutilityError::FileWriteError {
-                    path: potcar_dst.display().to_string(),
-                    source: std::io::Error::new(std::io::ErrorKind::Other, e.to_string()),
-                })?;
-            } else {
-                output::print_warning(&format!(
-                    "POTCAR not found in {}, job may fail",
-                    potcar_dir.display()
-                ));
-            }
-        } else {
-            output::print_warning(&format!(
-                "No POTCAR for {}, please provide manually",
-                structure_name
-            ));
-        }
+    if let Some(library) = potcars {
+        let species = poscar_species(&parse_poscar_file(&job_dir.join("POSCAR"))?)?;
+        let potcar = library.assemble(&species)?;
+        fs::write(&potcar_dst, potcar).map_err(|e| QutilityError::FileWriteError {
+            path: potcar_dst.display().to_string(),
+            source: e,
+        })?;
+    } else if !potcar_dst.exists() {
+        output::print_warning(&format!(
+            "No POTCAR for {}, please provide manually",
+            structure_name
+        ));
     }
 
-    // 生成 sbatch 脚本
-    let modules: Vec<String> = args
-        .vasp_modules
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    let config = SlurmConfig {
-        job_name: structure_name.to_string(),
-        partition: args.partition.clone(),
-        constraint: args.constraint.clone(),
-        nodes: args.nodes,
-        ntasks: args.ntasks,
-        cpus_per_task: args.cpus_per_task,
-        mem_per_cpu: args.mem_per_cpu.clone(),
-        time_limit: args.time.clone(),
-        modules,
+    Ok(PreparedJob {
+        exec_cmd: format!(
+            "{} {}",
+            settings.launcher(settings.vasp_np),
+            settings.vasp_exec
+        ),
+        modules: &settings.vasp_modules,
+        dft: "vasp",
+        seed: structure_name.to_string(),
+        np: settings.vasp_np,
+        input: job_dir.join("POSCAR"),
+        kpoint_mesh,
+    })
+}
+
+/// 按所选调度器写出作业脚本，返回脚本路径；
+/// `array_task` 时与所属数组作业一致，申请的 `-n` 为该代码的 MPI 进程数
+fn write_job_script(
+    settings: &JobSettings,
+    template: &ScriptTemplate,
+    job_dir: &Path,
+    job_name: &str,
+    prepared: &PreparedJob,
+    variables: &BTreeMap<&str, String>,
+    array_task: bool,
+) -> Result<PathBuf> {
+    let mut config = settings.job_config(job_name, prepared.modules.to_vec());
+    if array_task {
+        config.ntasks = prepared.np;
+    }
+    let scheduler = settings.scheduler.scheduler();
+    let content = generate_job_script(
+        scheduler,
+        template,
+        &config,
+        job_dir,
+        &prepared.exec_cmd,
+        variables,
+    )?;
+    let script_path = job_dir.join(scheduler.script_name());
+
+    fs::write(&script_path, content).map_err(|e| QutilityError::FileWriteError {
+        path: script_path.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(script_path)
+}
+
+/// 作业相关的模板变量；`with_metadata` 时读取作业目录中的结构文件得到化学式、原子数、元素与体积
+fn job_variables(
+    structure_name: &str,
+    prepared: &PreparedJob,
+    external_pressure: Option<f64>,
+    with_metadata: bool,
+) -> Result<BTreeMap<&'static str, String>> {
+    let mut variables = BTreeMap::from([
+        ("structure", structure_name.to_string()),
+        ("seed", prepared.seed.clone()),
+        ("dft", prepared.dft.to_string()),
+        ("np", prepared.np.to_string()),
+        (
+            "pressure",
+            external_pressure.map(|p| p.to_string()).unwrap_or_default(),
+        ),
+    ]);
+    if !with_metadata {
+        return Ok(variables);
+    }
+
+    let crystal = match prepared.dft {
+        "castep" => parse_cell_file(&prepared.input)?,
+        _ => parse_poscar_file(&prepared.input)?,
     };
+    let mut elements: Vec<&str> = Vec::new();
+    for atom in &crystal.atoms {
+        if !elements.contains(&atom.element.as_str()) {
+            elements.push(&atom.element);
+        }
+    }
+    variables.insert("formula", crystal.formula());
+    variables.insert("natoms", crystal.atoms.len().to_string());
+    variables.insert("elements", elements.join(" "));
+    variables.insert("volume", format!("{:.4}", crystal.lattice.volume().abs()));
+    Ok(variables)
+}
 
-    let exec_cmd = format!("mpirun -np {} {}", args.vasp_np, args.vasp_exec);
-    let sbatch_content = generate_sbatch_script(&config, job_dir, &exec_cmd);
-    let sbatch_path = job_dir.join("submit.sbatch");
+/// 在作业根目录写出共用的任务映射文件，并为每个 DFT 代码写出一个覆盖其连续任务号的 Slurm 数组脚本；
+/// `--submit` 时逐个提交，并在对应作业目录记录 `<作业ID>_<任务号>`。返回已提交的结构名
+fn write_array_jobs(
+    args: &SubmitArgs,
+    settings: &JobSettings,
+    runner: &dyn CommandRunner,
+    groups: &[ArrayGroup],
+) -> Result<Vec<String>> {
+    let root = &args.jobs_root;
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "qutility_array".to_string());
+
+    fs::create_dir_all(root.join(LOG_DIR)).ok();
+    let map_path = root.join(ARRAY_MAP_FILE);
+    let map = array_map(groups.iter().flat_map(|group| &group.tasks));
+    fs::write(&map_path, map).map_err(|e| QutilityError::FileWriteError {
+        path: map_path.display().to_string(),
+        source: e,
+    })?;
 
-    fs::write(&sbatch_path, sbatch_content).map_err(|e| QutilityError::FileWriteError {
-        path: sbatch_path.display().to_string(),
+    let submit = args.submit && !args.dry_run;
+    let root = std::path::absolute(root).map_err(|e| QutilityError::FileReadError {
+        path: root.display().to_string(),
         source: e,
     })?;
+    let mut submitted = Vec::new();
+    let mut first = 1;
+    for group in groups {
+        let tasks = first..=first + group.tasks.len() - 1;
+        first += group.tasks.len();
+
+        let mut config =
+            settings.job_config(&format!("{name}_{}", group.dft), group.modules.clone());
+        config.ntasks = group.np;
+        let script_path = root.join(array_script_name(group.dft));
+        let script = generate_array_script(&config, &root, tasks.clone(), args.array_limit);
+        fs::write(&script_path, script).map_err(|e| QutilityError::FileWriteError {
+            path: script_path.display().to_string(),
+            source: e,
+        })?;
+        output::print_info(&format!(
+            "Array of {} {} tasks ({}-{}) written to {} (map: {})",
+            group.tasks.len(),
+            group.dft,
+            tasks.start(),
+            tasks.end(),
+            script_path.display(),
+            map_path.display()
+        ));
+
+        if !submit {
+            output::print_info(&format!(
+                "[DRY] Generated array job: {}",
+                script_path.display()
+            ));
+            continue;
+        }
+
+        let job_id = match Slurm.submit(runner, &script_path, &root) {
+            Ok(job_id) => job_id,
+            Err(e) => {
+                output::print_error(&format!(
+                    "Slurm array submission failed for {} tasks: {e}",
+                    group.dft
+                ));
+                continue;
+            }
+        };
+        for (task_id, task) in tasks.zip(&group.tasks) {
+            let id_file = task.job_dir.join(JOB_ID_FILE);
+            fs::write(&id_file, format!("{job_id}_{task_id}\n")).map_err(|e| {
+                QutilityError::FileWriteError {
+                    path: id_file.display().to_string(),
+                    source: e,
+                }
+            })?;
+            submitted.push(task.structure.clone());
+        }
+        output::print_success(&format!(
+            "Submitted: Slurm array job {job_id} with {} {} tasks",
+            group.tasks.len(),
+            group.dft
+        ));
+    }
 
-    Ok(sbatch_path)
+    Ok(submitted)
 }
 
-fn submit_script_arg(sbatch_path: &Path) -> PathBuf {
-    sbatch_path
-        .file_name()
-        .map(PathBuf::from)
-        .unwrap_or_else(|| sbatch_path.to_path_buf())
+/// 命令行参数、集群配置档案与内置默认值合并后的作业设置
+struct JobSettings {
+    scheduler: SchedulerKind,
+    partition: String,
+    constraint: String,
+    nodes: u32,
+    ntasks: u32,
+    cpus_per_task: u32,
+    mem_per_cpu: String,
+    time: String,
+    sge_pe: String,
+    mpi_launcher: String,
+    /// 作业脚本模板文件；None 时使用内置模板
+    script_template: Option<PathBuf>,
+    castep_exec: String,
+    castep_np: u32,
+    castep_modules: Vec<String>,
+    vasp_exec: String,
+    vasp_np: u32,
+    vasp_modules: Vec<String>,
+    env: Vec<(String, String)>,
+    preamble: Vec<String>,
+}
+
+impl JobSettings {
+    /// 逐字段取命令行参数，其次配置档案，最后是内置默认值
+    fn resolve(args: &SubmitArgs, profile: ClusterProfile) -> Self {
+        JobSettings {
+            scheduler: args
+                .scheduler
+                .map(SchedulerKind::from)
+                .or(profile.scheduler)
+                .unwrap_or(SchedulerKind::Slurm),
+            partition: args
+                .partition
+                .clone()
+                .or(profile.partition)
+                .unwrap_or_default(),
+            constraint: args
+                .constraint
+                .clone()
+                .or(profile.constraint)
+                .unwrap_or_default(),
+            nodes: args.nodes.or(profile.nodes).unwrap_or(1),
+            ntasks: args.ntasks.or(profile.ntasks).unwrap_or(32),
+            cpus_per_task: args.cpus_per_task.or(profile.cpus_per_task).unwrap_or(1),
+            mem_per_cpu: args
+                .mem_per_cpu
+                .clone()
+                .or(profile.mem_per_cpu)
+                .unwrap_or_default(),
+            time: args
+                .time
+                .clone()
+                .or(profile.time)
+                .unwrap_or_else(|| "24:00:00".to_string()),
+            sge_pe: args
+                .sge_pe
+                .clone()
+                .or(profile.sge_pe)
+                .unwrap_or_else(|| "mpi".to_string()),
+            mpi_launcher: args
+                .mpi_launcher
+                .clone()
+                .or(profile.mpi_launcher)
+                .unwrap_or_else(|| "mpirun -np {np}".to_string()),
+            script_template: args.script_template.clone().or(profile.script_template),
+            castep_exec: args
+                .castep_exec
+                .clone()
+                .or(profile.castep_exec)
+                .unwrap_or_else(|| "castep.mpi".to_string()),
+            castep_np: args.castep_np.or(profile.castep_np).unwrap_or(32),
+            castep_modules: args
+                .castep_modules
+                .as_deref()
+                .map(split_modules)
+                .or(profile.castep_modules)
+                .unwrap_or_default(),
+            vasp_exec: args
+                .vasp_exec
+                .clone()
+                .or(profile.vasp_exec)
+                .unwrap_or_else(|| "vasp_std".to_string()),
+            vasp_np: args.vasp_np.or(profile.vasp_np).unwrap_or(32),
+            vasp_modules: args
+                .vasp_modules
+                .as_deref()
+                .map(split_modules)
+                .or(profile.vasp_modules)
+                .unwrap_or_default(),
+            env: profile.env.into_iter().collect(),
+            preamble: profile.preamble,
+        }
+    }
+
+    /// MPI 启动命令，`{np}` 替换为进程数
+    fn launcher(&self, np: u32) -> String {
+        self.mpi_launcher.replace("{np}", &np.to_string())
+    }
+
+    fn job_config(&self, job_name: &str, modules: Vec<String>) -> JobConfig {
+        JobConfig {
+            job_name: job_name.to_string(),
+            partition: self.partition.clone(),
+            constraint: self.constraint.clone(),
+            nodes: self.nodes,
+            ntasks: self.ntasks,
+            cpus_per_task: self.cpus_per_task,
+            mem_per_cpu: self.mem_per_cpu.clone(),
+            time_limit: self.time.clone(),
+            parallel_env: self.sge_pe.clone(),
+            modules,
+            env: self.env.clone(),
+            preamble: self.preamble.clone(),
+        }
+    }
+}
+
+fn split_modules(modules: &str) -> Vec<String> {
+    modules
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    fn unique_test_dir(name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time before UNIX_EPOCH")
-            .as_nanos();
-        std::env::temp_dir().join(format!("qutility-{name}-{nanos}"))
-    }
+    use crate::cli::submit::{KpointCentering, KpointParity, KpointUnit, PotcarSet, SchedulerType};
+    use crate::dft::scan_calculations;
+    use crate::models::DftCodeType;
+    use crate::utils::test_support::unique_test_dir;
 
     fn base_submit_args() -> SubmitArgs {
         SubmitArgs {
@@ -470,25 +1016,39 @@ mod tests {
             jobs_root: PathBuf::from("jobs"),
             dft: DftEngine::Vasp,
             param_template: None,
-            castep_exec: "castep.mpi".to_string(),
-            castep_np: 32,
-            castep_modules: "airss/arm-v2/0.2,castep/arm-v2/25.12".to_string(),
+            castep_exec: None,
+            castep_np: None,
+            castep_modules: None,
             external_pressure: None,
             incar_template: None,
             kpoints_template: None,
+            kpoint_spacing: None,
+            kpoint_unit: KpointUnit::TwoPiPerAngstrom,
+            kpoint_centering: KpointCentering::Gamma,
+            kpoint_parity: KpointParity::Any,
             potcar_dir: None,
-            vasp_exec: "vasp_std".to_string(),
-            vasp_np: 32,
-            vasp_modules: String::new(),
-            partition: "arm".to_string(),
-            constraint: "neoverse_v2".to_string(),
-            nodes: 1,
-            ntasks: 32,
-            cpus_per_task: 1,
-            mem_per_cpu: "3G".to_string(),
-            time: "24:00:00".to_string(),
+            potcar_map: Vec::new(),
+            potcar_preset: PotcarSet::Recommended,
+            vasp_exec: None,
+            vasp_np: None,
+            vasp_modules: None,
+            profile: None,
+            scheduler: None,
+            partition: None,
+            constraint: None,
+            sge_pe: None,
+            nodes: None,
+            ntasks: None,
+            cpus_per_task: None,
+            mem_per_cpu: None,
+            time: None,
+            mpi_launcher: None,
+            script_template: None,
             dry_run: true,
             submit: false,
+            scheduler_cmd: Vec::new(),
+            array: false,
+            array_limit: None,
         }
     }
 
@@ -506,10 +1066,30 @@ mod tests {
         let mut args = base_submit_args();
         args.incar_template = Some(incar_template.clone());
 
-        let sbatch_path =
-            prepare_vasp_job(&args, &job_dir, "test-structure", &poscar_src).expect("prepare");
-
-        assert!(sbatch_path.exists());
+        let settings = JobSettings::resolve(&args, ClusterProfile::default());
+        let prepared = prepare_vasp_job(
+            &args,
+            &settings,
+            None,
+            &job_dir,
+            "test-structure",
+            &poscar_src,
+        )
+        .expect("prepare");
+        let variables = job_variables("test-structure", &prepared, None, false).expect("variables");
+        let script_path = write_job_script(
+            &settings,
+            &ScriptTemplate::default(),
+            &job_dir,
+            "test-structure",
+            &prepared,
+            &variables,
+            false,
+        )
+        .expect("write script");
+
+        assert_eq!(prepared.exec_cmd, "mpirun -np 32 vasp_std");
+        assert!(script_path.exists());
         assert!(job_dir.join("POSCAR").exists());
         assert!(job_dir.join("INCAR").exists());
         assert!(!job_dir.join("KPOINTS").exists());
@@ -518,12 +1098,282 @@ mod tests {
     }
 
     #[test]
-    fn submit_script_arg_uses_local_script_name() {
-        let sbatch_path = PathBuf::from("jobs/TiC-957221-4973-44/submit.sbatch");
+    fn write_job_script_follows_selected_scheduler() {
+        let root = unique_test_dir("pbs-script");
+        fs::create_dir_all(&root).expect("create job dir");
 
+        let mut args = base_submit_args();
+        args.scheduler = Some(SchedulerType::Pbs);
+        args.constraint = Some("neoverse_v2".to_string());
+        let settings = JobSettings::resolve(&args, ClusterProfile::default());
+
+        let modules = ["vasp/6.4".to_string()];
+        let prepared = PreparedJob {
+            exec_cmd: "mpirun vasp_std".to_string(),
+            modules: &modules,
+            dft: "vasp",
+            seed: "TiC".to_string(),
+            np: 32,
+            input: root.join("POSCAR"),
+            kpoint_mesh: None,
+        };
+        let script = write_job_script(
+            &settings,
+            &ScriptTemplate::default(),
+            &root,
+            "TiC",
+            &prepared,
+            &BTreeMap::new(),
+            false,
+        )
+        .expect("write script");
+
+        assert_eq!(script, root.join("submit.pbs"));
+        let content = fs::read_to_string(&script).expect("read script");
+        assert!(content.contains("#PBS -N TiC"));
+        assert!(content.contains("#PBS -l nodes=1:ppn=32:neoverse_v2"));
+        assert!(content.contains("module load vasp/6.4"));
+        assert!(!content.contains("#SBATCH"));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn array_mode_writes_one_array_per_code_and_a_shared_task_map() {
+        let root = unique_test_dir("array");
+        let mut groups = Vec::new();
+        for (dft, np, names) in [("castep", 8, ["Si8", "C8"]), ("vasp", 64, ["Ge8", "Sn8"])] {
+            let mut tasks = Vec::new();
+            for name in names {
+                fs::create_dir_all(root.join(name)).expect("create job dir");
+                tasks.push(ArrayTask {
+                    structure: name.to_string(),
+                    job_dir: root.join(name),
+                    exec_cmd: format!("mpirun -np {np} {dft} \"{name}\""),
+                });
+            }
+            groups.push(ArrayGroup {
+                dft,
+                np,
+                modules: Vec::new(),
+                tasks,
+            });
+        }
+
+        let mut args = base_submit_args();
+        args.jobs_root = root.clone();
+        args.array = true;
+        args.array_limit = Some(1);
+
+        let settings = JobSettings::resolve(&args, ClusterProfile::default());
+        let submitted = write_array_jobs(&args, &settings, &SystemCommandRunner::new(), &groups)
+            .expect("write array");
+
+        assert!(submitted.is_empty());
+        let castep = fs::read_to_string(root.join("submit_array_castep.sbatch")).expect("read");
+        assert!(castep.contains("#SBATCH --array=1-2%1"));
+        assert!(castep.contains("#SBATCH -n 8\n"));
+        let vasp = fs::read_to_string(root.join("submit_array_vasp.sbatch")).expect("read");
+        assert!(vasp.contains("#SBATCH --array=3-4%1"));
+        assert!(vasp.contains("#SBATCH -n 64\n"));
+        let map = fs::read_to_string(root.join(ARRAY_MAP_FILE)).expect("read map");
+        assert_eq!(map.lines().count(), 5);
+        assert!(map.lines().nth(3).expect("task 3").starts_with("3\tGe8\t"));
+
+        // 作业根目录下的数组日志目录不能被扫描成作业
+        assert!(root.join("slurm_logs").is_dir());
+        let records = scan_calculations(&root, DftCodeType::Vasp).expect("scan");
+        let names: Vec<_> = records.iter().map(|r| r.structure_name.as_str()).collect();
+        assert_eq!(names, ["C8", "Ge8", "Si8", "Sn8"]);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn cli_flags_override_profile_fields() {
+        let profile = ClusterProfile {
+            scheduler: Some(SchedulerKind::Lsf),
+            partition: Some("arm".to_string()),
+            mem_per_cpu: Some("3G".to_string()),
+            mpi_launcher: Some("srun --mpi=pmix -n {np}".to_string()),
+            castep_modules: Some(vec!["castep/25.12".to_string()]),
+            env: [("OMP_NUM_THREADS".to_string(), "1".to_string())].into(),
+            ..ClusterProfile::default()
+        };
+
+        let mut args = base_submit_args();
+        args.partition = Some("debug".to_string());
+        args.castep_np = Some(8);
+        args.castep_modules = Some("castep/24.1, airss/0.9".to_string());
+
+        let settings = JobSettings::resolve(&args, profile);
+        assert_eq!(settings.scheduler, SchedulerKind::Lsf);
+        assert_eq!(settings.partition, "debug");
+        assert_eq!(settings.mem_per_cpu, "3G");
+        assert_eq!(settings.time, "24:00:00");
         assert_eq!(
-            submit_script_arg(&sbatch_path),
-            PathBuf::from("submit.sbatch")
+            settings.launcher(settings.castep_np),
+            "srun --mpi=pmix -n 8"
         );
+        assert_eq!(settings.castep_modules, vec!["castep/24.1", "airss/0.9"]);
+        assert_eq!(
+            settings.env,
+            vec![("OMP_NUM_THREADS".to_string(), "1".to_string())]
+        );
+    }
+
+    #[test]
+    fn script_template_receives_structure_metadata() {
+        let root = unique_test_dir("template");
+        let job_dir = root.join("job");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::write(
+            root.join("POSCAR.src"),
+            "NaCl\n1.0\n5.6 0 0\n0 5.6 0\n0 0 5.6\nNa Cl\n1 1\nDirect\n0 0 0\n0.5 0.5 0.5\n",
+        )
+        .expect("write POSCAR");
+        fs::write(root.join("INCAR"), "INCAR\n").expect("write INCAR");
+        let template_path = root.join("staged.tmpl");
+        fs::write(
+            &template_path,
+            "#!/bin/bash\n# {{formula}} {{natoms}} atoms ({{elements}}), {{volume}} A^3\n\
+             export OMP_NUM_THREADS={{cpus_per_task}}\n{{exec_cmd}} > {{dft}}-{{seed}}.log\n",
+        )
+        .expect("write template");
+
+        let mut args = base_submit_args();
+        args.incar_template = Some(root.join("INCAR"));
+        args.script_template = Some(template_path.clone());
+        args.vasp_np = Some(4);
+        args.mpi_launcher = Some("srun -n {np}".to_string());
+        let settings = JobSettings::resolve(&args, ClusterProfile::default());
+        let template = ScriptTemplate::from_file(&template_path).expect("template");
+
+        let prepared = prepare_vasp_job(
+            &args,
+            &settings,
+            None,
+            &job_dir,
+            "NaCl",
+            &root.join("POSCAR.src"),
+        )
+        .expect("prepare");
+        let variables = job_variables("NaCl", &prepared, None, true).expect("variables");
+        let script = write_job_script(
+            &settings, &template, &job_dir, "NaCl", &prepared, &variables, false,
+        )
+        .expect("write script");
+
+        let content = fs::read_to_string(script).expect("read script");
+        assert_eq!(
+            content,
+            "#!/bin/bash\n# ClNa 2 atoms (Na Cl), 175.6160 A^3\n\
+             export OMP_NUM_THREADS=1\nsrun -n 4 vasp_std > vasp-NaCl.log\n"
+        );
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn missing_potcar_species_fail_before_any_job_is_written() {
+        let root = unique_test_dir("potcar");
+        let structs = root.join("structures");
+        let library = root.join("potpaw");
+        fs::create_dir_all(&structs).expect("create structures");
+        fs::create_dir_all(library.join("Ti_sv")).expect("create library");
+        fs::write(
+            library.join("Ti_sv").join("POTCAR"),
+            "  PAW_PBE Ti_sv 26Sep2005\n   TITEL  = PAW_PBE Ti_sv 26Sep2005\n   \
+             POMASS =   47.880; ZVAL   =   12.000    mass and valenz\n",
+        )
+        .expect("write POTCAR");
+        fs::write(
+            structs.join("TiO2.POSCAR"),
+            "TiO2\n1.0\n4.6 0 0\n0 4.6 0\n0 0 3.0\nTi O\n1 2\nDirect\n0 0 0\n0.3 0.3 0\n0.7 0.7 0\n",
+        )
+        .expect("write POSCAR");
+
+        let mut args = base_submit_args();
+        args.struct_dir = structs;
+        args.jobs_root = root.join("jobs");
+        args.potcar_dir = Some(library.clone());
+        let structures = vec!["TiO2".to_string()];
+
+        let err = match load_potcar_library(&args, &library, &structures, &[1]) {
+            Ok(_) => panic!("O has no POTCAR"),
+            Err(err) => err.to_string(),
+        };
+        assert!(err.contains("O ("));
+        assert!(!err.contains("Ti ("));
+        assert!(!args.jobs_root.exists());
+
+        args.potcar_map = vec!["O=Ti_sv".to_string()];
+        assert!(load_potcar_library(&args, &library, &structures, &[1]).is_err());
+        args.potcar_map = vec!["O".to_string()];
+        assert!(parse_potcar_map(&args.potcar_map).is_err());
+
+        let single = library.join("Ti_sv").join("POTCAR");
+        args.potcar_map.clear();
+        let err = match load_potcar_library(&args, &single, &structures, &[1]) {
+            Ok(_) => panic!("a single POTCAR is not a library"),
+            Err(err) => err.to_string(),
+        };
+        assert!(err.contains("is a file"));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn kpoint_spacing_writes_mesh_and_metadata() {
+        let root = unique_test_dir("kpoints");
+        let job_dir = root.join("job");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::write(
+            root.join("POSCAR.src"),
+            "slab\n1.0\n4.0 0 0\n0 5.0 0\n0 0 20.0\nSi\n2\nDirect\n0 0 0\n0.5 0.5 0.1\n",
+        )
+        .expect("write POSCAR");
+        fs::write(root.join("INCAR"), "INCAR\n").expect("write INCAR");
+
+        let mut args = base_submit_args();
+        args.incar_template = Some(root.join("INCAR"));
+        args.kpoint_spacing = Some(0.04);
+        args.kpoint_centering = KpointCentering::MonkhorstPack;
+        args.kpoint_parity = KpointParity::Even;
+        let settings = JobSettings::resolve(&args, ClusterProfile::default());
+
+        let prepared = prepare_vasp_job(
+            &args,
+            &settings,
+            None,
+            &job_dir,
+            "slab",
+            &root.join("POSCAR.src"),
+        )
+        .expect("prepare");
+        // 1/(4*0.04) = 6.25 -> 7 -> 8, 1/(5*0.04) = 5 -> 6, 1/(20*0.04) = 1.25 -> 2
+        assert_eq!(prepared.kpoint_mesh, Some([8, 6, 2]));
+        let kpoints = fs::read_to_string(job_dir.join("KPOINTS")).expect("read KPOINTS");
+        assert!(kpoints.contains("Monkhorst-Pack\n  8 6 2\n"));
+
+        write_job_metadata(&job_dir, "slab", &prepared, Some(kpoint_spacing(&args)))
+            .expect("write metadata");
+        let metadata: toml::Value =
+            toml::from_str(&fs::read_to_string(job_dir.join(JOB_METADATA_FILE)).expect("read"))
+                .expect("parse metadata");
+        assert_eq!(metadata["dft"].as_str(), Some("vasp"));
+        let kpoints = &metadata["kpoints"];
+        assert_eq!(kpoints["unit"].as_str(), Some("2pi/ang"));
+        assert_eq!(kpoints["centering"].as_str(), Some("monkhorst-pack"));
+        assert_eq!(kpoints["parity"].as_str(), Some("even"));
+        let mesh: Vec<i64> = kpoints["mesh"]
+            .as_array()
+            .expect("mesh")
+            .iter()
+            .filter_map(toml::Value::as_integer)
+            .collect();
+        assert_eq!(mesh, vec![8, 6, 2]);
+
+        fs::remove_dir_all(&root).expect("cleanup");
     }
 }
@@ -0,0 +1,691 @@
+//! # 作业归档
+//!
+//! 把已结束的作业目录打包为 `.tar.gz`（每个作业一个归档，或按未压缩大小分片），
+//! 并在归档目录中写出 JSON/CSV 清单：结构名、状态、焓值（取自扫描记录）以及逐文件大小与 SHA-256。
+//! 可按清单选择作业解压恢复并校验；也可不解压、直接流式读取归档中的主输出与结构文件，
+//! 得到与扫描记录同形的结果，供旧批次继续参与焓值排序。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/archive.rs`、`commands/analyze/dft_postprocessing.rs` 与 `commands/report.rs`
+//! - 使用 `models/`、`parsers/`（流式 OUTCAR/.castep 解析与结构文本解析）
+//! - 使用 `tar`、`flate2`、`sha2`、`walkdir`、`serde_json` 与 `csv`
+
+use super::cache::CACHE_FILE;
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationScanRecord, CalculationStatus, Crystal, DftCodeType, DftResult};
+use crate::parsers::{castep_out, cell, outcar, poscar};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path};
+use walkdir::WalkDir;
+
+/// JSON 清单文件名，位于归档目录
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// CSV 清单文件名（每个已归档文件一行）
+pub const MANIFEST_CSV: &str = "manifest.csv";
+
+/// 清单格式版本
+const MANIFEST_VERSION: u32 = 1;
+
+/// 归档划分方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveLayout {
+    /// 每个作业一个归档
+    PerJob,
+    /// 按作业顺序装入分片，单个分片的未压缩大小不超过上限（超过上限的单个作业独占一个分片）
+    Sharded { max_bytes: u64 },
+}
+
+/// 归档中的单个文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// 相对作业目录的路径（`/` 分隔）
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// 清单中的单个作业
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedJob {
+    pub structure: String,
+    /// 相对作业根目录的路径（`/` 分隔），也是归档内的路径前缀
+    pub job: String,
+    pub seed: String,
+    pub code: DftCodeType,
+    pub status: CalculationStatus,
+    pub enthalpy_ev: Option<f64>,
+    pub energy_ev: Option<f64>,
+    pub num_atoms: Option<usize>,
+    /// 所在归档文件名
+    pub archive: String,
+    pub files: Vec<ArchivedFile>,
+}
+
+impl ArchivedJob {
+    /// 未压缩总字节数
+    pub fn bytes(&self) -> u64 {
+        self.files.iter().map(|file| file.bytes).sum()
+    }
+}
+
+/// 归档清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    version: u32,
+    /// 打包时的作业根目录
+    pub source: String,
+    pub jobs: Vec<ArchivedJob>,
+}
+
+impl Manifest {
+    pub fn load(archive_dir: &Path) -> Result<Self> {
+        let path = archive_dir.join(MANIFEST_FILE);
+        let content = fs::read_to_string(&path).map_err(|e| QutilityError::FileReadError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        let manifest: Self =
+            serde_json::from_str(&content).map_err(|e| QutilityError::ParseError {
+                format: "archive manifest".to_string(),
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+
+        if manifest.version != MANIFEST_VERSION {
+            return Err(QutilityError::ParseError {
+                format: "archive manifest".to_string(),
+                path: path.display().to_string(),
+                reason: format!("unsupported manifest version {}", manifest.version),
+            });
+        }
+        Ok(manifest)
+    }
+
+    /// 判断目录是否为归档目录（含 JSON 清单）
+    pub fn exists(dir: &Path) -> bool {
+        dir.join(MANIFEST_FILE).is_file()
+    }
+
+    /// 写出 JSON 清单与逐文件的 CSV 清单
+    pub fn save(&self, archive_dir: &Path) -> Result<()> {
+        let path = archive_dir.join(MANIFEST_FILE);
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| QutilityError::Other(format!("Cannot serialize manifest: {e}")))?;
+        fs::write(&path, content).map_err(|e| QutilityError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+
+        let path = archive_dir.join(MANIFEST_CSV);
+        let mut writer = csv::Writer::from_path(&path).map_err(QutilityError::CsvError)?;
+        writer
+            .write_record([
+                "structure",
+                "job",
+                "code",
+                "status",
+                "enthalpy_ev",
+                "num_atoms",
+                "archive",
+                "file",
+                "bytes",
+                "sha256",
+            ])
+            .map_err(QutilityError::CsvError)?;
+        for job in &self.jobs {
+            for file in &job.files {
+                writer
+                    .write_record([
+                        job.structure.clone(),
+                        job.job.clone(),
+                        job.code.to_string(),
+                        job.status.to_string(),
+                        job.enthalpy_ev.map(|h| h.to_string()).unwrap_or_default(),
+                        job.num_atoms.map(|n| n.to_string()).unwrap_or_default(),
+                        job.archive.clone(),
+                        file.path.clone(),
+                        file.bytes.to_string(),
+                        file.sha256.clone(),
+                    ])
+                    .map_err(QutilityError::CsvError)?;
+            }
+        }
+        writer.flush().map_err(|e| QutilityError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        })
+    }
+}
+
+/// 打包作业目录并写出清单；`out_dir` 位于作业根目录之内时会被跳过而不会打包自身
+pub fn create_archives(
+    records: &[&CalculationScanRecord],
+    root: &Path,
+    out_dir: &Path,
+    layout: ArchiveLayout,
+) -> Result<Manifest> {
+    fs::create_dir_all(out_dir).map_err(|e| QutilityError::FileWriteError {
+        path: out_dir.display().to_string(),
+        source: e,
+    })?;
+    let skip_dir = out_dir.canonicalize().ok();
+
+    let mut jobs = records
+        .iter()
+        .map(|record| {
+            let files = list_job_files(&record.job_dir, skip_dir.as_deref())?;
+            Ok((*record, files))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    jobs.sort_by(|a, b| a.0.job_dir.cmp(&b.0.job_dir));
+
+    // 按划分方式分组：(归档文件名, 待打包作业)
+    let mut groups: Vec<(String, Vec<PendingJob>)> = Vec::new();
+    let mut shard_bytes = 0;
+    for (record, files) in jobs {
+        let key = job_key(&record.job_dir, root);
+        let bytes: u64 = files.iter().map(|(_, bytes)| bytes).sum();
+        match layout {
+            ArchiveLayout::PerJob => {
+                groups.push((format!("{}.tar.gz", key.replace('/', "__")), Vec::new()));
+            }
+            ArchiveLayout::Sharded { max_bytes } => {
+                let full = groups
+                    .last()
+                    .is_none_or(|(_, group)| shard_bytes + bytes > max_bytes && !group.is_empty());
+                if full {
+                    groups.push((format!("shard-{:04}.tar.gz", groups.len() + 1), Vec::new()));
+                    shard_bytes = 0;
+                }
+                shard_bytes += bytes;
+            }
+        }
+        if let Some((_, group)) = groups.last_mut() {
+            group.push(PendingJob { record, key, files });
+        }
+    }
+
+    let archived = groups
+        .par_iter()
+        .map(|(name, group)| write_archive(&out_dir.join(name), name, group))
+        .collect::<Result<Vec<_>>>()?;
+
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        source: root.display().to_string(),
+        jobs: archived.into_iter().flatten().collect(),
+    };
+    manifest.save(out_dir)?;
+    Ok(manifest)
+}
+
+/// 从归档中恢复选中的作业到 `dest/<job>/`，并按清单校验每个文件的 SHA-256；返回恢复的文件数
+pub fn extract_jobs(archive_dir: &Path, jobs: &[&ArchivedJob], dest: &Path) -> Result<usize> {
+    let mut by_archive: BTreeMap<&str, Vec<&ArchivedJob>> = BTreeMap::new();
+    for job in jobs {
+        by_archive
+            .entry(job.archive.as_str())
+            .or_default()
+            .push(job);
+    }
+
+    let mut restored = 0;
+    for (name, jobs) in by_archive {
+        let path = archive_dir.join(name);
+        let expected: HashMap<String, &ArchivedFile> = jobs
+            .iter()
+            .flat_map(|job| {
+                job.files
+                    .iter()
+                    .map(move |file| (format!("{}/{}", job.job, file.path), file))
+            })
+            .collect();
+
+        for_each_entry(&path, |entry_path, mut entry| {
+            let Some(file) = expected.get(entry_path) else {
+                return Ok(());
+            };
+            // 清单与归档不可信时也不能写到目标目录之外
+            if !Path::new(entry_path)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsafe path {entry_path}"),
+                ));
+            }
+
+            let target = dest.join(entry_path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // unpack 保留权限与修改时间，写出后再校验
+            entry.unpack(&target)?;
+            let mut hasher = HashingReader::new(File::open(&target)?);
+            io::copy(&mut hasher, &mut io::sink())?;
+            if hasher.hex_digest() != file.sha256 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("checksum mismatch for {entry_path}"),
+                ));
+            }
+            restored += 1;
+            Ok(())
+        })?;
+    }
+
+    Ok(restored)
+}
+
+/// 不解压、直接流式读取归档中的作业：主输出与结构文件在内存中解析。
+/// 返回的扫描记录保留清单中的状态，`job_dir` 指向 `<归档目录>/<job>`（虚拟路径）
+pub fn read_archived_records(
+    archive_dir: &Path,
+    manifest: &Manifest,
+) -> Result<Vec<(CalculationScanRecord, Option<Crystal>)>> {
+    let mut by_archive: BTreeMap<&str, Vec<&ArchivedJob>> = BTreeMap::new();
+    for job in &manifest.jobs {
+        by_archive
+            .entry(job.archive.as_str())
+            .or_default()
+            .push(job);
+    }
+
+    let groups: Vec<(&str, Vec<&ArchivedJob>)> = by_archive.into_iter().collect();
+    let records = groups
+        .par_iter()
+        .map(|(name, jobs)| read_archive(&archive_dir.join(name), archive_dir, jobs))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(records.into_iter().flatten().collect())
+}
+
+/// 单个作业在归档中读到的内容
+#[derive(Default)]
+struct ArchivedContent {
+    parsed: Option<io::Result<DftResult>>,
+    /// 按优先级保存的结构文本：(优先级, 内容)，优先级小者优先
+    structure: Option<(usize, String)>,
+}
+
+fn read_archive(
+    path: &Path,
+    archive_dir: &Path,
+    jobs: &[&ArchivedJob],
+) -> Result<Vec<(CalculationScanRecord, Option<Crystal>)>> {
+    let by_prefix: HashMap<&str, &ArchivedJob> =
+        jobs.iter().map(|job| (job.job.as_str(), *job)).collect();
+    let mut contents: HashMap<&str, ArchivedContent> = HashMap::new();
+
+    for_each_entry(path, |entry_path, mut entry| {
+        // 只读取作业目录顶层的文件，`attempt_N/` 等备份不参与
+        let Some((prefix, file_name)) = entry_path.rsplit_once('/') else {
+            return Ok(());
+        };
+        let Some(job) = by_prefix.get(prefix) else {
+            return Ok(());
+        };
+        let content = contents.entry(job.job.as_str()).or_default();
+
+        if file_name == output_file_name(job) {
+            let reader = BufReader::new(entry);
+            content.parsed = Some(match job.code {
+                DftCodeType::Vasp => outcar::parse_outcar_reader(reader, &job.structure),
+                DftCodeType::Castep => {
+                    castep_out::parse_castep_output_reader(reader, &job.structure)
+                }
+            });
+        } else if let Some(priority) = structure_priority(job, file_name) {
+            if content
+                .structure
+                .as_ref()
+                .is_none_or(|(current, _)| priority < *current)
+            {
+                let mut text = String::new();
+                entry.read_to_string(&mut text)?;
+                if !text.trim().is_empty() {
+                    content.structure = Some((priority, text));
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(jobs
+        .iter()
+        .map(|job| {
+            let content = contents.remove(job.job.as_str()).unwrap_or_default();
+            let mut record = CalculationScanRecord::new(
+                job.structure.clone(),
+                archive_dir.join(&job.job),
+                job.code,
+                job.status,
+            );
+            record.seed = job.seed.clone();
+
+            match content.parsed {
+                Some(Ok(result)) => record.parsed = Some(result),
+                Some(Err(err)) => {
+                    record.status = CalculationStatus::ParseError;
+                    record.reason = Some(format!("Cannot read archived output: {err}"));
+                }
+                None if matches!(
+                    job.status,
+                    CalculationStatus::Completed | CalculationStatus::Unconverged
+                ) =>
+                {
+                    record.status = CalculationStatus::ParseError;
+                    record.reason = Some(format!("{} missing from archive", output_file_name(job)));
+                }
+                None => {}
+            }
+
+            let crystal = content
+                .structure
+                .and_then(|(_, text)| parse_structure_text(job, &text));
+            (record, crystal)
+        })
+        .collect())
+}
+
+fn output_file_name(job: &ArchivedJob) -> String {
+    match job.code {
+        DftCodeType::Vasp => "OUTCAR".to_string(),
+        DftCodeType::Castep => format!("{}.castep", job.seed),
+    }
+}
+
+/// 与目录扫描一致：优先使用优化后的结构（CONTCAR / `<seed>-out.cell`）
+fn structure_priority(job: &ArchivedJob, file_name: &str) -> Option<usize> {
+    let candidates = match job.code {
+        DftCodeType::Vasp => ["CONTCAR".to_string(), "POSCAR".to_string()],
+        DftCodeType::Castep => [
+            format!("{}-out.cell", job.seed),
+            format!("{}.cell", job.seed),
+        ],
+    };
+    candidates.iter().position(|name| name == file_name)
+}
+
+fn parse_structure_text(job: &ArchivedJob, text: &str) -> Option<Crystal> {
+    match job.code {
+        DftCodeType::Vasp => poscar::parse_poscar_content(text, &job.structure).ok(),
+        DftCodeType::Castep => cell::parse_cell_content(text, &job.structure).ok(),
+    }
+}
+
+/// 作业目录相对根目录的路径；根目录本身即作业时取其目录名
+fn job_key(job_dir: &Path, root: &Path) -> String {
+    let relative = job_dir.strip_prefix(root).unwrap_or(job_dir);
+    let key = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if key.is_empty() {
+        root.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "job".to_string())
+    } else {
+        key
+    }
+}
+
+/// 列出作业目录下的全部普通文件（相对路径与大小），不跟随符号链接
+fn list_job_files(job_dir: &Path, skip_dir: Option<&Path>) -> Result<Vec<(String, u64)>> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(job_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            skip_dir.is_none_or(|skip| entry.path().canonicalize().ok().as_deref() != Some(skip))
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(|e| QutilityError::FileReadError {
+            path: job_dir.display().to_string(),
+            source: e.into(),
+        })?;
+        if !entry.file_type().is_file() || entry.file_name() == CACHE_FILE {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(job_dir)
+            .unwrap_or(entry.path())
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        files.push((relative, bytes));
+    }
+    Ok(files)
+}
+
+/// 待打包的作业
+struct PendingJob<'a> {
+    record: &'a CalculationScanRecord,
+    /// 相对作业根目录的路径
+    key: String,
+    /// (相对作业目录的路径, 字节数)
+    files: Vec<(String, u64)>,
+}
+
+fn write_archive(path: &Path, name: &str, group: &[PendingJob]) -> Result<Vec<ArchivedJob>> {
+    let write_error = |e| QutilityError::FileWriteError {
+        path: path.display().to_string(),
+        source: e,
+    };
+
+    let file = File::create(path).map_err(write_error)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mut jobs = Vec::with_capacity(group.len());
+
+    for PendingJob { record, key, files } in group {
+        let mut archived_files = Vec::with_capacity(files.len());
+        for (relative, _) in files {
+            let source = record.job_dir.join(relative);
+            let input = File::open(&source).map_err(|e| QutilityError::FileReadError {
+                path: source.display().to_string(),
+                source: e,
+            })?;
+            let metadata = input.metadata().map_err(write_error)?;
+
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&metadata);
+            let mut reader = HashingReader::new(input);
+            builder
+                .append_data(&mut header, format!("{key}/{relative}"), &mut reader)
+                .map_err(write_error)?;
+
+            archived_files.push(ArchivedFile {
+                path: relative.clone(),
+                bytes: metadata.len(),
+                sha256: reader.hex_digest(),
+            });
+        }
+
+        let parsed = record.parsed.as_ref();
+        jobs.push(ArchivedJob {
+            structure: record.structure_name.clone(),
+            job: key.clone(),
+            seed: record.seed.clone(),
+            code: record.code,
+            status: record.status,
+            enthalpy_ev: parsed.and_then(|result| result.enthalpy_ev),
+            energy_ev: parsed.and_then(|result| result.energy_ev),
+            num_atoms: parsed.and_then(|result| result.num_atoms),
+            archive: name.to_string(),
+            files: archived_files,
+        });
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(write_error)?;
+    Ok(jobs)
+}
+
+/// 依次处理归档中的普通文件条目；条目路径以 `/` 分隔
+fn for_each_entry(
+    path: &Path,
+    mut visit: impl FnMut(&str, tar::Entry<'_, GzDecoder<File>>) -> io::Result<()>,
+) -> Result<()> {
+    let read_error = |e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    };
+
+    let file = File::open(path).map_err(read_error)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    for entry in archive.entries().map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry.path().map_err(read_error)?;
+        let entry_path = entry_path.to_string_lossy().replace('\\', "/");
+        visit(&entry_path, entry).map_err(read_error)?;
+    }
+    Ok(())
+}
+
+/// 读取时同步计算 SHA-256
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    fn hex_digest(self) -> String {
+        self.hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    fn write_vasp_job(dir: &Path, enthalpy: f64) -> CalculationScanRecord {
+        fs::create_dir_all(dir.join("attempt_1")).expect("create job dir");
+        fs::write(
+            dir.join("OUTCAR"),
+            format!(
+                "   NIONS =       2\n\
+                 enthalpy is  TOTEN    =      {enthalpy:.6} eV\n\
+                 energy  without entropy=     {enthalpy:.6}  energy(sigma->0) =     {enthalpy:.6}\n"
+            ),
+        )
+        .expect("write OUTCAR");
+        fs::write(
+            dir.join("CONTCAR"),
+            "Si\n1.0\n3.0 0.0 0.0\n0.0 3.0 0.0\n0.0 0.0 3.0\nSi\n2\nDirect\n0.0 0.0 0.0\n0.5 0.5 0.5\n",
+        )
+        .expect("write CONTCAR");
+        fs::write(dir.join("attempt_1").join("OUTCAR"), "old run\n").expect("write backup");
+
+        let name = dir.file_name().unwrap().to_string_lossy().to_string();
+        let mut record = CalculationScanRecord::new(
+            name.clone(),
+            dir.to_path_buf(),
+            DftCodeType::Vasp,
+            CalculationStatus::Completed,
+        );
+        let mut result = DftResult::new(name, DftCodeType::Vasp);
+        result.enthalpy_ev = Some(enthalpy);
+        record.parsed = Some(result);
+        record
+    }
+
+    #[test]
+    fn archives_reads_and_extracts_jobs() {
+        let root = unique_test_dir("archive-round-trip");
+        let alpha = write_vasp_job(&root.join("10GPa").join("alpha"), -12.5);
+        let beta = write_vasp_job(&root.join("10GPa").join("beta"), -11.0);
+        let out_dir = root.join("archive");
+
+        let manifest = create_archives(
+            &[&beta, &alpha],
+            &root,
+            &out_dir,
+            ArchiveLayout::Sharded { max_bytes: 1 << 30 },
+        )
+        .expect("archive");
+        assert_eq!(manifest.jobs.len(), 2);
+        assert_eq!(manifest.jobs[0].job, "10GPa/alpha");
+        assert_eq!(manifest.jobs[0].archive, "shard-0001.tar.gz");
+        assert_eq!(manifest.jobs[0].enthalpy_ev, Some(-12.5));
+        let paths: Vec<&str> = manifest.jobs[0]
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, ["CONTCAR", "OUTCAR", "attempt_1/OUTCAR"]);
+        assert!(out_dir.join(MANIFEST_CSV).is_file());
+
+        let per_job_dir = root.join("per-job");
+        let per_job = create_archives(&[&alpha], &root, &per_job_dir, ArchiveLayout::PerJob)
+            .expect("archive per job");
+        assert_eq!(per_job.jobs[0].archive, "10GPa__alpha.tar.gz");
+
+        // 不解压直接读取：结果来自归档中的 OUTCAR，备份目录中的输出被忽略
+        let loaded = Manifest::load(&out_dir).expect("load manifest");
+        let records = read_archived_records(&out_dir, &loaded).expect("read archive");
+        assert_eq!(records.len(), 2);
+        let (record, crystal) = &records[0];
+        assert_eq!(record.status, CalculationStatus::Completed);
+        assert_eq!(
+            record.parsed.as_ref().and_then(|result| result.enthalpy_ev),
+            Some(-12.5)
+        );
+        assert_eq!(crystal.as_ref().map(|c| c.atoms.len()), Some(2));
+
+        let dest = root.join("restored");
+        let selected: Vec<&ArchivedJob> = loaded
+            .jobs
+            .iter()
+            .filter(|job| job.structure == "beta")
+            .collect();
+        let restored = extract_jobs(&out_dir, &selected, &dest).expect("extract");
+        assert_eq!(restored, 3);
+        assert_eq!(
+            fs::read_to_string(dest.join("10GPa/beta/attempt_1/OUTCAR")).expect("read"),
+            "old run\n"
+        );
+        assert!(!dest.join("10GPa/alpha").exists());
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
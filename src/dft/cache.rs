@@ -0,0 +1,179 @@
+//! # 增量扫描缓存
+//!
+//! 在作业根目录的 `.qutility-cache`（JSON）中按作业相对路径保存扫描记录及主输出文件的大小与修改时间，
+//! 输出文件未变化的已结束作业（completed / unconverged）复用缓存记录而不再重新解析；
+//! 未结束或失败的作业依赖日志与规则文件，每次都重新扫描。
+//!
+//! ## 依赖关系
+//! - 被 `dft/scan.rs` 使用，经 `dft/mod.rs` 导出给命令层加载、保存与清除
+//! - 使用 `models/calculation.rs` 与 `serde_json`
+
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationScanRecord, CalculationStatus};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// 缓存文件名，位于作业根目录
+pub const CACHE_FILE: &str = ".qutility-cache";
+
+/// 缓存格式版本；记录结构变化时递增，旧缓存直接丢弃
+const CACHE_VERSION: u32 = 1;
+
+/// 主输出文件的大小与修改时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputFingerprint {
+    pub size: u64,
+    pub mtime_secs: u64,
+    pub mtime_nanos: u32,
+}
+
+impl OutputFingerprint {
+    /// 读取文件元数据；文件不存在或无法取得修改时间时返回 `None`
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    output: OutputFingerprint,
+    record: CalculationScanRecord,
+}
+
+/// 扫描缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCache {
+    version: u32,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl Default for ScanCache {
+    fn default() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl ScanCache {
+    /// 读取作业根目录下的缓存；文件缺失、损坏或版本不符时返回空缓存
+    pub fn load(root: &Path) -> Self {
+        fs::read_to_string(root.join(CACHE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let path = root.join(CACHE_FILE);
+        let content = serde_json::to_string(self)
+            .map_err(|e| QutilityError::Other(format!("Cannot serialize scan cache: {e}")))?;
+        fs::write(&path, content).map_err(|e| QutilityError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        })
+    }
+
+    /// 删除作业根目录下的缓存文件，返回是否存在过
+    pub fn clear(root: &Path) -> Result<bool> {
+        let path = root.join(CACHE_FILE);
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        fs::remove_file(&path).map_err(|e| QutilityError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        Ok(true)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 输出文件指纹一致时返回缓存记录
+    pub fn lookup(&self, key: &str, output: OutputFingerprint) -> Option<&CalculationScanRecord> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.output == output)
+            .map(|entry| &entry.record)
+    }
+
+    /// 只缓存输出已正常结束的记录
+    pub fn is_cacheable(record: &CalculationScanRecord) -> bool {
+        matches!(
+            record.status,
+            CalculationStatus::Completed | CalculationStatus::Unconverged
+        )
+    }
+
+    pub fn insert(
+        &mut self,
+        key: String,
+        output: OutputFingerprint,
+        record: CalculationScanRecord,
+    ) {
+        self.entries.insert(key, CacheEntry { output, record });
+    }
+
+    /// 丢弃本次扫描未出现的作业，避免缓存无限增长
+    pub fn retain_keys(&mut self, keys: &HashSet<String>) {
+        self.entries.retain(|key, _| keys.contains(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DftCodeType;
+    use crate::utils::test_support::unique_test_dir;
+
+    #[test]
+    fn round_trips_and_matches_fingerprint() {
+        let root = unique_test_dir("dft-cache-round-trip");
+        fs::create_dir_all(&root).expect("create root");
+        let output = root.join("OUTCAR");
+        fs::write(&output, "done\n").expect("write OUTCAR");
+        let fingerprint = OutputFingerprint::of(&output).expect("fingerprint");
+
+        let record = CalculationScanRecord::new(
+            "alpha",
+            root.join("alpha"),
+            DftCodeType::Vasp,
+            CalculationStatus::Completed,
+        );
+        let mut cache = ScanCache::default();
+        cache.insert("alpha".to_string(), fingerprint, record);
+        cache.save(&root).expect("save");
+
+        let loaded = ScanCache::load(&root);
+        assert!(loaded.lookup("alpha", fingerprint).is_some());
+        let changed = OutputFingerprint {
+            size: fingerprint.size + 1,
+            ..fingerprint
+        };
+        assert!(loaded.lookup("alpha", changed).is_none());
+
+        assert!(ScanCache::clear(&root).expect("clear"));
+        assert!(ScanCache::load(&root).is_empty());
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
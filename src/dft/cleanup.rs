@@ -0,0 +1,207 @@
+//! # 作业目录清理
+//!
+//! 在已结束作业的目录中按文件名模式挑选可删除的大体积中间文件
+//! （VASP 的 WAVECAR/CHGCAR/CHG/vasprun.xml，CASTEP 的 .check/.castep_bin），
+//! 支持保留列表与最小文件年龄；只检查作业目录本身，不进入 `attempt_N/` 备份与 `slurm_logs/`。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/clean.rs`
+//! - 使用 `models/calculation.rs`、`dft/scan.rs` 的失败状态判定与 `glob` 模式匹配
+
+use crate::dft::scan::is_explicit_failure;
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationStatus, DftCodeType};
+
+use glob::Pattern;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// VASP 默认清理的文件
+pub const VASP_SCRATCH_PATTERNS: &[&str] = &["WAVECAR", "CHGCAR", "CHG", "vasprun.xml"];
+
+/// CASTEP 默认清理的文件
+pub const CASTEP_SCRATCH_PATTERNS: &[&str] = &["*.check", "*.castep_bin"];
+
+/// 允许清理的作业状态类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupTarget {
+    Completed,
+    Unconverged,
+    /// 失败、超时、节点故障与内存不足
+    Failed,
+}
+
+impl CleanupTarget {
+    pub fn matches(self, status: CalculationStatus) -> bool {
+        match self {
+            CleanupTarget::Completed => status == CalculationStatus::Completed,
+            CleanupTarget::Unconverged => status == CalculationStatus::Unconverged,
+            CleanupTarget::Failed => is_explicit_failure(status),
+        }
+    }
+}
+
+/// 清理策略：删除模式（未指定时按代码取默认值）、保留模式与最小文件年龄
+#[derive(Debug, Clone, Default)]
+pub struct CleanupPolicy {
+    patterns: Option<Vec<Pattern>>,
+    keep: Vec<Pattern>,
+    min_age: Option<Duration>,
+}
+
+impl CleanupPolicy {
+    pub fn new(patterns: &[String], keep: &[String], min_age: Option<Duration>) -> Result<Self> {
+        Ok(Self {
+            patterns: (!patterns.is_empty())
+                .then(|| compile_patterns(patterns))
+                .transpose()?,
+            keep: compile_patterns(keep)?,
+            min_age,
+        })
+    }
+
+    /// 列出作业目录中符合策略的文件，按文件名排序
+    pub fn plan(
+        &self,
+        job_dir: &Path,
+        code: DftCodeType,
+        now: SystemTime,
+    ) -> Result<Vec<CleanupCandidate>> {
+        let defaults;
+        let patterns = match &self.patterns {
+            Some(patterns) => patterns,
+            None => {
+                defaults = compile_patterns(default_patterns(code))?;
+                &defaults
+            }
+        };
+
+        let entries = fs::read_dir(job_dir).map_err(|e| QutilityError::FileReadError {
+            path: job_dir.display().to_string(),
+            source: e,
+        })?;
+
+        let mut candidates = Vec::new();
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(pattern) = patterns.iter().find(|pattern| pattern.matches(&name)) else {
+                continue;
+            };
+            if self.keep.iter().any(|keep| keep.matches(&name)) {
+                continue;
+            }
+
+            // 不跟随符号链接，也不删除目录
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+
+            if let Some(min_age) = self.min_age {
+                let age = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| now.duration_since(modified).ok())
+                    .unwrap_or_default();
+                if age < min_age {
+                    continue;
+                }
+            }
+
+            candidates.push(CleanupCandidate {
+                path: entry.path(),
+                pattern: pattern.as_str().to_string(),
+                bytes: metadata.len(),
+            });
+        }
+
+        candidates.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(candidates)
+    }
+}
+
+/// 待删除的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanupCandidate {
+    pub path: PathBuf,
+    /// 命中的删除模式
+    pub pattern: String,
+    pub bytes: u64,
+}
+
+pub fn default_patterns(code: DftCodeType) -> &'static [&'static str] {
+    match code {
+        DftCodeType::Vasp => VASP_SCRATCH_PATTERNS,
+        DftCodeType::Castep => CASTEP_SCRATCH_PATTERNS,
+    }
+}
+
+fn compile_patterns<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let pattern = pattern.as_ref();
+            Pattern::new(pattern).map_err(|e| {
+                QutilityError::InvalidArgument(format!("Invalid pattern '{pattern}': {e}"))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    #[test]
+    fn plans_default_patterns_with_keep_list_and_age() {
+        let root = unique_test_dir("cleanup-plan");
+        fs::create_dir_all(root.join("attempt_1")).expect("create job dir");
+        for (name, size) in [
+            ("WAVECAR", 100),
+            ("CHGCAR", 50),
+            ("OUTCAR", 10),
+            ("vasprun.xml", 20),
+        ] {
+            fs::write(root.join(name), vec![b'x'; size]).expect("write file");
+        }
+        fs::write(root.join("attempt_1").join("WAVECAR"), "x").expect("write backup");
+
+        let policy = CleanupPolicy::default();
+        let names = |candidates: Vec<CleanupCandidate>| -> Vec<String> {
+            candidates
+                .iter()
+                .map(|c| c.path.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        let planned = policy
+            .plan(&root, DftCodeType::Vasp, SystemTime::now())
+            .expect("plan");
+        assert_eq!(planned.iter().map(|c| c.bytes).sum::<u64>(), 170);
+        assert_eq!(names(planned), ["CHGCAR", "WAVECAR", "vasprun.xml"]);
+
+        let policy = CleanupPolicy::new(&["WAVE*".to_string()], &["CHG*".to_string()], None)
+            .expect("policy");
+        let planned = policy
+            .plan(&root, DftCodeType::Vasp, SystemTime::now())
+            .expect("plan");
+        assert_eq!(names(planned), ["WAVECAR"]);
+
+        // 刚写入的文件未达到最小年龄
+        let policy =
+            CleanupPolicy::new(&[], &["CHG*".to_string()], Some(Duration::from_secs(3600)))
+                .expect("policy");
+        let planned = policy
+            .plan(&root, DftCodeType::Vasp, SystemTime::now())
+            .expect("plan");
+        assert!(planned.is_empty());
+
+        assert!(CleanupTarget::Failed.matches(CalculationStatus::TimedOut));
+        assert!(!CleanupTarget::Completed.matches(CalculationStatus::Running));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
@@ -0,0 +1,354 @@
+//! # 收敛诊断
+//!
+//! 读取作业的逐离子步收敛轨迹（OUTCAR / OSZICAR / .castep），
+//! 并用启发式规则标记受力停滞、SCF 反复达到上限与能量振荡。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/convergence.rs` 与 `commands/report.rs` 使用
+//! - 使用 `parsers/outcar.rs`、`parsers/castep_out.rs` 与 `models/calculation.rs`
+
+use crate::error::{QutilityError, Result};
+use crate::models::{ConvergenceTrace, DftCodeType, IonicStep};
+use crate::parsers::{castep_out, outcar};
+
+use std::fmt;
+use std::path::Path;
+
+/// VASP NELM 默认值
+const VASP_DEFAULT_NELM: usize = 60;
+/// CASTEP max_scf_cycles 默认值
+const CASTEP_DEFAULT_SCF_CYCLES: usize = 30;
+
+/// 诊断阈值
+#[derive(Debug, Clone, Copy)]
+pub struct ConvergenceCriteria {
+    /// 最大受力连续多少步未创新低视为停滞
+    pub stagnation_steps: usize,
+    /// 末尾连续多少步 SCF 达到上限视为异常
+    pub scf_limit_steps: usize,
+    /// 检查能量振荡的 ΔE 窗口长度
+    pub oscillation_window: usize,
+}
+
+impl Default for ConvergenceCriteria {
+    fn default() -> Self {
+        Self {
+            stagnation_steps: 5,
+            scf_limit_steps: 3,
+            oscillation_window: 6,
+        }
+    }
+}
+
+/// 诊断出的收敛问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvergenceIssue {
+    /// 最大受力已连续 `steps` 步未低于 `best_force`
+    ForceStagnation { steps: usize, best_force: f64 },
+    /// 末尾连续 `steps` 步 SCF 达到上限 `limit`
+    ScfLimit { steps: usize, limit: usize },
+    /// 最近 `window` 个 ΔE 中符号翻转 `sign_changes` 次
+    EnergyOscillation { sign_changes: usize, window: usize },
+}
+
+impl fmt::Display for ConvergenceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvergenceIssue::ForceStagnation { steps, best_force } => write!(
+                f,
+                "max force has not dropped below {best_force:.4} eV/Å for {steps} steps"
+            ),
+            ConvergenceIssue::ScfLimit { steps, limit } => write!(
+                f,
+                "SCF hit the limit of {limit} iterations in each of the last {steps} steps"
+            ),
+            ConvergenceIssue::EnergyOscillation {
+                sign_changes,
+                window,
+            } => write!(
+                f,
+                "energy change flipped sign {sign_changes} times in the last {window} steps"
+            ),
+        }
+    }
+}
+
+/// 单个作业的诊断结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvergenceDiagnosis {
+    pub issues: Vec<ConvergenceIssue>,
+    /// 距最大受力最小值所在步的步数
+    pub steps_since_force_minimum: usize,
+    /// SCF 达到上限的总步数
+    pub scf_limit_hits: usize,
+}
+
+/// 读取作业目录的收敛轨迹；VASP 优先 OUTCAR，缺失时回退到 OSZICAR。
+/// 输出中未给出 SCF 上限时使用代码默认值。
+pub fn load_trace(
+    job_dir: &Path,
+    structure_name: &str,
+    code: DftCodeType,
+) -> Result<ConvergenceTrace> {
+    let mut trace = match code {
+        DftCodeType::Vasp => {
+            let outcar_path = job_dir.join("OUTCAR");
+            let oszicar_path = job_dir.join("OSZICAR");
+            if outcar_path.exists() {
+                outcar::parse_outcar_trace(&outcar_path)?
+            } else if oszicar_path.exists() {
+                outcar::parse_oszicar_trace(&oszicar_path)?
+            } else {
+                return Err(QutilityError::FileNotFound {
+                    path: outcar_path.display().to_string(),
+                });
+            }
+        }
+        DftCodeType::Castep => {
+            let castep_path = job_dir.join(format!("{structure_name}.castep"));
+            if !castep_path.exists() {
+                return Err(QutilityError::FileNotFound {
+                    path: castep_path.display().to_string(),
+                });
+            }
+            castep_out::parse_castep_trace(&castep_path)?
+        }
+    };
+
+    trace.scf_limit.get_or_insert(match code {
+        DftCodeType::Vasp => VASP_DEFAULT_NELM,
+        DftCodeType::Castep => CASTEP_DEFAULT_SCF_CYCLES,
+    });
+
+    Ok(trace)
+}
+
+/// 每一步相对上一步的能量变化 (eV)；第一步或缺少能量时为 None
+pub fn energy_changes(trace: &ConvergenceTrace) -> Vec<Option<f64>> {
+    let mut previous = None;
+    trace
+        .steps
+        .iter()
+        .map(|step| {
+            let delta = match (previous, step.energy_ev) {
+                (Some(prev), Some(energy)) => Some(energy - prev),
+                _ => None,
+            };
+            if step.energy_ev.is_some() {
+                previous = step.energy_ev;
+            }
+            delta
+        })
+        .collect()
+}
+
+/// 用启发式规则诊断收敛轨迹
+pub fn diagnose(trace: &ConvergenceTrace, criteria: &ConvergenceCriteria) -> ConvergenceDiagnosis {
+    let mut issues = Vec::new();
+
+    // 受力停滞：最小受力之后又经过了多少步
+    let forces: Vec<f64> = trace
+        .steps
+        .iter()
+        .filter_map(|step| step.max_force_ev_per_a)
+        .collect();
+    let (min_index, best_force) =
+        forces
+            .iter()
+            .enumerate()
+            .fold((0, f64::INFINITY), |(best_i, best), (i, &force)| {
+                if force < best {
+                    (i, force)
+                } else {
+                    (best_i, best)
+                }
+            });
+    let steps_since_force_minimum = forces.len().saturating_sub(min_index + 1);
+    if criteria.stagnation_steps > 0 && steps_since_force_minimum >= criteria.stagnation_steps {
+        issues.push(ConvergenceIssue::ForceStagnation {
+            steps: steps_since_force_minimum,
+            best_force,
+        });
+    }
+
+    // SCF 达到上限
+    let mut scf_limit_hits = 0;
+    if let Some(limit) = trace.scf_limit {
+        let hits = |step: &&IonicStep| step.scf_iterations >= limit;
+        scf_limit_hits = trace.steps.iter().filter(hits).count();
+        let trailing = trace.steps.iter().rev().take_while(hits).count();
+        if criteria.scf_limit_steps > 0 && trailing >= criteria.scf_limit_steps {
+            issues.push(ConvergenceIssue::ScfLimit {
+                steps: trailing,
+                limit,
+            });
+        }
+    }
+
+    // 能量振荡：最近窗口内 ΔE 符号频繁翻转
+    let deltas: Vec<f64> = energy_changes(trace)
+        .into_iter()
+        .flatten()
+        .filter(|delta| *delta != 0.0)
+        .collect();
+    let window = criteria.oscillation_window;
+    if window >= 3 && deltas.len() >= window {
+        let recent = &deltas[deltas.len() - window..];
+        let sign_changes = recent
+            .windows(2)
+            .filter(|pair| pair[0].signum() != pair[1].signum())
+            .count();
+        if sign_changes >= window - 2 {
+            issues.push(ConvergenceIssue::EnergyOscillation {
+                sign_changes,
+                window,
+            });
+        }
+    }
+
+    ConvergenceDiagnosis {
+        issues,
+        steps_since_force_minimum,
+        scf_limit_hits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+    use std::fs;
+
+    fn outcar_step(step: usize, scf: usize, force: f64, energy: f64) -> String {
+        let mut text = String::new();
+        for i in 1..=scf {
+            text.push_str(&format!(
+                "--------------------------------------- Iteration {step:>6}({i:>4})  ---------------------------------------\n"
+            ));
+        }
+        text.push_str("  in kB       5.00     3.00     1.00     0.00     0.00     0.00\n");
+        text.push_str("  external pressure =        3.00 kB  Pullay stress =        0.00 kB\n");
+        text.push_str(" POSITION                                       TOTAL-FORCE (eV/Angst)\n");
+        text.push_str(" -----------------------------------------------------------------------------------\n");
+        text.push_str(&format!(
+            "      0.00000      0.00000      0.00000         {force:.5}      0.00000      0.00000\n"
+        ));
+        text.push_str(" -----------------------------------------------------------------------------------\n");
+        text.push_str(&format!(
+            "  energy  without entropy=      {energy:.6}  energy(sigma->0) =      {energy:.6}\n"
+        ));
+        text
+    }
+
+    #[test]
+    fn parses_outcar_trace_and_flags_stagnation_and_scf_limit() {
+        let root = unique_test_dir("dft-convergence-outcar");
+        fs::create_dir_all(&root).expect("create job dir");
+        let mut outcar = "   NELM   =      4;   NELMIN=  2; NELMDL= -5\n".to_string();
+        outcar.push_str(&outcar_step(1, 2, 0.5, -10.0));
+        outcar.push_str(&outcar_step(2, 4, 0.2, -10.5));
+        for step in 3..=5 {
+            outcar.push_str(&outcar_step(step, 4, 0.3, -10.4));
+        }
+        fs::write(root.join("OUTCAR"), outcar).expect("write OUTCAR");
+
+        let trace = load_trace(&root, "alpha", DftCodeType::Vasp).expect("trace");
+        assert_eq!(trace.scf_limit, Some(4));
+        assert_eq!(trace.steps.len(), 5);
+        assert_eq!(trace.steps[0].scf_iterations, 2);
+        assert_eq!(trace.steps[1].max_force_ev_per_a, Some(0.2));
+        assert!((trace.steps[0].max_stress_gpa.expect("stress") - 0.5).abs() < 1e-9);
+        assert!((energy_changes(&trace)[1].expect("delta") + 0.5).abs() < 1e-9);
+
+        let diagnosis = diagnose(
+            &trace,
+            &ConvergenceCriteria {
+                stagnation_steps: 3,
+                ..ConvergenceCriteria::default()
+            },
+        );
+        assert_eq!(diagnosis.steps_since_force_minimum, 3);
+        assert_eq!(diagnosis.scf_limit_hits, 4);
+        assert!(diagnosis
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, ConvergenceIssue::ForceStagnation { steps: 3, .. })));
+        assert!(diagnosis
+            .issues
+            .contains(&ConvergenceIssue::ScfLimit { steps: 4, limit: 4 }));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn falls_back_to_oszicar_and_detects_oscillation() {
+        let root = unique_test_dir("dft-convergence-oszicar");
+        fs::create_dir_all(&root).expect("create job dir");
+        let energies = [-10.0, -10.2, -10.1, -10.3, -10.15, -10.35, -10.2];
+        let mut oszicar = String::new();
+        for (i, energy) in energies.iter().enumerate() {
+            oszicar.push_str(
+                "       N       E                     dE             d eps       ncg     rms\n",
+            );
+            oszicar.push_str("DAV:   1    -0.1E+02   -0.1E+02   -0.1E+03   100   0.1E+01\n");
+            oszicar.push_str("RMM:   2    -0.1E+02   -0.1E-02   -0.1E-02   100   0.1E-01\n");
+            oszicar.push_str(&format!(
+                "{:>4} F= {energy:.8E} E0= {energy:.8E}  d E =0.0\n",
+                i + 1
+            ));
+        }
+        fs::write(root.join("OSZICAR"), oszicar).expect("write OSZICAR");
+
+        let trace = load_trace(&root, "alpha", DftCodeType::Vasp).expect("trace");
+        assert_eq!(trace.steps.len(), 7);
+        assert_eq!(trace.steps[3].scf_iterations, 2);
+        assert_eq!(trace.scf_limit, Some(VASP_DEFAULT_NELM));
+
+        let diagnosis = diagnose(&trace, &ConvergenceCriteria::default());
+        assert!(diagnosis
+            .issues
+            .contains(&ConvergenceIssue::EnergyOscillation {
+                sign_changes: 5,
+                window: 6,
+            }));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn parses_castep_trace() {
+        let root = unique_test_dir("dft-convergence-castep");
+        fs::create_dir_all(&root).expect("create job dir");
+        let mut castep =
+            "  max. number of SCF cycles                      :         20\n".to_string();
+        for (step, (scf, force)) in [(12, 0.8), (7, 0.4)].into_iter().enumerate() {
+            castep.push_str("Initial  -2.10000000E+003  0.00000000E+000     1.00  <-- SCF\n");
+            for i in 1..=scf {
+                castep.push_str(&format!(
+                    "     {i:>3}  -2.10000000E+003  0.00000000E+000  1.0E-003   1.00  <-- SCF\n"
+                ));
+            }
+            castep.push_str(&format!(
+                "Final energy, E             =  -{}.5 eV\n",
+                2100 + step
+            ));
+            castep.push_str(&format!(
+                " |  |F|max   |   {force:.6E} |   5.000000E-002 |   eV/A | No  | <-- BFGS\n"
+            ));
+            castep.push_str(
+                " |  Smax     |   1.000000E-001 |   1.000000E-001 |    GPa | Yes | <-- BFGS\n",
+            );
+        }
+        fs::write(root.join("alpha.castep"), castep).expect("write castep");
+
+        let trace = load_trace(&root, "alpha", DftCodeType::Castep).expect("trace");
+        assert_eq!(trace.scf_limit, Some(20));
+        assert_eq!(trace.steps.len(), 2);
+        assert_eq!(trace.steps[0].scf_iterations, 12);
+        assert_eq!(trace.steps[1].energy_ev, Some(-2101.5));
+        assert_eq!(trace.steps[1].max_force_ev_per_a, Some(0.4));
+        assert_eq!(trace.steps[1].max_stress_gpa, Some(0.1));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
@@ -0,0 +1,676 @@
+//! # 自动纠错与续算准备
+//!
+//! 按失败原因（失败规则的修复标签或调度器状态）为作业生成逐级升级的输入修改方案，
+//! 将旧文件备份到 `attempt_N/`，改写 INCAR / .param / POSCAR，并在作业目录记录纠错历史，
+//! 同一错误的方案用尽或总次数达到上限后不再纠错，避免作业无限循环。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/dft_status.rs` 使用
+//! - 使用 `models/calculation.rs`、`error.rs` 与 `utils/scheduler.rs` 的日志目录名
+//! - 修复标签来自 `dft/failure_rules.toml` 的 `fix` 字段
+
+use crate::error::{QutilityError, Result};
+use crate::models::{CalculationScanRecord, CalculationStatus, DftCodeType};
+use crate::utils::scheduler::LOG_DIR;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 作业目录中的纠错历史文件
+pub const HISTORY_FILE: &str = ".qutility_corrections.toml";
+
+/// 备份时保留在原位的大文件或续算所需文件
+const KEEP_IN_PLACE: &[&str] = &["WAVECAR", "CHGCAR", "CHG"];
+const KEEP_IN_PLACE_EXTENSIONS: &[&str] = &["check", "castep_bin"];
+
+/// 备份时复制（而非移动）的输入文件
+const INPUT_FILES: &[&str] = &["INCAR", "POSCAR", "KPOINTS", "POTCAR"];
+const INPUT_EXTENSIONS: &[&str] = &["cell", "param", "sbatch", "pbs", "lsf", "sge"];
+
+/// 单项输入修改
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrectionAction {
+    /// 设置 INCAR 标签
+    SetIncar { key: String, value: String },
+    /// 设置 CASTEP .param 关键字
+    SetParam { key: String, value: String },
+    /// 删除 CASTEP .param 关键字
+    RemoveParam { key: String },
+    /// 以上一次的 CONTCAR 作为新的 POSCAR
+    RestartFromContcar,
+}
+
+impl fmt::Display for CorrectionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrectionAction::SetIncar { key, value } => write!(f, "INCAR {key} = {value}"),
+            CorrectionAction::SetParam { key, value } => write!(f, "param {key} : {value}"),
+            CorrectionAction::RemoveParam { key } => write!(f, "param remove {key}"),
+            CorrectionAction::RestartFromContcar => write!(f, "POSCAR <- CONTCAR"),
+        }
+    }
+}
+
+/// 针对某个错误的纠错方案
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrectionPlan {
+    /// 错误标签（修复标签或 timeout / node_fail / unconverged）
+    pub error: String,
+    /// 本次方案在该错误上的级别（从 1 开始）
+    pub stage: usize,
+    pub actions: Vec<CorrectionAction>,
+}
+
+/// 纠错决策
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrectionDecision {
+    Apply(CorrectionPlan),
+    Skip(String),
+}
+
+/// 一次已执行的纠错
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectionEntry {
+    pub attempt: usize,
+    pub error: String,
+    pub stage: usize,
+    pub reason: Option<String>,
+    pub job_id: Option<String>,
+    pub actions: Vec<String>,
+    pub backup: String,
+    /// UNIX 时间戳（秒）
+    pub applied_at: u64,
+}
+
+/// 作业的纠错历史
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorrectionHistory {
+    #[serde(default, rename = "correction")]
+    pub entries: Vec<CorrectionEntry>,
+}
+
+impl CorrectionHistory {
+    /// 读取作业目录中的历史；文件不存在时返回空历史
+    pub fn load(job_dir: &Path) -> Result<Self> {
+        let path = job_dir.join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| QutilityError::FileReadError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        toml::from_str(&content).map_err(|e| QutilityError::ParseError {
+            format: "correction history TOML".to_string(),
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    pub fn save(&self, job_dir: &Path) -> Result<()> {
+        let path = job_dir.join(HISTORY_FILE);
+        let content = toml::to_string(self).map_err(|e| {
+            QutilityError::Other(format!("Cannot serialize correction history: {e}"))
+        })?;
+        fs::write(&path, content).map_err(|e| QutilityError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        })
+    }
+
+    /// 某个错误已执行的纠错次数
+    pub fn count(&self, error: &str) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.error == error)
+            .count()
+    }
+}
+
+/// 根据扫描记录与纠错历史决定下一步纠错方案
+pub fn plan_correction(
+    record: &CalculationScanRecord,
+    history: &CorrectionHistory,
+    max_attempts: usize,
+) -> CorrectionDecision {
+    let Some(error) = error_tag(record) else {
+        return CorrectionDecision::Skip("no known correction for this failure".to_string());
+    };
+
+    if history.entries.len() >= max_attempts {
+        return CorrectionDecision::Skip(format!(
+            "reached the limit of {max_attempts} correction attempts"
+        ));
+    }
+
+    let stage = history.count(&error);
+    let actions = match record.code {
+        DftCodeType::Vasp => vasp_actions(&error, stage, &record.job_dir),
+        DftCodeType::Castep => castep_actions(&error, stage, record),
+    };
+
+    match actions {
+        Some(actions) => CorrectionDecision::Apply(CorrectionPlan {
+            error,
+            stage: stage + 1,
+            actions,
+        }),
+        None if stage > 0 => CorrectionDecision::Skip(format!(
+            "all corrections for '{error}' have already been tried"
+        )),
+        None => CorrectionDecision::Skip(format!("no correction available for '{error}'")),
+    }
+}
+
+/// 失败的错误标签：调度器状态优先，其次为失败规则给出的修复标签。
+///
+/// 未完成（Incomplete）的作业可能仍在运行，只记录警告的规则不足以据此改写输入
+fn error_tag(record: &CalculationScanRecord) -> Option<String> {
+    match record.status {
+        CalculationStatus::TimedOut => Some("timeout".to_string()),
+        CalculationStatus::NodeFail => Some("node_fail".to_string()),
+        CalculationStatus::Unconverged => Some("unconverged".to_string()),
+        CalculationStatus::Failed => record.suggested_fix.clone(),
+        _ => None,
+    }
+}
+
+/// VASP 纠错方案；返回 None 表示该级别没有可用方案
+fn vasp_actions(error: &str, stage: usize, job_dir: &Path) -> Option<Vec<CorrectionAction>> {
+    let incar = |key: &str, value: &str| CorrectionAction::SetIncar {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let has_contcar = fs::metadata(job_dir.join("CONTCAR")).is_ok_and(|m| m.len() > 0);
+
+    let mut actions = match (error, stage) {
+        ("zbrent", 0) => vec![incar("IBRION", "1")],
+        ("zbrent", 1) => {
+            let potim = read_incar_tag(&job_dir.join("INCAR"), "POTIM")
+                .and_then(|value| value.parse::<f64>().ok())
+                .unwrap_or(0.5);
+            vec![
+                incar("IBRION", "1"),
+                incar("POTIM", &format!("{}", potim / 2.0)),
+            ]
+        }
+        ("brmix", 0) => vec![
+            incar("AMIX", "0.1"),
+            incar("BMIX", "0.01"),
+            incar("ICHARG", "2"),
+        ],
+        ("brmix", 1) => vec![
+            incar("AMIX", "0.05"),
+            incar("BMIX", "0.001"),
+            incar("AMIX_MAG", "0.8"),
+            incar("BMIX_MAG", "0.0001"),
+        ],
+        ("edddav" | "eddrmm" | "pssyevx", 0) => vec![incar("ALGO", "Normal")],
+        ("edddav" | "eddrmm", 1) => vec![incar("ALGO", "All")],
+        ("subspacematrix", 0) => vec![incar("LREAL", ".FALSE.")],
+        ("timeout" | "node_fail" | "unconverged", _) if has_contcar => Vec::new(),
+        _ => return None,
+    };
+
+    // 离子步相关错误与续算都从最新构型继续
+    if has_contcar && matches!(error, "zbrent" | "timeout" | "node_fail" | "unconverged") {
+        actions.push(CorrectionAction::RestartFromContcar);
+    }
+
+    Some(actions)
+}
+
+/// CASTEP 纠错方案；续算依赖 `<seed>.check`
+fn castep_actions(
+    error: &str,
+    stage: usize,
+    record: &CalculationScanRecord,
+) -> Option<Vec<CorrectionAction>> {
+    let param = |key: &str, value: &str| CorrectionAction::SetParam {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+    let has_check = record
+        .job_dir
+        .join(format!("{}.check", record.seed))
+        .is_file();
+
+    let actions = match (error, stage) {
+        ("timeout" | "node_fail" | "unconverged", _) if has_check => vec![
+            CorrectionAction::RemoveParam {
+                key: "reuse".to_string(),
+            },
+            param("continuation", "default"),
+        ],
+        ("scf", 0) => vec![
+            param("max_scf_cycles", "200"),
+            param("mix_charge_amp", "0.2"),
+        ],
+        ("scf", 1) => vec![
+            param("mixing_scheme", "Broyden"),
+            param("mix_charge_amp", "0.1"),
+        ],
+        _ => return None,
+    };
+
+    Some(actions)
+}
+
+/// 备份旧文件到 `attempt_N/`、改写输入并追加纠错历史
+pub fn apply_correction(
+    record: &CalculationScanRecord,
+    plan: &CorrectionPlan,
+) -> Result<CorrectionEntry> {
+    let job_dir = &record.job_dir;
+    let mut history = CorrectionHistory::load(job_dir)?;
+
+    let mut attempt = history.entries.len() + 1;
+    while job_dir.join(format!("attempt_{attempt}")).exists() {
+        attempt += 1;
+    }
+    let backup_name = format!("attempt_{attempt}");
+    let backup_dir = job_dir.join(&backup_name);
+    backup_job_files(job_dir, &backup_dir)?;
+
+    for action in &plan.actions {
+        apply_action(record, &backup_dir, action)?;
+    }
+
+    let entry = CorrectionEntry {
+        attempt,
+        error: plan.error.clone(),
+        stage: plan.stage,
+        reason: record.reason.clone(),
+        job_id: record.job_id.clone(),
+        actions: plan.actions.iter().map(ToString::to_string).collect(),
+        backup: backup_name,
+        applied_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    history.entries.push(entry.clone());
+    history.save(job_dir)?;
+
+    Ok(entry)
+}
+
+/// 输入文件复制到备份目录，输出与日志移入备份目录，大文件与续算文件保留原位
+fn backup_job_files(job_dir: &Path, backup_dir: &Path) -> Result<()> {
+    create_dir(backup_dir)?;
+
+    for path in dir_entries(job_dir)? {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if path.is_dir() {
+            if name == LOG_DIR {
+                let log_backup = backup_dir.join(LOG_DIR);
+                create_dir(&log_backup)?;
+                for log in dir_entries(&path)?.into_iter().filter(|p| p.is_file()) {
+                    move_file(&log, &log_backup)?;
+                }
+            }
+            continue;
+        }
+
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if name == HISTORY_FILE
+            || KEEP_IN_PLACE.contains(&name.as_str())
+            || KEEP_IN_PLACE_EXTENSIONS.contains(&extension)
+        {
+            continue;
+        }
+
+        if INPUT_FILES.contains(&name.as_str()) || INPUT_EXTENSIONS.contains(&extension) {
+            let dest = backup_dir.join(&name);
+            fs::copy(&path, &dest).map_err(|e| QutilityError::FileWriteError {
+                path: dest.display().to_string(),
+                source: e,
+            })?;
+        } else {
+            move_file(&path, backup_dir)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_action(
+    record: &CalculationScanRecord,
+    backup_dir: &Path,
+    action: &CorrectionAction,
+) -> Result<()> {
+    let job_dir = &record.job_dir;
+    match action {
+        CorrectionAction::SetIncar { key, value } => edit_file(&job_dir.join("INCAR"), |content| {
+            set_incar_tag(content, key, value)
+        }),
+        CorrectionAction::SetParam { key, value } => {
+            edit_file(&job_dir.join(format!("{}.param", record.seed)), |content| {
+                set_param_keyword(content, key, Some(value))
+            })
+        }
+        CorrectionAction::RemoveParam { key } => {
+            edit_file(&job_dir.join(format!("{}.param", record.seed)), |content| {
+                set_param_keyword(content, key, None)
+            })
+        }
+        CorrectionAction::RestartFromContcar => {
+            let contcar = backup_dir.join("CONTCAR");
+            let poscar = job_dir.join("POSCAR");
+            fs::copy(&contcar, &poscar).map_err(|e| QutilityError::FileWriteError {
+                path: poscar.display().to_string(),
+                source: e,
+            })?;
+            Ok(())
+        }
+    }
+}
+
+/// 设置 INCAR 标签：替换已有标签（支持 `;` 分隔的多标签行），否则追加到末尾
+pub fn set_incar_tag(content: &str, key: &str, value: &str) -> String {
+    let mut found = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let (body, comment) = split_comment(line);
+            let segments: Vec<String> = body
+                .split(';')
+                .map(|segment| match segment.split_once('=') {
+                    Some((tag, _)) if tag.trim().eq_ignore_ascii_case(key) => {
+                        found = true;
+                        let leading = &segment[..segment.len() - segment.trim_start().len()];
+                        let trailing = &segment[segment.trim_end().len()..];
+                        format!("{leading}{} = {value}{trailing}", key.to_uppercase())
+                    }
+                    _ => segment.to_string(),
+                })
+                .collect();
+            format!("{}{}", segments.join(";"), comment)
+        })
+        .collect();
+
+    if !found {
+        lines.push(format!("{} = {}", key.to_uppercase(), value));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// 读取 INCAR 标签值
+pub fn read_incar_tag(path: &Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    content.lines().find_map(|line| {
+        split_comment(line).0.split(';').find_map(|segment| {
+            let (tag, value) = segment.split_once('=')?;
+            tag.trim()
+                .eq_ignore_ascii_case(key)
+                .then(|| value.trim().to_string())
+        })
+    })
+}
+
+/// 设置（`Some`）或删除（`None`）CASTEP .param 关键字，关键字不区分大小写
+pub fn set_param_keyword(content: &str, key: &str, value: Option<&str>) -> String {
+    let mut found = false;
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let keyword = split_comment(line)
+            .0
+            .split(|c: char| c.is_whitespace() || c == ':' || c == '=')
+            .find(|token| !token.is_empty());
+
+        if keyword.is_some_and(|keyword| keyword.eq_ignore_ascii_case(key)) {
+            if let (Some(value), false) = (value, found) {
+                lines.push(format!("{key} : {value}"));
+            }
+            found = true;
+            continue;
+        }
+
+        lines.push(line.to_string());
+    }
+
+    if let (Some(value), false) = (value, found) {
+        lines.push(format!("{key} : {value}"));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// 拆分行内 `!` / `#` 注释
+fn split_comment(line: &str) -> (&str, &str) {
+    match line.find(['!', '#']) {
+        Some(pos) => line.split_at(pos),
+        None => (line, ""),
+    }
+}
+
+fn edit_file(path: &Path, edit: impl FnOnce(&str) -> String) -> Result<()> {
+    let content = fs::read_to_string(path).map_err(|e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    fs::write(path, edit(&content)).map_err(|e| QutilityError::FileWriteError {
+        path: path.display().to_string(),
+        source: e,
+    })
+}
+
+fn dir_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| QutilityError::FileReadError {
+            path: dir.display().to_string(),
+            source: e,
+        })?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn create_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|e| QutilityError::FileWriteError {
+        path: dir.display().to_string(),
+        source: e,
+    })
+}
+
+fn move_file(path: &Path, dest_dir: &Path) -> Result<()> {
+    let dest = dest_dir.join(path.file_name().unwrap_or_default());
+    fs::rename(path, &dest).map_err(|e| QutilityError::FileWriteError {
+        path: dest.display().to_string(),
+        source: e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    fn failed_record(job_dir: &Path, code: DftCodeType, fix: &str) -> CalculationScanRecord {
+        let mut record = CalculationScanRecord::new(
+            "alpha",
+            job_dir.to_path_buf(),
+            code,
+            CalculationStatus::Failed,
+        );
+        record.suggested_fix = Some(fix.to_string());
+        record
+    }
+
+    #[test]
+    fn edits_incar_and_param_keywords() {
+        let incar = "SYSTEM = test\nISMEAR = 0; ALGO = Fast ! fast\nEDIFF = 1E-6\n";
+        assert_eq!(
+            set_incar_tag(incar, "algo", "Normal"),
+            "SYSTEM = test\nISMEAR = 0; ALGO = Normal ! fast\nEDIFF = 1E-6\n"
+        );
+        assert_eq!(
+            set_incar_tag("EDIFF = 1E-6\n", "AMIX", "0.1"),
+            "EDIFF = 1E-6\nAMIX = 0.1\n"
+        );
+
+        let param = "task : GeometryOptimization\nREUSE : default\ncut_off_energy = 500\n";
+        let param = set_param_keyword(param, "reuse", None);
+        let param = set_param_keyword(&param, "continuation", Some("default"));
+        assert_eq!(
+            param,
+            "task : GeometryOptimization\ncut_off_energy = 500\ncontinuation : default\n"
+        );
+    }
+
+    #[test]
+    fn escalates_and_stops_repeating_corrections() {
+        let root = unique_test_dir("dft-correction-plan");
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("INCAR"), "POTIM = 0.4\n").expect("write INCAR");
+        fs::write(root.join("CONTCAR"), "relaxed\n").expect("write CONTCAR");
+        let record = failed_record(&root, DftCodeType::Vasp, "zbrent");
+
+        let mut history = CorrectionHistory::default();
+        let CorrectionDecision::Apply(first) = plan_correction(&record, &history, 5) else {
+            panic!("expected first zbrent correction");
+        };
+        assert_eq!(first.stage, 1);
+        assert!(first
+            .actions
+            .contains(&CorrectionAction::RestartFromContcar));
+
+        let entry = |error: &str| CorrectionEntry {
+            attempt: 1,
+            error: error.to_string(),
+            stage: 1,
+            reason: None,
+            job_id: None,
+            actions: Vec::new(),
+            backup: "attempt_1".to_string(),
+            applied_at: 0,
+        };
+        history.entries.push(entry("zbrent"));
+        let CorrectionDecision::Apply(second) = plan_correction(&record, &history, 5) else {
+            panic!("expected second zbrent correction");
+        };
+        assert!(second.actions.contains(&CorrectionAction::SetIncar {
+            key: "POTIM".to_string(),
+            value: "0.2".to_string(),
+        }));
+
+        history.entries.push(entry("zbrent"));
+        assert!(matches!(
+            plan_correction(&record, &history, 5),
+            CorrectionDecision::Skip(_)
+        ));
+
+        let mut running = failed_record(&root, DftCodeType::Vasp, "zbrent");
+        running.status = CalculationStatus::Incomplete;
+        assert!(matches!(
+            plan_correction(&running, &CorrectionHistory::default(), 5),
+            CorrectionDecision::Skip(_)
+        ));
+
+        let timeout = CalculationScanRecord::new(
+            "alpha",
+            root.clone(),
+            DftCodeType::Vasp,
+            CalculationStatus::TimedOut,
+        );
+        assert!(matches!(
+            plan_correction(&timeout, &history, 2),
+            CorrectionDecision::Skip(reason) if reason.contains("limit")
+        ));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn applies_vasp_correction_with_backup_and_history() {
+        let root = unique_test_dir("dft-correction-apply-vasp");
+        fs::create_dir_all(root.join("slurm_logs")).expect("create job dir");
+        fs::write(root.join("INCAR"), "IBRION = 2\nPOTIM = 0.5\n").expect("write INCAR");
+        fs::write(root.join("POSCAR"), "initial\n").expect("write POSCAR");
+        fs::write(root.join("CONTCAR"), "relaxed\n").expect("write CONTCAR");
+        fs::write(root.join("OUTCAR"), "ZBRENT: fatal error\n").expect("write OUTCAR");
+        fs::write(root.join("WAVECAR"), "big").expect("write WAVECAR");
+        fs::write(root.join("slurm_logs").join("alpha.err"), "").expect("write log");
+
+        let record = failed_record(&root, DftCodeType::Vasp, "zbrent");
+        let CorrectionDecision::Apply(plan) =
+            plan_correction(&record, &CorrectionHistory::default(), 3)
+        else {
+            panic!("expected correction");
+        };
+        let entry = apply_correction(&record, &plan).expect("apply");
+
+        let backup = root.join("attempt_1");
+        assert_eq!(entry.backup, "attempt_1");
+        assert!(backup.join("OUTCAR").exists() && !root.join("OUTCAR").exists());
+        assert!(backup.join("slurm_logs").join("alpha.err").exists());
+        assert_eq!(
+            fs::read_to_string(backup.join("POSCAR")).expect("read backup"),
+            "initial\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("POSCAR")).expect("read POSCAR"),
+            "relaxed\n"
+        );
+        assert!(root.join("WAVECAR").exists());
+        assert_eq!(
+            read_incar_tag(&root.join("INCAR"), "IBRION").as_deref(),
+            Some("1")
+        );
+
+        let history = CorrectionHistory::load(&root).expect("load history");
+        assert_eq!(history.count("zbrent"), 1);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn castep_timeout_continues_from_check_file() {
+        let root = unique_test_dir("dft-correction-castep");
+        fs::create_dir_all(&root).expect("create job dir");
+        fs::write(root.join("alpha.param"), "task : GeometryOptimization\n").expect("write param");
+        fs::write(root.join("alpha.castep"), "partial\n").expect("write castep");
+
+        let mut record = CalculationScanRecord::new(
+            "alpha",
+            root.clone(),
+            DftCodeType::Castep,
+            CalculationStatus::TimedOut,
+        );
+        assert!(matches!(
+            plan_correction(&record, &CorrectionHistory::default(), 3),
+            CorrectionDecision::Skip(_)
+        ));
+
+        fs::write(root.join("alpha.check"), "binary").expect("write check");
+        record.job_id = Some("42".to_string());
+        let CorrectionDecision::Apply(plan) =
+            plan_correction(&record, &CorrectionHistory::default(), 3)
+        else {
+            panic!("expected continuation");
+        };
+        apply_correction(&record, &plan).expect("apply");
+
+        assert!(root.join("alpha.check").exists());
+        assert!(root.join("attempt_1").join("alpha.castep").exists());
+        assert!(fs::read_to_string(root.join("alpha.param"))
+            .expect("read param")
+            .contains("continuation : default"));
+        let history = CorrectionHistory::load(&root).expect("load history");
+        assert_eq!(history.entries[0].job_id.as_deref(), Some("42"));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
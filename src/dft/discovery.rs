@@ -0,0 +1,451 @@
+//! # 作业目录发现
+//!
+//! 在作业根目录下按最大深度递归查找作业目录：含输入/输出标记文件（INCAR、OUTCAR、`*.param`、
+//! `*.castep`）的目录即为作业且不再向下搜索，指定代码时到达最大深度的目录一律视为作业。
+//! `auto` 模式按目录内文件逐个判断代码，允许 VASP 与 CASTEP 作业混合在同一根目录下。
+//! CASTEP 种子名取自 `.param` 文件名，结构名由路径模板生成；各子目录并行检查，结果顺序与排序后的路径一致。
+//! 调度器日志目录 `slurm_logs/` 与纠错备份 `attempt_N/` 不是作业，任何深度都跳过。
+//!
+//! ## 依赖关系
+//! - 被 `dft/scan.rs` 调用，经 `dft/mod.rs` 导出给命令层构造布局
+//! - 使用 `models/calculation.rs`、`utils/scheduler.rs`（日志目录名）、`error.rs` 与 `rayon`
+
+use crate::error::{QutilityError, Result};
+use crate::models::DftCodeType;
+use crate::utils::scheduler::LOG_DIR;
+
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 默认结构名模板：相对路径各级以 `_` 连接（单层布局即目录名）
+pub const DEFAULT_NAME_TEMPLATE: &str = "{path}";
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    /// 相对路径，各级以 `_` 连接
+    Path,
+    /// 作业目录名
+    Dir,
+    /// 上一级目录名
+    Parent,
+    /// 种子名
+    Seed,
+    /// 相对路径的第 N 级（从 0 开始）
+    Component(usize),
+}
+
+/// 结构名路径模板，如 `{0}_{1}_{seed}` 对应 `jobs/<formula>/<pressure>/<seed>/`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl NameTemplate {
+    /// 解析模板；支持 `{path}`、`{dir}`、`{parent}`、`{seed}`、`{0}`、`{1}`…，`{{`/`}}` 表示花括号本身
+    pub fn parse(template: &str) -> Result<Self> {
+        let invalid = |reason: String| {
+            QutilityError::InvalidArgument(format!("Invalid name template '{template}': {reason}"))
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(invalid("unclosed '{'".to_string())),
+                        }
+                    }
+
+                    let part = match name.as_str() {
+                        "path" => TemplatePart::Path,
+                        "dir" => TemplatePart::Dir,
+                        "parent" => TemplatePart::Parent,
+                        "seed" => TemplatePart::Seed,
+                        other => match other.parse::<usize>() {
+                            Ok(index) => TemplatePart::Component(index),
+                            Err(_) => {
+                                return Err(invalid(format!(
+                                    "unknown placeholder '{{{other}}}' \
+                                     (expected path, dir, parent, seed or a component index)"
+                                )))
+                            }
+                        },
+                    };
+
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                }
+                '}' => return Err(invalid("unmatched '}'".to_string())),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// 按相对路径各级与种子名渲染结构名
+    pub fn render(&self, components: &[String], seed: &str) -> Result<String> {
+        let component = |index: usize, placeholder: &str| {
+            components.get(index).cloned().ok_or_else(|| {
+                QutilityError::InvalidArgument(format!(
+                    "Name template placeholder '{{{placeholder}}}' is out of range for job path '{}'",
+                    components.join("/")
+                ))
+            })
+        };
+
+        let mut name = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => name.push_str(text),
+                TemplatePart::Path => name.push_str(&components.join("_")),
+                TemplatePart::Dir => name.push_str(components.last().map_or("", String::as_str)),
+                TemplatePart::Parent => {
+                    let index = components.len().checked_sub(2).unwrap_or(usize::MAX);
+                    name.push_str(&component(index, "parent")?);
+                }
+                TemplatePart::Seed => name.push_str(seed),
+                TemplatePart::Component(index) => {
+                    name.push_str(&component(*index, &index.to_string())?)
+                }
+            }
+        }
+
+        Ok(name)
+    }
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        Self {
+            parts: vec![TemplatePart::Path],
+        }
+    }
+}
+
+/// 作业目录布局：递归深度与结构名模板
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobLayout {
+    /// 最大搜索深度，1 表示只看根目录的直接子目录
+    pub max_depth: usize,
+    pub name_template: NameTemplate,
+}
+
+impl Default for JobLayout {
+    fn default() -> Self {
+        Self {
+            max_depth: 1,
+            name_template: NameTemplate::default(),
+        }
+    }
+}
+
+/// 扫描使用的代码：固定代码或按目录自动识别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeSelection {
+    Fixed(DftCodeType),
+    Auto,
+}
+
+impl From<DftCodeType> for CodeSelection {
+    fn from(code: DftCodeType) -> Self {
+        CodeSelection::Fixed(code)
+    }
+}
+
+/// 发现的作业目录
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredJob {
+    pub dir: PathBuf,
+    /// 作业使用的代码（`auto` 模式下为识别结果）
+    pub code: DftCodeType,
+    /// 由模板生成的结构名
+    pub name: String,
+    /// 输入/输出文件的种子名（VASP 为目录名）
+    pub seed: String,
+}
+
+/// 按布局在根目录下发现作业，结果按相对路径排序；`auto` 模式跳过无法识别代码的目录
+pub fn discover_jobs(
+    root: &Path,
+    code: CodeSelection,
+    layout: &JobLayout,
+) -> Result<Vec<DiscoveredJob>> {
+    visit(root, &[], 1, code, layout)
+}
+
+/// 并行检查各子目录；按排序后的子目录顺序拼接结果，保证输出确定
+fn visit(
+    dir: &Path,
+    components: &[String],
+    depth: usize,
+    code: CodeSelection,
+    layout: &JobLayout,
+) -> Result<Vec<DiscoveredJob>> {
+    let subdirs: Vec<PathBuf> = sorted_entries(dir)?
+        .into_iter()
+        .filter(|path| path.is_dir() && !is_auxiliary_dir(path))
+        .collect();
+
+    let nested = subdirs
+        .into_par_iter()
+        .map(|subdir| {
+            let mut sub_components = components.to_vec();
+            sub_components.push(file_name(&subdir));
+
+            let detected = match code {
+                CodeSelection::Fixed(code) => is_job_dir(&subdir, code).then_some(code),
+                CodeSelection::Auto => detect_code(&subdir),
+            };
+            let job_code = match (detected, code) {
+                (Some(job_code), _) => Some(job_code),
+                (None, CodeSelection::Fixed(code)) if depth >= layout.max_depth => Some(code),
+                _ => None,
+            };
+
+            if let Some(job_code) = job_code {
+                let seed = seed_name(&subdir, job_code);
+                let name = layout.name_template.render(&sub_components, &seed)?;
+                Ok(vec![DiscoveredJob {
+                    dir: subdir,
+                    code: job_code,
+                    name,
+                    seed,
+                }])
+            } else if depth < layout.max_depth {
+                visit(&subdir, &sub_components, depth + 1, code, layout)
+            } else {
+                Ok(Vec::new())
+            }
+        })
+        .collect::<Result<Vec<Vec<DiscoveredJob>>>>()?;
+
+    Ok(nested.into_iter().flatten().collect())
+}
+
+/// 调度器日志目录（含 `--array` 写在作业根目录下的日志）与纠错备份 `attempt_N/`
+fn is_auxiliary_dir(path: &Path) -> bool {
+    let name = file_name(path);
+    name == LOG_DIR
+        || name
+            .strip_prefix("attempt_")
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// 目录是否含有该代码的输入或输出标记文件
+fn is_job_dir(dir: &Path, code: DftCodeType) -> bool {
+    match code {
+        DftCodeType::Vasp => ["INCAR", "OUTCAR", "OSZICAR"]
+            .iter()
+            .any(|marker| dir.join(marker).is_file()),
+        DftCodeType::Castep => {
+            !files_with_extension(dir, "param").is_empty()
+                || !files_with_extension(dir, "castep").is_empty()
+        }
+    }
+}
+
+/// 按目录内文件识别代码；两种标记并存时以已有输出（OUTCAR / `.castep`）的一方为准，否则视为 VASP
+fn detect_code(dir: &Path) -> Option<DftCodeType> {
+    let vasp = is_job_dir(dir, DftCodeType::Vasp);
+    let castep = is_job_dir(dir, DftCodeType::Castep);
+
+    match (vasp, castep) {
+        (true, true) => {
+            let castep_output = !files_with_extension(dir, "castep").is_empty();
+            if castep_output && !dir.join("OUTCAR").is_file() {
+                Some(DftCodeType::Castep)
+            } else {
+                Some(DftCodeType::Vasp)
+            }
+        }
+        (true, false) => Some(DftCodeType::Vasp),
+        (false, true) => Some(DftCodeType::Castep),
+        (false, false) => None,
+    }
+}
+
+/// CASTEP 种子名：优先与目录同名的 `.param`，其次任一 `.param`，再次 `.castep`，最后用目录名
+fn seed_name(dir: &Path, code: DftCodeType) -> String {
+    let dir_name = file_name(dir);
+    if code == DftCodeType::Vasp {
+        return dir_name;
+    }
+
+    for extension in ["param", "castep"] {
+        let stems: Vec<String> = files_with_extension(dir, extension)
+            .iter()
+            .filter_map(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .collect();
+        if stems.contains(&dir_name) {
+            return dir_name;
+        }
+        if let Some(stem) = stems.into_iter().next() {
+            return stem;
+        }
+    }
+
+    dir_name
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    sorted_entries(dir)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(extension)
+        })
+        .collect()
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| QutilityError::FileReadError {
+            path: dir.display().to_string(),
+            source: e,
+        })?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    #[test]
+    fn parses_and_renders_templates() {
+        let template = NameTemplate::parse("{0}-{1}_{seed}{{x}}").expect("parse");
+        let components = vec!["TiO2".to_string(), "P050".to_string(), "run".to_string()];
+        assert_eq!(
+            template.render(&components, "rutile").expect("render"),
+            "TiO2-P050_rutile{x}"
+        );
+        assert_eq!(
+            NameTemplate::default()
+                .render(&components, "rutile")
+                .expect("render"),
+            "TiO2_P050_run"
+        );
+
+        let err = NameTemplate::parse("{formula}").expect_err("unknown placeholder");
+        assert!(err.to_string().contains("unknown placeholder '{formula}'"));
+        assert!(NameTemplate::parse("{0").is_err());
+        assert!(NameTemplate::parse("{3}")
+            .expect("parse")
+            .render(&components, "s")
+            .is_err());
+    }
+
+    #[test]
+    fn discovers_nested_castep_jobs_by_markers() {
+        let root = unique_test_dir("dft-discovery-nested");
+        let rutile = root.join("TiO2").join("P050").join("job1");
+        let anatase = root.join("TiO2").join("P100").join("job2");
+        let notes = root.join("TiO2").join("notes");
+        for dir in [&rutile, &anatase, &notes] {
+            fs::create_dir_all(dir).expect("create dir");
+        }
+        fs::write(rutile.join("rutile.param"), "").expect("write param");
+        fs::write(anatase.join("anatase.castep"), "").expect("write castep");
+        fs::create_dir_all(rutile.join("attempt_1")).expect("create attempt dir");
+
+        let layout = JobLayout {
+            max_depth: 3,
+            name_template: NameTemplate::parse("{0}_{1}_{seed}").expect("template"),
+        };
+        let jobs = discover_jobs(&root, DftCodeType::Castep.into(), &layout).expect("discover");
+
+        let names: Vec<_> = jobs.iter().map(|job| job.name.as_str()).collect();
+        assert_eq!(names, ["TiO2_P050_rutile", "TiO2_P100_anatase"]);
+        assert_eq!(jobs[0].seed, "rutile");
+        assert_eq!(jobs[0].dir, rutile);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn default_layout_keeps_direct_subdirectories() {
+        let root = unique_test_dir("dft-discovery-flat");
+        fs::create_dir_all(root.join("alpha").join("deep")).expect("create alpha");
+        fs::create_dir_all(root.join("beta")).expect("create beta");
+        fs::write(root.join("beta").join("INCAR"), "").expect("write INCAR");
+        fs::create_dir_all(root.join("slurm_logs")).expect("create logs");
+        fs::create_dir_all(root.join("attempt_2")).expect("create attempt dir");
+        fs::create_dir_all(root.join("attempt_x")).expect("create attempt-like dir");
+
+        let jobs = discover_jobs(&root, DftCodeType::Vasp.into(), &JobLayout::default())
+            .expect("discover");
+
+        let names: Vec<_> = jobs.iter().map(|job| job.name.as_str()).collect();
+        assert_eq!(names, ["alpha", "attempt_x", "beta"]);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn auto_detects_code_per_directory() {
+        let root = unique_test_dir("dft-discovery-auto");
+        let vasp = root.join("refine").join("alpha");
+        let castep = root.join("screen").join("beta");
+        let empty = root.join("screen").join("empty");
+        for dir in [&vasp, &castep, &empty] {
+            fs::create_dir_all(dir).expect("create dir");
+        }
+        fs::write(vasp.join("INCAR"), "").expect("write INCAR");
+        fs::write(castep.join("beta.param"), "").expect("write param");
+
+        let layout = JobLayout {
+            max_depth: 2,
+            ..JobLayout::default()
+        };
+        let jobs = discover_jobs(&root, CodeSelection::Auto, &layout).expect("discover");
+
+        let found: Vec<_> = jobs
+            .iter()
+            .map(|job| (job.name.as_str(), job.code))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("refine_alpha", DftCodeType::Vasp),
+                ("screen_beta", DftCodeType::Castep)
+            ]
+        );
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
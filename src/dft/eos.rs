@@ -0,0 +1,426 @@
+//! # 状态方程拟合
+//!
+//! 对 E(V) 数据拟合 Birch–Murnaghan (三阶)、Vinet 与 Murnaghan 状态方程，并由拟合结果推导 P(V) 与 H(P)。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/eos.rs` 使用
+//! - 无外部模块依赖（Levenberg–Marquardt 最小二乘在本文件内实现）
+
+use crate::error::{QutilityError, Result};
+
+/// eV/Å³ 到 GPa 的换算系数
+pub const EV_PER_A3_TO_GPA: f64 = 160.217_662_08;
+
+/// 状态方程形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EosForm {
+    BirchMurnaghan,
+    Vinet,
+    Murnaghan,
+}
+
+impl EosForm {
+    /// 计算能量 E(V)，参数 B0 单位为 eV/Å³
+    pub fn energy(self, params: &EosParams, volume: f64) -> f64 {
+        let EosParams {
+            e0,
+            v0,
+            b0,
+            b0_prime,
+        } = *params;
+
+        match self {
+            EosForm::BirchMurnaghan => {
+                let eta = (v0 / volume).powf(2.0 / 3.0);
+                e0 + 9.0 * v0 * b0 / 16.0
+                    * ((eta - 1.0).powi(3) * b0_prime + (eta - 1.0).powi(2) * (6.0 - 4.0 * eta))
+            }
+            EosForm::Vinet => {
+                let x = (volume / v0).powf(1.0 / 3.0);
+                let eta = 1.5 * (b0_prime - 1.0);
+                e0 + 2.0 * b0 * v0 / (b0_prime - 1.0).powi(2)
+                    * (2.0
+                        - (5.0 + 3.0 * b0_prime * (x - 1.0) - 3.0 * x) * (-eta * (x - 1.0)).exp())
+            }
+            EosForm::Murnaghan => {
+                e0 + b0 * volume / b0_prime
+                    * ((v0 / volume).powf(b0_prime) / (b0_prime - 1.0) + 1.0)
+                    - v0 * b0 / (b0_prime - 1.0)
+            }
+        }
+    }
+
+    /// 计算压力 P(V) = -dE/dV，单位 eV/Å³
+    pub fn pressure(self, params: &EosParams, volume: f64) -> f64 {
+        let EosParams {
+            v0, b0, b0_prime, ..
+        } = *params;
+
+        match self {
+            EosForm::BirchMurnaghan => {
+                let eta = (v0 / volume).powf(2.0 / 3.0);
+                1.5 * b0
+                    * (eta.powf(3.5) - eta.powf(2.5))
+                    * (1.0 + 0.75 * (b0_prime - 4.0) * (eta - 1.0))
+            }
+            EosForm::Vinet => {
+                let x = (volume / v0).powf(1.0 / 3.0);
+                3.0 * b0 * (1.0 - x) / (x * x) * (1.5 * (b0_prime - 1.0) * (1.0 - x)).exp()
+            }
+            EosForm::Murnaghan => b0 / b0_prime * ((v0 / volume).powf(b0_prime) - 1.0),
+        }
+    }
+
+    /// 计算焓 H = E + PV，单位 eV
+    pub fn enthalpy(self, params: &EosParams, volume: f64) -> f64 {
+        self.energy(params, volume) + self.pressure(params, volume) * volume
+    }
+}
+
+impl std::fmt::Display for EosForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EosForm::BirchMurnaghan => write!(f, "birch-murnaghan"),
+            EosForm::Vinet => write!(f, "vinet"),
+            EosForm::Murnaghan => write!(f, "murnaghan"),
+        }
+    }
+}
+
+/// 状态方程参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EosParams {
+    /// 平衡能量 (eV)
+    pub e0: f64,
+    /// 平衡体积 (Å³)
+    pub v0: f64,
+    /// 体弹模量 (eV/Å³)
+    pub b0: f64,
+    /// 体弹模量对压力的导数（无量纲）
+    pub b0_prime: f64,
+}
+
+impl EosParams {
+    /// 体弹模量 (GPa)
+    pub fn b0_gpa(&self) -> f64 {
+        self.b0 * EV_PER_A3_TO_GPA
+    }
+
+    fn to_array(self) -> [f64; 4] {
+        [self.e0, self.v0, self.b0, self.b0_prime]
+    }
+
+    fn from_array(p: [f64; 4]) -> Self {
+        EosParams {
+            e0: p[0],
+            v0: p[1],
+            b0: p[2],
+            b0_prime: p[3],
+        }
+    }
+}
+
+/// 单个状态方程的拟合结果
+#[derive(Debug, Clone)]
+pub struct EosFit {
+    pub form: EosForm,
+    pub params: EosParams,
+    /// 每个数据点的残差 E_fit - E_data (eV)
+    pub residuals: Vec<f64>,
+}
+
+impl EosFit {
+    /// 残差均方根 (eV)
+    pub fn rms_residual(&self) -> f64 {
+        let sum: f64 = self.residuals.iter().map(|r| r * r).sum();
+        (sum / self.residuals.len() as f64).sqrt()
+    }
+
+    /// 最大残差绝对值 (eV)
+    pub fn max_abs_residual(&self) -> f64 {
+        self.residuals.iter().map(|r| r.abs()).fold(0.0, f64::max)
+    }
+
+    /// 由目标压力 (GPa) 反解体积，要求压力位于 `[v_min, v_max]` 对应的区间内
+    pub fn volume_at_pressure(&self, pressure_gpa: f64, v_min: f64, v_max: f64) -> Option<f64> {
+        let target = pressure_gpa / EV_PER_A3_TO_GPA;
+        let f = |v: f64| self.form.pressure(&self.params, v) - target;
+
+        let (mut lo, mut hi) = (v_min, v_max);
+        let (mut f_lo, f_hi) = (f(lo), f(hi));
+        if !f_lo.is_finite() || !f_hi.is_finite() || f_lo * f_hi > 0.0 {
+            return None;
+        }
+
+        for _ in 0..200 {
+            let mid = 0.5 * (lo + hi);
+            let f_mid = f(mid);
+            if f_mid == 0.0 || (hi - lo) < 1e-10 * mid.abs().max(1.0) {
+                return Some(mid);
+            }
+            if f_lo * f_mid < 0.0 {
+                hi = mid;
+            } else {
+                lo = mid;
+                f_lo = f_mid;
+            }
+        }
+
+        Some(0.5 * (lo + hi))
+    }
+}
+
+/// 对 (V, E) 数据点拟合指定状态方程
+pub fn fit_eos(form: EosForm, points: &[(f64, f64)]) -> Result<EosFit> {
+    if points.len() < 4 {
+        return Err(QutilityError::InvalidArgument(format!(
+            "EOS fitting needs at least 4 E(V) points, got {}",
+            points.len()
+        )));
+    }
+
+    let initial = initial_guess(points)?;
+    let params = levenberg_marquardt(form, points, initial)?;
+    let residuals = points
+        .iter()
+        .map(|&(v, e)| form.energy(&params, v) - e)
+        .collect();
+
+    Ok(EosFit {
+        form,
+        params,
+        residuals,
+    })
+}
+
+/// 用抛物线拟合给出初值
+fn initial_guess(points: &[(f64, f64)]) -> Result<EosParams> {
+    // 最小二乘求解 E = c0 + c1 V + c2 V²
+    let mut ata = [[0.0; 3]; 3];
+    let mut atb = [0.0; 3];
+    for &(v, e) in points {
+        let row = [1.0, v, v * v];
+        for i in 0..3 {
+            for j in 0..3 {
+                ata[i][j] += row[i] * row[j];
+            }
+            atb[i] += row[i] * e;
+        }
+    }
+
+    let c = solve_linear::<3>(ata, atb).ok_or_else(|| {
+        QutilityError::Other("EOS initial guess failed: degenerate volumes".to_string())
+    })?;
+
+    if c[2] <= 0.0 {
+        return Err(QutilityError::Other(
+            "EOS data has no energy minimum (parabolic fit opens downward)".to_string(),
+        ));
+    }
+
+    let v0 = -c[1] / (2.0 * c[2]);
+    Ok(EosParams {
+        e0: c[0] + c[1] * v0 + c[2] * v0 * v0,
+        v0,
+        b0: 2.0 * c[2] * v0,
+        b0_prime: 4.0,
+    })
+}
+
+fn levenberg_marquardt(
+    form: EosForm,
+    points: &[(f64, f64)],
+    initial: EosParams,
+) -> Result<EosParams> {
+    let residuals = |p: &[f64; 4]| -> Vec<f64> {
+        let params = EosParams::from_array(*p);
+        points
+            .iter()
+            .map(|&(v, e)| form.energy(&params, v) - e)
+            .collect()
+    };
+    let cost = |r: &[f64]| r.iter().map(|x| x * x).sum::<f64>();
+
+    let mut p = initial.to_array();
+    let mut r = residuals(&p);
+    let mut current_cost = cost(&r);
+    let mut lambda = 1e-3;
+
+    for _ in 0..500 {
+        // 数值雅可比（中心差分）
+        let mut jac = vec![[0.0; 4]; points.len()];
+        for j in 0..4 {
+            let h = 1e-6 * p[j].abs().max(1e-3);
+            let mut plus = p;
+            let mut minus = p;
+            plus[j] += h;
+            minus[j] -= h;
+            let r_plus = residuals(&plus);
+            let r_minus = residuals(&minus);
+            for (row, (rp, rm)) in jac.iter_mut().zip(r_plus.iter().zip(&r_minus)) {
+                row[j] = (rp - rm) / (2.0 * h);
+            }
+        }
+
+        let mut jtj = [[0.0; 4]; 4];
+        let mut jtr = [0.0; 4];
+        for (row, ri) in jac.iter().zip(&r) {
+            for a in 0..4 {
+                for b in 0..4 {
+                    jtj[a][b] += row[a] * row[b];
+                }
+                jtr[a] += row[a] * ri;
+            }
+        }
+
+        let mut improved = false;
+        while lambda < 1e12 {
+            let mut damped = jtj;
+            for (k, row) in damped.iter_mut().enumerate() {
+                row[k] += lambda * jtj[k][k].max(1e-12);
+            }
+            let neg_jtr = jtr.map(|x| -x);
+
+            if let Some(delta) = solve_linear::<4>(damped, neg_jtr) {
+                let mut trial = p;
+                for k in 0..4 {
+                    trial[k] += delta[k];
+                }
+                let trial_r = residuals(&trial);
+                let trial_cost = cost(&trial_r);
+
+                if trial_cost.is_finite() && trial_cost < current_cost {
+                    let rel_change = (current_cost - trial_cost) / current_cost.max(1e-30);
+                    p = trial;
+                    r = trial_r;
+                    current_cost = trial_cost;
+                    lambda = (lambda * 0.3).max(1e-12);
+                    improved = true;
+
+                    if rel_change < 1e-14 {
+                        return finish(p);
+                    }
+                    break;
+                }
+            }
+            lambda *= 10.0;
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    finish(p)
+}
+
+fn finish(p: [f64; 4]) -> Result<EosParams> {
+    let params = EosParams::from_array(p);
+    if params.v0 <= 0.0 || params.b0 <= 0.0 || p.iter().any(|x| !x.is_finite()) {
+        return Err(QutilityError::Other(format!(
+            "EOS fit did not converge to a physical solution (V0 = {:.4}, B0 = {:.4})",
+            params.v0, params.b0
+        )));
+    }
+    Ok(params)
+}
+
+/// 高斯消元（部分主元）求解 N×N 线性方程组
+fn solve_linear<const N: usize>(mut a: [[f64; N]; N], mut b: [f64; N]) -> Option<[f64; N]> {
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in (col + 1)..N {
+            let factor = a[row][col] / a[col][col];
+            let (upper, lower) = a.split_at_mut(row);
+            for (x, pivot_x) in lower[0][col..].iter_mut().zip(&upper[col][col..]) {
+                *x -= factor * pivot_x;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let tail: f64 = ((row + 1)..N).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row][row];
+    }
+
+    x.iter().all(|v| v.is_finite()).then_some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_FORMS: [EosForm; 3] = [EosForm::BirchMurnaghan, EosForm::Vinet, EosForm::Murnaghan];
+
+    fn reference_params() -> EosParams {
+        EosParams {
+            e0: -10.0,
+            v0: 40.0,
+            b0: 100.0 / EV_PER_A3_TO_GPA,
+            b0_prime: 4.5,
+        }
+    }
+
+    fn synthetic_points(form: EosForm) -> Vec<(f64, f64)> {
+        let params = reference_params();
+        (0..9)
+            .map(|i| {
+                let v = 34.0 + i as f64 * 1.5;
+                (v, form.energy(&params, v))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_parameters_for_every_form() {
+        for form in ALL_FORMS {
+            let fit = fit_eos(form, &synthetic_points(form)).expect("fit");
+
+            assert!(
+                (fit.params.v0 - 40.0).abs() < 1e-4,
+                "{form}: {:?}",
+                fit.params
+            );
+            assert!((fit.params.b0_gpa() - 100.0).abs() < 1e-2, "{form}");
+            assert!((fit.params.b0_prime - 4.5).abs() < 1e-3, "{form}");
+            assert!(fit.rms_residual() < 1e-8, "{form}");
+        }
+    }
+
+    #[test]
+    fn pressure_is_negative_energy_derivative() {
+        let params = reference_params();
+        for form in ALL_FORMS {
+            let v = 37.0;
+            let h = 1e-5;
+            let numeric = -(form.energy(&params, v + h) - form.energy(&params, v - h)) / (2.0 * h);
+            assert!((form.pressure(&params, v) - numeric).abs() < 1e-7, "{form}");
+        }
+    }
+
+    #[test]
+    fn volume_at_pressure_inverts_pressure() {
+        let form = EosForm::BirchMurnaghan;
+        let fit = fit_eos(form, &synthetic_points(form)).expect("fit");
+
+        let v = fit.volume_at_pressure(10.0, 20.0, 60.0).expect("volume");
+        let p = fit.form.pressure(&fit.params, v) * EV_PER_A3_TO_GPA;
+
+        assert!((p - 10.0).abs() < 1e-6);
+        assert!(v < fit.params.v0);
+    }
+
+    #[test]
+    fn rejects_too_few_points() {
+        let points = vec![(1.0, 1.0), (2.0, 0.5), (3.0, 1.0)];
+        assert!(fit_eos(EosForm::Vinet, &points).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! # 按 k 点间距生成网格
+//!
+//! 由晶格的倒格矢长度与目标间距计算每个方向的 k 点数 N_i = ceil(|b_i| / Δk)，
+//! 间距可按 CASTEP `KPOINTS_MP_SPACING` 的 2π/Å 或 VASP `KSPACING` 的 1/Å 给出，可强制奇数或偶数网格；
+//! 生成 Monkhorst–Pack 或 Γ 中心的 VASP 自动 KPOINTS 文件。
+//!
+//! ## 依赖关系
+//! - 被 `commands/submit.rs` 用于为每个 VASP 作业写出 KPOINTS
+//! - 使用 `models/structure.rs` 的 `Lattice::reciprocal`
+
+use crate::models::Lattice;
+
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// k 点间距的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SpacingUnit {
+    /// 2π/Å，与 CASTEP `KPOINTS_MP_SPACING` 相同
+    #[serde(rename = "2pi/ang")]
+    TwoPiPerAngstrom,
+    /// 1/Å（倒格矢含 2π），与 VASP `KSPACING` 相同
+    #[serde(rename = "1/ang")]
+    PerAngstrom,
+}
+
+/// 网格中心
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MeshCentering {
+    Gamma,
+    MonkhorstPack,
+}
+
+/// 网格各方向 k 点数的奇偶约束
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MeshParity {
+    Any,
+    Odd,
+    Even,
+}
+
+/// 由间距生成 k 点网格的设置；序列化后连同网格写入作业元数据
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct KpointSpacing {
+    pub spacing: f64,
+    pub unit: SpacingUnit,
+    pub centering: MeshCentering,
+    pub parity: MeshParity,
+}
+
+impl KpointSpacing {
+    /// 每个倒格矢方向的 k 点数；至少为 1，强制偶数时至少为 2
+    pub fn mesh(&self, lattice: &Lattice) -> [u32; 3] {
+        // 换算为 1/Å（含 2π）下的间距
+        let delta = match self.unit {
+            SpacingUnit::TwoPiPerAngstrom => self.spacing * 2.0 * PI,
+            SpacingUnit::PerAngstrom => self.spacing,
+        };
+
+        lattice.reciprocal().map(|b| {
+            let length = (b[0] * b[0] + b[1] * b[1] + b[2] * b[2]).sqrt();
+            // 扣除舍入误差，避免 |b|/Δk 恰为整数时多加一个点
+            let n = ((length / delta) - 1e-8).ceil().max(1.0) as u32;
+            match self.parity {
+                MeshParity::Odd if n.is_multiple_of(2) => n + 1,
+                MeshParity::Even if !n.is_multiple_of(2) => n + 1,
+                _ => n,
+            }
+        })
+    }
+
+    /// VASP 自动网格 KPOINTS 文件
+    pub fn kpoints_file(&self, mesh: [u32; 3]) -> String {
+        let unit = match self.unit {
+            SpacingUnit::TwoPiPerAngstrom => "2pi/A",
+            SpacingUnit::PerAngstrom => "1/A",
+        };
+        let centering = match self.centering {
+            MeshCentering::Gamma => "Gamma",
+            MeshCentering::MonkhorstPack => "Monkhorst-Pack",
+        };
+        format!(
+            "k-point spacing {} {unit}\n0\n{centering}\n  {} {} {}\n  0 0 0\n",
+            self.spacing, mesh[0], mesh[1], mesh[2]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mesh_follows_reciprocal_lengths_units_and_parity() {
+        let mut settings = KpointSpacing {
+            spacing: 0.05,
+            unit: SpacingUnit::TwoPiPerAngstrom,
+            centering: MeshCentering::Gamma,
+            parity: MeshParity::Any,
+        };
+
+        // |b_i|/2π = 1/a：1/(5.43*0.05) = 3.68，1/(10.0*0.05) = 2 整除不进位，1/(30*0.05) < 1
+        let lattice = Lattice::from_parameters(5.43, 10.0, 30.0, 90.0, 90.0, 90.0);
+        assert_eq!(settings.mesh(&lattice), [4, 2, 1]);
+
+        // VASP KSPACING 0.5 1/Å：2π/5.43/0.5 = 2.31
+        settings.unit = SpacingUnit::PerAngstrom;
+        settings.spacing = 0.5;
+        assert_eq!(settings.mesh(&lattice), [3, 2, 1]);
+
+        settings.parity = MeshParity::Odd;
+        assert_eq!(settings.mesh(&lattice), [3, 3, 1]);
+        settings.parity = MeshParity::Even;
+        assert_eq!(settings.mesh(&lattice), [4, 2, 2]);
+
+        // 六方晶胞：|b1| = 2π / (a sin γ)
+        let hexagonal = Lattice::from_parameters(3.0, 3.0, 5.0, 90.0, 90.0, 120.0);
+        settings.parity = MeshParity::Any;
+        settings.spacing = 0.2;
+        assert_eq!(settings.mesh(&hexagonal), [13, 13, 7]);
+
+        settings.centering = MeshCentering::MonkhorstPack;
+        assert_eq!(
+            settings.kpoints_file([13, 13, 7]),
+            "k-point spacing 0.2 1/A\n0\nMonkhorst-Pack\n  13 13 7\n  0 0 0\n"
+        );
+    }
+}
@@ -1,11 +1,49 @@
 //! # DFT 共享领域模块
 //!
-//! 提供 VASP/CASTEP 作业扫描、状态分类与重算候选筛选能力。
+//! 提供 VASP/CASTEP 作业目录发现（递归布局、按目录识别代码与结构名模板）与扫描（含增量缓存）、状态分类（含可配置失败模式规则、调度器队列与日志细化）、重算候选筛选、自动纠错与续算准备、已结束作业中间文件清理、逐离子步收敛诊断、状态方程拟合、焓–压力相变分析、EDDP–DFT 一致性统计、作业墙钟时间/核时估算、作业压缩归档（含免解压读取）、按元素组装 VASP POTCAR 与按间距生成 k 点网格能力。
 //!
 //! ## 依赖关系
-//! - 被 `commands/analyze/` 与 `commands/collect.rs` 复用
-//! - 使用 `models/calculation.rs` 与 `parsers/`
+//! - 被 `commands/analyze/`、`commands/collect.rs`、`commands/clean.rs`、`commands/archive.rs`、`commands/report.rs` 与 `commands/submit.rs` 复用
+//! - 使用 `models/calculation.rs`、`parsers/` 与 `utils/scheduler.rs`；`usage.rs` 读取作业脚本与 `slurm_logs/`
+//! - `rules.rs` 通过 `include_str!` 内嵌 `failure_rules.toml`；`archive.rs` 使用 `tar`、`flate2` 与 `sha2`
 
+mod archive;
+mod cache;
+mod cleanup;
+mod convergence;
+mod correction;
+mod discovery;
+mod eos;
+mod kpoints;
+mod parity;
+mod potcar;
+mod queue;
+mod rules;
 mod scan;
+mod transitions;
+mod usage;
 
-pub use scan::{retry_candidates, scan_calculations, RetryScope};
+pub use archive::{
+    create_archives, extract_jobs, read_archived_records, ArchiveLayout, ArchivedJob, Manifest,
+};
+pub use cache::{ScanCache, CACHE_FILE};
+pub use cleanup::{CleanupCandidate, CleanupPolicy, CleanupTarget};
+pub use convergence::{
+    diagnose, energy_changes, load_trace, ConvergenceCriteria, ConvergenceDiagnosis,
+    ConvergenceIssue,
+};
+pub use correction::{apply_correction, plan_correction, CorrectionDecision, CorrectionHistory};
+pub use discovery::{CodeSelection, JobLayout, NameTemplate, DEFAULT_NAME_TEMPLATE};
+pub use eos::{fit_eos, EosFit, EosForm, EV_PER_A3_TO_GPA};
+pub use kpoints::{KpointSpacing, MeshCentering, MeshParity, SpacingUnit};
+pub use parity::{parity_stats, ParityPair, ParityStats};
+pub use potcar::{poscar_species, PotcarLibrary, PotcarPreset};
+pub use queue::{apply_scheduler_states, apply_slurm_log_states, find_queued_job};
+pub use rules::FailureRules;
+pub use scan::{
+    group_by_code, retry_candidates, scan_calculations, scan_calculations_with_rules, RetryScope,
+};
+pub use transitions::{
+    find_crossings, relative_enthalpies, stable_phase_transitions, EnthalpyCrossing, PhaseSeries,
+};
+pub use usage::{is_wasted, job_atoms, job_usage, size_bin, JobUsage};
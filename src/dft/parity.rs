@@ -0,0 +1,206 @@
+//! # EDDP–DFT 一致性统计
+//!
+//! 对按结构名配对的 EDDP/DFT 每原子焓计算误差、秩相关系数与 top-N 命中数。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/dft_postprocessing.rs` 与 `commands/report.rs` 使用；`report/html.rs` 使用其统计类型
+//! - 无外部模块依赖
+
+/// 一对 EDDP/DFT 每原子焓
+#[derive(Debug, Clone)]
+pub struct ParityPair {
+    /// 结构名称
+    pub structure_name: String,
+    /// EDDP 每原子焓 (eV/atom)
+    pub eddp_per_atom: f64,
+    /// DFT 每原子焓 (eV/atom)
+    pub dft_per_atom: f64,
+}
+
+/// 一致性统计结果
+#[derive(Debug, Clone)]
+pub struct ParityStats {
+    /// 配对数量
+    pub count: usize,
+    /// 平均绝对误差 (eV/atom)
+    pub mae: f64,
+    /// 均方根误差 (eV/atom)
+    pub rmse: f64,
+    /// 平均有符号误差 EDDP - DFT (eV/atom)
+    pub mean_error: f64,
+    /// Spearman 秩相关系数
+    pub spearman: f64,
+    /// Kendall tau-b 秩相关系数
+    pub kendall: f64,
+    /// 比较的 top-N 大小（不超过配对数量）
+    pub top_n: usize,
+    /// DFT top-N 中同时出现在 EDDP top-N 的结构数
+    pub top_n_hits: usize,
+}
+
+/// 计算一致性统计；配对数量不足 2 时返回 `None`
+pub fn parity_stats(pairs: &[ParityPair], top_n: usize) -> Option<ParityStats> {
+    let n = pairs.len();
+    if n < 2 {
+        return None;
+    }
+
+    let errors: Vec<f64> = pairs
+        .iter()
+        .map(|p| p.eddp_per_atom - p.dft_per_atom)
+        .collect();
+    let mae = errors.iter().map(|e| e.abs()).sum::<f64>() / n as f64;
+    let rmse = (errors.iter().map(|e| e * e).sum::<f64>() / n as f64).sqrt();
+    let mean_error = errors.iter().sum::<f64>() / n as f64;
+
+    let eddp: Vec<f64> = pairs.iter().map(|p| p.eddp_per_atom).collect();
+    let dft: Vec<f64> = pairs.iter().map(|p| p.dft_per_atom).collect();
+
+    let top_n = top_n.min(n);
+    let eddp_top = top_indices(&eddp, top_n);
+    let top_n_hits = top_indices(&dft, top_n)
+        .iter()
+        .filter(|idx| eddp_top.contains(idx))
+        .count();
+
+    Some(ParityStats {
+        count: n,
+        mae,
+        rmse,
+        mean_error,
+        spearman: pearson(&average_ranks(&eddp), &average_ranks(&dft)),
+        kendall: kendall_tau_b(&eddp, &dft),
+        top_n,
+        top_n_hits,
+    })
+}
+
+/// 计算平均秩（从 1 开始，并列取平均）
+pub fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &idx in &order[i..=j] {
+            ranks[idx] = rank;
+        }
+        i = j + 1;
+    }
+
+    ranks
+}
+
+fn top_indices(values: &[f64], n: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    order.truncate(n);
+    order
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        cov += (a - mean_x) * (b - mean_y);
+        var_x += (a - mean_x).powi(2);
+        var_y += (b - mean_y).powi(2);
+    }
+
+    if var_x == 0.0 || var_y == 0.0 {
+        return f64::NAN;
+    }
+
+    cov / (var_x * var_y).sqrt()
+}
+
+fn kendall_tau_b(x: &[f64], y: &[f64]) -> f64 {
+    let (mut concordant, mut discordant) = (0_i64, 0_i64);
+    let (mut ties_x, mut ties_y) = (0_i64, 0_i64);
+
+    for i in 0..x.len() {
+        for j in (i + 1)..x.len() {
+            let dx = x[i] - x[j];
+            let dy = y[i] - y[j];
+            if dx == 0.0 && dy == 0.0 {
+                continue;
+            } else if dx == 0.0 {
+                ties_x += 1;
+            } else if dy == 0.0 {
+                ties_y += 1;
+            } else if dx.signum() == dy.signum() {
+                concordant += 1;
+            } else {
+                discordant += 1;
+            }
+        }
+    }
+
+    let denom =
+        (((concordant + discordant + ties_x) * (concordant + discordant + ties_y)) as f64).sqrt();
+    if denom == 0.0 {
+        return f64::NAN;
+    }
+
+    (concordant - discordant) as f64 / denom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(data: &[(f64, f64)]) -> Vec<ParityPair> {
+        data.iter()
+            .enumerate()
+            .map(|(i, &(eddp, dft))| ParityPair {
+                structure_name: format!("s{i}"),
+                eddp_per_atom: eddp,
+                dft_per_atom: dft,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn perfect_ordering_gives_unit_correlations() {
+        let stats =
+            parity_stats(&pairs(&[(-1.0, -1.1), (-2.0, -2.1), (-3.0, -3.1)]), 2).expect("stats");
+
+        assert!((stats.mae - 0.1).abs() < 1e-12);
+        assert!((stats.rmse - 0.1).abs() < 1e-12);
+        assert!((stats.mean_error - 0.1).abs() < 1e-12);
+        assert!((stats.spearman - 1.0).abs() < 1e-12);
+        assert!((stats.kendall - 1.0).abs() < 1e-12);
+        assert_eq!(stats.top_n_hits, 2);
+    }
+
+    #[test]
+    fn reversed_ordering_gives_negative_correlations() {
+        let stats = parity_stats(&pairs(&[(1.0, 3.0), (2.0, 2.0), (3.0, 1.0)]), 1).expect("stats");
+
+        assert!((stats.spearman + 1.0).abs() < 1e-12);
+        assert!((stats.kendall + 1.0).abs() < 1e-12);
+        assert_eq!(stats.top_n_hits, 0);
+    }
+
+    #[test]
+    fn average_ranks_handles_ties() {
+        assert_eq!(
+            average_ranks(&[3.0, 1.0, 1.0, 2.0]),
+            vec![4.0, 1.5, 1.5, 3.0]
+        );
+    }
+
+    #[test]
+    fn needs_at_least_two_pairs() {
+        assert!(parity_stats(&pairs(&[(1.0, 1.0)]), 5).is_none());
+    }
+}
@@ -0,0 +1,378 @@
+//! # VASP POTCAR 组装
+//!
+//! 按 POSCAR 中的元素顺序，从 POTCAR 库目录拼接 `<库目录>/<元素><后缀>/POTCAR`。
+//! 每个元素的变体依次取自用户映射（如 `Fe=Fe_pv`）、预设（VASP 推荐集或元素名本身）；
+//! 载入时检查 TITEL 的元素（忽略 `_pv` 等后缀与赝氢 `H.75` 的价电子数）与 ZVAL，拼接时检查各元素的泛函一致，缺失的元素一次性全部列出。
+//!
+//! ## 依赖关系
+//! - 被 `commands/submit.rs` 用于生成 VASP 作业的 POTCAR
+//! - 使用 `models/structure.rs` 与 `error.rs`
+
+use crate::error::{QutilityError, Result};
+use crate::models::Crystal;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// VASP 推荐的 PAW 势（PBE.54）；未列出的元素使用元素名本身
+const RECOMMENDED_POTCARS: &[(&str, &str)] = &[
+    ("Li", "Li_sv"),
+    ("Na", "Na_pv"),
+    ("K", "K_sv"),
+    ("Ca", "Ca_sv"),
+    ("Sc", "Sc_sv"),
+    ("Ti", "Ti_sv"),
+    ("V", "V_sv"),
+    ("Cr", "Cr_pv"),
+    ("Mn", "Mn_pv"),
+    ("Ga", "Ga_d"),
+    ("Ge", "Ge_d"),
+    ("Rb", "Rb_sv"),
+    ("Sr", "Sr_sv"),
+    ("Y", "Y_sv"),
+    ("Zr", "Zr_sv"),
+    ("Nb", "Nb_sv"),
+    ("Mo", "Mo_sv"),
+    ("Tc", "Tc_pv"),
+    ("Ru", "Ru_pv"),
+    ("Rh", "Rh_pv"),
+    ("In", "In_d"),
+    ("Sn", "Sn_d"),
+    ("Cs", "Cs_sv"),
+    ("Ba", "Ba_sv"),
+    ("Pr", "Pr_3"),
+    ("Nd", "Nd_3"),
+    ("Pm", "Pm_3"),
+    ("Sm", "Sm_3"),
+    ("Eu", "Eu_2"),
+    ("Gd", "Gd_3"),
+    ("Tb", "Tb_3"),
+    ("Dy", "Dy_3"),
+    ("Ho", "Ho_3"),
+    ("Er", "Er_3"),
+    ("Tm", "Tm_3"),
+    ("Yb", "Yb_2"),
+    ("Lu", "Lu_3"),
+    ("Hf", "Hf_pv"),
+    ("Ta", "Ta_pv"),
+    ("W", "W_sv"),
+    ("Tl", "Tl_d"),
+    ("Pb", "Pb_d"),
+    ("Bi", "Bi_d"),
+    ("Po", "Po_d"),
+    ("Fr", "Fr_sv"),
+    ("Ra", "Ra_sv"),
+];
+
+/// 未被映射覆盖的元素使用的 POTCAR 变体
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PotcarPreset {
+    /// VASP 推荐集（如 Ti→Ti_sv、Ga→Ga_d）
+    Recommended,
+    /// 元素名本身
+    Plain,
+}
+
+/// 已载入并通过检查的单元素 POTCAR
+#[derive(Debug, Clone)]
+pub struct PotcarEntry {
+    /// 库中的目录名，如 "Fe_pv"
+    pub variant: String,
+    /// TITEL 行的值，如 "PAW_PBE Fe_pv 02Aug2007"
+    pub titel: String,
+    /// 价电子数
+    pub zval: f64,
+    content: String,
+}
+
+impl PotcarEntry {
+    /// TITEL 中的势类型与泛函，如 "PAW_PBE"
+    pub fn functional(&self) -> &str {
+        self.titel.split_whitespace().next().unwrap_or("")
+    }
+}
+
+/// POTCAR 库：变体选择规则与已载入的势
+pub struct PotcarLibrary {
+    dir: PathBuf,
+    mapping: BTreeMap<String, String>,
+    preset: PotcarPreset,
+    loaded: BTreeMap<String, PotcarEntry>,
+}
+
+impl PotcarLibrary {
+    /// `mapping` 的值为完整目录名（`Fe_pv`）或以 `_` 开头的后缀（`_pv`）
+    pub fn new(dir: &Path, mapping: BTreeMap<String, String>, preset: PotcarPreset) -> Self {
+        PotcarLibrary {
+            dir: dir.to_path_buf(),
+            mapping,
+            preset,
+            loaded: BTreeMap::new(),
+        }
+    }
+
+    /// 元素对应的库目录名
+    pub fn variant(&self, element: &str) -> String {
+        if let Some(mapped) = self.mapping.get(element) {
+            return if mapped.starts_with('_') {
+                format!("{element}{mapped}")
+            } else {
+                mapped.clone()
+            };
+        }
+        match self.preset {
+            PotcarPreset::Recommended => RECOMMENDED_POTCARS
+                .iter()
+                .find(|(el, _)| *el == element)
+                .map_or(element, |(_, variant)| variant)
+                .to_string(),
+            PotcarPreset::Plain => element.to_string(),
+        }
+    }
+
+    /// 载入并检查各元素的 POTCAR；缺失或无效的元素全部列出后报错
+    pub fn load(&mut self, elements: &[String]) -> Result<()> {
+        let mut problems = Vec::new();
+        for element in elements {
+            if self.loaded.contains_key(element) {
+                continue;
+            }
+            let variant = self.variant(element);
+            let path = self.dir.join(&variant).join("POTCAR");
+            match read_potcar(&path, element, &variant) {
+                Ok(entry) => {
+                    self.loaded.insert(element.clone(), entry);
+                }
+                Err(reason) => problems.push(format!("{element} ({}: {reason})", path.display())),
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(QutilityError::InvalidArgument(format!(
+                "Cannot build POTCAR from {}: {}",
+                self.dir.display(),
+                problems.join("; ")
+            )))
+        }
+    }
+
+    /// 已载入的元素 POTCAR
+    pub fn entry(&self, element: &str) -> Option<&PotcarEntry> {
+        self.loaded.get(element)
+    }
+
+    /// 按 `species` 的顺序拼接 POTCAR；要求所有势的类型与泛函一致
+    pub fn assemble(&mut self, species: &[String]) -> Result<String> {
+        self.load(species)?;
+
+        let entries: Vec<&PotcarEntry> = species.iter().map(|el| &self.loaded[el]).collect();
+        if let Some(first) = entries.first() {
+            if let Some(other) = entries
+                .iter()
+                .find(|entry| entry.functional() != first.functional())
+            {
+                return Err(QutilityError::InvalidArgument(format!(
+                    "POTCARs mix functionals: '{}' and '{}'",
+                    first.titel, other.titel
+                )));
+            }
+        }
+
+        Ok(entries.iter().map(|entry| entry.content.as_str()).collect())
+    }
+}
+
+/// POSCAR 中各元素组的顺序（与物种行一致，重复出现的元素分别保留）
+pub fn poscar_species(crystal: &Crystal) -> Result<Vec<String>> {
+    let mut species: Vec<String> = Vec::new();
+    for atom in &crystal.atoms {
+        if species.last() != Some(&atom.element) {
+            species.push(atom.element.clone());
+        }
+    }
+
+    // VASP 4 格式没有元素行，解析器以 X1、X2… 占位，含数字即被拒绝
+    let symbol = |s: &String| {
+        s.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+            && s.chars().all(|c| c.is_ascii_alphabetic())
+    };
+    if let Some(bad) = species.iter().find(|s| !symbol(s)) {
+        return Err(QutilityError::ParseError {
+            format: "poscar".to_string(),
+            path: crystal.name.clone(),
+            reason: format!(
+                "'{bad}' is not an element symbol; POTCAR assembly needs the VASP 5 species line"
+            ),
+        });
+    }
+    Ok(species)
+}
+
+/// 读取单元素 POTCAR，检查 TITEL 的元素与 ZVAL
+fn read_potcar(
+    path: &Path,
+    element: &str,
+    variant: &str,
+) -> std::result::Result<PotcarEntry, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let titels: Vec<&str> = content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("TITEL"))
+        .filter_map(|rest| rest.trim_start().strip_prefix('='))
+        .map(str::trim)
+        .collect();
+    let titel = match titels.as_slice() {
+        [titel] => titel.to_string(),
+        [] => return Err("no TITEL line".to_string()),
+        _ => {
+            return Err(format!(
+                "contains {} potentials, expected one",
+                titels.len()
+            ))
+        }
+    };
+    // 变体名去掉 `_` 后缀与赝氢的价电子数（`H.75`、`H1.25`）即为元素
+    let symbol = titel.split_whitespace().nth(1).unwrap_or("");
+    let symbol_element = symbol
+        .split('_')
+        .next()
+        .unwrap_or("")
+        .trim_end_matches(|c: char| !c.is_ascii_alphabetic());
+    if symbol_element != element {
+        return Err(format!("TITEL '{titel}' is not a {element} potential"));
+    }
+
+    let zval = content
+        .lines()
+        .find_map(|line| line.split_once("ZVAL"))
+        .and_then(|(_, rest)| rest.trim_start().strip_prefix('='))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.trim_end_matches(';').parse::<f64>().ok())
+        .filter(|zval| *zval > 0.0)
+        .ok_or_else(|| "missing or invalid ZVAL".to_string())?;
+
+    let mut content = content;
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    Ok(PotcarEntry {
+        variant: variant.to_string(),
+        titel,
+        zval,
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Atom, Lattice};
+    use crate::utils::test_support::unique_test_dir;
+
+    fn write_potcar(dir: &Path, variant: &str, functional: &str, zval: f64) {
+        fs::create_dir_all(dir.join(variant)).expect("create potcar dir");
+        fs::write(
+            dir.join(variant).join("POTCAR"),
+            format!(
+                "  {functional} {variant} 06Sep2000\n {zval}\n parameters from PSCTR are:\n   \
+                 TITEL  = {functional} {variant} 06Sep2000\n   \
+                 POMASS =   1.000; ZVAL   =    {zval:.3}    mass and valenz\n End of Dataset\n"
+            ),
+        )
+        .expect("write POTCAR");
+    }
+
+    #[test]
+    fn assembles_potcar_in_poscar_order_with_mapping() {
+        let dir = unique_test_dir("potcar-library");
+        write_potcar(&dir, "Fe_pv", "PAW_PBE", 14.0);
+        write_potcar(&dir, "O", "PAW_PBE", 6.0);
+        write_potcar(&dir, "Ti_sv", "PAW_PBE", 12.0);
+        write_potcar(&dir, "O_h", "PAW_LDA", 6.0);
+
+        let crystal = Crystal::new(
+            "Fe2TiO4",
+            Lattice::from_parameters(5.0, 5.0, 5.0, 90.0, 90.0, 90.0),
+            ["O", "O", "Fe", "Ti", "O"]
+                .iter()
+                .map(|el| Atom::new(*el, [0.0; 3]))
+                .collect(),
+        );
+        let species = poscar_species(&crystal).expect("species");
+        assert_eq!(species, vec!["O", "Fe", "Ti", "O"]);
+
+        let mapping = BTreeMap::from([("Fe".to_string(), "_pv".to_string())]);
+        let mut library = PotcarLibrary::new(&dir, mapping, PotcarPreset::Recommended);
+        assert_eq!(library.variant("Ti"), "Ti_sv");
+        let potcar = library.assemble(&species).expect("assemble");
+        let titels: Vec<&str> = potcar
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("TITEL  = "))
+            .collect();
+        assert_eq!(
+            titels,
+            vec![
+                "PAW_PBE O 06Sep2000",
+                "PAW_PBE Fe_pv 06Sep2000",
+                "PAW_PBE Ti_sv 06Sep2000",
+                "PAW_PBE O 06Sep2000"
+            ]
+        );
+        assert_eq!(library.entry("Fe").map(|entry| entry.zval), Some(14.0));
+
+        let mut plain = PotcarLibrary::new(&dir, BTreeMap::new(), PotcarPreset::Plain);
+        let err = plain
+            .load(&["Ti".to_string(), "Fe".to_string(), "O".to_string()])
+            .expect_err("Ti and Fe are missing");
+        let message = err.to_string();
+        assert!(message.contains("Ti ("));
+        assert!(message.contains("Fe ("));
+        assert!(!message.contains("O ("));
+
+        let mapping = BTreeMap::from([
+            ("Fe".to_string(), "Fe_pv".to_string()),
+            ("O".to_string(), "O_h".to_string()),
+        ]);
+        let mut mixed = PotcarLibrary::new(&dir, mapping, PotcarPreset::Plain);
+        assert!(mixed
+            .assemble(&["Fe".to_string(), "O".to_string()])
+            .expect_err("mixed functionals")
+            .to_string()
+            .contains("mix functionals"));
+
+        write_potcar(&dir, "H.75", "PAW_PBE", 0.75);
+        write_potcar(&dir, "H1.25", "PAW_PBE", 1.25);
+        let mapping = BTreeMap::from([("H".to_string(), "H.75".to_string())]);
+        let mut pseudo = PotcarLibrary::new(&dir, mapping, PotcarPreset::Plain);
+        pseudo
+            .load(&["H".to_string()])
+            .expect("H.75 is a H potential");
+        assert_eq!(pseudo.entry("H").map(|entry| entry.zval), Some(0.75));
+        let mapping = BTreeMap::from([("H".to_string(), "H1.25".to_string())]);
+        let mut pseudo = PotcarLibrary::new(&dir, mapping, PotcarPreset::Plain);
+        pseudo
+            .load(&["H".to_string()])
+            .expect("H1.25 is a H potential");
+
+        let vasp4 = Crystal::new(
+            "vasp4",
+            Lattice::from_parameters(5.0, 5.0, 5.0, 90.0, 90.0, 90.0),
+            vec![Atom::new("X1", [0.0; 3])],
+        );
+        assert!(poscar_species(&vasp4).is_err());
+
+        let mapping = BTreeMap::from([("Ti".to_string(), "Fe_pv".to_string())]);
+        let mut wrong = PotcarLibrary::new(&dir, mapping, PotcarPreset::Plain);
+        assert!(wrong
+            .load(&["Ti".to_string()])
+            .expect_err("wrong element")
+            .to_string()
+            .contains("is not a Ti potential"));
+
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
+}
@@ -0,0 +1,367 @@
+//! # 调度器状态细化
+//!
+//! 将扫描得到的未完成/缺输出作业与调度器队列记录及当前提交最新的 `slurm_logs/*.err` 对照，
+//! 细化为运行中、排队、超时、节点故障、内存不足等状态。Slurm 数组作业的任务通过作业目录中记录的 `<作业ID>_<任务号>`
+//! 对应回结构，排队中合并显示的任务范围（`123_[5-500%20]`）也能匹配；数组任务的超时、内存不足等消息
+//! 从作业根目录下同名的 `slurm_logs/<作业ID>_<任务号>.err` 读取。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/dft_status.rs` 使用；`commands/clean.rs` 用它跳过仍在队列中的作业
+//! - 使用 `models/calculation.rs` 与 `utils/scheduler.rs`（各调度器的状态已归一化为 Slurm 状态名）
+
+use crate::models::{CalculationScanRecord, CalculationStatus};
+use crate::utils::scheduler::{array_task_log, job_logs, SchedulerJob, JOB_ID_FILE};
+
+use std::fs;
+use std::path::Path;
+
+/// 按作业 ID（优先读取作业目录中记录的 ID，数组任务记为 `123_4`）或作业名匹配队列记录并细化状态。
+///
+/// 实时队列（如 `squeue`）的状态优先于历史记录（如 `sacct`）；同名作业取 ID 最大者。
+pub fn apply_scheduler_states(
+    records: &mut [CalculationScanRecord],
+    queued: &[SchedulerJob],
+    history: &[SchedulerJob],
+) {
+    for record in records.iter_mut().filter(|r| is_refinable(r.status)) {
+        let recorded_id = read_recorded_job_id(&record.job_dir);
+        let job = find_job(queued, recorded_id.as_deref(), &record.seed)
+            .or_else(|| find_job(history, recorded_id.as_deref(), &record.seed));
+
+        let Some(job) = job else {
+            record.job_id = recorded_id;
+            continue;
+        };
+
+        // 合并显示的排队数组任务保留记录的任务 ID
+        record.job_id = match recorded_id {
+            Some(id) if array_range_contains(&job.id, &id) => Some(id),
+            _ => Some(job.id.clone()),
+        };
+        if let Some(status) = status_from_slurm_state(&job.state) {
+            record.status = status;
+            record.reason = match status {
+                CalculationStatus::Running | CalculationStatus::Pending => None,
+                _ => Some(format!("Job {} ended with state {}", job.id, job.state)),
+            };
+        }
+    }
+}
+
+/// 查找与作业目录对应、仍在调度器队列中排队或运行的作业（与状态无关，用于保护正在使用的目录）
+pub fn find_queued_job<'a>(
+    record: &CalculationScanRecord,
+    queued: &'a [SchedulerJob],
+) -> Option<&'a SchedulerJob> {
+    let recorded_id = read_recorded_job_id(&record.job_dir);
+    find_job(queued, recorded_id.as_deref(), &record.seed)
+}
+
+/// 按作业最新的 `slurm_logs/*.err` 中的超时、节点故障与 OOM 信息细化状态。
+///
+/// 早于本次提交的日志不计（见 `job_logs`）；仍在 `queued` 中排队或运行的作业不细化
+pub fn apply_slurm_log_states(records: &mut [CalculationScanRecord], queued: &[SchedulerJob]) {
+    for record in records.iter_mut().filter(|r| is_refinable(r.status)) {
+        if find_queued_job(record, queued).is_some() {
+            continue;
+        }
+        if let Some((status, reason)) = inspect_slurm_logs(&record.job_dir) {
+            record.status = status;
+            record.reason = Some(reason);
+        }
+    }
+}
+
+/// 仅细化无法从输出文件判断结局的作业
+fn is_refinable(status: CalculationStatus) -> bool {
+    matches!(
+        status,
+        CalculationStatus::Incomplete | CalculationStatus::MissingOutput
+    )
+}
+
+fn read_recorded_job_id(job_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(job_dir.join(JOB_ID_FILE)).ok()?;
+    let id = content.trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+fn find_job<'a>(
+    jobs: &'a [SchedulerJob],
+    recorded_id: Option<&str>,
+    name: &str,
+) -> Option<&'a SchedulerJob> {
+    if let Some(id) = recorded_id {
+        if let Some(job) = jobs.iter().find(|job| job.id == id) {
+            return Some(job);
+        }
+        if let Some(job) = jobs.iter().find(|job| array_range_contains(&job.id, id)) {
+            return Some(job);
+        }
+    }
+
+    jobs.iter()
+        .filter(|job| job.name == name)
+        .max_by_key(|job| job_id_sort_key(&job.id))
+}
+
+/// 排队中的数组任务合并为一行（`123_[5-500%20]`、`123_[1,3,7-9]`），判断记录的 `123_6` 是否在其中
+fn array_range_contains(job_id: &str, recorded_id: &str) -> bool {
+    let Some((main, spec)) = job_id.split_once('_') else {
+        return false;
+    };
+    let Some(spec) = spec.strip_prefix('[').and_then(|s| s.strip_suffix(']')) else {
+        return false;
+    };
+    let Some(task) = recorded_id
+        .strip_prefix(main)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .and_then(|task| task.parse::<u64>().ok())
+    else {
+        return false;
+    };
+
+    let spec = spec.split('%').next().unwrap_or(spec);
+    spec.split(',').any(|part| match part.split_once('-') {
+        Some((lo, hi)) => match (
+            lo.parse::<u64>(),
+            hi.split(':').next().unwrap_or(hi).parse::<u64>(),
+        ) {
+            (Ok(lo), Ok(hi)) => (lo..=hi).contains(&task),
+            _ => false,
+        },
+        None => part.parse::<u64>() == Ok(task),
+    })
+}
+
+/// 数组作业 `123_4` 按 (123, 4) 排序
+fn job_id_sort_key(id: &str) -> (u64, u64) {
+    let mut parts = id.splitn(2, '_');
+    let main = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    let task = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    (main, task)
+}
+
+/// Slurm 状态到作业状态的映射；`COMPLETED` 等不改变扫描结论的状态返回 None
+fn status_from_slurm_state(state: &str) -> Option<CalculationStatus> {
+    let status = match state {
+        "PENDING" | "REQUEUED" | "REQUEUE_HOLD" | "REQUEUE_FED" | "RESV_DEL_HOLD" => {
+            CalculationStatus::Pending
+        }
+        "RUNNING" | "CONFIGURING" | "COMPLETING" | "SUSPENDED" | "STAGE_OUT" | "RESIZING"
+        | "SIGNALING" => CalculationStatus::Running,
+        "TIMEOUT" => CalculationStatus::TimedOut,
+        "NODE_FAIL" => CalculationStatus::NodeFail,
+        "OUT_OF_MEMORY" => CalculationStatus::OutOfMemory,
+        "FAILED" | "CANCELLED" | "BOOT_FAIL" | "DEADLINE" | "PREEMPTED" => {
+            CalculationStatus::Failed
+        }
+        _ => return None,
+    };
+
+    Some(status)
+}
+
+/// 检查数组任务在作业根目录的调度器日志，以及作业目录中最新的 .err 日志
+fn inspect_slurm_logs(job_dir: &Path) -> Option<(CalculationStatus, String)> {
+    let mut logs = array_task_log(job_dir, "err")
+        .into_iter()
+        .chain(job_logs(job_dir, "err").into_iter().take(1));
+
+    logs.find_map(|path| {
+        let content = fs::read_to_string(&path).ok()?;
+        content.lines().find_map(|line| {
+            let status = slurm_log_status(&line.to_ascii_lowercase())?;
+            let shown = path.strip_prefix(job_dir).unwrap_or(&path);
+            Some((status, format!("{}: {}", shown.display(), line.trim())))
+        })
+    })
+}
+
+fn slurm_log_status(line: &str) -> Option<CalculationStatus> {
+    if line.contains("due to time limit") {
+        Some(CalculationStatus::TimedOut)
+    } else if line.contains("due to node failure") {
+        Some(CalculationStatus::NodeFail)
+    } else if line.contains("oom-kill")
+        || line.contains("oom_kill")
+        || line.contains("out of memory")
+        || line.contains("out-of-memory")
+    {
+        Some(CalculationStatus::OutOfMemory)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DftCodeType;
+    use crate::utils::test_support::unique_test_dir;
+    use std::time::{Duration, SystemTime};
+
+    fn job(id: &str, name: &str, state: &str) -> SchedulerJob {
+        SchedulerJob {
+            id: id.to_string(),
+            name: name.to_string(),
+            state: state.to_string(),
+        }
+    }
+
+    fn record(root: &Path, name: &str, status: CalculationStatus) -> CalculationScanRecord {
+        CalculationScanRecord::new(name, root.join(name), DftCodeType::Vasp, status)
+    }
+
+    #[test]
+    fn matches_by_recorded_id_then_name() {
+        let root = unique_test_dir("dft-queue-match");
+        fs::create_dir_all(root.join("alpha")).expect("create alpha");
+        fs::write(root.join("alpha").join(JOB_ID_FILE), "200\n").expect("write job id");
+
+        let mut records = vec![
+            record(&root, "alpha", CalculationStatus::Incomplete),
+            record(&root, "beta", CalculationStatus::MissingOutput),
+            record(&root, "gamma", CalculationStatus::Incomplete),
+            record(&root, "delta", CalculationStatus::Completed),
+        ];
+        let squeue = vec![job("301", "beta", "PENDING")];
+        let sacct = vec![
+            job("100", "alpha", "RUNNING"),
+            job("200", "renamed", "TIMEOUT"),
+            job("150", "gamma", "OUT_OF_MEMORY"),
+            job("250", "gamma", "NODE_FAIL"),
+            job("260", "delta", "TIMEOUT"),
+        ];
+
+        apply_scheduler_states(&mut records, &squeue, &sacct);
+
+        assert_eq!(records[0].status, CalculationStatus::TimedOut);
+        assert_eq!(records[0].job_id.as_deref(), Some("200"));
+        assert_eq!(records[1].status, CalculationStatus::Pending);
+        assert_eq!(records[2].status, CalculationStatus::NodeFail);
+        assert_eq!(records[2].job_id.as_deref(), Some("250"));
+        assert_eq!(records[3].status, CalculationStatus::Completed);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn maps_array_task_ids_back_to_job_directories() {
+        let root = unique_test_dir("dft-queue-array");
+        for (name, id) in [("alpha", "500_1"), ("beta", "500_2"), ("gamma", "500_7")] {
+            fs::create_dir_all(root.join(name)).expect("create job dir");
+            fs::write(root.join(name).join(JOB_ID_FILE), format!("{id}\n")).expect("write id");
+        }
+
+        let mut records = vec![
+            record(&root, "alpha", CalculationStatus::Incomplete),
+            record(&root, "beta", CalculationStatus::MissingOutput),
+            record(&root, "gamma", CalculationStatus::MissingOutput),
+        ];
+        let squeue = vec![
+            job("500_1", "jobs", "RUNNING"),
+            job("500_[3-9%2]", "jobs", "PENDING"),
+        ];
+        let sacct = vec![job("500_2", "jobs", "TIMEOUT")];
+
+        apply_scheduler_states(&mut records, &squeue, &sacct);
+
+        assert_eq!(records[0].status, CalculationStatus::Running);
+        assert_eq!(records[1].status, CalculationStatus::TimedOut);
+        assert_eq!(records[1].job_id.as_deref(), Some("500_2"));
+        assert_eq!(records[2].status, CalculationStatus::Pending);
+        assert_eq!(records[2].job_id.as_deref(), Some("500_7"));
+        assert!(!array_range_contains("500_[3-9%2]", "500_10"));
+        assert!(array_range_contains("500_[1,4-5]", "500_4"));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn reads_terminal_states_from_err_logs() {
+        let root = unique_test_dir("dft-queue-logs");
+        for (name, line) in [
+            ("alpha", "slurmstepd: error: *** JOB 12 ON n1 CANCELLED AT 2024-01-01T00:00:00 DUE TO TIME LIMIT ***"),
+            ("beta", "slurmstepd: error: Detected 1 oom-kill event(s) in StepId=13.batch."),
+            ("gamma", "forrtl: warning"),
+            ("delta", "slurmstepd: error: *** JOB 14 ON n1 CANCELLED AT 2024-01-01T00:00:00 DUE TO TIME LIMIT ***"),
+            ("epsilon", "slurmstepd: error: Detected 1 oom-kill event(s) in StepId=15.batch."),
+        ] {
+            let log_dir = root.join(name).join("slurm_logs");
+            fs::create_dir_all(&log_dir).expect("create log dir");
+            fs::write(log_dir.join(format!("{name}.err")), format!("{line}\n")).expect("write log");
+        }
+
+        let set_age = |path: &Path, hours: u64| {
+            let time = SystemTime::now() - Duration::from_secs(hours * 3600);
+            fs::File::options()
+                .append(true)
+                .open(path)
+                .and_then(|file| file.set_modified(time))
+                .expect("set mtime");
+        };
+        // alpha 较旧的日志来自上一次提交，只看最新的日志
+        let old_log = root.join("alpha").join("slurm_logs").join("old.err");
+        fs::write(
+            &old_log,
+            "slurmstepd: error: Detected 1 oom-kill event(s)\n",
+        )
+        .expect("write");
+        set_age(&old_log, 2);
+        // delta 重新提交后尚未运行：日志早于作业 ID 文件
+        set_age(&root.join("delta").join("slurm_logs").join("delta.err"), 1);
+        fs::write(root.join("delta").join(JOB_ID_FILE), "20\n").expect("write job id");
+
+        let mut records = vec![
+            record(&root, "alpha", CalculationStatus::Incomplete),
+            record(&root, "beta", CalculationStatus::Incomplete),
+            record(&root, "gamma", CalculationStatus::Incomplete),
+            record(&root, "delta", CalculationStatus::Incomplete),
+            record(&root, "epsilon", CalculationStatus::Incomplete),
+        ];
+
+        // epsilon 仍在队列中
+        apply_slurm_log_states(&mut records, &[job("21", "epsilon", "PENDING")]);
+
+        assert_eq!(records[0].status, CalculationStatus::TimedOut);
+        assert_eq!(records[1].status, CalculationStatus::OutOfMemory);
+        assert_eq!(records[2].status, CalculationStatus::Incomplete);
+        assert_eq!(records[3].status, CalculationStatus::Incomplete);
+        assert_eq!(records[4].status, CalculationStatus::Incomplete);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn reads_array_task_messages_from_the_job_root() {
+        let root = unique_test_dir("dft-queue-array-logs");
+        for (name, id) in [("alpha", "500_1"), ("beta", "500_2")] {
+            let log_dir = root.join(name).join("slurm_logs");
+            fs::create_dir_all(&log_dir).expect("create log dir");
+            fs::write(log_dir.join(format!("{name}.err")), "").expect("write task log");
+            fs::write(root.join(name).join(JOB_ID_FILE), format!("{id}\n")).expect("write id");
+        }
+        fs::create_dir_all(root.join("slurm_logs")).expect("create array log dir");
+        fs::write(
+            root.join("slurm_logs").join("500_1.err"),
+            "slurmstepd: error: *** JOB 501 ON n1 CANCELLED AT 2024-01-01T00:00:00 DUE TO TIME LIMIT ***\n",
+        )
+        .expect("write array log");
+
+        let mut records = vec![
+            record(&root, "alpha", CalculationStatus::Incomplete),
+            record(&root, "beta", CalculationStatus::Incomplete),
+        ];
+        apply_slurm_log_states(&mut records, &[]);
+
+        assert_eq!(records[0].status, CalculationStatus::TimedOut);
+        assert!(records[0]
+            .reason
+            .as_deref()
+            .is_some_and(|reason| reason.contains("500_1.err")));
+        assert_eq!(records[1].status, CalculationStatus::Incomplete);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
@@ -0,0 +1,438 @@
+//! # 失败模式规则
+//!
+//! 以 TOML 描述 DFT 作业失败/警告模式（子串或正则、适用代码、搜索文件、严重程度、
+//! 原因与修复建议标签）。内置规则见 `failure_rules.toml`，可由用户配置与项目配置追加或覆盖。
+//!
+//! ## 依赖关系
+//! - 被 `dft/scan.rs` 用于判定失败原因，经 `dft/mod.rs` 导出给命令层
+//! - 使用 `models/calculation.rs`、`utils/config.rs`（用户配置目录）与 `error.rs`
+
+use crate::error::{QutilityError, Result};
+use crate::models::DftCodeType;
+use crate::utils::config::user_config_dir;
+
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// 内置规则
+const BUILTIN_RULES: &str = include_str!("failure_rules.toml");
+
+/// 项目级规则文件名（位于作业根目录）
+pub const PROJECT_RULES_FILE: &str = "qutility_rules.toml";
+
+/// 用户级规则文件名（位于 `~/.config/qutility/`）
+const USER_RULES_FILE: &str = "failure_rules.toml";
+
+/// 规则适用的代码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleCode {
+    Vasp,
+    Castep,
+    Any,
+}
+
+impl RuleCode {
+    fn applies_to(self, code: DftCodeType) -> bool {
+        matches!(
+            (self, code),
+            (RuleCode::Any, _)
+                | (RuleCode::Vasp, DftCodeType::Vasp)
+                | (RuleCode::Castep, DftCodeType::Castep)
+        )
+    }
+}
+
+/// 规则搜索的文件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleTarget {
+    /// VASP `OUTCAR`
+    Outcar,
+    /// 程序标准输出（`slurm_logs/*.out`、`vasp.out`、`stdout` 等）
+    Stdout,
+    /// 标准错误与 CASTEP 错误文件（`slurm_logs/*.err`、`*.err`）
+    Err,
+    /// CASTEP `<seed>.castep`
+    Castep,
+}
+
+/// 规则严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// 未完成作业据此判为失败
+    Error,
+    /// 仅记录原因，不改变状态
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// 小写子串，匹配时不区分大小写
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn is_match(&self, line: &str, lowercase_line: &str) -> bool {
+        match self {
+            Matcher::Substring(pattern) => lowercase_line.contains(pattern.as_str()),
+            Matcher::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// 单条失败模式规则
+#[derive(Debug, Clone)]
+pub struct FailureRule {
+    pub id: String,
+    pub code: RuleCode,
+    pub files: Vec<RuleTarget>,
+    pub severity: Severity,
+    pub reason: String,
+    pub fix: Option<String>,
+    matcher: Matcher,
+}
+
+impl FailureRule {
+    pub fn is_match(&self, line: &str, lowercase_line: &str) -> bool {
+        self.matcher.is_match(line, lowercase_line)
+    }
+}
+
+/// 命中的规则
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub reason: String,
+    pub fix: Option<String>,
+}
+
+impl From<&FailureRule> for RuleMatch {
+    fn from(rule: &FailureRule) -> Self {
+        Self {
+            rule_id: rule.id.clone(),
+            severity: rule.severity,
+            reason: rule.reason.clone(),
+            fix: rule.fix.clone(),
+        }
+    }
+}
+
+/// 有序规则集；靠前的规则优先
+#[derive(Debug, Clone)]
+pub struct FailureRules {
+    rules: Vec<FailureRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    /// 为 true 时丢弃优先级更低的规则（含内置规则）
+    #[serde(default)]
+    replace_defaults: bool,
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleSpec {
+    id: Option<String>,
+    code: RuleCode,
+    pattern: Option<String>,
+    regex: Option<String>,
+    files: Vec<RuleTarget>,
+    #[serde(default = "default_severity")]
+    severity: Severity,
+    reason: String,
+    fix: Option<String>,
+}
+
+fn default_severity() -> Severity {
+    Severity::Error
+}
+
+impl FailureRules {
+    /// 内置规则
+    pub fn builtin() -> Self {
+        let (rules, _) =
+            parse_rules(BUILTIN_RULES, "built-in rules").expect("built-in failure rules are valid");
+        Self { rules }
+    }
+
+    /// 依次合并内置规则、用户配置、项目配置（`<job_root>/qutility_rules.toml`）与显式指定的规则文件；
+    /// 越后加载的规则优先级越高，同 id 规则被覆盖
+    pub fn discover(job_root: &Path, explicit: Option<&Path>) -> Result<Self> {
+        let mut merged = Self::builtin();
+
+        let candidates = [
+            user_config_dir().map(|dir| dir.join(USER_RULES_FILE)),
+            Some(job_root.join(PROJECT_RULES_FILE)),
+        ];
+        for path in candidates.into_iter().flatten().filter(|p| p.is_file()) {
+            merged = merged.with_file(&path)?;
+        }
+
+        if let Some(path) = explicit {
+            if !path.is_file() {
+                return Err(QutilityError::FileNotFound {
+                    path: path.display().to_string(),
+                });
+            }
+            merged = merged.with_file(path)?;
+        }
+
+        Ok(merged)
+    }
+
+    /// 加载规则文件，其规则优先于当前规则
+    pub fn with_file(self, path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| QutilityError::FileReadError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        self.with_toml(&content, &path.display().to_string())
+    }
+
+    fn with_toml(self, content: &str, source: &str) -> Result<Self> {
+        let (mut rules, replace_defaults) = parse_rules(content, source)?;
+
+        if !replace_defaults {
+            let inherited: Vec<FailureRule> = self
+                .rules
+                .into_iter()
+                .filter(|old| !rules.iter().any(|new| new.id == old.id))
+                .collect();
+            rules.extend(inherited);
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// 适用于指定代码与文件类别的规则（按优先级排序）
+    pub fn for_target(
+        &self,
+        code: DftCodeType,
+        target: RuleTarget,
+    ) -> impl Iterator<Item = &FailureRule> {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.code.applies_to(code) && rule.files.contains(&target))
+    }
+
+    /// 指定代码需要搜索的文件类别
+    pub fn targets(&self, code: DftCodeType) -> Vec<RuleTarget> {
+        let mut targets = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.code.applies_to(code)) {
+            for target in &rule.files {
+                if !targets.contains(target) {
+                    targets.push(*target);
+                }
+            }
+        }
+        targets
+    }
+
+    /// 规则在集合中的优先级（越小越优先）
+    pub fn priority(&self, rule_id: &str) -> usize {
+        self.rules
+            .iter()
+            .position(|rule| rule.id == rule_id)
+            .unwrap_or(usize::MAX)
+    }
+}
+
+fn parse_rules(content: &str, source: &str) -> Result<(Vec<FailureRule>, bool)> {
+    let parse_error = |reason: String| QutilityError::ParseError {
+        format: "failure rules TOML".to_string(),
+        path: source.to_string(),
+        reason,
+    };
+
+    let file: RulesFile = toml::from_str(content).map_err(|e| parse_error(e.to_string()))?;
+
+    let rules = file
+        .rules
+        .into_iter()
+        .enumerate()
+        .map(|(i, spec)| {
+            let id = spec.id.unwrap_or_else(|| format!("{}#{}", source, i + 1));
+            let matcher = match (spec.pattern, spec.regex) {
+                (Some(pattern), None) => Matcher::Substring(pattern.to_lowercase()),
+                (None, Some(regex)) => Matcher::Regex(
+                    Regex::new(&regex)
+                        .map_err(|e| parse_error(format!("rule '{id}': invalid regex: {e}")))?,
+                ),
+                _ => {
+                    return Err(parse_error(format!(
+                        "rule '{id}' must set exactly one of 'pattern' or 'regex'"
+                    )))
+                }
+            };
+
+            if spec.files.is_empty() {
+                return Err(parse_error(format!("rule '{id}' has no files to search")));
+            }
+
+            Ok(FailureRule {
+                id,
+                code: spec.code,
+                files: spec.files,
+                severity: spec.severity,
+                reason: spec.reason,
+                fix: spec.fix,
+                matcher,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((rules, file.replace_defaults))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_match(rules: &FailureRules, code: DftCodeType, line: &str) -> Option<String> {
+        let lower = line.to_lowercase();
+        rules
+            .targets(code)
+            .into_iter()
+            .flat_map(|target| rules.for_target(code, target))
+            .filter(|rule| rule.is_match(line, &lower))
+            .min_by_key(|rule| rules.priority(&rule.id))
+            .map(|rule| rule.id.clone())
+    }
+
+    #[test]
+    fn builtin_rules_cover_common_failures() {
+        let rules = FailureRules::builtin();
+
+        assert_eq!(
+            first_match(
+                &rules,
+                DftCodeType::Vasp,
+                " internal ERROR RSPHER: running out of buffer"
+            ),
+            Some("vasp-rspher".to_string())
+        );
+        assert_eq!(
+            first_match(&rules, DftCodeType::Vasp, "LAPACK: Routine ZPOTRF failed!"),
+            Some("vasp-zpotrf".to_string())
+        );
+        assert_eq!(
+            first_match(
+                &rules,
+                DftCodeType::Castep,
+                "Warning: SCF cycles did not converge"
+            ),
+            Some("castep-scf-not-converged".to_string())
+        );
+        assert_eq!(first_match(&rules, DftCodeType::Castep, "ZPOTRF"), None);
+
+        // 只有 severe/error 级的 Fortran 运行时消息算失败
+        assert_eq!(
+            first_match(
+                &rules,
+                DftCodeType::Vasp,
+                "forrtl: severe (41): insufficient virtual memory"
+            ),
+            Some("vasp-forrtl".to_string())
+        );
+        assert_eq!(
+            first_match(
+                &rules,
+                DftCodeType::Castep,
+                "forrtl: warning (406): fort: (1): In call to I/O Write routine"
+            ),
+            None
+        );
+        assert_eq!(
+            first_match(
+                &rules,
+                DftCodeType::Castep,
+                "forrtl: info (58): format syntax error"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn user_rules_take_precedence_and_override_ids() {
+        let rules = FailureRules::builtin()
+            .with_toml(
+                r#"
+[[rule]]
+id = "vasp-zpotrf"
+code = "vasp"
+pattern = "ZPOTRF"
+files = ["outcar"]
+reason = "custom ZPOTRF"
+fix = "my_fix"
+
+[[rule]]
+code = "any"
+regex = "^CUSTOM-\\d+$"
+files = ["stdout"]
+severity = "warning"
+reason = "custom marker"
+"#,
+                "test.toml",
+            )
+            .expect("parse rules");
+
+        let zpotrf: Vec<_> = rules
+            .for_target(DftCodeType::Vasp, RuleTarget::Outcar)
+            .filter(|rule| rule.id == "vasp-zpotrf")
+            .collect();
+        assert_eq!(zpotrf.len(), 1);
+        assert_eq!(zpotrf[0].reason, "custom ZPOTRF");
+        assert_eq!(rules.priority("vasp-zpotrf"), 0);
+
+        assert_eq!(
+            first_match(&rules, DftCodeType::Castep, "CUSTOM-42"),
+            Some("test.toml#2".to_string())
+        );
+    }
+
+    #[test]
+    fn replace_defaults_drops_builtin_rules() {
+        let rules = FailureRules::builtin()
+            .with_toml(
+                r#"
+replace_defaults = true
+
+[[rule]]
+code = "vasp"
+pattern = "only this"
+files = ["outcar"]
+reason = "only rule"
+"#,
+                "test.toml",
+            )
+            .expect("parse rules");
+
+        assert_eq!(rules.targets(DftCodeType::Vasp), vec![RuleTarget::Outcar]);
+        assert!(rules.targets(DftCodeType::Castep).is_empty());
+    }
+
+    #[test]
+    fn rejects_rule_without_matcher() {
+        let err = FailureRules::builtin()
+            .with_toml(
+                r#"
+[[rule]]
+code = "vasp"
+files = ["outcar"]
+reason = "missing pattern"
+"#,
+                "bad.toml",
+            )
+            .expect_err("rule without pattern");
+
+        assert!(err.to_string().contains("exactly one of"));
+    }
+}
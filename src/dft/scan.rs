@@ -1,17 +1,26 @@
 //! # DFT 作业扫描器
 //!
-//! 统一扫描 VASP/CASTEP 作业目录，并产出显式状态与可选解析结果。
+//! 统一扫描 VASP/CASTEP 作业目录（按 `dft/discovery.rs` 的布局递归发现），在 rayon 线程池中并行检查与解析，
+//! 按发现顺序产出显式状态与可选解析结果；可选使用 `dft/cache.rs` 的增量缓存跳过输出未变化的已结束作业；
+//! 输出正常结束但几何优化未收敛的作业标记为 `Unconverged`；
+//! 失败原因由 `dft/rules.rs` 的规则在 OUTCAR、stdout、.err、.castep 中匹配得到；
+//! 完成标记与规则匹配只检查各文件末尾 8 MiB，不整文件读取。
 //!
 //! ## 依赖关系
 //! - 被 `dft/mod.rs` 导出给命令层复用
-//! - 使用 `models/calculation.rs` 和 `parsers/`
+//! - 使用 `models/calculation.rs`、`parsers/`（含 `parsers/tail.rs` 末尾读取）、`dft/discovery.rs`、`dft/cache.rs` 与 `dft/rules.rs`
+//! - 使用 `utils/scheduler.rs` 的作业日志目录名与数组任务日志定位
 
+use crate::dft::cache::{OutputFingerprint, ScanCache};
+use crate::dft::discovery::{discover_jobs, CodeSelection, DiscoveredJob, JobLayout};
+use crate::dft::rules::{FailureRule, FailureRules, RuleMatch, RuleTarget, Severity};
 use crate::error::{QutilityError, Result};
-use crate::models::{CalculationScanRecord, CalculationStatus, DftCodeType};
-use crate::parsers::{castep_out, outcar};
+use crate::models::{CalculationScanRecord, CalculationStatus, DftCodeType, DftResult};
+use crate::parsers::{castep_out, outcar, tail};
+use crate::utils::scheduler::{array_task_log, LOG_DIR};
 
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader};
+use rayon::prelude::*;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,46 +30,137 @@ pub enum RetryScope {
 }
 
 impl RetryScope {
-    fn matches(self, status: CalculationStatus) -> bool {
+    /// 该状态的作业是否属于本范围的重试候选
+    pub fn matches(self, status: CalculationStatus) -> bool {
         match self {
             RetryScope::FailedAndIncomplete => {
-                matches!(
-                    status,
-                    CalculationStatus::Failed | CalculationStatus::Incomplete
-                )
+                status == CalculationStatus::Incomplete
+                    || status == CalculationStatus::Unconverged
+                    || is_explicit_failure(status)
             }
-            RetryScope::FailedOnly => status == CalculationStatus::Failed,
+            RetryScope::FailedOnly => is_explicit_failure(status),
         }
     }
 }
 
+/// 输出或调度器明确表明作业已失败
+pub(super) fn is_explicit_failure(status: CalculationStatus) -> bool {
+    matches!(
+        status,
+        CalculationStatus::Failed
+            | CalculationStatus::TimedOut
+            | CalculationStatus::NodeFail
+            | CalculationStatus::OutOfMemory
+    )
+}
+
+/// 只使用内置失败模式规则扫描根目录的直接子目录；
+/// 需要用户/项目规则文件时由命令层 `FailureRules::discover` 后调用 `scan_calculations_with_rules`
 pub fn scan_calculations(root: &Path, code: DftCodeType) -> Result<Vec<CalculationScanRecord>> {
+    scan_calculations_with_rules(
+        root,
+        code.into(),
+        &FailureRules::builtin(),
+        &JobLayout::default(),
+        None,
+    )
+}
+
+/// 使用给定失败模式规则与作业布局扫描作业目录；提供缓存时复用输出未变化的记录并回写新结果
+pub fn scan_calculations_with_rules(
+    root: &Path,
+    code: CodeSelection,
+    rules: &FailureRules,
+    layout: &JobLayout,
+    cache: Option<&mut ScanCache>,
+) -> Result<Vec<CalculationScanRecord>> {
     if !root.exists() {
         return Err(QutilityError::DirectoryNotFound {
             path: root.display().to_string(),
         });
     }
 
-    let mut entries: Vec<_> = fs::read_dir(root)
-        .map_err(|e| QutilityError::FileReadError {
-            path: root.display().to_string(),
-            source: e,
-        })?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().is_dir())
+    let jobs = discover_jobs(root, code, layout)?;
+    let Some(cache) = cache else {
+        // 并行检查与解析；有序 collect 保持发现顺序
+        return Ok(jobs
+            .into_par_iter()
+            .map(|job| scan_calculation(job, rules))
+            .collect());
+    };
+
+    let scanned: Vec<CachedScan> = jobs
+        .into_par_iter()
+        .map(|job| {
+            let key = cache_key(root, &job.dir);
+            let fingerprint = output_file_path(&job.dir, &job.seed, job.code)
+                .and_then(|path| OutputFingerprint::of(&path));
+
+            let cached = fingerprint
+                .and_then(|fingerprint| cache.lookup(&key, fingerprint))
+                .filter(|record| record.code == job.code)
+                .map(|record| refresh_cached_record(record.clone(), &job));
+
+            let from_cache = cached.is_some();
+            CachedScan {
+                key,
+                fingerprint,
+                record: cached.unwrap_or_else(|| scan_calculation(job, rules)),
+                from_cache,
+            }
+        })
         .collect();
 
-    entries.sort_by_key(|entry| entry.file_name().to_string_lossy().to_string());
+    let keys = scanned.iter().map(|scan| scan.key.clone()).collect();
+    cache.retain_keys(&keys);
 
-    Ok(entries
+    Ok(scanned
         .into_iter()
-        .map(|entry| {
-            let structure_name = entry.file_name().to_string_lossy().to_string();
-            scan_calculation(entry.path(), structure_name, code)
+        .map(|scan| {
+            if let (false, Some(fingerprint)) = (scan.from_cache, scan.fingerprint) {
+                if ScanCache::is_cacheable(&scan.record) {
+                    cache.insert(scan.key, fingerprint, scan.record.clone());
+                }
+            }
+            scan.record
         })
         .collect())
 }
 
+/// 带缓存扫描的单个作业结果
+struct CachedScan {
+    key: String,
+    fingerprint: Option<OutputFingerprint>,
+    record: CalculationScanRecord,
+    from_cache: bool,
+}
+
+/// 作业目录相对根目录的路径，作为缓存键
+fn cache_key(root: &Path, job_dir: &Path) -> String {
+    job_dir
+        .strip_prefix(root)
+        .unwrap_or(job_dir)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 缓存记录只复用状态与解析结果，名称、路径等随本次布局与根目录更新
+fn refresh_cached_record(
+    mut record: CalculationScanRecord,
+    job: &DiscoveredJob,
+) -> CalculationScanRecord {
+    record.structure_name = job.name.clone();
+    record.job_dir = job.dir.clone();
+    record.seed = job.seed.clone();
+    record.structure_file = structure_file_path(&job.dir, &job.seed, job.code);
+    if let Some(parsed) = record.parsed.as_mut() {
+        parsed.structure_name = job.name.clone();
+    }
+    record
+}
+
 pub fn retry_candidates(
     records: &[CalculationScanRecord],
     scope: RetryScope,
@@ -71,32 +171,33 @@ pub fn retry_candidates(
         .collect()
 }
 
-fn scan_calculation(
-    calc_dir: PathBuf,
-    structure_name: String,
-    code: DftCodeType,
-) -> CalculationScanRecord {
-    let output_file = output_file_path(&calc_dir, &structure_name, code);
-    let structure_file = structure_file_path(&calc_dir, &structure_name, code);
+/// 按代码分组（VASP 在前），只返回出现过的代码
+pub fn group_by_code(
+    records: &[CalculationScanRecord],
+) -> Vec<(DftCodeType, Vec<&CalculationScanRecord>)> {
+    [DftCodeType::Vasp, DftCodeType::Castep]
+        .into_iter()
+        .map(|code| {
+            let group: Vec<_> = records.iter().filter(|r| r.code == code).collect();
+            (code, group)
+        })
+        .filter(|(_, group)| !group.is_empty())
+        .collect()
+}
+
+fn scan_calculation(job: DiscoveredJob, rules: &FailureRules) -> CalculationScanRecord {
+    let code = job.code;
+    let output_file = output_file_path(&job.dir, &job.seed, code);
+    let structure_file = structure_file_path(&job.dir, &job.seed, code);
 
     let Some(output_file) = output_file else {
-        return CalculationScanRecord::new(
-            structure_name,
-            calc_dir,
-            code,
-            CalculationStatus::MissingOutput,
-        );
+        return new_record(job, CalculationStatus::MissingOutput);
     };
 
-    let inspection = match inspect_output_file(&output_file, code) {
+    let inspection = match inspect_output_file(&output_file, code, rules) {
         Ok(inspection) => inspection,
         Err(err) => {
-            let mut record = CalculationScanRecord::new(
-                structure_name,
-                calc_dir,
-                code,
-                CalculationStatus::ParseError,
-            );
+            let mut record = new_record(job, CalculationStatus::ParseError);
             record.reason = Some(err.to_string());
             record.structure_file = structure_file;
             return record;
@@ -104,55 +205,59 @@ fn scan_calculation(
     };
 
     if inspection.completed {
-        return build_completed_record(
-            calc_dir,
-            structure_name,
-            code,
-            structure_file,
-            &output_file,
-        );
-    }
-
-    if let Some(reason) = inspection.failure_reason {
-        let mut record =
-            CalculationScanRecord::new(structure_name, calc_dir, code, CalculationStatus::Failed);
-        record.reason = Some(reason);
-        record.structure_file = structure_file;
-        return record;
+        let record = new_record(job, CalculationStatus::Completed);
+        return build_completed_record(record, structure_file, &output_file);
     }
 
-    let mut record = CalculationScanRecord::new(
-        structure_name,
-        calc_dir,
+    let mut best = inspection.rule_match;
+    match_auxiliary_files(
+        &job.dir,
+        &job.seed,
         code,
-        CalculationStatus::Incomplete,
+        rules,
+        output_target(code),
+        &mut best,
     );
+
+    let status = match &best {
+        Some(ranked) if ranked.rule_match.severity == Severity::Error => CalculationStatus::Failed,
+        _ => CalculationStatus::Incomplete,
+    };
+
+    let mut record = new_record(job, status);
     record.structure_file = structure_file;
+    if let Some(ranked) = best {
+        record.reason = Some(ranked.rule_match.reason);
+        record.suggested_fix = ranked.rule_match.fix;
+    }
+    record
+}
+
+fn new_record(job: DiscoveredJob, status: CalculationStatus) -> CalculationScanRecord {
+    let mut record = CalculationScanRecord::new(job.name, job.dir, job.code, status);
+    record.seed = job.seed;
     record
 }
 
 fn build_completed_record(
-    calc_dir: PathBuf,
-    structure_name: String,
-    code: DftCodeType,
+    mut record: CalculationScanRecord,
     structure_file: Option<PathBuf>,
     output_file: &Path,
 ) -> CalculationScanRecord {
-    let mut record = CalculationScanRecord::new(
-        structure_name.clone(),
-        calc_dir,
-        code,
-        CalculationStatus::Completed,
-    );
     record.structure_file = structure_file;
 
+    let code = record.code;
     let parsed = match code {
-        DftCodeType::Vasp => outcar::parse_outcar(output_file, &structure_name),
-        DftCodeType::Castep => castep_out::parse_castep_output(output_file, &structure_name),
+        DftCodeType::Vasp => outcar::parse_outcar(output_file, &record.structure_name),
+        DftCodeType::Castep => castep_out::parse_castep_output(output_file, &record.structure_name),
     };
 
     match parsed {
         Ok(result) => {
+            if result.geometry_converged == Some(false) {
+                record.status = CalculationStatus::Unconverged;
+                record.reason = Some(unconverged_reason(&result, code));
+            }
             record.parsed = Some(result);
             record
         }
@@ -164,20 +269,44 @@ fn build_completed_record(
     }
 }
 
-fn output_file_path(calc_dir: &Path, structure_name: &str, code: DftCodeType) -> Option<PathBuf> {
+fn unconverged_reason(result: &DftResult, code: DftCodeType) -> String {
+    let mut reason = match code {
+        DftCodeType::Vasp => {
+            "VASP relaxation used all NSW steps without reaching required accuracy"
+        }
+        DftCodeType::Castep => "CASTEP geometry optimization failed to converge",
+    }
+    .to_string();
+
+    let residuals: Vec<String> = [
+        result
+            .max_force_ev_per_a
+            .map(|f| format!("max force {f:.4} eV/Å")),
+        result
+            .max_stress_gpa
+            .map(|s| format!("max stress {s:.4} GPa")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if !residuals.is_empty() {
+        reason.push_str(&format!(" ({})", residuals.join(", ")));
+    }
+
+    reason
+}
+
+fn output_file_path(calc_dir: &Path, seed: &str, code: DftCodeType) -> Option<PathBuf> {
     let path = match code {
         DftCodeType::Vasp => calc_dir.join("OUTCAR"),
-        DftCodeType::Castep => calc_dir.join(format!("{structure_name}.castep")),
+        DftCodeType::Castep => calc_dir.join(format!("{seed}.castep")),
     };
 
     path.exists().then_some(path)
 }
 
-fn structure_file_path(
-    calc_dir: &Path,
-    structure_name: &str,
-    code: DftCodeType,
-) -> Option<PathBuf> {
+fn structure_file_path(calc_dir: &Path, seed: &str, code: DftCodeType) -> Option<PathBuf> {
     match code {
         DftCodeType::Vasp => {
             let contcar = calc_dir.join("CONTCAR");
@@ -189,42 +318,52 @@ fn structure_file_path(
             }
         }
         DftCodeType::Castep => {
-            let out_cell = calc_dir.join(format!("{structure_name}-out.cell"));
+            let out_cell = calc_dir.join(format!("{seed}-out.cell"));
             if out_cell.exists() {
                 Some(out_cell)
             } else {
-                let cell = calc_dir.join(format!("{structure_name}.cell"));
+                let cell = calc_dir.join(format!("{seed}.cell"));
                 cell.exists().then_some(cell)
             }
         }
     }
 }
 
-fn inspect_output_file(path: &Path, code: DftCodeType) -> std::io::Result<OutputInspection> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+/// 完成标记与导致作业中止的报错都位于输出末尾；
+/// 状态检查与规则匹配只读取这一窗口，多 GB 的输出也不必整文件读取
+const INSPECT_TAIL_BYTES: u64 = 8 * 1024 * 1024;
 
+fn inspect_output_file(
+    path: &Path,
+    code: DftCodeType,
+    rules: &FailureRules,
+) -> std::io::Result<OutputInspection> {
+    let applicable: Vec<&FailureRule> = rules.for_target(code, output_target(code)).collect();
     let mut completed = false;
-    let mut failure_reason = None;
-
-    for line in reader.lines() {
-        let line = line?;
+    let mut rule_match = None;
 
-        if matches_completion(&line, code) {
+    tail::for_each_tail_line(path, INSPECT_TAIL_BYTES, |line| {
+        if matches_completion(line, code) {
             completed = true;
         }
 
-        if failure_reason.is_none() {
-            failure_reason = detect_failure_reason(&line, code);
-        }
-    }
+        match_line(line, rules, &applicable, &mut rule_match);
+    })?;
 
     Ok(OutputInspection {
         completed,
-        failure_reason,
+        rule_match,
     })
 }
 
+/// 主输出文件对应的规则文件类别
+fn output_target(code: DftCodeType) -> RuleTarget {
+    match code {
+        DftCodeType::Vasp => RuleTarget::Outcar,
+        DftCodeType::Castep => RuleTarget::Castep,
+    }
+}
+
 fn matches_completion(line: &str, code: DftCodeType) -> bool {
     match code {
         DftCodeType::Vasp => {
@@ -234,95 +373,123 @@ fn matches_completion(line: &str, code: DftCodeType) -> bool {
     }
 }
 
-fn detect_failure_reason(line: &str, code: DftCodeType) -> Option<String> {
-    let normalized = line.to_ascii_lowercase();
-    let reason = match code {
-        DftCodeType::Vasp => vasp_failure_reason(&normalized),
-        DftCodeType::Castep => castep_failure_reason(&normalized),
-    }?;
+struct OutputInspection {
+    completed: bool,
+    rule_match: Option<RankedMatch>,
+}
 
-    Some(reason.to_string())
+/// 命中的规则及其排序键（严重程度优先，其次规则优先级）
+struct RankedMatch {
+    key: (Severity, usize),
+    rule_match: RuleMatch,
 }
 
-fn vasp_failure_reason(line: &str) -> Option<&'static str> {
-    for (pattern, reason) in [
-        (
-            "zbrent: fatal error in bracketing",
-            "VASP ionic relaxation failed (ZBRENT)",
-        ),
-        (
-            "brmix: very serious problems",
-            "VASP electronic minimization failed (BRMIX)",
-        ),
-        (
-            "edddav: call to zhegv failed",
-            "VASP diagonalization failed (EDDDAV)",
-        ),
-        (
-            "dav: sub-space-matrix is not hermitian",
-            "VASP subspace matrix became non-hermitian",
-        ),
-        (
-            "error in subspace rotation pssyevx",
-            "VASP subspace rotation failed",
-        ),
-        (
-            "error fexcf:",
-            "VASP exchange-correlation evaluation failed",
-        ),
-        ("segmentation fault", "VASP crashed with segmentation fault"),
-        ("forrtl:", "VASP Fortran runtime error"),
-    ] {
-        if line.contains(pattern) {
-            return Some(reason);
-        }
+fn keep_best(best: &mut Option<RankedMatch>, candidate: RankedMatch) {
+    if best
+        .as_ref()
+        .is_none_or(|current| candidate.key < current.key)
+    {
+        *best = Some(candidate);
     }
+}
 
-    None
+/// 对一行文本应用规则，保留最优命中
+fn match_line(
+    line: &str,
+    rules: &FailureRules,
+    applicable: &[&FailureRule],
+    best: &mut Option<RankedMatch>,
+) {
+    let lowercase = line.to_lowercase();
+    for rule in applicable {
+        if rule.is_match(line, &lowercase) {
+            keep_best(
+                best,
+                RankedMatch {
+                    key: (rule.severity, rules.priority(&rule.id)),
+                    rule_match: RuleMatch::from(*rule),
+                },
+            );
+        }
+    }
 }
 
-fn castep_failure_reason(line: &str) -> Option<&'static str> {
-    for (pattern, reason) in [
-        (
-            "error terminating execution",
-            "CASTEP terminated with an error",
-        ),
-        ("aborting the calculation", "CASTEP aborted the calculation"),
-        (
-            "segmentation fault",
-            "CASTEP crashed with segmentation fault",
-        ),
-        ("forrtl:", "CASTEP Fortran runtime error"),
-    ] {
-        if line.contains(pattern) {
-            return Some(reason);
+/// 在输出文件以外的文件类别（stdout、.err 等）中搜索规则
+fn match_auxiliary_files(
+    calc_dir: &Path,
+    seed: &str,
+    code: DftCodeType,
+    rules: &FailureRules,
+    output_target: RuleTarget,
+    best: &mut Option<RankedMatch>,
+) {
+    for target in rules.targets(code) {
+        if target == output_target {
+            continue;
+        }
+
+        let applicable: Vec<&FailureRule> = rules.for_target(code, target).collect();
+        for path in target_files(calc_dir, seed, target) {
+            // 文件不存在或不可读时跳过
+            let _ = tail::for_each_tail_line(&path, INSPECT_TAIL_BYTES, |line| {
+                match_line(line, rules, &applicable, best);
+            });
         }
     }
+}
+
+/// 规则文件类别对应的实际文件
+fn target_files(calc_dir: &Path, seed: &str, target: RuleTarget) -> Vec<PathBuf> {
+    let log_dir = calc_dir.join(LOG_DIR);
+    let mut files = match target {
+        RuleTarget::Outcar => vec![calc_dir.join("OUTCAR")],
+        RuleTarget::Castep => vec![calc_dir.join(format!("{seed}.castep"))],
+        RuleTarget::Stdout => {
+            let mut files = files_with_extension(&log_dir, "out");
+            files.extend(
+                ["stdout", "vasp.out", "vasp.log"]
+                    .into_iter()
+                    .map(|name| calc_dir.join(name)),
+            );
+            files.push(calc_dir.join(format!("{seed}.out")));
+            files
+        }
+        RuleTarget::Err => {
+            let mut files = files_with_extension(&log_dir, "err");
+            files.extend(files_with_extension(calc_dir, "err"));
+            files.extend(array_task_log(calc_dir, "err"));
+            files
+        }
+    };
 
-    None
+    files.retain(|path| path.is_file());
+    files
 }
 
-struct OutputInspection {
-    completed: bool,
-    failure_reason: Option<String>,
+fn files_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(extension))
+        .collect();
+    files.sort();
+    files
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::{SystemTime, UNIX_EPOCH};
-
-    fn unique_test_dir(name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time before UNIX_EPOCH")
-            .as_nanos();
-        std::env::temp_dir().join(format!("qutility-dft-{name}-{nanos}"))
-    }
+    use crate::dft::cache::{OutputFingerprint, ScanCache};
+    use crate::dft::discovery::NameTemplate;
+    use crate::utils::test_support::unique_test_dir;
 
     #[test]
     fn scan_marks_missing_output() {
-        let root = unique_test_dir("missing-output");
+        let root = unique_test_dir("dft-missing-output");
         let job_dir = root.join("alpha");
         fs::create_dir_all(&job_dir).expect("create job dir");
 
@@ -336,7 +503,7 @@ mod tests {
 
     #[test]
     fn scan_marks_incomplete_vasp() {
-        let root = unique_test_dir("incomplete-vasp");
+        let root = unique_test_dir("dft-incomplete-vasp");
         let job_dir = root.join("beta");
         fs::create_dir_all(&job_dir).expect("create job dir");
         fs::write(job_dir.join("OUTCAR"), "still running\n").expect("write OUTCAR");
@@ -350,7 +517,7 @@ mod tests {
 
     #[test]
     fn scan_marks_failed_vasp() {
-        let root = unique_test_dir("failed-vasp");
+        let root = unique_test_dir("dft-failed-vasp");
         let job_dir = root.join("gamma");
         fs::create_dir_all(&job_dir).expect("create job dir");
         fs::write(
@@ -367,13 +534,77 @@ mod tests {
             .as_deref()
             .expect("reason")
             .contains("BRMIX"));
+        assert_eq!(records[0].suggested_fix.as_deref(), Some("brmix"));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn scan_matches_rules_in_stdout_and_err_files() {
+        let root = unique_test_dir("dft-aux-files");
+        let vasp_dir = root.join("vasp").join("alpha");
+        fs::create_dir_all(vasp_dir.join("slurm_logs")).expect("create log dir");
+        fs::write(vasp_dir.join("OUTCAR"), "running\n").expect("write OUTCAR");
+        fs::write(
+            vasp_dir.join("slurm_logs").join("alpha.out"),
+            "ZBRENT: fatal error in bracketing\n",
+        )
+        .expect("write stdout");
+
+        let castep_dir = root.join("castep").join("beta");
+        fs::create_dir_all(&castep_dir).expect("create castep dir");
+        fs::write(castep_dir.join("beta.castep"), "SCF loop\n").expect("write castep");
+        fs::write(
+            castep_dir.join("beta.0001.err"),
+            "Error in reading the cell file\n",
+        )
+        .expect("write err");
+
+        let vasp = scan_calculations(&root.join("vasp"), DftCodeType::Vasp).expect("scan vasp");
+        assert_eq!(vasp[0].status, CalculationStatus::Failed);
+        assert_eq!(vasp[0].suggested_fix.as_deref(), Some("zbrent"));
+
+        let castep =
+            scan_calculations(&root.join("castep"), DftCodeType::Castep).expect("scan castep");
+        assert_eq!(castep[0].status, CalculationStatus::Failed);
+        assert_eq!(castep[0].suggested_fix.as_deref(), Some("cell"));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn scan_applies_project_rules_file() {
+        let root = unique_test_dir("dft-project-rules");
+        let job_dir = root.join("alpha");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::write(job_dir.join("OUTCAR"), "WARNING: licence expired\n").expect("write OUTCAR");
+        fs::write(
+            root.join(crate::dft::rules::PROJECT_RULES_FILE),
+            "[[rule]]\nid = \"licence\"\ncode = \"vasp\"\npattern = \"licence expired\"\nfiles = [\"outcar\"]\nseverity = \"warning\"\nreason = \"VASP licence expired\"\n",
+        )
+        .expect("write rules");
+
+        let rules = FailureRules::builtin()
+            .with_file(&root.join(crate::dft::rules::PROJECT_RULES_FILE))
+            .expect("load rules");
+        let records = scan_calculations_with_rules(
+            &root,
+            DftCodeType::Vasp.into(),
+            &rules,
+            &JobLayout::default(),
+            None,
+        )
+        .expect("scan");
+
+        assert_eq!(records[0].status, CalculationStatus::Incomplete);
+        assert_eq!(records[0].reason.as_deref(), Some("VASP licence expired"));
 
         fs::remove_dir_all(&root).expect("cleanup");
     }
 
     #[test]
     fn scan_marks_completed_vasp() {
-        let root = unique_test_dir("completed-vasp");
+        let root = unique_test_dir("dft-completed-vasp");
         let job_dir = root.join("delta");
         fs::create_dir_all(&job_dir).expect("create job dir");
         fs::write(
@@ -403,6 +634,238 @@ General timing and accounting informations for this job
         fs::remove_dir_all(&root).expect("cleanup");
     }
 
+    #[test]
+    fn scan_parses_vasp_pressure_including_pulay_stress() {
+        let root = unique_test_dir("dft-pressure-vasp");
+        let job_dir = root.join("epsilon");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::write(
+            job_dir.join("OUTCAR"),
+            "\
+  external pressure =       12.00 kB  Pullay stress =      100.00 kB
+  external pressure =       -0.50 kB  Pullay stress =      500.00 kB
+enthalpy is  TOTEN    =      -10.000000 eV
+General timing and accounting informations for this job
+",
+        )
+        .expect("write OUTCAR");
+
+        let records = scan_calculations(&root, DftCodeType::Vasp).expect("scan");
+
+        assert_eq!(
+            records[0]
+                .parsed
+                .as_ref()
+                .and_then(|result| result.pressure_kbar),
+            Some(499.5)
+        );
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn scan_marks_vasp_relaxation_that_exhausted_nsw() {
+        let root = unique_test_dir("dft-unconverged-vasp");
+        let job_dir = root.join("zeta");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::write(
+            job_dir.join("OUTCAR"),
+            "\
+   NSW    =      2    number of steps for IOM
+   IBRION =      2    ionic relax: 0-MD 1-quasi-New 2-CG
+--------------------------------------- Iteration      1(   1)  ---------------------------------------
+--------------------------------------- Iteration      2(   1)  ---------------------------------------
+  in kB     505.00   498.00   501.00     3.00    -1.00     0.00
+  external pressure =        1.33 kB  Pullay stress =      500.00 kB
+ POSITION                                       TOTAL-FORCE (eV/Angst)
+ -----------------------------------------------------------------------------------
+      0.00000      0.00000      0.00000         0.030000      0.040000      0.000000
+      1.50000      1.50000      1.50000        -0.030000     -0.040000      0.000000
+ -----------------------------------------------------------------------------------
+enthalpy is  TOTEN    =      -10.000000 eV
+General timing and accounting informations for this job
+",
+        )
+        .expect("write OUTCAR");
+
+        let records = scan_calculations(&root, DftCodeType::Vasp).expect("scan");
+        let parsed = records[0].parsed.as_ref().expect("parsed result");
+
+        assert_eq!(records[0].status, CalculationStatus::Unconverged);
+        assert_eq!(parsed.geometry_converged, Some(false));
+        assert!((parsed.max_force_ev_per_a.expect("force") - 0.05).abs() < 1e-9);
+        assert!((parsed.max_stress_gpa.expect("stress") - 0.5).abs() < 1e-9);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn scan_reads_castep_geometry_convergence() {
+        let root = unique_test_dir("dft-geom-castep");
+        for (name, verdict) in [
+            (
+                "good",
+                "LBFGS: Geometry optimization completed successfully.",
+            ),
+            (
+                "bad",
+                "LBFGS: Geometry optimization failed to converge after 100 steps.",
+            ),
+        ] {
+            let job_dir = root.join(name);
+            fs::create_dir_all(&job_dir).expect("create job dir");
+            fs::write(
+                job_dir.join(format!("{name}.castep")),
+                format!(
+                    "\
+ LBFGS: Final Enthalpy     = -1.0E+002 eV
++-----------+-------------+-------------+-----------------+
+|  |F|max   |   1.000E-001 |   5.000E-002 |         eV/A | No  | <-- LBFGS
+|   Smax    |   2.000E-002 |   1.000E-001 |          GPa | Yes | <-- LBFGS
++-----------+-------------+-------------+-----------------+
+|  |F|max   |   3.000E-002 |   5.000E-002 |         eV/A | Yes | <-- LBFGS
+{verdict}
+Total time          =    100.00 s
+"
+                ),
+            )
+            .expect("write castep");
+        }
+
+        let records = scan_calculations(&root, DftCodeType::Castep).expect("scan");
+        let bad = &records[0];
+        let good = &records[1];
+
+        assert_eq!(bad.status, CalculationStatus::Unconverged);
+        assert_eq!(good.status, CalculationStatus::Completed);
+        let parsed = good.parsed.as_ref().expect("parsed result");
+        assert_eq!(parsed.geometry_converged, Some(true));
+        assert_eq!(parsed.max_force_ev_per_a, Some(0.03));
+        assert_eq!(parsed.max_stress_gpa, Some(0.02));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn scan_uses_castep_seed_in_nested_layout() {
+        let root = unique_test_dir("dft-nested-castep");
+        let job_dir = root.join("TiO2").join("P050");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        fs::write(
+            job_dir.join("rutile.param"),
+            "task : geometryoptimization\n",
+        )
+        .expect("write param");
+        fs::write(job_dir.join("rutile.cell"), "%block positions_frac\n").expect("write cell");
+        fs::write(job_dir.join("rutile.castep"), "SCF loop\n").expect("write castep");
+
+        let layout = JobLayout {
+            max_depth: 3,
+            name_template: NameTemplate::parse("{0}_{1}_{seed}").expect("template"),
+        };
+        let records = scan_calculations_with_rules(
+            &root,
+            DftCodeType::Castep.into(),
+            &FailureRules::builtin(),
+            &layout,
+            None,
+        )
+        .expect("scan");
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].structure_name, "TiO2_P050_rutile");
+        assert_eq!(records[0].seed, "rutile");
+        assert_eq!(records[0].status, CalculationStatus::Incomplete);
+        assert_eq!(records[0].structure_file, Some(job_dir.join("rutile.cell")));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn scan_auto_detects_mixed_codes() {
+        let root = unique_test_dir("dft-mixed-codes");
+        let vasp_dir = root.join("alpha");
+        let castep_dir = root.join("beta");
+        fs::create_dir_all(&vasp_dir).expect("create vasp dir");
+        fs::create_dir_all(&castep_dir).expect("create castep dir");
+        fs::write(vasp_dir.join("OUTCAR"), "running\n").expect("write OUTCAR");
+        fs::write(castep_dir.join("beta.param"), "task : singlepoint\n").expect("write param");
+
+        let records = scan_calculations_with_rules(
+            &root,
+            CodeSelection::Auto,
+            &FailureRules::builtin(),
+            &JobLayout::default(),
+            None,
+        )
+        .expect("scan");
+
+        assert_eq!(records[0].code, DftCodeType::Vasp);
+        assert_eq!(records[0].status, CalculationStatus::Incomplete);
+        assert_eq!(records[1].code, DftCodeType::Castep);
+        assert_eq!(records[1].status, CalculationStatus::MissingOutput);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn parallel_scan_keeps_sorted_order() {
+        let root = unique_test_dir("dft-parallel-order");
+        for i in (0..64).rev() {
+            let job_dir = root.join(format!("job{i:03}"));
+            fs::create_dir_all(&job_dir).expect("create job dir");
+            fs::write(job_dir.join("OUTCAR"), "running\n").expect("write OUTCAR");
+        }
+
+        let records = scan_calculations(&root, DftCodeType::Vasp).expect("scan");
+
+        let names: Vec<_> = records.iter().map(|r| r.structure_name.clone()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names.len(), 64);
+        assert_eq!(names, sorted);
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn cache_reuses_unchanged_completed_jobs() {
+        let root = unique_test_dir("dft-scan-cache");
+        let job_dir = root.join("alpha");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        let outcar = job_dir.join("OUTCAR");
+        let completed = "\
+enthalpy is  TOTEN    =      -12.500000 eV
+General timing and accounting informations for this job
+";
+        fs::write(&outcar, completed).expect("write OUTCAR");
+        fs::create_dir_all(root.join("beta")).expect("create missing-output job");
+
+        let rules = FailureRules::builtin();
+        let layout = JobLayout::default();
+        let code = CodeSelection::from(DftCodeType::Vasp);
+        let mut cache = ScanCache::default();
+        scan_calculations_with_rules(&root, code, &rules, &layout, Some(&mut cache)).expect("scan");
+        assert_eq!(cache.len(), 1);
+
+        // 篡改缓存中的焓值：输出未变化时应直接返回缓存记录
+        let fingerprint = OutputFingerprint::of(&outcar).expect("fingerprint");
+        let mut stale = cache.lookup("alpha", fingerprint).expect("cached").clone();
+        stale.parsed.as_mut().expect("parsed").enthalpy_ev = Some(-99.0);
+        cache.insert("alpha".to_string(), fingerprint, stale);
+
+        let records = scan_calculations_with_rules(&root, code, &rules, &layout, Some(&mut cache))
+            .expect("rescan");
+        assert_eq!(records[0].parsed.as_ref().unwrap().enthalpy_ev, Some(-99.0));
+
+        fs::write(&outcar, format!("{completed}\n")).expect("rewrite OUTCAR");
+        let records = scan_calculations_with_rules(&root, code, &rules, &layout, Some(&mut cache))
+            .expect("rescan after change");
+        assert_eq!(records[0].parsed.as_ref().unwrap().enthalpy_ev, Some(-12.5));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
     #[test]
     fn retry_scope_filters_records() {
         let failed = CalculationScanRecord::new(
@@ -424,12 +887,39 @@ General timing and accounting informations for this job
             CalculationStatus::Completed,
         );
 
-        let records = vec![failed, incomplete, completed];
+        let unconverged = CalculationScanRecord::new(
+            "unconverged",
+            PathBuf::from("unconverged"),
+            DftCodeType::Vasp,
+            CalculationStatus::Unconverged,
+        );
+
+        let timed_out = CalculationScanRecord::new(
+            "timed-out",
+            PathBuf::from("timed-out"),
+            DftCodeType::Vasp,
+            CalculationStatus::TimedOut,
+        );
+        let running = CalculationScanRecord::new(
+            "running",
+            PathBuf::from("running"),
+            DftCodeType::Vasp,
+            CalculationStatus::Running,
+        );
+
+        let records = vec![
+            failed,
+            incomplete,
+            completed,
+            unconverged,
+            timed_out,
+            running,
+        ];
 
         assert_eq!(
             retry_candidates(&records, RetryScope::FailedAndIncomplete).len(),
-            2
+            4
         );
-        assert_eq!(retry_candidates(&records, RetryScope::FailedOnly).len(), 1);
+        assert_eq!(retry_candidates(&records, RetryScope::FailedOnly).len(), 2);
     }
 }
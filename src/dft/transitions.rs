@@ -0,0 +1,243 @@
+//! # 焓–压力相变分析
+//!
+//! 将多个压力点的每原子焓按相对齐，计算相对参考相的 ΔH(P) 并插值求解焓曲线交点。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/transitions.rs` 使用
+//! - 无外部模块依赖
+
+/// 单个相的 H(P) 序列
+#[derive(Debug, Clone)]
+pub struct PhaseSeries {
+    /// 相名称
+    pub phase: String,
+    /// (压力 GPa, 每原子焓 eV)，按压力升序
+    pub points: Vec<(f64, f64)>,
+}
+
+impl PhaseSeries {
+    /// 从无序数据点构造；同一压力下保留焓最低者
+    pub fn new(phase: impl Into<String>, mut points: Vec<(f64, f64)>) -> Self {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        points.dedup_by(|later, earlier| (later.0 - earlier.0).abs() < PRESSURE_TOLERANCE);
+
+        Self {
+            phase: phase.into(),
+            points,
+        }
+    }
+
+    /// 在数据范围内线性插值每原子焓
+    pub fn enthalpy_at(&self, pressure: f64) -> Option<f64> {
+        interpolate(&self.points, pressure)
+    }
+
+    fn pressure_range(&self) -> Option<(f64, f64)> {
+        Some((self.points.first()?.0, self.points.last()?.0))
+    }
+}
+
+/// 两条焓曲线的交点
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnthalpyCrossing {
+    /// 低压侧更稳定的相
+    pub low_pressure_phase: String,
+    /// 高压侧更稳定的相
+    pub high_pressure_phase: String,
+    /// 插值得到的相变压力 (GPa)
+    pub pressure_gpa: f64,
+}
+
+/// 压力对齐容差 (GPa)
+const PRESSURE_TOLERANCE: f64 = 1e-6;
+
+/// 计算 `series` 相对 `reference` 的 ΔH(P)，仅在参考相覆盖的压力范围内取值
+pub fn relative_enthalpies(series: &PhaseSeries, reference: &PhaseSeries) -> Vec<(f64, f64)> {
+    series
+        .points
+        .iter()
+        .filter_map(|&(p, h)| Some((p, h - reference.enthalpy_at(p)?)))
+        .collect()
+}
+
+/// 求两相焓曲线在共同压力范围内的所有交点
+pub fn find_crossings(a: &PhaseSeries, b: &PhaseSeries) -> Vec<EnthalpyCrossing> {
+    let diffs = difference_on_grid(a, b);
+    let mut crossings = Vec::new();
+    let mut last_nonzero: Option<(f64, f64)> = None;
+    let mut touching_at: Option<f64> = None;
+
+    for &(p1, d1) in &diffs {
+        if d1 == 0.0 {
+            touching_at.get_or_insert(p1);
+            continue;
+        }
+
+        if let Some((p0, d0)) = last_nonzero {
+            if d0.signum() != d1.signum() {
+                let pressure = touching_at.unwrap_or(p0 + (p1 - p0) * d0 / (d0 - d1));
+                let (low, high) = if d0 < 0.0 { (a, b) } else { (b, a) };
+                crossings.push(EnthalpyCrossing {
+                    low_pressure_phase: low.phase.clone(),
+                    high_pressure_phase: high.phase.clone(),
+                    pressure_gpa: pressure,
+                });
+            }
+        }
+
+        last_nonzero = Some((p1, d1));
+        touching_at = None;
+    }
+
+    crossings
+}
+
+/// 求最稳定相随压力变化的相变序列
+pub fn stable_phase_transitions(series: &[PhaseSeries]) -> Vec<EnthalpyCrossing> {
+    let mut grid: Vec<f64> = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|(p, _)| *p))
+        .collect();
+    grid.sort_by(f64::total_cmp);
+    grid.dedup_by(|a, b| (*a - *b).abs() < PRESSURE_TOLERANCE);
+
+    let lowest_at = |p: f64| {
+        series
+            .iter()
+            .filter_map(|s| Some((s, s.enthalpy_at(p)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(s, _)| s)
+    };
+
+    let mut transitions = Vec::new();
+    for window in grid.windows(2) {
+        let (Some(before), Some(after)) = (lowest_at(window[0]), lowest_at(window[1])) else {
+            continue;
+        };
+
+        if before.phase == after.phase {
+            continue;
+        }
+
+        let crossing = find_crossings(before, after)
+            .into_iter()
+            .find(|c| c.pressure_gpa >= window[0] && c.pressure_gpa <= window[1]);
+
+        if let Some(crossing) = crossing {
+            transitions.push(crossing);
+        }
+    }
+
+    transitions
+}
+
+/// 在两相共同压力范围内，取两者压力点的并集计算 H_a - H_b
+fn difference_on_grid(a: &PhaseSeries, b: &PhaseSeries) -> Vec<(f64, f64)> {
+    let (Some((a_min, a_max)), Some((b_min, b_max))) = (a.pressure_range(), b.pressure_range())
+    else {
+        return Vec::new();
+    };
+    let (lo, hi) = (a_min.max(b_min), a_max.min(b_max));
+
+    let mut grid: Vec<f64> = a
+        .points
+        .iter()
+        .chain(&b.points)
+        .map(|(p, _)| *p)
+        .filter(|p| *p >= lo - PRESSURE_TOLERANCE && *p <= hi + PRESSURE_TOLERANCE)
+        .collect();
+    grid.sort_by(f64::total_cmp);
+    grid.dedup_by(|x, y| (*x - *y).abs() < PRESSURE_TOLERANCE);
+
+    grid.into_iter()
+        .filter_map(|p| Some((p, a.enthalpy_at(p)? - b.enthalpy_at(p)?)))
+        .collect()
+}
+
+fn interpolate(points: &[(f64, f64)], x: f64) -> Option<f64> {
+    let first = points.first()?;
+    let last = points.last()?;
+    if x < first.0 - PRESSURE_TOLERANCE || x > last.0 + PRESSURE_TOLERANCE {
+        return None;
+    }
+
+    if points.len() == 1 {
+        return Some(first.1);
+    }
+
+    let idx = points
+        .windows(2)
+        .position(|w| x <= w[1].0 + PRESSURE_TOLERANCE)
+        .unwrap_or(points.len() - 2);
+    let (x0, y0) = points[idx];
+    let (x1, y1) = points[idx + 1];
+
+    if (x1 - x0).abs() < PRESSURE_TOLERANCE {
+        return Some(y0);
+    }
+
+    Some(y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(phase: &str, points: &[(f64, f64)]) -> PhaseSeries {
+        PhaseSeries::new(phase, points.to_vec())
+    }
+
+    #[test]
+    fn finds_linear_crossing_between_phases() {
+        let alpha = series("alpha", &[(0.0, -5.0), (50.0, -4.0), (100.0, -3.0)]);
+        let beta = series("beta", &[(0.0, -4.9), (50.0, -4.0), (100.0, -2.9)]);
+
+        let crossings = find_crossings(&alpha, &beta);
+
+        assert!(
+            crossings.is_empty(),
+            "touching at 50 GPa is not a sign change"
+        );
+
+        let beta = series("beta", &[(0.0, -4.8), (50.0, -3.95), (100.0, -3.2)]);
+        let crossings = find_crossings(&alpha, &beta);
+        assert_eq!(crossings.len(), 1);
+        assert_eq!(crossings[0].low_pressure_phase, "alpha");
+        assert_eq!(crossings[0].high_pressure_phase, "beta");
+        assert!((crossings[0].pressure_gpa - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn relative_enthalpy_interpolates_reference() {
+        let reference = series("ref", &[(0.0, -5.0), (100.0, -3.0)]);
+        let other = series("other", &[(49.9, -3.9)]);
+
+        let delta = relative_enthalpies(&other, &reference);
+
+        assert_eq!(delta.len(), 1);
+        assert!((delta[0].1 - (-3.9 - (-4.002))).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stable_sequence_tracks_lowest_phase() {
+        let a = series("a", &[(0.0, 0.0), (50.0, 0.0), (100.0, 0.0)]);
+        let b = series("b", &[(0.0, 0.1), (50.0, -0.1), (100.0, -0.2)]);
+        let c = series("c", &[(0.0, 0.3), (50.0, 0.0), (100.0, -0.4)]);
+
+        let transitions = stable_phase_transitions(&[a, b, c]);
+
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].low_pressure_phase, "a");
+        assert_eq!(transitions[0].high_pressure_phase, "b");
+        assert!((transitions[0].pressure_gpa - 25.0).abs() < 1e-9);
+        assert_eq!(transitions[1].high_pressure_phase, "c");
+        assert!((transitions[1].pressure_gpa - 200.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn duplicate_pressures_keep_lowest_enthalpy() {
+        let s = series("x", &[(10.0, -1.0), (10.0, -2.0), (0.0, 0.0)]);
+
+        assert_eq!(s.points, vec![(0.0, 0.0), (10.0, -2.0)]);
+    }
+}
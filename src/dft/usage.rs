@@ -0,0 +1,434 @@
+//! # 作业资源用量
+//!
+//! 估算每个作业的墙钟时间与核时。优先读取主输出：VASP 取 OUTCAR 末尾的 `Elapsed time (sec)`、
+//! `Total CPU time used (sec)` 与头部的 MPI 进程数；CASTEP 取 .castep 中每次运行的 `Total time`
+//! 与 `Calculation parallelised over N processes`，续算追加的多次运行逐次累加。
+//! 输出没有计时信息时退回作业日志：Slurm 作业脚本末尾 `sacct` 打印的 CPUTime，
+//! 或日志记录因超时被终止时按作业脚本的时间上限计。作业脚本按各调度器的文件名查找
+//! （`submit.sbatch`、`submit.pbs`、`submit.lsf`、`submit.sge`），申请的核数与时间上限由对应调度器解析。
+//! 核时 = 墙钟时间 × 核数（MPI 进程数 × 每进程线程数）。
+//!
+//! ## 依赖关系
+//! - 被 `dft/mod.rs` 导出给 `commands/analyze/usage.rs`
+//! - 使用 `models/calculation.rs`、`parsers/`（含 `parsers/tail.rs` 首尾读取）与 `dft/scan.rs` 的失败状态判定
+//! - 使用 `utils/scheduler.rs` 的作业脚本资源解析、超时记录识别与日志目录
+
+use crate::dft::scan::is_explicit_failure;
+use crate::models::{CalculationScanRecord, CalculationStatus, DftCodeType};
+use crate::parsers::{self, tail};
+use crate::utils::scheduler::{array_task_log, Scheduler, SchedulerKind, ScriptResources, LOG_DIR};
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// 计时信息的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageSource {
+    /// OUTCAR / .castep 中的计时行
+    Output,
+    /// Slurm 日志中 `sacct` 打印的 CPUTime
+    SlurmLog,
+    /// 因超时被终止，按脚本时间上限计
+    TimeLimit,
+}
+
+impl std::fmt::Display for UsageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsageSource::Output => write!(f, "output"),
+            UsageSource::SlurmLog => write!(f, "slurm-log"),
+            UsageSource::TimeLimit => write!(f, "time-limit"),
+        }
+    }
+}
+
+/// 单个作业的资源用量；无法得到计时信息时各字段为 `None`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobUsage {
+    /// 墙钟时间 (s)
+    pub elapsed_sec: Option<f64>,
+    /// 程序报告的 CPU 时间 (s)；VASP 为主进程的 CPU 时间，CASTEP 不报告
+    pub cpu_sec: Option<f64>,
+    /// 核数（MPI 进程数 × 每进程线程数）
+    pub cores: Option<usize>,
+    /// 核时
+    pub core_hours: Option<f64>,
+    pub source: Option<UsageSource>,
+}
+
+/// 失败作业的用量计为浪费
+pub fn is_wasted(status: CalculationStatus) -> bool {
+    is_explicit_failure(status)
+}
+
+/// 读取作业的计时信息并估算核时
+pub fn job_usage(record: &CalculationScanRecord) -> JobUsage {
+    let script = job_script(&record.job_dir);
+    let script_cores = script.and_then(|(_, resources)| resources.cores);
+    let timing = match record.code {
+        DftCodeType::Vasp => vasp_timing(&record.job_dir.join("OUTCAR")),
+        DftCodeType::Castep => {
+            castep_timing(&record.job_dir.join(format!("{}.castep", record.seed)))
+        }
+    };
+
+    if let Some(timing) = timing {
+        let cores = timing.cores.or(script_cores);
+        let core_sec = timing
+            .core_sec
+            .or_else(|| cores.map(|cores| timing.elapsed_sec * cores as f64));
+        return JobUsage {
+            elapsed_sec: Some(timing.elapsed_sec),
+            cpu_sec: timing.cpu_sec,
+            cores,
+            core_hours: core_sec.map(|sec| sec / 3600.0),
+            source: Some(UsageSource::Output),
+        };
+    }
+
+    log_usage(&record.job_dir, script).unwrap_or_default()
+}
+
+/// 按各调度器的脚本文件名查找作业脚本，并以该调度器的指令格式读取申请的资源
+fn job_script(job_dir: &Path) -> Option<(&'static dyn Scheduler, ScriptResources)> {
+    SchedulerKind::ALL
+        .into_iter()
+        .map(SchedulerKind::scheduler)
+        .find_map(|scheduler| {
+            let content = fs::read_to_string(job_dir.join(scheduler.script_name())).ok()?;
+            Some((scheduler, scheduler.script_resources(&content)))
+        })
+}
+
+/// 作业原子数：优先取解析结果，否则读取结构文件
+pub fn job_atoms(record: &CalculationScanRecord) -> Option<usize> {
+    record
+        .parsed
+        .as_ref()
+        .and_then(|parsed| parsed.num_atoms)
+        .or_else(|| {
+            let crystal = parsers::parse_structure_file(record.structure_file.as_deref()?).ok()?;
+            Some(crystal.atoms.len())
+        })
+}
+
+/// 按原子数的 2 的幂次分档：`1-8`、`9-16`、…、`>256`
+pub fn size_bin(atoms: Option<usize>) -> String {
+    let Some(atoms) = atoms else {
+        return "unknown".to_string();
+    };
+
+    let mut upper = 8;
+    while upper <= 256 {
+        if atoms <= upper {
+            let lower = if upper == 8 { 1 } else { upper / 2 + 1 };
+            return format!("{lower}-{upper}");
+        }
+        upper *= 2;
+    }
+    ">256".to_string()
+}
+
+/// 主输出中的计时信息
+struct OutputTiming {
+    elapsed_sec: f64,
+    cpu_sec: Option<f64>,
+    cores: Option<usize>,
+    /// 多次运行逐次累加的核秒数（各次运行核数可能不同）
+    core_sec: Option<f64>,
+}
+
+/// OUTCAR 的计时块位于末尾，进程数位于头部
+fn vasp_timing(path: &Path) -> Option<OutputTiming> {
+    let mut elapsed = None;
+    let mut cpu = None;
+    tail::for_each_tail_line(path, tail::TAIL_BYTES, |line| {
+        if line.contains("Elapsed time (sec):") {
+            elapsed = last_number(line);
+        } else if line.contains("Total CPU time used (sec):") {
+            cpu = last_number(line);
+        }
+    })
+    .ok()?;
+    let elapsed_sec = elapsed?;
+
+    // VASP 5: `running on   32 total cores`；VASP 6: `running   32 mpi-ranks, with    2 threads/rank`
+    let mut cores = None;
+    tail::for_each_head_line(path, tail::HEAD_BYTES, |line| {
+        if cores.is_some() {
+            return;
+        }
+        if let Some(total) = integer_before(line, "total cores") {
+            cores = Some(total);
+        } else if let Some(ranks) = integer_before(line, "mpi-ranks") {
+            cores = Some(ranks * integer_before(line, "threads/rank").unwrap_or(1));
+        }
+    })
+    .ok()?;
+
+    Some(OutputTiming {
+        elapsed_sec,
+        cpu_sec: cpu,
+        cores,
+        core_sec: None,
+    })
+}
+
+/// 续算会在同一 .castep 中追加多次运行，每次运行都有自己的进程数与 `Total time`，
+/// 因此需要顺序读取全文件逐次累加（流式读取，内存占用与文件大小无关）
+fn castep_timing(path: &Path) -> Option<OutputTiming> {
+    let file = File::open(path).ok()?;
+
+    let mut procs = 1;
+    let mut runs = 0;
+    let mut elapsed_sec = 0.0;
+    let mut core_sec = 0.0;
+
+    for line in BufReader::new(file).lines().map_while(|line| line.ok()) {
+        if line.contains("Calculation parallelised over") {
+            procs = integer_before(&line, "processes").unwrap_or(1);
+        } else if line.contains("Calculation not parallelised") {
+            procs = 1;
+        } else if line.trim_start().starts_with("Total time") {
+            // `Total time          =        123.45 s`
+            if let Some(seconds) = line
+                .split_once('=')
+                .and_then(|(_, rest)| rest.split_whitespace().next())
+                .and_then(|token| token.parse::<f64>().ok())
+            {
+                runs += 1;
+                elapsed_sec += seconds;
+                core_sec += seconds * procs as f64;
+            }
+        }
+    }
+
+    (runs > 0).then_some(OutputTiming {
+        elapsed_sec,
+        cpu_sec: None,
+        cores: Some(procs),
+        core_sec: Some(core_sec),
+    })
+}
+
+/// 从作业日志估算：`slurm_logs/*.out` 中 sacct 打印的 CPUTime（已是分配核数 × 时间），
+/// 或作业日志与数组任务日志中由作业脚本所属调度器识别的超时终止记录（按脚本时间上限计）
+fn log_usage(
+    job_dir: &Path,
+    script: Option<(&'static dyn Scheduler, ScriptResources)>,
+) -> Option<JobUsage> {
+    let log_dir = job_dir.join(LOG_DIR);
+    let cores = script.and_then(|(_, resources)| resources.cores);
+
+    for path in log_files(&log_dir, "out") {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(cpu_time) = sacct_cpu_time(&content) {
+            return Some(JobUsage {
+                elapsed_sec: cores.map(|cores| cpu_time / cores as f64),
+                cpu_sec: None,
+                cores,
+                core_hours: Some(cpu_time / 3600.0),
+                source: Some(UsageSource::SlurmLog),
+            });
+        }
+    }
+
+    let (scheduler, resources) = script?;
+    let limit = resources.time_limit_sec? as f64;
+    // LSF 把作业报告（含 TERM_RUNLIMIT）写在标准输出日志中；Slurm 数组任务的超时消息在作业根目录的数组日志中
+    let timed_out = ["err", "out"]
+        .into_iter()
+        .flat_map(|extension| {
+            let mut logs = log_files(&log_dir, extension);
+            logs.extend(array_task_log(job_dir, extension));
+            logs
+        })
+        .any(|path| {
+            fs::read_to_string(path)
+                .map(|content| scheduler.time_limit_exceeded(&content))
+                .unwrap_or(false)
+        });
+
+    timed_out.then(|| JobUsage {
+        elapsed_sec: Some(limit),
+        cpu_sec: None,
+        cores,
+        core_hours: cores.map(|cores| limit * cores as f64 / 3600.0),
+        source: Some(UsageSource::TimeLimit),
+    })
+}
+
+fn log_files(log_dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(log_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(extension))
+        .collect();
+    files.sort();
+    files
+}
+
+/// 解析 `sacct -o JobID,Submit,Start,End,CPUTime,State` 表格首行（作业本身，而非作业步）的 CPUTime
+fn sacct_cpu_time(content: &str) -> Option<f64> {
+    let mut lines = content.lines();
+    let header = lines.find(|line| line.contains("JobID") && line.contains("CPUTime"))?;
+    let column = header
+        .split_whitespace()
+        .position(|token| token == "CPUTime")?;
+
+    lines
+        .filter(|line| !line.trim_start().starts_with("---"))
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|tokens| tokens.first().is_some_and(|id| !id.contains('.')))
+        .and_then(|tokens| parse_slurm_duration(tokens.get(column)?))
+}
+
+/// Slurm 时长格式：`MM`、`MM:SS`、`HH:MM:SS`、`D-HH`、`D-HH:MM`、`D-HH:MM:SS`
+fn parse_slurm_duration(value: &str) -> Option<f64> {
+    let (days, clock) = match value.split_once('-') {
+        Some((days, clock)) => (Some(days.parse::<f64>().ok()?), clock),
+        None => (None, value),
+    };
+    let parts: Vec<f64> = clock
+        .split(':')
+        .map(|part| part.parse::<f64>().ok())
+        .collect::<Option<_>>()?;
+
+    let seconds = match (days, parts.as_slice()) {
+        (Some(_), [h]) => h * 3600.0,
+        (Some(_), [h, m]) => h * 3600.0 + m * 60.0,
+        (None, [m]) => m * 60.0,
+        (None, [m, s]) => m * 60.0 + s,
+        (_, [h, m, s]) => h * 3600.0 + m * 60.0 + s,
+        _ => return None,
+    };
+
+    Some(days.unwrap_or(0.0) * 86400.0 + seconds)
+}
+
+fn integer_before(line: &str, marker: &str) -> Option<usize> {
+    let before = &line[..line.find(marker)?];
+    before.split_whitespace().last()?.parse().ok()
+}
+
+fn last_number(line: &str) -> Option<f64> {
+    line.split_whitespace()
+        .filter_map(|token| token.parse::<f64>().ok())
+        .next_back()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    fn record(root: &Path, name: &str, code: DftCodeType) -> CalculationScanRecord {
+        CalculationScanRecord::new(name, root.join(name), code, CalculationStatus::Completed)
+    }
+
+    #[test]
+    fn reads_timings_from_outputs() {
+        let root = unique_test_dir("usage-outputs");
+        fs::create_dir_all(root.join("vasp")).expect("create vasp dir");
+        fs::create_dir_all(root.join("castep")).expect("create castep dir");
+        fs::write(
+            root.join("vasp").join("OUTCAR"),
+            " running   16 mpi-ranks, with    2 threads/rank\n\
+             \x20                 Total CPU time used (sec):     3500.000\n\
+             \x20                           Elapsed time (sec):     3600.000\n",
+        )
+        .expect("write OUTCAR");
+        fs::write(
+            root.join("castep").join("castep.castep"),
+            "Calculation parallelised over   8 processes.\n\
+             Total time          =        1800.00 s\n\
+             Calculation parallelised over  16 processes.\n\
+             Total time          =        1800.00 s\n",
+        )
+        .expect("write castep");
+
+        let vasp = job_usage(&record(&root, "vasp", DftCodeType::Vasp));
+        assert_eq!(vasp.source, Some(UsageSource::Output));
+        assert_eq!(vasp.cores, Some(32));
+        assert_eq!(vasp.cpu_sec, Some(3500.0));
+        assert_eq!(vasp.core_hours, Some(32.0));
+
+        // 两次运行：0.5 h × 8 核 + 0.5 h × 16 核
+        let castep = job_usage(&record(&root, "castep", DftCodeType::Castep));
+        assert_eq!(castep.elapsed_sec, Some(3600.0));
+        assert_eq!(castep.core_hours, Some(12.0));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+
+    #[test]
+    fn falls_back_to_slurm_logs() {
+        let root = unique_test_dir("usage-slurm");
+        for name in ["sacct", "timeout"] {
+            let logs = root.join(name).join("slurm_logs");
+            fs::create_dir_all(&logs).expect("create log dir");
+            fs::write(
+                root.join(name).join("submit.sbatch"),
+                "#!/bin/bash\n#SBATCH --time 1-00:00:00\n#SBATCH -c 2\n#SBATCH -n 16\n",
+            )
+            .expect("write script");
+        }
+        fs::write(
+            root.join("sacct").join("slurm_logs").join("sacct.out"),
+            "Timings:\n\
+             \x20      JobID              Submit               Start                 End    CPUTime      State\n\
+             ------------ ------------------- ------------------- ------------------- ---------- ----------\n\
+             123          2024-01-01T10:00:00 2024-01-01T10:01:00             Unknown   16:00:00    RUNNING\n\
+             123.batch    2024-01-01T10:01:00 2024-01-01T10:01:00             Unknown   16:00:00    RUNNING\n",
+        )
+        .expect("write out log");
+        fs::write(
+            root.join("timeout").join("slurm_logs").join("timeout.err"),
+            "slurmstepd: error: *** JOB 124 ON node01 CANCELLED AT 2024-01-02T10:00:00 DUE TO TIME LIMIT ***\n",
+        )
+        .expect("write err log");
+
+        let sacct = job_usage(&record(&root, "sacct", DftCodeType::Vasp));
+        assert_eq!(sacct.source, Some(UsageSource::SlurmLog));
+        assert_eq!(sacct.core_hours, Some(16.0));
+        assert_eq!(sacct.elapsed_sec, Some(1800.0));
+
+        let timeout = job_usage(&record(&root, "timeout", DftCodeType::Vasp));
+        assert_eq!(timeout.source, Some(UsageSource::TimeLimit));
+        assert_eq!(timeout.core_hours, Some(24.0 * 32.0));
+
+        // PBS 作业：资源取自 submit.pbs，超时记录由 PBS 后端识别
+        let logs = root.join("pbs").join(LOG_DIR);
+        fs::create_dir_all(&logs).expect("create log dir");
+        fs::write(
+            root.join("pbs").join("submit.pbs"),
+            "#!/bin/bash\n#PBS -l nodes=2:ppn=8\n#PBS -l walltime=12:00:00\n",
+        )
+        .expect("write script");
+        fs::write(
+            logs.join("pbs.err"),
+            "=>> PBS: job killed: walltime 43215 exceeded limit 43200\n",
+        )
+        .expect("write err log");
+        let pbs = job_usage(&record(&root, "pbs", DftCodeType::Vasp));
+        assert_eq!(pbs.source, Some(UsageSource::TimeLimit));
+        assert_eq!(pbs.cores, Some(16));
+        assert_eq!(pbs.core_hours, Some(12.0 * 16.0));
+
+        assert_eq!(
+            parse_slurm_duration("2-03"),
+            Some(2.0 * 86400.0 + 3.0 * 3600.0)
+        );
+        assert_eq!(parse_slurm_duration("90"), Some(5400.0));
+        assert_eq!(size_bin(Some(12)), "9-16");
+        assert_eq!(size_bin(Some(300)), ">256");
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
@@ -7,8 +7,11 @@
 //! - `analyze` - 分析功能
 //!   - `dft-status` - DFT 作业状态扫描
 //!   - `dft-postprocessing` - DFT 完成结果后处理
+//!   - `eos` - 状态方程拟合
+//!   - `transitions` - 焓–压力相变分析
 //!   - `xrd` - XRD 衍射图样计算
 //! - `collect` - 收集完成的 DFT 计算结果
+//! - `report`  - 生成自包含的 HTML 计算批次报告
 //! - `submit`  - 批量提交作业到 Slurm
 //!
 //! ## 依赖关系
@@ -16,6 +19,7 @@
 //! main.rs
 //!   ├── cli/        (命令行参数定义)
 //!   ├── commands/   (命令执行逻辑)
+//!   ├── report/     (HTML 报告渲染)
 //!   │     ├── parsers/   (格式解析器)
 //!   │     ├── converters/(格式转换器)
 //!   │     └── models/    (数据模型)
@@ -30,6 +34,7 @@ mod dft;
 mod error;
 mod models;
 mod parsers;
+mod report;
 mod utils;
 mod xrd;
 
@@ -42,6 +47,7 @@ fn main() {
     colored::control::set_virtual_terminal(true).ok();
 
     let cli = Cli::parse();
+    utils::structured::init(cli.output_format.into());
 
     if let Err(e) = commands::run(cli.command) {
         utils::output::print_error(&format!("{}", e));
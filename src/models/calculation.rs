@@ -1,6 +1,6 @@
 //! # DFT 计算领域模型
 //!
-//! 定义 DFT 结果、作业状态与扫描记录的数据结构。
+//! 定义 DFT 结果（含几何优化收敛标记与残余力/应力）、逐离子步收敛轨迹、作业状态与扫描记录的数据结构。
 //!
 //! ## 依赖关系
 //! - 被 `parsers/` 写入，被 `dft/` 与 `commands/` 读取
@@ -29,20 +29,38 @@ impl std::fmt::Display for DftCodeType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CalculationStatus {
     Completed,
+    /// 输出正常结束，但几何优化未收敛
+    Unconverged,
     Failed,
     Incomplete,
     MissingOutput,
     ParseError,
+    /// 调度器显示作业正在运行
+    Running,
+    /// 调度器显示作业正在排队
+    Pending,
+    /// 作业因超出墙钟时间被终止
+    TimedOut,
+    /// 作业因节点故障被终止
+    NodeFail,
+    /// 作业因内存不足被终止
+    OutOfMemory,
 }
 
 impl std::fmt::Display for CalculationStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             CalculationStatus::Completed => write!(f, "completed"),
+            CalculationStatus::Unconverged => write!(f, "unconverged"),
             CalculationStatus::Failed => write!(f, "failed"),
             CalculationStatus::Incomplete => write!(f, "incomplete"),
             CalculationStatus::MissingOutput => write!(f, "missing-output"),
             CalculationStatus::ParseError => write!(f, "parse-error"),
+            CalculationStatus::Running => write!(f, "running"),
+            CalculationStatus::Pending => write!(f, "pending"),
+            CalculationStatus::TimedOut => write!(f, "timed-out"),
+            CalculationStatus::NodeFail => write!(f, "node-fail"),
+            CalculationStatus::OutOfMemory => write!(f, "out-of-memory"),
         }
     }
 }
@@ -70,6 +88,15 @@ pub struct DftResult {
 
     /// 原子数
     pub num_atoms: Option<usize>,
+
+    /// 几何优化是否收敛；单点计算或无法判断时为 None
+    pub geometry_converged: Option<bool>,
+
+    /// 最后一个离子步的最大原子受力 (eV/Å)
+    pub max_force_ev_per_a: Option<f64>,
+
+    /// 最后一个离子步相对目标压力的最大残余应力 (GPa)
+    pub max_stress_gpa: Option<f64>,
 }
 
 /// 单个作业目录的扫描结果
@@ -79,6 +106,8 @@ pub struct CalculationScanRecord {
     pub structure_name: String,
     /// 作业目录
     pub job_dir: PathBuf,
+    /// 输入/输出文件种子名（VASP 为目录名，CASTEP 取自 `.param` 文件名）
+    pub seed: String,
     /// 使用的 DFT 代码
     pub code: DftCodeType,
     /// 扫描得到的状态
@@ -89,6 +118,33 @@ pub struct CalculationScanRecord {
     pub structure_file: Option<PathBuf>,
     /// 已解析结果，仅在可解析时存在
     pub parsed: Option<DftResult>,
+    /// 匹配到的调度器作业 ID
+    pub job_id: Option<String>,
+    /// 失败规则给出的修复建议标签
+    pub suggested_fix: Option<String>,
+}
+
+/// 单个离子步的收敛数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IonicStep {
+    /// 离子步编号（从 1 开始）
+    pub step: usize,
+    /// 该步的 SCF 迭代次数
+    pub scf_iterations: usize,
+    /// 该步结束时的能量 (eV)
+    pub energy_ev: Option<f64>,
+    /// 最大原子受力 (eV/Å)
+    pub max_force_ev_per_a: Option<f64>,
+    /// 最大残余应力分量 (GPa)
+    pub max_stress_gpa: Option<f64>,
+}
+
+/// 一个作业的逐离子步收敛轨迹
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConvergenceTrace {
+    pub steps: Vec<IonicStep>,
+    /// 每个离子步允许的最大 SCF 迭代次数（VASP NELM / CASTEP max_scf_cycles）
+    pub scf_limit: Option<usize>,
 }
 
 impl DftResult {
@@ -101,6 +157,9 @@ impl DftResult {
             pressure_kbar: None,
             volume: None,
             num_atoms: None,
+            geometry_converged: None,
+            max_force_ev_per_a: None,
+            max_stress_gpa: None,
         }
     }
 
@@ -128,14 +187,18 @@ impl CalculationScanRecord {
         code: DftCodeType,
         status: CalculationStatus,
     ) -> Self {
+        let structure_name = structure_name.into();
         Self {
-            structure_name: structure_name.into(),
+            seed: structure_name.clone(),
+            structure_name,
             job_dir,
             code,
             status,
             reason: None,
             structure_file: None,
             parsed: None,
+            job_id: None,
+            suggested_fix: None,
         }
     }
 }
@@ -1,6 +1,6 @@
 //! # 数据模型模块
 //!
-//! 定义结构、DFT 结果与作业状态的共享模型。
+//! 定义结构、DFT 结果、收敛轨迹与作业状态的共享模型。
 //!
 //! ## 依赖关系
 //! - 被 `parsers/`、`dft/`、`commands/` 使用
@@ -9,5 +9,7 @@
 pub mod calculation;
 pub mod structure;
 
-pub use calculation::{CalculationScanRecord, CalculationStatus, DftCodeType, DftResult};
+pub use calculation::{
+    CalculationScanRecord, CalculationStatus, ConvergenceTrace, DftCodeType, DftResult, IonicStep,
+};
 pub use structure::{Atom, Crystal, Lattice};
@@ -3,7 +3,7 @@
 //! 定义统一的晶体结构表示，可以从不同格式解析并转换为不同格式。
 //!
 //! ## 依赖关系
-//! - 被 `parsers/` 和 `converters/` 使用
+//! - 被 `parsers/` 和 `converters/` 使用；约化化学式供 `commands/analyze/dft_postprocessing.rs` 排序分组；倒格矢供 `xrd/calculator.rs` 与 `dft/kpoints.rs` 使用
 //! - 无外部模块依赖
 
 use serde::{Deserialize, Serialize};
@@ -83,6 +83,37 @@ impl Lattice {
         a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
             + a[2] * (b[0] * c[1] - b[1] * c[0])
     }
+
+    /// 倒格矢矩阵（含 2π 因子），行向量表示 b1, b2, b3；晶胞体积为零时返回零矩阵
+    pub fn reciprocal(&self) -> [[f64; 3]; 3] {
+        let [a, b, c] = self.matrix;
+
+        // 体积 V = a · (b × c)
+        let b_cross_c = cross(&b, &c);
+        let volume = dot(&a, &b_cross_c);
+
+        if volume.abs() < 1e-10 {
+            return [[0.0; 3]; 3];
+        }
+
+        // 倒格矢：b1 = 2π(b×c)/V, b2 = 2π(c×a)/V, b3 = 2π(a×b)/V
+        let factor = 2.0 * std::f64::consts::PI / volume;
+        [b_cross_c, cross(&c, &a), cross(&a, &b)].map(|v| v.map(|x| x * factor))
+    }
+}
+
+/// 向量叉积
+fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// 向量点积
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
 }
 
 /// 原子信息
@@ -169,24 +200,22 @@ impl Crystal {
 
     /// 计算化学式
     pub fn formula(&self) -> String {
-        use std::collections::BTreeMap;
-        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        format_formula(&self.element_counts(), 1)
+    }
+
+    /// 计算约化化学式与晶胞内的化学式单元数 Z
+    pub fn reduced_formula(&self) -> (String, usize) {
+        let counts = self.element_counts();
+        let z = counts.values().copied().fold(0, gcd).max(1);
+        (format_formula(&counts, z), z)
+    }
 
+    fn element_counts(&self) -> std::collections::BTreeMap<&str, usize> {
+        let mut counts = std::collections::BTreeMap::new();
         for atom in &self.atoms {
             *counts.entry(atom.element.as_str()).or_insert(0) += 1;
         }
-
         counts
-            .into_iter()
-            .map(|(el, count)| {
-                if count == 1 {
-                    el.to_string()
-                } else {
-                    format!("{}{}", el, count)
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("")
     }
 
     /// 计算每原子焓
@@ -201,6 +230,26 @@ impl Crystal {
     }
 }
 
+/// 按元素计数拼接化学式，计数先除以 `divisor`
+fn format_formula(counts: &std::collections::BTreeMap<&str, usize>, divisor: usize) -> String {
+    counts
+        .iter()
+        .map(|(el, count)| match count / divisor {
+            1 => el.to_string(),
+            n => format!("{}{}", el, n),
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,6 +286,26 @@ mod tests {
         assert!((c - 4.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_lattice_reciprocal_hexagonal() {
+        let lattice = Lattice::from_parameters(3.0, 3.0, 5.0, 90.0, 90.0, 120.0);
+        let recip = lattice.reciprocal();
+
+        // a_i · b_j = 2π δ_ij
+        for (i, a) in lattice.matrix.iter().enumerate() {
+            for (j, b) in recip.iter().enumerate() {
+                let expected = if i == j {
+                    2.0 * std::f64::consts::PI
+                } else {
+                    0.0
+                };
+                assert!((dot(a, b) - expected).abs() < 1e-9);
+            }
+        }
+        // |b3| = 2π / c
+        assert!((dot(&recip[2], &recip[2]).sqrt() - 2.0 * std::f64::consts::PI / 5.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_lattice_hexagonal() {
         let lattice = Lattice::from_parameters(3.0, 3.0, 5.0, 90.0, 90.0, 120.0);
@@ -268,6 +337,23 @@ mod tests {
         assert!(formula.contains("Na"));
     }
 
+    #[test]
+    fn test_crystal_reduced_formula() {
+        let lattice = Lattice::from_parameters(5.0, 5.0, 5.0, 90.0, 90.0, 90.0);
+        let atoms = vec![
+            Atom::new("Mg", [0.0, 0.0, 0.0]),
+            Atom::new("Mg", [0.5, 0.5, 0.5]),
+            Atom::new("O", [0.5, 0.0, 0.0]),
+            Atom::new("O", [0.0, 0.5, 0.0]),
+            Atom::new("O", [0.0, 0.0, 0.5]),
+            Atom::new("O", [0.5, 0.5, 0.0]),
+        ];
+        let crystal = Crystal::new("MgO2", lattice, atoms);
+
+        assert_eq!(crystal.formula(), "Mg2O4");
+        assert_eq!(crystal.reduced_formula(), ("MgO2".to_string(), 2));
+    }
+
     #[test]
     fn test_crystal_enthalpy_per_atom() {
         let lattice = Lattice::from_parameters(5.0, 5.0, 5.0, 90.0, 90.0, 90.0);
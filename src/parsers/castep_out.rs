@@ -1,58 +1,135 @@
 //! # CASTEP .castep 解析器
 //!
-//! 解析 CASTEP .castep 输出，提取已完成计算的物理量数据。
+//! 解析 CASTEP .castep 输出，提取已完成计算的物理量数据，
+//! 以及几何优化收敛结论与最后一步的 |F|max、Smax；
+//! 结果解析只读取文件末尾与有界的头部区域，不随输出大小线性增长；归档条目等不可定位的流则正向读取一次；
+//! 另可逐行解析每次 SCF 的迭代次数、能量与收敛判据表，得到收敛轨迹。
 //!
 //! ## 依赖关系
-//! - 被 `dft/` 共享扫描模块调用
+//! - 被 `dft/` 共享扫描与归档读取模块调用
 //! - 使用 `models/calculation.rs`
+//! - 使用 `parsers/tail.rs` 读取首尾区域
 
 use crate::error::{QutilityError, Result};
-use crate::models::{DftCodeType, DftResult};
+use crate::models::{ConvergenceTrace, DftCodeType, DftResult, IonicStep};
+use crate::parsers::tail;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 
+/// 解析 .castep：最终能量、收敛结论与末步应力从文件末尾读取，
+/// 原子数与初始体积只从有界的头部区域读取
 pub fn parse_castep_output(path: &Path, structure_name: &str) -> Result<DftResult> {
-    let file = File::open(path).map_err(|e| QutilityError::FileReadError {
+    let read_error = |e| QutilityError::FileReadError {
         path: path.display().to_string(),
         source: e,
+    };
+
+    let tail = tail::scan_from_end(path, CastepTail::consume, CastepTail::is_complete)
+        .map_err(read_error)?;
+    let mut head = CastepHead::default();
+    tail::for_each_head_line(path, tail::HEAD_BYTES, |line| head.consume(line))
+        .map_err(read_error)?;
+
+    Ok(castep_result(structure_name, head, tail))
+}
+
+/// 从不可定位的流（如归档中的 .castep 条目）解析：全部行正向处理一次，
+/// 头部数据只取前 `HEAD_BYTES` 字节
+pub fn parse_castep_output_reader(
+    reader: impl BufRead,
+    structure_name: &str,
+) -> io::Result<DftResult> {
+    let mut head = CastepHead::default();
+    let mut tail = CastepTail::default();
+    tail::for_each_stream_line(reader, |offset, line| {
+        if offset <= tail::HEAD_BYTES {
+            head.consume(line);
+        }
+        tail.consume(line);
     })?;
 
-    let reader = BufReader::new(file);
+    Ok(castep_result(structure_name, head, tail))
+}
+
+fn castep_result(structure_name: &str, head: CastepHead, tail: CastepTail) -> DftResult {
     let mut result = DftResult::new(structure_name, DftCodeType::Castep);
+    result.enthalpy_ev = tail.final_enthalpy;
+    result.energy_ev = tail.final_energy;
+    // 固定晶胞的优化只在头部打印一次体积
+    result.volume = tail.volume.or(head.volume);
+    result.num_atoms = head.num_atoms;
+    result.pressure_kbar = tail.pressure;
+    result.geometry_converged = tail.geometry_converged;
+    result.max_force_ev_per_a = tail.max_force;
+    result.max_stress_gpa = tail.max_stress;
 
-    let mut final_enthalpy = None;
-    let mut final_energy = None;
-    let mut volume = None;
-    let mut num_atoms = None;
-    let mut pressure = None;
+    result
+}
 
-    let lines: Vec<String> = reader.lines().filter_map(|line| line.ok()).collect();
+/// .castep 头部数据：离子数与初始晶胞体积
+#[derive(Default)]
+struct CastepHead {
+    num_atoms: Option<usize>,
+    volume: Option<f64>,
+}
 
-    for line in lines.iter().rev() {
-        if line.contains("Final Enthalpy") && final_enthalpy.is_none() {
-            if let Some(value) = extract_value_after_eq(line) {
-                final_enthalpy = Some(value);
-            }
+impl CastepHead {
+    fn consume(&mut self, line: &str) {
+        if self.num_atoms.is_none() && line.contains("Total number of ions in cell") {
+            self.num_atoms = extract_value_after_eq(line).map(|value| value as usize);
         }
 
-        if line.contains("Final energy, E") && final_energy.is_none() {
+        if self.volume.is_none() && line.contains("Current cell volume") {
+            self.volume = extract_value_after_eq(line);
+        }
+    }
+}
+
+/// .castep 末尾数据：窗口内正向处理，后出现的值覆盖先出现的值
+#[derive(Default)]
+struct CastepTail {
+    final_enthalpy: Option<f64>,
+    final_energy: Option<f64>,
+    volume: Option<f64>,
+    pressure: Option<f64>,
+    geometry_converged: Option<bool>,
+    max_force: Option<f64>,
+    max_stress: Option<f64>,
+}
+
+impl CastepTail {
+    fn consume(&mut self, line: &str) {
+        if line.contains("Geometry optimization completed successfully") {
+            self.geometry_converged = Some(true);
+        } else if line.contains("Geometry optimization failed to converge") {
+            self.geometry_converged = Some(false);
+        }
+
+        // 收敛判据表：|  |F|max   |   1.234E-002 |   5.000E-002 |   eV/A | Yes |
+        if let Some(value) = extract_convergence_value(line, "|F|max") {
+            self.max_force = Some(value);
+        }
+
+        if let Some(value) = extract_convergence_value(line, "Smax") {
+            self.max_stress = Some(value);
+        }
+
+        if line.contains("Final Enthalpy") {
             if let Some(value) = extract_value_after_eq(line) {
-                final_energy = Some(value);
+                self.final_enthalpy = Some(value);
             }
         }
-    }
 
-    for line in &lines {
-        if line.contains("Total number of ions in cell") {
+        if line.contains("Final energy, E") {
             if let Some(value) = extract_value_after_eq(line) {
-                num_atoms = Some(value as usize);
+                self.final_energy = Some(value);
             }
         }
 
         if line.contains("Current cell volume") {
             if let Some(value) = extract_value_after_eq(line) {
-                volume = Some(value);
+                self.volume = Some(value);
             }
         }
 
@@ -63,19 +140,86 @@ pub fn parse_castep_output(path: &Path, structure_name: &str) -> Result<DftResul
                     .get(idx + 1)
                     .and_then(|value| value.parse::<f64>().ok())
                 {
-                    pressure = Some(value * 10.0);
+                    self.pressure = Some(value * 10.0);
                 }
             }
         }
     }
 
-    result.enthalpy_ev = final_enthalpy;
-    result.energy_ev = final_energy;
-    result.volume = volume;
-    result.num_atoms = num_atoms;
-    result.pressure_kbar = pressure;
+    /// 最后一次 SCF 的 `Final energy` 之后才是末步受力、应力表与优化结论
+    fn is_complete(&self) -> bool {
+        self.final_energy.is_some()
+    }
+}
+
+/// 逐行解析 .castep 的收敛轨迹：每个 `Final energy` 结束一个步，
+/// 其后的收敛判据表（|F|max、Smax）归入该步
+pub fn parse_castep_trace(path: &Path) -> Result<ConvergenceTrace> {
+    let file = File::open(path).map_err(|e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    let mut trace = ConvergenceTrace::default();
+    let mut scf_iterations = 0;
+
+    for line in BufReader::new(file).lines().map_while(|line| line.ok()) {
+        if trace.scf_limit.is_none() && line.contains("max. number of SCF cycles") {
+            trace.scf_limit = line
+                .split_whitespace()
+                .last()
+                .and_then(|token| token.parse().ok());
+        }
+
+        // SCF 表行：`      12  -2.1E+003  ...  <-- SCF`
+        if line.trim_end().ends_with("<-- SCF") {
+            if let Some(n) = line
+                .split_whitespace()
+                .next()
+                .and_then(|token| token.parse::<usize>().ok())
+            {
+                scf_iterations = scf_iterations.max(n);
+            }
+            continue;
+        }
+
+        if line.contains("Final energy, E") || line.trim_start().starts_with("Final energy =") {
+            trace.steps.push(IonicStep {
+                step: trace.steps.len() + 1,
+                scf_iterations,
+                energy_ev: extract_value_after_eq(&line),
+                max_force_ev_per_a: None,
+                max_stress_gpa: None,
+            });
+            scf_iterations = 0;
+            continue;
+        }
+
+        if let Some(step) = trace.steps.last_mut() {
+            if let Some(force) = extract_convergence_value(&line, "|F|max") {
+                step.max_force_ev_per_a = Some(force);
+            }
+            if let Some(stress) = extract_convergence_value(&line, "Smax") {
+                step.max_stress_gpa = Some(stress);
+            }
+        }
+    }
+
+    Ok(trace)
+}
+
+/// 读取几何优化收敛判据表中某一判据的当前值
+fn extract_convergence_value(line: &str, label: &str) -> Option<f64> {
+    let pos = line.find(label)?;
+    if !line.trim_start().starts_with('|') {
+        return None;
+    }
 
-    Ok(result)
+    line[pos + label.len()..]
+        .split(|c: char| c == '|' || c.is_whitespace())
+        .find(|token| !token.is_empty())?
+        .parse()
+        .ok()
 }
 
 fn extract_value_after_eq(s: &str) -> Option<f64> {
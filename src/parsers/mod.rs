@@ -5,13 +5,14 @@
 //! ## 依赖关系
 //! - 被 `commands/` 模块使用
 //! - 使用 `models/` 数据模型
-//! - 子模块: res, cell, poscar, cif, outcar, castep_out
+//! - 子模块: res, cell, poscar, cif, outcar, castep_out, tail
 
 pub mod castep_out;
 pub mod cell;
 pub mod outcar;
 pub mod poscar;
 pub mod res;
+pub mod tail;
 
 use crate::error::{QutilityError, Result};
 use crate::models::Crystal;
@@ -1,71 +1,397 @@
 //! # VASP OUTCAR 解析器
 //!
-//! 解析 VASP OUTCAR，提取已完成输出中的物理量数据。
+//! 解析 VASP OUTCAR，提取已完成输出中的物理量数据（含外压与 Pulay 应力之和），
+//! 以及几何优化收敛情况（`reached required accuracy` / NSW 耗尽）与最终残余力、应力；
+//! 结果解析只读取文件末尾与有界的头部区域，不随 OUTCAR 大小线性增长；归档条目等不可定位的流则正向读取一次；
+//! 另可逐行解析 OUTCAR 或 OSZICAR 的逐离子步收敛轨迹。
 //!
 //! ## 依赖关系
-//! - 被 `dft/` 共享扫描模块调用
+//! - 被 `dft/` 共享扫描与归档读取模块调用
 //! - 使用 `models/calculation.rs`
+//! - 使用 `parsers/tail.rs` 读取首尾区域
 
 use crate::error::{QutilityError, Result};
-use crate::models::{DftCodeType, DftResult};
+use crate::models::{ConvergenceTrace, DftCodeType, DftResult, IonicStep};
+use crate::parsers::tail;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 
+/// 解析 OUTCAR：最终能量、应力、受力与收敛标记从文件末尾读取，
+/// 原子数、NSW/IBRION 与初始体积只从有界的头部区域读取
 pub fn parse_outcar(path: &Path, structure_name: &str) -> Result<DftResult> {
-    let file = File::open(path).map_err(|e| QutilityError::FileReadError {
+    let read_error = |e| QutilityError::FileReadError {
         path: path.display().to_string(),
         source: e,
+    };
+
+    let tail = tail::scan_from_end(path, OutcarTail::consume, OutcarTail::is_complete)
+        .map_err(read_error)?;
+    let mut head = OutcarHead::default();
+    tail::for_each_head_line(path, tail::HEAD_BYTES, |line| head.consume(line))
+        .map_err(read_error)?;
+
+    Ok(outcar_result(structure_name, head, tail))
+}
+
+/// 从不可定位的流（如归档中的 OUTCAR 条目）解析：全部行正向处理一次，
+/// 末尾数据后出现的值覆盖先出现的值，头部数据只取前 `HEAD_BYTES` 字节
+pub fn parse_outcar_reader(reader: impl BufRead, structure_name: &str) -> io::Result<DftResult> {
+    let mut head = OutcarHead::default();
+    let mut tail = OutcarTail::default();
+    tail::for_each_stream_line(reader, |offset, line| {
+        if offset <= tail::HEAD_BYTES {
+            head.consume(line);
+        }
+        tail.consume(line);
     })?;
 
-    let reader = BufReader::new(file);
+    Ok(outcar_result(structure_name, head, tail))
+}
+
+fn outcar_result(structure_name: &str, head: OutcarHead, tail: OutcarTail) -> DftResult {
     let mut result = DftResult::new(structure_name, DftCodeType::Vasp);
+    result.enthalpy_ev = tail.final_enthalpy;
+    result.energy_ev = tail.final_energy;
+    // 末尾没有逐步体积时退回头部的初始体积
+    result.volume = tail.volume.or(head.volume);
+    result.num_atoms = head.num_atoms;
+    result.pressure_kbar = tail.pressure;
+    result.max_force_ev_per_a = tail.max_force;
+    result.max_stress_gpa = tail
+        .stress_kbar
+        .map(|stress| residual_stress_gpa(&stress, tail.pulay_stress));
 
-    let mut final_enthalpy = None;
-    let mut final_energy = None;
-    let mut volume = None;
-    let mut num_atoms = None;
+    // IBRION 1/2/3 且 NSW > 0 才是几何优化；未达到精度即视为 NSW 耗尽
+    let nsw = head.nsw;
+    let is_relaxation = matches!(head.ibrion, Some(1..=3)) && nsw.is_some_and(|n| n > 0);
+    if tail.reached_accuracy {
+        result.geometry_converged = Some(true);
+    } else if is_relaxation && nsw.is_some_and(|n| tail.ionic_steps >= n) {
+        result.geometry_converged = Some(false);
+    }
 
-    for line in reader.lines() {
-        let line = match line {
-            Ok(line) => line,
-            Err(_) => continue,
-        };
+    result
+}
+
+/// OUTCAR 头部数据：INCAR 参数回显、NIONS 与初始晶胞体积
+#[derive(Default)]
+struct OutcarHead {
+    nsw: Option<usize>,
+    ibrion: Option<i32>,
+    num_atoms: Option<usize>,
+    volume: Option<f64>,
+}
+
+impl OutcarHead {
+    fn consume(&mut self, line: &str) {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("NSW") {
+            if let Some(value) = extract_number_after(line, "=") {
+                self.nsw = Some(value as usize);
+            }
+        }
+
+        if trimmed.starts_with("IBRION") {
+            if let Some(value) = extract_number_after(line, "=") {
+                self.ibrion = Some(value as i32);
+            }
+        }
+
+        if line.contains("NIONS =") {
+            if let Some(value) = extract_last_number(line) {
+                self.num_atoms = Some(value as usize);
+            }
+        }
+
+        if self.volume.is_none() && line.contains("volume of cell") {
+            self.volume = extract_last_number(line);
+        }
+    }
+}
+
+/// OUTCAR 末尾数据：窗口内正向处理，后出现的值覆盖先出现的值
+#[derive(Default)]
+struct OutcarTail {
+    final_enthalpy: Option<f64>,
+    final_energy: Option<f64>,
+    volume: Option<f64>,
+    pressure: Option<f64>,
+    pulay_stress: f64,
+    stress_kbar: Option<[f64; 6]>,
+    max_force: Option<f64>,
+    force_block: Option<ForceBlock>,
+    ionic_steps: usize,
+    reached_accuracy: bool,
+}
+
+impl OutcarTail {
+    fn consume(&mut self, line: &str) {
+        if let Some(block) = self.force_block.as_mut() {
+            if block.consume(line) {
+                self.max_force = Some(block.max_force);
+                self.force_block = None;
+            }
+            return;
+        }
+
+        if line.contains("TOTAL-FORCE (eV/Angst)") {
+            self.force_block = Some(ForceBlock::default());
+            return;
+        }
+
+        let trimmed = line.trim_start();
+
+        if line.contains("Iteration") && line.contains('(') && line.contains("----") {
+            if let Some(step) = extract_ionic_step(line) {
+                self.ionic_steps = self.ionic_steps.max(step);
+            }
+        }
+
+        if line.contains("reached required accuracy") {
+            self.reached_accuracy = true;
+        }
+
+        if trimmed.starts_with("in kB") {
+            let values: Vec<f64> = trimmed
+                .split_whitespace()
+                .skip(2)
+                .filter_map(|token| token.parse().ok())
+                .collect();
+            if let Ok(values) = <[f64; 6]>::try_from(values) {
+                self.stress_kbar = Some(values);
+            }
+        }
 
         if line.contains("enthalpy is  TOTEN") {
-            if let Some(value) = extract_number_before(&line, "eV") {
-                final_enthalpy = Some(value);
+            if let Some(value) = extract_number_before(line, "eV") {
+                self.final_enthalpy = Some(value);
             }
         }
 
         if line.contains("energy  without entropy") {
             if let Some(pos) = line.find("energy(sigma->0)") {
-                let rest = &line[pos..];
-                if let Some(value) = extract_number_after(rest, "=") {
-                    final_energy = Some(value);
+                if let Some(value) = extract_number_after(&line[pos..], "=") {
+                    self.final_energy = Some(value);
                 }
             }
         }
 
         if line.contains("volume of cell") {
-            if let Some(value) = extract_last_number(&line) {
-                volume = Some(value);
+            if let Some(value) = extract_last_number(line) {
+                self.volume = Some(value);
             }
         }
 
-        if line.contains("NIONS =") {
-            if let Some(value) = extract_last_number(&line) {
-                num_atoms = Some(value as usize);
+        if line.contains("external pressure =") {
+            // 总压力 = 残余外压 + Pulay 应力（PSTRESS 目标压力）
+            let external = extract_number_after(line, "external pressure =");
+            let pullay = extract_number_after(line, "Pullay stress =").unwrap_or(0.0);
+            self.pulay_stress = pullay;
+            if let Some(external) = external {
+                self.pressure = Some(external + pullay);
+            }
+        }
+    }
+
+    /// 最后一个离子步从 `Iteration` 行开始、以能量汇总行结束，
+    /// 两者都在窗口内时该步的应力、受力与压力也都在窗口内
+    fn is_complete(&self) -> bool {
+        self.ionic_steps > 0 && self.final_energy.is_some()
+    }
+}
+
+/// 逐行解析 OUTCAR 的逐离子步 SCF 次数、能量、最大受力与残余应力
+pub fn parse_outcar_trace(path: &Path) -> Result<ConvergenceTrace> {
+    let file = File::open(path).map_err(|e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    let mut trace = ConvergenceTrace::default();
+    let mut ionic_step = 0;
+    let mut scf_iterations = 0;
+    let mut pulay_stress = 0.0;
+    let mut stress_kbar: Option<[f64; 6]> = None;
+    let mut max_force = None;
+    let mut force_block: Option<ForceBlock> = None;
+
+    for line in BufReader::new(file).lines().map_while(|line| line.ok()) {
+        if let Some(block) = force_block.as_mut() {
+            if block.consume(&line) {
+                max_force = Some(block.max_force);
+                force_block = None;
+            }
+            continue;
+        }
+
+        if line.contains("TOTAL-FORCE (eV/Angst)") {
+            force_block = Some(ForceBlock::default());
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+
+        if trace.scf_limit.is_none() && trimmed.starts_with("NELM") {
+            trace.scf_limit = extract_leading_integer_after(trimmed, "=");
+        }
+
+        if line.contains("Iteration") && line.contains('(') && line.contains("----") {
+            if let Some((step, electronic)) = extract_iteration(&line) {
+                if step != ionic_step {
+                    ionic_step = step;
+                    scf_iterations = 0;
+                }
+                scf_iterations = scf_iterations.max(electronic);
+            }
+        }
+
+        if trimmed.starts_with("in kB") {
+            let values: Vec<f64> = trimmed
+                .split_whitespace()
+                .skip(2)
+                .filter_map(|token| token.parse().ok())
+                .collect();
+            if let Ok(values) = <[f64; 6]>::try_from(values) {
+                stress_kbar = Some(values);
+            }
+        }
+
+        if line.contains("external pressure =") {
+            pulay_stress = extract_number_after(&line, "Pullay stress =").unwrap_or(0.0);
+        }
+
+        // 每个离子步以 `energy  without entropy` 汇总行结束
+        if line.contains("energy  without entropy") {
+            let energy = line
+                .find("energy(sigma->0)")
+                .and_then(|pos| extract_number_after(&line[pos..], "="));
+            trace.steps.push(IonicStep {
+                step: ionic_step.max(trace.steps.len() + 1),
+                scf_iterations,
+                energy_ev: energy,
+                max_force_ev_per_a: max_force.take(),
+                max_stress_gpa: stress_kbar
+                    .take()
+                    .map(|stress| residual_stress_gpa(&stress, pulay_stress)),
+            });
+        }
+    }
+
+    Ok(trace)
+}
+
+/// 逐行解析 OSZICAR 的逐离子步 SCF 次数与能量（OSZICAR 不含受力与应力）
+pub fn parse_oszicar_trace(path: &Path) -> Result<ConvergenceTrace> {
+    let file = File::open(path).map_err(|e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    let mut trace = ConvergenceTrace::default();
+    let mut scf_iterations = 0;
+
+    for line in BufReader::new(file).lines().map_while(|line| line.ok()) {
+        let mut tokens = line.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+
+        // 电子步行：`DAV:   3   ...`、`RMM:  12   ...`
+        if first.len() <= 4 && first.ends_with(':') {
+            if let Some(n) = tokens.next().and_then(|token| token.parse::<usize>().ok()) {
+                scf_iterations = scf_iterations.max(n);
+            }
+            continue;
+        }
+
+        // 离子步行：`   3 F= -.12345E+03 E0= -.12344E+03  d E =-.1E-02`
+        if line.contains(" F=") {
+            if let Ok(step) = first.parse::<usize>() {
+                trace.steps.push(IonicStep {
+                    step,
+                    scf_iterations,
+                    energy_ev: extract_number_after(&line, "E0="),
+                    max_force_ev_per_a: None,
+                    max_stress_gpa: None,
+                });
+                scf_iterations = 0;
             }
         }
     }
 
-    result.enthalpy_ev = final_enthalpy;
-    result.energy_ev = final_energy;
-    result.volume = volume;
-    result.num_atoms = num_atoms;
+    Ok(trace)
+}
+
+/// `Iteration    12(   3)` 中的 (离子步, 电子步)
+fn extract_iteration(line: &str) -> Option<(usize, usize)> {
+    let after = &line[line.find("Iteration")? + "Iteration".len()..];
+    let open = after.find('(')?;
+    let close = after.find(')')?;
+    let ionic = after[..open].trim().parse().ok()?;
+    let electronic = after[open + 1..close].trim().parse().ok()?;
+    Some((ionic, electronic))
+}
+
+/// `NELM   =     60;   NELMIN=  2` 中 `=` 后的整数
+fn extract_leading_integer_after(s: &str, marker: &str) -> Option<usize> {
+    let after = s[s.find(marker)? + marker.len()..].trim_start();
+    let digits: String = after.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// TOTAL-FORCE 块解析状态：表头后第一条分隔线开始，第二条分隔线结束
+#[derive(Default)]
+struct ForceBlock {
+    started: bool,
+    max_force: f64,
+}
+
+impl ForceBlock {
+    /// 处理一行；块结束时返回 true
+    fn consume(&mut self, line: &str) -> bool {
+        if line.trim_start().starts_with("---") {
+            if self.started {
+                return true;
+            }
+            self.started = true;
+            return false;
+        }
+
+        let values: Vec<f64> = line
+            .split_whitespace()
+            .filter_map(|token| token.parse().ok())
+            .collect();
+        if values.len() >= 6 {
+            let force = values[3..6].iter().map(|f| f * f).sum::<f64>().sqrt();
+            self.max_force = self.max_force.max(force);
+        }
+
+        false
+    }
+}
+
+/// `Iteration    12(   3)` 中的离子步编号
+fn extract_ionic_step(line: &str) -> Option<usize> {
+    let after = &line[line.find("Iteration")? + "Iteration".len()..];
+    after[..after.find('(')?].trim().parse().ok()
+}
 
-    Ok(result)
+/// 相对 PSTRESS 目标压力的最大残余应力分量 (kB → GPa)
+fn residual_stress_gpa(stress: &[f64; 6], target_kbar: f64) -> f64 {
+    stress
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            if i < 3 {
+                (value - target_kbar).abs()
+            } else {
+                value.abs()
+            }
+        })
+        .fold(0.0, f64::max)
+        * 0.1
 }
 
 fn extract_number_before(s: &str, marker: &str) -> Option<f64> {
@@ -91,3 +417,64 @@ fn extract_last_number(s: &str) -> Option<f64> {
         .filter_map(|word| word.parse::<f64>().ok())
         .last()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn unique_test_file(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before UNIX_EPOCH")
+            .as_nanos();
+        std::env::temp_dir().join(format!("qutility-outcar-{name}-{nanos}"))
+    }
+
+    fn ionic_step(step: usize, energy: f64, stress: f64) -> String {
+        format!(
+            "--------------------------------------- Iteration {step:>6}(   1)  ---------------------------------------
+  in kB  {stress:.5} {stress:.5} {stress:.5} 0.00000 0.00000 0.00000
+  external pressure =        {stress:.2} kB  Pullay stress =        0.00 kB
+  volume of cell :       {volume:.2}
+ POSITION                                       TOTAL-FORCE (eV/Angst)
+ -----------------------------------------------------------------------------------
+      0.00000      0.00000      0.00000         0.{step}0000      0.00000      0.00000
+ -----------------------------------------------------------------------------------
+  energy  without entropy=     {energy:.6}  energy(sigma->0) =     {energy:.6}
+",
+            volume = 100.0 + step as f64
+        )
+    }
+
+    #[test]
+    fn reads_final_step_from_tail_and_parameters_from_head() {
+        let path = unique_test_file("large");
+        let mut content = String::from(
+            "   NIONS =       1\n   NSW    =      3    number of steps for IOM\n   IBRION =      2    ionic relax\n",
+        );
+        content.push_str(&ionic_step(1, -10.0, 50.0));
+        // 中间的填充让首个末尾窗口只能看到最后一步
+        let filler = format!("{}\n", " ".repeat(99));
+        content.push_str(&filler.repeat(12_000));
+        content.push_str(&ionic_step(2, -11.0, 20.0));
+        content.push_str(&filler.repeat(12_000));
+        content.push_str(&ionic_step(3, -12.0, 5.0));
+        content.push_str(" General timing and accounting informations for this job:\n");
+        fs::write(&path, content).expect("write OUTCAR");
+
+        let result = parse_outcar(&path, "large").expect("parse OUTCAR");
+        assert_eq!(result.energy_ev, Some(-12.0));
+        assert_eq!(result.num_atoms, Some(1));
+        assert_eq!(result.volume, Some(103.0));
+        assert_eq!(result.pressure_kbar, Some(5.0));
+        assert_eq!(result.max_force_ev_per_a, Some(0.3));
+        assert_eq!(result.max_stress_gpa, Some(0.5));
+        // 没有 reached required accuracy 且已用满 NSW
+        assert_eq!(result.geometry_converged, Some(false));
+
+        fs::remove_file(&path).expect("cleanup");
+    }
+}
@@ -14,8 +14,10 @@
 //! END
 //! ```
 //!
+//! 同时支持读取多结构集合（单个拼接 `.res` 文件或 `.res` 目录）。
+//!
 //! ## 依赖关系
-//! - 被 `parsers/mod.rs` 使用
+//! - 被 `parsers/mod.rs` 与 `commands/analyze/dft_postprocessing.rs` 使用
 //! - 使用 `models/structure.rs`
 
 use crate::error::{QutilityError, Result};
@@ -38,6 +40,63 @@ pub fn parse_res_file(path: &Path) -> Result<Crystal> {
     )
 }
 
+/// 解析 .res 结构集合：目录下的全部 `.res` 文件，或以 TITL/END 分块的拼接文件
+pub fn parse_res_collection(path: &Path) -> Result<Vec<Crystal>> {
+    if path.is_dir() {
+        let mut files: Vec<_> = fs::read_dir(path)
+            .map_err(|e| QutilityError::FileReadError {
+                path: path.display().to_string(),
+                source: e,
+            })?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| {
+                p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case("res"))
+            })
+            .collect();
+        files.sort();
+
+        return files.iter().map(|file| parse_res_file(file)).collect();
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    split_res_blocks(&content)
+        .into_iter()
+        .enumerate()
+        .map(|(i, block)| parse_res_content(&block, &format!("structure-{}", i + 1)))
+        .collect()
+}
+
+/// 按 TITL 行切分拼接的 .res 内容
+fn split_res_blocks(content: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        let is_title = line
+            .split_whitespace()
+            .next()
+            .is_some_and(|token| token.eq_ignore_ascii_case("TITL"));
+        if is_title && !current.trim().is_empty() {
+            blocks.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+
+    if !current.trim().is_empty() {
+        blocks.push(current);
+    }
+
+    blocks
+}
+
 /// 从字符串内容解析 .res 格式
 pub fn parse_res_content(content: &str, default_name: &str) -> Result<Crystal> {
     let mut name = default_name.to_string();
@@ -336,6 +395,34 @@ END
         assert_eq!(crystal.atoms[1].element, "Cl");
     }
 
+    #[test]
+    fn test_split_concatenated_res() {
+        let content = r#"TITL A-1 0.0 125.0 -10.0 0 0 2 (P1) n - 1
+CELL 1.54180 5.0 5.0 5.0 90.0 90.0 90.0
+LATT -1
+SFAC Na Cl
+Na 1 0.0 0.0 0.0 1.0
+Cl 2 0.5 0.5 0.5 1.0
+END
+TITL B-2 0.0 64.0 -4.0 0 0 1 (P1) n - 1
+CELL 1.54180 4.0 4.0 4.0 90.0 90.0 90.0
+LATT -1
+SFAC Fe
+Fe 1 0.0 0.0 0.0 1.0
+END
+"#;
+        let crystals: Vec<Crystal> = split_res_blocks(content)
+            .iter()
+            .map(|block| parse_res_content(block, "test").unwrap())
+            .collect();
+
+        assert_eq!(crystals.len(), 2);
+        assert_eq!(crystals[0].name, "A-1");
+        assert_eq!(crystals[0].atoms.len(), 2);
+        assert_eq!(crystals[1].name, "B-2");
+        assert_eq!(crystals[1].enthalpy, Some(-4.0));
+    }
+
     #[test]
     fn test_parse_res_missing_cell() {
         let content = r#"
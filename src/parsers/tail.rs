@@ -0,0 +1,195 @@
+//! # 大文件首尾读取
+//!
+//! 多 GB 的 OUTCAR / .castep 中，完成标记与最终能量、受力、应力都位于文件末尾，
+//! 原子数、INCAR 参数等头部信息位于文件开头。本模块只读取这两个区域：
+//! 末尾从 1 MiB 窗口开始定位（seek）并按 4 倍逐级扩大，直到所需数据齐全或覆盖全文件；
+//! 开头只读取有界区域。两者均为流式逐行读取，内存占用与文件大小无关。
+//! 归档条目等不可定位的流则整体正向读取一次，并按字节偏移区分头部区域。
+//!
+//! ## 依赖关系
+//! - 被 `parsers/outcar.rs`、`parsers/castep_out.rs`、`dft/scan.rs` 与 `dft/usage.rs` 使用
+//! - 仅依赖标准库
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// 头部信息（原子数、INCAR 参数、初始体积）的读取上限
+pub const HEAD_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 末尾首个读取窗口
+pub const TAIL_BYTES: u64 = 1024 * 1024;
+
+/// 数据不齐全时窗口的扩大倍数
+const TAIL_GROWTH: u64 = 4;
+
+/// 逐行读取文件开头至多 `limit` 字节；被上限截断的最后一行不会传给 `visit`
+pub fn for_each_head_line(path: &Path, limit: u64, mut visit: impl FnMut(&str)) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(path)?.take(limit));
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            return Ok(());
+        }
+        // 未以换行结束且已到达上限：这是被截断的行
+        if buffer.last() != Some(&b'\n') && reader.get_ref().limit() == 0 {
+            return Ok(());
+        }
+        visit(&decode_line(&buffer));
+    }
+}
+
+/// 逐行读取文件末尾 `window` 字节；窗口起点落在行中间时跳过该残行。
+/// 返回窗口是否覆盖了整个文件
+pub fn for_each_tail_line(
+    path: &Path,
+    window: u64,
+    mut visit: impl FnMut(&str),
+) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(window);
+
+    // 多读前一个字节，用来判断窗口起点是否恰好是行首
+    let mut reader = if start > 0 {
+        file.seek(SeekFrom::Start(start - 1))?;
+        let mut reader = BufReader::new(file);
+        let mut partial = Vec::new();
+        reader.read_until(b'\n', &mut partial)?;
+        reader
+    } else {
+        BufReader::new(file)
+    };
+
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            return Ok(start == 0);
+        }
+        visit(&decode_line(&buffer));
+    }
+}
+
+/// 从文件末尾开始逐级扩大窗口读取：每轮以全新状态正向处理窗口内的行，
+/// `complete` 满足或窗口已覆盖全文件时返回该状态。
+/// 窗口按几何级数增长，总读取量不超过最终窗口的 4/3
+pub fn scan_from_end<S: Default>(
+    path: &Path,
+    mut visit: impl FnMut(&mut S, &str),
+    complete: impl Fn(&S) -> bool,
+) -> io::Result<S> {
+    let mut window = TAIL_BYTES;
+
+    loop {
+        let mut state = S::default();
+        let covered = for_each_tail_line(path, window, |line| visit(&mut state, line))?;
+        if covered || complete(&state) {
+            return Ok(state);
+        }
+        window = window.saturating_mul(TAIL_GROWTH);
+    }
+}
+
+/// 逐行读取不可定位的流（如归档中的条目），同时给出该行结束处的字节偏移，
+/// 调用方据此只把前 `HEAD_BYTES` 字节内的行交给头部解析
+pub fn for_each_stream_line(
+    mut reader: impl BufRead,
+    mut visit: impl FnMut(u64, &str),
+) -> io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        offset += read as u64;
+        visit(offset, &decode_line(&buffer));
+    }
+}
+
+/// 去掉行尾换行；非 UTF-8 字节按替换字符处理，避免个别坏字节中断整个文件的解析
+fn decode_line(bytes: &[u8]) -> String {
+    let line = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn unique_test_file(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before UNIX_EPOCH")
+            .as_nanos();
+        std::env::temp_dir().join(format!("qutility-tail-{name}-{nanos}"))
+    }
+
+    #[test]
+    fn reads_bounded_head_and_tail_windows() {
+        let path = unique_test_file("windows");
+        fs::write(&path, "alpha\nbeta\r\ngamma\ndelta").expect("write file");
+
+        let mut head = Vec::new();
+        for_each_head_line(&path, 13, |line| head.push(line.to_string())).expect("head");
+        // 第 13 字节落在 gamma 中间，截断行被丢弃
+        assert_eq!(head, ["alpha", "beta"]);
+
+        let mut tail = Vec::new();
+        let covered =
+            for_each_tail_line(&path, 9, |line| tail.push(line.to_string())).expect("tail");
+        // 窗口起点落在 gamma 中间，残行被跳过
+        assert!(!covered);
+        assert_eq!(tail, ["delta"]);
+
+        let mut tail = Vec::new();
+        for_each_tail_line(&path, 11, |line| tail.push(line.to_string())).expect("tail");
+        // 窗口恰好从 gamma 行首开始
+        assert_eq!(tail, ["gamma", "delta"]);
+
+        let mut all = Vec::new();
+        let covered =
+            for_each_tail_line(&path, 1 << 20, |line| all.push(line.to_string())).expect("tail");
+        assert!(covered);
+        assert_eq!(all, ["alpha", "beta", "gamma", "delta"]);
+
+        fs::remove_file(&path).expect("cleanup");
+    }
+
+    #[test]
+    fn grows_tail_window_until_complete() {
+        let path = unique_test_file("grow");
+        let mut content = String::from("marker 7\n");
+        let filler = "x".repeat(99);
+        for _ in 0..30_000 {
+            content.push_str(&filler);
+            content.push('\n');
+        }
+        fs::write(&path, content).expect("write file");
+
+        // 标记位于约 3 MB 之前，首个 1 MiB 窗口找不到，需要扩大窗口
+        let found: Option<u32> = scan_from_end(
+            &path,
+            |state: &mut Option<u32>, line| {
+                if let Some(value) = line.strip_prefix("marker ") {
+                    *state = value.parse().ok();
+                }
+            },
+            Option::is_some,
+        )
+        .expect("scan");
+        assert_eq!(found, Some(7));
+
+        fs::remove_file(&path).expect("cleanup");
+    }
+}
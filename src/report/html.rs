@@ -0,0 +1,627 @@
+//! # HTML 报告页面
+//!
+//! 定义报告内容并渲染为单个自包含 HTML 文件：样式、表格排序脚本与 SVG 图表全部内嵌，
+//! 不引用外部资源，可直接用浏览器打开或作为附件分享。
+//!
+//! ## 依赖关系
+//! - 被 `commands/report.rs` 填充并调用
+//! - 使用 `report/plot.rs` 渲染内嵌图表、`dft/parity.rs` 的一致性统计
+
+use crate::dft::{ParityPair, ParityStats};
+use crate::error::Result;
+use crate::report::plot::{self, RankingSeries};
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 报告内容
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    /// 作业根目录或归档目录
+    pub source: String,
+    /// 排序依据（`per-atom` 等）与其单位
+    pub ranked_by: String,
+    pub unit: String,
+    /// 排序图最多绘制的结构数（每组）
+    pub plot_top: usize,
+    pub status: Vec<StatusGroup>,
+    /// 完整排名；混合代码时按代码分组，组内名次从 1 开始
+    pub ranking: Vec<RankedStructure>,
+    pub parity: Option<ParitySection>,
+    pub in_progress: Vec<InProgressJob>,
+    pub failures: Vec<FailedJob>,
+}
+
+/// 一组作业的状态计数；单一代码时无标签
+#[derive(Debug, Clone)]
+pub struct StatusGroup {
+    pub label: Option<String>,
+    pub counts: Vec<(String, usize)>,
+}
+
+/// 排名中的一个结构
+#[derive(Debug, Clone)]
+pub struct RankedStructure {
+    pub rank: usize,
+    pub group: Option<String>,
+    pub structure: String,
+    pub code: String,
+    pub formula: Option<String>,
+    /// 结构数据中记录的空间群（`.res` 或对应的 EDDP 结构）
+    pub space_group: Option<String>,
+    /// 排序量，单位为 `Report::unit`
+    pub value: f64,
+    /// 相对组内最低值的差，单位同上
+    pub delta: f64,
+    pub enthalpy_ev: Option<f64>,
+    pub num_atoms: Option<usize>,
+    pub volume_per_atom: Option<f64>,
+    pub pressure_gpa: Option<f64>,
+    pub max_force_ev_per_a: Option<f64>,
+    pub geometry_converged: Option<bool>,
+    /// a, b, c (Å), α, β, γ (°)
+    pub lattice: Option<(f64, f64, f64, f64, f64, f64)>,
+}
+
+/// EDDP–DFT 一致性统计与配对数据
+#[derive(Debug, Clone)]
+pub struct ParitySection {
+    pub source: String,
+    pub stats: ParityStats,
+    pub pairs: Vec<ParityPair>,
+    /// 没有对应 EDDP 结构的 DFT 结果数
+    pub unmatched: usize,
+}
+
+/// 未完成作业的收敛诊断
+#[derive(Debug, Clone)]
+pub struct InProgressJob {
+    pub structure: String,
+    pub code: String,
+    pub status: String,
+    pub steps: usize,
+    pub last_force_ev_per_a: Option<f64>,
+    pub steps_since_force_minimum: usize,
+    pub issues: Vec<String>,
+}
+
+/// 失败或需要处理的作业
+#[derive(Debug, Clone)]
+pub struct FailedJob {
+    pub structure: String,
+    pub code: String,
+    pub status: String,
+    pub job_id: Option<String>,
+    pub reason: Option<String>,
+    pub suggested_fix: Option<String>,
+}
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1200px; padding: 0 1em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: 0.2em; margin-top: 2em; }
+.meta, .note { color: #666; font-size: 0.9em; }
+.cards { display: flex; gap: 1em; flex-wrap: wrap; margin: 1em 0; }
+.card { border: 1px solid #ddd; border-radius: 6px; padding: 0.6em 1.2em; min-width: 8em; }
+.card b { display: block; font-size: 1.6em; }
+table { border-collapse: collapse; margin: 0.8em 0; font-size: 0.9em; }
+th, td { border: 1px solid #ddd; padding: 0.3em 0.6em; text-align: left; }
+th { background: #f4f4f4; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+table.sortable th { cursor: pointer; user-select: none; }
+table.sortable th.asc::after { content: " \25B2"; }
+table.sortable th.desc::after { content: " \25BC"; }
+.bar { background: #4c78a8; height: 0.8em; display: inline-block; }
+.warn { color: #c05000; }
+.scroll { overflow-x: auto; }
+figure { margin: 1em 0; }
+"#;
+
+/// 点击表头排序：优先按单元格的 data-sort 数值比较，否则按文本比较
+const SORT_SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable").forEach(function (table) {
+  table.querySelectorAll("th").forEach(function (th, col) {
+    th.addEventListener("click", function () {
+      var asc = !th.classList.contains("asc");
+      table.querySelectorAll("th").forEach(function (h) { h.classList.remove("asc", "desc"); });
+      th.classList.add(asc ? "asc" : "desc");
+      var body = table.tBodies[0];
+      var key = function (row) {
+        var cell = row.cells[col];
+        var raw = cell.getAttribute("data-sort");
+        var text = raw !== null ? raw : cell.textContent;
+        var num = parseFloat(text);
+        return text !== "" && !isNaN(num) && isFinite(text) ? num : text.toLowerCase();
+      };
+      Array.from(body.rows)
+        .sort(function (a, b) {
+          var x = key(a), y = key(b);
+          if (x === y) return 0;
+          if (x === "") return 1;
+          if (y === "") return -1;
+          var less = typeof x === typeof y ? x < y : typeof x === "number";
+          return (less ? -1 : 1) * (asc ? 1 : -1);
+        })
+        .forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+"#;
+
+/// 渲染完整报告
+pub fn render(report: &Report) -> Result<String> {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n",
+        escape(&report.title)
+    );
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&report.title));
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">Source: <code>{}</code> &middot; generated {} by qutility {}</p>",
+        escape(&report.source),
+        utc_timestamp(SystemTime::now()),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    render_cards(&mut html, report);
+    render_status(&mut html, report);
+    render_ranking(&mut html, report)?;
+    if let Some(parity) = &report.parity {
+        render_parity(&mut html, parity)?;
+    }
+    render_convergence(&mut html, report)?;
+    render_failures(&mut html, report);
+    render_structures(&mut html, report);
+
+    let _ = write!(html, "<script>{SORT_SCRIPT}</script>\n</body>\n</html>\n");
+    Ok(html)
+}
+
+fn render_cards(html: &mut String, report: &Report) {
+    let total: usize = report
+        .status
+        .iter()
+        .flat_map(|group| group.counts.iter().map(|(_, count)| count))
+        .sum();
+    let count = |name: &str| -> usize {
+        report
+            .status
+            .iter()
+            .flat_map(|group| group.counts.iter())
+            .filter(|(status, _)| status == name)
+            .map(|(_, count)| count)
+            .sum()
+    };
+
+    html.push_str("<div class=\"cards\">\n");
+    for (label, value) in [
+        ("Jobs", total),
+        ("Completed", count("completed")),
+        ("Ranked", report.ranking.len()),
+        ("In progress", report.in_progress.len()),
+        ("Need attention", report.failures.len()),
+    ] {
+        let _ = writeln!(html, "<div class=\"card\"><b>{value}</b>{label}</div>");
+    }
+    html.push_str("</div>\n");
+}
+
+fn render_status(html: &mut String, report: &Report) {
+    html.push_str("<h2>Status Breakdown</h2>\n");
+    for group in &report.status {
+        if let Some(label) = &group.label {
+            let _ = writeln!(html, "<h3>{}</h3>", escape(label));
+        }
+        let total: usize = group.counts.iter().map(|(_, count)| count).sum();
+        html.push_str("<table>\n<tr><th>Status</th><th>Count</th><th>Share</th></tr>\n");
+        for (status, count) in &group.counts {
+            let share = if total > 0 {
+                *count as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{count}</td>\
+                 <td><span class=\"bar\" style=\"width:{:.1}em\"></span> {share:.1}%</td></tr>",
+                escape(status),
+                share / 5.0
+            );
+        }
+        html.push_str("</table>\n");
+    }
+}
+
+fn render_ranking(html: &mut String, report: &Report) -> Result<()> {
+    let _ = writeln!(
+        html,
+        "<h2>Enthalpy Ranking ({})</h2>",
+        escape(&report.ranked_by)
+    );
+    if report.ranking.is_empty() {
+        html.push_str("<p class=\"note\">No completed calculations with a valid enthalpy.</p>\n");
+        return Ok(());
+    }
+
+    let delta_unit = format!("m{}", report.unit);
+    let mut groups: Vec<RankingSeries> = Vec::new();
+    for entry in &report.ranking {
+        let label = entry.group.as_deref().unwrap_or("DFT");
+        if groups.last().is_none_or(|series| series.label != label) {
+            groups.push(RankingSeries {
+                label,
+                points: Vec::new(),
+            });
+        }
+        if entry.rank <= report.plot_top {
+            if let Some(series) = groups.last_mut() {
+                series.points.push((entry.rank, entry.delta * 1000.0));
+            }
+        }
+    }
+    let svg = plot::ranking_svg(&groups, &format!("ΔH ({delta_unit})"))?;
+    let _ = writeln!(
+        html,
+        "<figure>{svg}<figcaption class=\"note\">Top {} structures per group relative to the lowest enthalpy.</figcaption></figure>",
+        report.plot_top
+    );
+
+    let grouped = report.ranking.iter().any(|entry| entry.group.is_some());
+    html.push_str("<p class=\"note\">Click a column header to sort.</p>\n");
+    html.push_str("<div class=\"scroll\"><table class=\"sortable\">\n<thead><tr><th>Rank</th>");
+    if grouped {
+        html.push_str("<th>Group</th>");
+    }
+    let _ = writeln!(
+        html,
+        "<th>Structure</th><th>Code</th><th>Formula</th><th>H ({unit})</th><th>ΔH ({delta_unit})</th>\
+         <th>H (eV)</th><th>Atoms</th><th>V/atom (Å³)</th><th>P (GPa)</th>\
+         <th>Max |F| (eV/Å)</th><th>Converged</th></tr></thead>\n<tbody>",
+        unit = escape(&report.unit),
+        delta_unit = escape(&delta_unit)
+    );
+    for entry in &report.ranking {
+        html.push_str("<tr>");
+        html.push_str(&num_cell(Some(entry.rank as f64), 0));
+        if grouped {
+            html.push_str(&text_cell(entry.group.as_deref()));
+        }
+        html.push_str(&text_cell(Some(&entry.structure)));
+        html.push_str(&text_cell(Some(&entry.code)));
+        html.push_str(&text_cell(entry.formula.as_deref()));
+        html.push_str(&num_cell(Some(entry.value), 6));
+        html.push_str(&num_cell(Some(entry.delta * 1000.0), 2));
+        html.push_str(&num_cell(entry.enthalpy_ev, 6));
+        html.push_str(&num_cell(entry.num_atoms.map(|n| n as f64), 0));
+        html.push_str(&num_cell(entry.volume_per_atom, 3));
+        html.push_str(&num_cell(entry.pressure_gpa, 2));
+        html.push_str(&num_cell(entry.max_force_ev_per_a, 4));
+        html.push_str(&converged_cell(entry.geometry_converged));
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table></div>\n");
+    Ok(())
+}
+
+fn render_parity(html: &mut String, parity: &ParitySection) -> Result<()> {
+    html.push_str("<h2>EDDP vs DFT Parity</h2>\n");
+    let _ = writeln!(
+        html,
+        "<p class=\"note\">EDDP ranking: <code>{}</code></p>",
+        escape(&parity.source)
+    );
+    let stats = &parity.stats;
+    html.push_str("<table>\n<tr><th>Metric</th><th>Value</th></tr>\n");
+    for (metric, value) in [
+        ("Matched structures".to_string(), stats.count.to_string()),
+        (
+            "Unmatched DFT results".to_string(),
+            parity.unmatched.to_string(),
+        ),
+        (
+            "MAE (meV/atom)".to_string(),
+            format!("{:.3}", stats.mae * 1000.0),
+        ),
+        (
+            "RMSE (meV/atom)".to_string(),
+            format!("{:.3}", stats.rmse * 1000.0),
+        ),
+        (
+            "Mean error EDDP-DFT (meV/atom)".to_string(),
+            format!("{:.3}", stats.mean_error * 1000.0),
+        ),
+        ("Spearman ρ".to_string(), format!("{:.4}", stats.spearman)),
+        ("Kendall τ".to_string(), format!("{:.4}", stats.kendall)),
+        (
+            format!("DFT top-{0} ∩ EDDP top-{0}", stats.top_n),
+            format!("{}/{}", stats.top_n_hits, stats.top_n),
+        ),
+    ] {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td></tr>",
+            escape(&metric),
+            value
+        );
+    }
+    html.push_str("</table>\n");
+
+    let points: Vec<(f64, f64)> = parity
+        .pairs
+        .iter()
+        .map(|pair| (pair.eddp_per_atom, pair.dft_per_atom))
+        .collect();
+    let _ = writeln!(html, "<figure>{}</figure>", plot::parity_svg(&points)?);
+    Ok(())
+}
+
+fn render_convergence(html: &mut String, report: &Report) -> Result<()> {
+    html.push_str("<h2>Convergence Summary</h2>\n");
+
+    let forces: Vec<(usize, f64, bool)> = report
+        .ranking
+        .iter()
+        .filter(|entry| entry.group == report.ranking[0].group)
+        .filter_map(|entry| {
+            Some((
+                entry.rank,
+                entry.max_force_ev_per_a?,
+                entry.geometry_converged != Some(false),
+            ))
+        })
+        .collect();
+    let unconverged = report
+        .ranking
+        .iter()
+        .filter(|entry| entry.geometry_converged == Some(false))
+        .count();
+    if forces.is_empty() {
+        html.push_str("<p class=\"note\">No final forces were found in the ranked outputs.</p>\n");
+    } else {
+        let _ = writeln!(
+            html,
+            "<figure>{}<figcaption class=\"note\">Final maximum residual force of each ranked structure{}.</figcaption></figure>",
+            plot::force_svg(&forces)?,
+            match &report.ranking[0].group {
+                Some(group) => format!(" ({})", escape(group)),
+                None => String::new(),
+            }
+        );
+    }
+    if unconverged > 0 {
+        let _ = writeln!(
+            html,
+            "<p class=\"warn\">{unconverged} ranked geometry optimisations did not converge.</p>"
+        );
+    }
+
+    let _ = writeln!(
+        html,
+        "<h3>Jobs in Progress ({})</h3>",
+        report.in_progress.len()
+    );
+    if report.in_progress.is_empty() {
+        html.push_str("<p class=\"note\">No incomplete or running jobs with ionic steps.</p>\n");
+        return Ok(());
+    }
+    html.push_str(
+        "<table class=\"sortable\">\n<thead><tr><th>Structure</th><th>Code</th><th>Status</th>\
+         <th>Steps</th><th>Last |F| (eV/Å)</th><th>Steps since F min</th><th>Issues</th></tr></thead>\n<tbody>\n",
+    );
+    for job in &report.in_progress {
+        html.push_str("<tr>");
+        html.push_str(&text_cell(Some(&job.structure)));
+        html.push_str(&text_cell(Some(&job.code)));
+        html.push_str(&text_cell(Some(&job.status)));
+        html.push_str(&num_cell(Some(job.steps as f64), 0));
+        html.push_str(&num_cell(job.last_force_ev_per_a, 4));
+        html.push_str(&num_cell(Some(job.steps_since_force_minimum as f64), 0));
+        html.push_str(&text_cell(Some(&job.issues.join("; "))));
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table>\n");
+    Ok(())
+}
+
+fn render_failures(html: &mut String, report: &Report) {
+    let _ = writeln!(html, "<h2>Failure Reasons ({})</h2>", report.failures.len());
+    if report.failures.is_empty() {
+        html.push_str("<p class=\"note\">No failed, timed-out or unparsable jobs.</p>\n");
+        return;
+    }
+
+    let mut reasons: BTreeMap<String, usize> = BTreeMap::new();
+    for job in &report.failures {
+        let reason = job.reason.clone().unwrap_or_else(|| job.status.clone());
+        *reasons.entry(reason).or_default() += 1;
+    }
+    let mut reasons: Vec<(String, usize)> = reasons.into_iter().collect();
+    reasons.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    html.push_str("<table>\n<tr><th>Reason</th><th>Jobs</th></tr>\n");
+    for (reason, count) in &reasons {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{count}</td></tr>",
+            escape(reason)
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str(
+        "<table class=\"sortable\">\n<thead><tr><th>Structure</th><th>Code</th><th>Status</th>\
+         <th>Job ID</th><th>Reason</th><th>Suggested fix</th></tr></thead>\n<tbody>\n",
+    );
+    for job in &report.failures {
+        html.push_str("<tr>");
+        html.push_str(&text_cell(Some(&job.structure)));
+        html.push_str(&text_cell(Some(&job.code)));
+        html.push_str(&text_cell(Some(&job.status)));
+        html.push_str(&text_cell(job.job_id.as_deref()));
+        html.push_str(&text_cell(job.reason.as_deref()));
+        html.push_str(&text_cell(job.suggested_fix.as_deref()));
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table>\n");
+}
+
+fn render_structures(html: &mut String, report: &Report) {
+    if report.ranking.is_empty() {
+        return;
+    }
+    html.push_str("<h2>Structure Details</h2>\n");
+    html.push_str(
+        "<p class=\"note\">Space groups are taken from the structure data (.res) or the matching EDDP structure when available.</p>\n",
+    );
+    html.push_str(
+        "<div class=\"scroll\"><table class=\"sortable\">\n<thead><tr><th>Structure</th><th>Formula</th>\
+         <th>Space group</th><th>Atoms</th><th>a (Å)</th><th>b (Å)</th><th>c (Å)</th>\
+         <th>α (°)</th><th>β (°)</th><th>γ (°)</th><th>V/atom (Å³)</th></tr></thead>\n<tbody>\n",
+    );
+    for entry in &report.ranking {
+        html.push_str("<tr>");
+        html.push_str(&text_cell(Some(&entry.structure)));
+        html.push_str(&text_cell(entry.formula.as_deref()));
+        html.push_str(&text_cell(entry.space_group.as_deref()));
+        html.push_str(&num_cell(entry.num_atoms.map(|n| n as f64), 0));
+        let lattice = entry.lattice;
+        for (value, precision) in [
+            (lattice.map(|l| l.0), 4),
+            (lattice.map(|l| l.1), 4),
+            (lattice.map(|l| l.2), 4),
+            (lattice.map(|l| l.3), 2),
+            (lattice.map(|l| l.4), 2),
+            (lattice.map(|l| l.5), 2),
+        ] {
+            html.push_str(&num_cell(value, precision));
+        }
+        html.push_str(&num_cell(entry.volume_per_atom, 3));
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table></div>\n");
+}
+
+fn text_cell(text: Option<&str>) -> String {
+    match text {
+        Some(text) if !text.is_empty() => format!("<td>{}</td>", escape(text)),
+        _ => "<td data-sort=\"\">-</td>".to_string(),
+    }
+}
+
+/// 数值单元格；data-sort 保存完整精度供表头排序使用
+fn num_cell(value: Option<f64>, precision: usize) -> String {
+    match value {
+        Some(value) => format!("<td class=\"num\" data-sort=\"{value}\">{value:.precision$}</td>"),
+        None => "<td class=\"num\" data-sort=\"\">-</td>".to_string(),
+    }
+}
+
+fn converged_cell(converged: Option<bool>) -> String {
+    match converged {
+        Some(true) => "<td>yes</td>".to_string(),
+        Some(false) => "<td class=\"warn\">no</td>".to_string(),
+        None => "<td data-sort=\"\">-</td>".to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `YYYY-MM-DD HH:MM UTC` 格式的时间
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // 公历日期换算（Howard Hinnant 的 civil_from_days）
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn renders_self_contained_page_with_escaped_names() {
+        let report = Report {
+            title: "Campaign <A&B>".to_string(),
+            source: "jobs".to_string(),
+            ranked_by: "per-atom".to_string(),
+            unit: "eV/atom".to_string(),
+            plot_top: 50,
+            status: vec![StatusGroup {
+                label: None,
+                counts: vec![("completed".to_string(), 1), ("failed".to_string(), 1)],
+            }],
+            ranking: vec![RankedStructure {
+                rank: 1,
+                group: None,
+                structure: "Si-<1>".to_string(),
+                code: "VASP".to_string(),
+                formula: Some("Si2".to_string()),
+                space_group: Some("Fd-3m".to_string()),
+                value: -5.4,
+                delta: 0.0,
+                enthalpy_ev: Some(-10.8),
+                num_atoms: Some(2),
+                volume_per_atom: Some(20.0),
+                pressure_gpa: Some(0.0),
+                max_force_ev_per_a: Some(0.01),
+                geometry_converged: Some(true),
+                lattice: Some((3.8, 3.8, 3.8, 60.0, 60.0, 60.0)),
+            }],
+            parity: None,
+            in_progress: Vec::new(),
+            failures: vec![FailedJob {
+                structure: "Si-2".to_string(),
+                code: "VASP".to_string(),
+                status: "failed".to_string(),
+                job_id: None,
+                reason: Some("ZBRENT: fatal error".to_string()),
+                suggested_fix: None,
+            }],
+        };
+
+        let html = render(&report).expect("render report");
+        assert!(html.contains("<title>Campaign &lt;A&amp;B&gt;</title>"));
+        assert!(html.contains("<td>Si-&lt;1&gt;</td>"));
+        assert!(html.contains("<td>Fd-3m</td>"));
+        assert!(html.contains("ZBRENT: fatal error"));
+        assert!(html.contains("table class=\"sortable\""));
+        assert_eq!(html.matches("<svg").count(), 2);
+        // 不引用任何外部资源
+        assert!(!html.contains("src=\"http") && !html.contains("href=\"http"));
+    }
+
+    #[test]
+    fn formats_utc_timestamps() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(utc_timestamp(time), "2024-02-29 12:34 UTC");
+    }
+}
@@ -0,0 +1,18 @@
+//! # 报告模块
+//!
+//! 生成自包含的 HTML 计算批次报告。
+//!
+//! ## 子模块
+//! - `html`: 报告内容与页面渲染
+//! - `plot`: 内嵌 SVG 图表，以及分析子命令共用的图像文件写出
+//!
+//! ## 依赖关系
+//! - 被 `commands/report.rs` 使用
+//! - 使用 `dft/` 的一致性统计类型与 `plotters`
+
+pub mod html;
+pub mod plot;
+
+pub use html::{
+    render, FailedJob, InProgressJob, ParitySection, RankedStructure, Report, StatusGroup,
+};
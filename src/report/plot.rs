@@ -0,0 +1,280 @@
+//! # 报告内嵌图表
+//!
+//! 使用 `plotters` 的 SVG 后端把排序图、EDDP–DFT parity 图与残余受力图渲染为字符串，
+//! 直接内嵌到 HTML 报告中，不写出单独的图像文件。
+//! 另提供 `PlotFigure` 与 `save_plot`：分析子命令的图按输出扩展名写为 SVG 或位图文件。
+//!
+//! ## 依赖关系
+//! - 被 `report/html.rs` 调用
+//! - `save_plot` 被 `commands/analyze/` 的 dft-pp、eos、transitions 与 convergence 子命令使用
+//! - 使用 `plotters`
+
+use crate::error::{QutilityError, Result};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+/// 每组排序曲线的颜色，按组循环使用
+const GROUP_COLORS: [RGBColor; 5] = [
+    RGBColor(31, 119, 180),
+    RGBColor(214, 39, 40),
+    RGBColor(44, 160, 44),
+    RGBColor(148, 103, 189),
+    RGBColor(255, 127, 14),
+];
+
+/// 可绘制到任意 plotters 后端的图像
+pub trait PlotFigure {
+    /// 画布大小（像素）
+    const SIZE: (u32, u32);
+
+    /// 在绘图区上作图；写出文件由 `save_plot` 负责
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static;
+}
+
+/// 把图写入文件：扩展名为 svg（不区分大小写）时输出 SVG，否则按扩展名输出位图
+pub fn save_plot<F: PlotFigure>(figure: &F, output_path: &Path) -> Result<()> {
+    let is_svg = output_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+
+    if is_svg {
+        let root = SVGBackend::new(output_path, F::SIZE).into_drawing_area();
+        figure.draw(&root)?;
+        root.present().map_err(plot_error)
+    } else {
+        let root = BitMapBackend::new(output_path, F::SIZE).into_drawing_area();
+        figure.draw(&root)?;
+        root.present().map_err(plot_error)
+    }
+}
+
+/// 一组排序曲线：组标签与 (名次, 相对焓) 点
+pub struct RankingSeries<'a> {
+    pub label: &'a str,
+    pub points: Vec<(usize, f64)>,
+}
+
+/// 相对焓随名次变化的折线图，每组一条曲线
+pub fn ranking_svg(series: &[RankingSeries], y_desc: &str) -> Result<String> {
+    let max_rank = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|(rank, _)| *rank))
+        .max()
+        .unwrap_or(1);
+    let (y_min, y_max) = value_range(series.iter().flat_map(|s| s.points.iter().map(|p| p.1)));
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (800, 450)).into_drawing_area();
+        root.fill(&WHITE).map_err(plot_error)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(70)
+            .build_cartesian_2d(0.5..(max_rank as f64 + 0.5), y_min..y_max)
+            .map_err(plot_error)?;
+        chart
+            .configure_mesh()
+            .x_desc("Rank")
+            .y_desc(y_desc)
+            .draw()
+            .map_err(plot_error)?;
+
+        for (i, s) in series.iter().enumerate() {
+            let color = GROUP_COLORS[i % GROUP_COLORS.len()];
+            let points: Vec<(f64, f64)> = s.points.iter().map(|(x, y)| (*x as f64, *y)).collect();
+            chart
+                .draw_series(LineSeries::new(points.clone(), color.stroke_width(1)))
+                .map_err(plot_error)?;
+            chart
+                .draw_series(points.iter().map(|p| Circle::new(*p, 4, color.filled())))
+                .map_err(plot_error)?
+                .label(s.label)
+                .legend(move |(x, y)| Circle::new((x + 10, y), 4, color.filled()));
+        }
+
+        if series.len() > 1 {
+            chart
+                .configure_series_labels()
+                .position(SeriesLabelPosition::UpperLeft)
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()
+                .map_err(plot_error)?;
+        }
+        root.present().map_err(plot_error)?;
+    }
+    Ok(svg)
+}
+
+/// EDDP 与 DFT 每原子焓的散点图，两轴同范围并带 y = x 参考线
+pub fn parity_svg(points: &[(f64, f64)]) -> Result<String> {
+    let (lo, hi) = value_range(points.iter().flat_map(|(x, y)| [*x, *y]));
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (520, 520)).into_drawing_area();
+        root.fill(&WHITE).map_err(plot_error)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(15)
+            .x_label_area_size(45)
+            .y_label_area_size(80)
+            .build_cartesian_2d(lo..hi, lo..hi)
+            .map_err(plot_error)?;
+        chart
+            .configure_mesh()
+            .x_desc("EDDP enthalpy (eV/atom)")
+            .y_desc("DFT enthalpy (eV/atom)")
+            .draw()
+            .map_err(plot_error)?;
+
+        chart
+            .draw_series(LineSeries::new(
+                [(lo, lo), (hi, hi)],
+                BLACK.mix(0.5).stroke_width(1),
+            ))
+            .map_err(plot_error)?;
+        chart
+            .draw_series(
+                points
+                    .iter()
+                    .map(|p| Circle::new(*p, 4, GROUP_COLORS[0].mix(0.7).filled())),
+            )
+            .map_err(plot_error)?;
+        root.present().map_err(plot_error)?;
+    }
+    Ok(svg)
+}
+
+/// 按名次排列的最终最大残余受力，几何优化未收敛的点以橙色标出
+pub fn force_svg(points: &[(usize, f64, bool)]) -> Result<String> {
+    let max_rank = points.iter().map(|p| p.0).max().unwrap_or(1);
+    let y_max = points.iter().map(|p| p.1).fold(0.0, f64::max).max(1e-3) * 1.1;
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (800, 350)).into_drawing_area();
+        root.fill(&WHITE).map_err(plot_error)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(70)
+            .build_cartesian_2d(0.5..(max_rank as f64 + 0.5), 0.0..y_max)
+            .map_err(plot_error)?;
+        chart
+            .configure_mesh()
+            .x_desc("Rank")
+            .y_desc("Max |F| (eV/Å)")
+            .draw()
+            .map_err(plot_error)?;
+
+        let converged = GROUP_COLORS[0];
+        let unconverged = GROUP_COLORS[4];
+        chart
+            .draw_series(
+                points
+                    .iter()
+                    .filter(|p| p.2)
+                    .map(|p| Circle::new((p.0 as f64, p.1), 4, converged.filled())),
+            )
+            .map_err(plot_error)?
+            .label("Converged")
+            .legend(move |(x, y)| Circle::new((x + 10, y), 4, converged.filled()));
+        chart
+            .draw_series(
+                points
+                    .iter()
+                    .filter(|p| !p.2)
+                    .map(|p| Circle::new((p.0 as f64, p.1), 4, unconverged.filled())),
+            )
+            .map_err(plot_error)?
+            .label("Not converged")
+            .legend(move |(x, y)| Circle::new((x + 10, y), 4, unconverged.filled()));
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(plot_error)?;
+        root.present().map_err(plot_error)?;
+    }
+    Ok(svg)
+}
+
+/// 数据范围加 5% 边距；只有一个值时给出最小宽度
+fn value_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    if !lo.is_finite() {
+        return (0.0, 1.0);
+    }
+    let margin = ((hi - lo).abs() * 0.05).max(1e-3);
+    (lo - margin, hi + margin)
+}
+
+fn plot_error<E: std::fmt::Debug>(e: E) -> QutilityError {
+    QutilityError::Other(format!("Plot rendering failed: {e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    use std::fs;
+
+    #[test]
+    fn renders_inline_svg_strings() {
+        let svg = ranking_svg(
+            &[RankingSeries {
+                label: "VASP",
+                points: vec![(1, 0.0), (2, 3.5), (3, 12.0)],
+            }],
+            "ΔH (meV/atom)",
+        )
+        .expect("ranking plot");
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Rank"));
+
+        let svg = force_svg(&[(1, 0.01, true), (2, 0.2, false)]).expect("force plot");
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    struct Blank;
+
+    impl PlotFigure for Blank {
+        const SIZE: (u32, u32) = (64, 48);
+
+        fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+        where
+            DB::ErrorType: 'static,
+        {
+            root.fill(&WHITE).map_err(plot_error)
+        }
+    }
+
+    #[test]
+    fn saves_svg_by_extension() {
+        let root = unique_test_dir("report-plot");
+        fs::create_dir_all(&root).expect("create dir");
+        let path = root.join("blank.SVG");
+
+        save_plot(&Blank, &path).expect("save plot");
+        let content = fs::read_to_string(&path).expect("read plot");
+        assert!(content.starts_with("<svg"));
+        assert!(content.contains("width=\"64\""));
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
@@ -0,0 +1,120 @@
+//! # 外部命令执行器
+//!
+//! 以可替换的方式执行外部命令并返回标准输出，便于用本地桩脚本替代调度器命令。
+//!
+//! ## 依赖关系
+//! - 被 `utils/scheduler.rs` 及各调度器后端（slurm、pbs、lsf、sge）的提交与队列查询使用
+//! - 使用 `error.rs`
+
+use crate::error::{QutilityError, Result};
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// 外部命令执行接口
+pub trait CommandRunner {
+    /// 执行 `program args...`，成功时返回标准输出
+    fn run(&self, program: &str, args: &[String]) -> Result<String>;
+
+    /// 执行命令并把 `input` 写入其标准输入（如 LSF 的 `bsub < script`）
+    fn run_with_input(&self, program: &str, args: &[String], input: &str) -> Result<String>;
+}
+
+/// 通过 `std::process::Command` 执行命令，可按程序名替换为其他可执行文件
+#[derive(Debug, Clone, Default)]
+pub struct SystemCommandRunner {
+    overrides: HashMap<String, String>,
+}
+
+impl SystemCommandRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 将 `program` 替换为 `replacement`（例如测试用桩脚本路径）
+    pub fn with_override(
+        mut self,
+        program: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.overrides.insert(program.into(), replacement.into());
+        self
+    }
+
+    /// 批量替换，如 `--scheduler-cmd NAME=PATH` 给出的各项
+    pub fn with_overrides<K, V>(self, overrides: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        overrides
+            .into_iter()
+            .fold(self, |runner, (program, replacement)| {
+                runner.with_override(program, replacement)
+            })
+    }
+
+    fn resolve<'a>(&'a self, program: &'a str) -> &'a str {
+        self.overrides
+            .get(program)
+            .map(String::as_str)
+            .unwrap_or(program)
+    }
+}
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(&self, program: &str, args: &[String]) -> Result<String> {
+        let resolved = self.resolve(program);
+        let output = Command::new(resolved)
+            .args(args)
+            .output()
+            .map_err(|e| spawn_error(resolved, e))?;
+        finish(resolved, args, output)
+    }
+
+    fn run_with_input(&self, program: &str, args: &[String], input: &str) -> Result<String> {
+        let resolved = self.resolve(program);
+        let mut child = Command::new(resolved)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| spawn_error(resolved, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| spawn_error(resolved, e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| spawn_error(resolved, e))?;
+        finish(resolved, args, output)
+    }
+}
+
+fn spawn_error(resolved: &str, e: std::io::Error) -> QutilityError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        QutilityError::CommandNotFound {
+            command: resolved.to_string(),
+        }
+    } else {
+        QutilityError::CommandFailed {
+            command: resolved.to_string(),
+            stderr: e.to_string(),
+        }
+    }
+}
+
+/// 非零退出码视为失败，否则返回标准输出
+fn finish(resolved: &str, args: &[String], output: std::process::Output) -> Result<String> {
+    if !output.status.success() {
+        return Err(QutilityError::CommandFailed {
+            command: format!("{} {}", resolved, args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,236 @@
+//! # 用户配置与集群配置档案
+//!
+//! 定位用户配置目录，并从 `~/.config/qutility/config.toml` 与项目目录下的 `qutility.toml` 读取命名的集群配置档案
+//! （调度器参数、模块、环境变量、MPI 启动器、可执行文件、脚本前导行与作业脚本模板）。项目文件中的同名档案整体覆盖用户档案。
+//!
+//! ## 依赖关系
+//! - 被 `commands/submit.rs` 用于解析 `--profile`；`user_config_dir` 被 `dft/rules.rs` 用于定位用户规则
+//! - 使用 `utils/scheduler.rs` 的调度器类型与 `error.rs`
+
+use crate::error::{QutilityError, Result};
+use crate::utils::scheduler::SchedulerKind;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 用户级配置文件名（位于 `~/.config/qutility/`）
+pub const USER_CONFIG_FILE: &str = "config.toml";
+
+/// 项目级配置文件名（位于当前目录）
+pub const PROJECT_CONFIG_FILE: &str = "qutility.toml";
+
+/// 集群配置档案；未设置的字段使用命令行参数或内置默认值
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClusterProfile {
+    pub scheduler: Option<SchedulerKind>,
+    pub partition: Option<String>,
+    pub constraint: Option<String>,
+    pub nodes: Option<u32>,
+    pub ntasks: Option<u32>,
+    pub cpus_per_task: Option<u32>,
+    pub mem_per_cpu: Option<String>,
+    pub time: Option<String>,
+    pub sge_pe: Option<String>,
+    /// MPI 启动命令，`{np}` 替换为进程数，如 `mpirun -np {np}` 或 `srun`
+    pub mpi_launcher: Option<String>,
+    pub castep_exec: Option<String>,
+    pub castep_np: Option<u32>,
+    pub castep_modules: Option<Vec<String>>,
+    pub vasp_exec: Option<String>,
+    pub vasp_np: Option<u32>,
+    pub vasp_modules: Option<Vec<String>>,
+    /// 作业脚本中导出的环境变量（按名称排序写出）
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// 加载模块之前原样写入作业脚本的命令行
+    #[serde(default)]
+    pub preamble: Vec<String>,
+    /// 作业脚本模板文件；相对路径相对于配置文件所在目录
+    pub script_template: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    /// 未指定 `--profile` 时使用的档案
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, ClusterProfile>,
+}
+
+/// 依次读取用户配置与 `project_dir` 下的项目配置，返回 `name`（未给出时为 `default_profile`）对应的档案。
+///
+/// 两者都未给出档案名时返回 None；档案名不存在时报错并列出可用档案。
+pub fn load_profile(
+    name: Option<&str>,
+    project_dir: &Path,
+) -> Result<Option<(String, ClusterProfile)>> {
+    let candidates: Vec<PathBuf> = [
+        user_config_dir().map(|dir| dir.join(USER_CONFIG_FILE)),
+        Some(project_dir.join(PROJECT_CONFIG_FILE)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    select_profile(name, &candidates)
+}
+
+/// 按顺序合并 `paths` 中存在的配置文件（后者的同名档案与 `default_profile` 覆盖前者），再选出档案
+fn select_profile(
+    name: Option<&str>,
+    paths: &[PathBuf],
+) -> Result<Option<(String, ClusterProfile)>> {
+    let mut merged = ConfigFile::default();
+    let mut sources = Vec::new();
+    for path in paths.iter().filter(|p| p.is_file()) {
+        let mut file = read_config(path)?;
+        // 模板路径相对于所在配置文件的目录
+        for profile in file.profiles.values_mut() {
+            if let (Some(template), Some(dir)) = (&profile.script_template, path.parent()) {
+                profile.script_template = Some(dir.join(template));
+            }
+        }
+        if file.default_profile.is_some() {
+            merged.default_profile = file.default_profile;
+        }
+        merged.profiles.extend(file.profiles);
+        sources.push(path.display().to_string());
+    }
+
+    let Some(name) = name.map(str::to_string).or(merged.default_profile) else {
+        return Ok(None);
+    };
+    match merged.profiles.remove(&name) {
+        Some(profile) => Ok(Some((name, profile))),
+        None => {
+            let available: Vec<&str> = merged.profiles.keys().map(String::as_str).collect();
+            Err(QutilityError::InvalidArgument(format!(
+                "Unknown profile '{name}' (available: {}; searched: {})",
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                },
+                if sources.is_empty() {
+                    format!("no {USER_CONFIG_FILE} or {PROJECT_CONFIG_FILE} found")
+                } else {
+                    sources.join(", ")
+                }
+            )))
+        }
+    }
+}
+
+fn read_config(path: &Path) -> Result<ConfigFile> {
+    let content = fs::read_to_string(path).map_err(|e| QutilityError::FileReadError {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    toml::from_str(&content).map_err(|e| QutilityError::ParseError {
+        format: "qutility config TOML".to_string(),
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// 用户配置目录：`$XDG_CONFIG_HOME/qutility` 或 `~/.config/qutility`
+pub fn user_config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("qutility"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::unique_test_dir;
+
+    #[test]
+    fn project_profiles_override_and_select_default() {
+        let root = unique_test_dir("config-profiles");
+        let user = root.join("user").join(USER_CONFIG_FILE);
+        let project = root.join("project").join(PROJECT_CONFIG_FILE);
+        fs::create_dir_all(root.join("user")).expect("create user dir");
+        fs::create_dir_all(root.join("project")).expect("create project dir");
+        fs::write(
+            &user,
+            r#"
+default_profile = "archer"
+
+[profiles.unity]
+partition = "x86"
+time = "72:00:00"
+
+[profiles.home]
+scheduler = "lsf"
+"#,
+        )
+        .expect("write user config");
+        fs::write(
+            &project,
+            r#"
+default_profile = "unity"
+
+[profiles.unity]
+scheduler = "slurm"
+partition = "arm"
+constraint = "neoverse_v2"
+castep_modules = ["airss/arm-v2/0.2", "castep/arm-v2/25.12"]
+preamble = ["source /etc/profile.d/modules.sh"]
+script_template = "templates/srun.sh"
+
+[profiles.unity.env]
+MODULEPATH = "/home/me/Modulefiles:$MODULEPATH"
+
+[profiles.archer]
+scheduler = "torque"
+mpi_launcher = "aprun -n {np}"
+"#,
+        )
+        .expect("write project config");
+        let paths = [user.clone(), project.clone()];
+
+        // 项目文件的 default_profile 与同名档案整体覆盖用户文件
+        let (name, profile) = select_profile(None, &paths)
+            .expect("load")
+            .expect("default profile");
+        assert_eq!(name, "unity");
+        assert_eq!(profile.partition.as_deref(), Some("arm"));
+        assert_eq!(profile.time, None);
+        assert_eq!(profile.castep_modules.as_ref().map(Vec::len), Some(2));
+        assert!(profile.env.contains_key("MODULEPATH"));
+        assert_eq!(
+            profile.script_template,
+            Some(root.join("project").join("templates/srun.sh"))
+        );
+
+        let (_, archer) = select_profile(Some("archer"), &paths)
+            .expect("load")
+            .expect("named profile");
+        assert_eq!(archer.scheduler, Some(SchedulerKind::Pbs));
+        let (_, home) = select_profile(Some("home"), &paths)
+            .expect("load")
+            .expect("user profile");
+        assert_eq!(home.scheduler, Some(SchedulerKind::Lsf));
+
+        let err = select_profile(Some("missing"), &paths).expect_err("unknown profile");
+        assert!(err.to_string().contains("archer, home, unity"));
+
+        let missing = [root.join("none.toml")];
+        assert!(select_profile(None, &missing).expect("no config").is_none());
+        assert!(select_profile(Some("archer"), &missing)
+            .expect_err("no config")
+            .to_string()
+            .contains("no config.toml or qutility.toml found"));
+
+        fs::write(&project, "[profiles.x]\nqueue = 1\n").expect("write");
+        assert!(select_profile(Some("x"), &paths).is_err());
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
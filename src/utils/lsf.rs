@@ -0,0 +1,191 @@
+//! # LSF 调度器后端
+//!
+//! 实现 `Scheduler`：生成 `#BSUB` 脚本头，通过 `bsub < script` 提交（脚本经标准输入传入，`#BSUB` 指令才会生效），
+//! 并以 `bjobs -o` 的定界输出查询作业；LSF 状态归一化为 Slurm 状态名。
+//!
+//! ## 依赖关系
+//! - 实现 `utils/scheduler.rs` 的 `Scheduler`
+//! - 使用 `utils/command.rs` 执行调度器命令
+
+use crate::error::{QutilityError, Result};
+use crate::utils::command::CommandRunner;
+use crate::utils::scheduler::{
+    directives, log_path, memory_mib, require_job_id, time_limit_seconds, JobConfig, Scheduler,
+    SchedulerJob, ScriptResources,
+};
+
+use std::fs;
+use std::path::Path;
+
+/// `bjobs -o` 的输出列与分隔符
+const BJOBS_FORMAT: &str = "jobid job_name stat delimiter='|'";
+
+/// IBM Spectrum LSF 后端
+pub struct Lsf;
+
+impl Scheduler for Lsf {
+    fn name(&self) -> &'static str {
+        "LSF"
+    }
+
+    fn script_name(&self) -> &'static str {
+        "submit.lsf"
+    }
+
+    fn script_header(&self, config: &JobConfig, workdir: &Path) -> String {
+        let mut lines = vec![format!("#BSUB -J {}", config.job_name)];
+        if !config.partition.is_empty() {
+            lines.push(format!("#BSUB -q {}", config.partition));
+        }
+        lines.push(format!("#BSUB -n {}", config.cores()));
+        lines.push(format!(
+            "#BSUB -R \"span[ptile={}]\"",
+            config.cores_per_node()
+        ));
+        if let Some(mib) = memory_mib(&config.mem_per_cpu) {
+            lines.push(format!("#BSUB -R \"rusage[mem={mib}MB]\""));
+        }
+        if !config.constraint.is_empty() {
+            lines.push(format!("#BSUB -R \"select[{}]\"", config.constraint));
+        }
+        if let Some(secs) = time_limit_seconds(&config.time_limit) {
+            let minutes = secs.div_ceil(60);
+            lines.push(format!("#BSUB -W {}:{:02}", minutes / 60, minutes % 60));
+        }
+        lines.push(format!("#BSUB -cwd {}", workdir.display()));
+        lines.push(format!(
+            "#BSUB -o {}",
+            log_path(workdir, &config.job_name, "out")
+        ));
+        lines.push(format!(
+            "#BSUB -e {}",
+            log_path(workdir, &config.job_name, "err")
+        ));
+        lines.join("\n") + "\n"
+    }
+
+    fn submit(&self, runner: &dyn CommandRunner, script: &Path, _workdir: &Path) -> Result<String> {
+        let content = fs::read_to_string(script).map_err(|e| QutilityError::FileReadError {
+            path: script.display().to_string(),
+            source: e,
+        })?;
+        let stdout = runner.run_with_input("bsub", &[], &content)?;
+        require_job_id(self, "bsub", &stdout)
+    }
+
+    /// 读取 `-n` 核数与 `-W [HH:]MM` 运行时间上限
+    fn script_resources(&self, script: &str) -> ScriptResources {
+        let mut resources = ScriptResources::default();
+        for args in directives(script, "#BSUB") {
+            match args.as_slice() {
+                ["-n", cores, ..] => resources.cores = cores.parse().ok(),
+                ["-W", limit, ..] => resources.time_limit_sec = run_limit_seconds(limit),
+                _ => {}
+            }
+        }
+        resources
+    }
+
+    /// `TERM_RUNLIMIT: job killed after reaching LSF run time limit.`
+    fn time_limit_exceeded(&self, log: &str) -> bool {
+        log.contains("TERM_RUNLIMIT")
+    }
+
+    /// 解析 "Job <123> is submitted to queue <normal>."
+    fn parse_job_id(&self, stdout: &str) -> Option<String> {
+        stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Job <"))
+            .and_then(|rest| rest.split_once('>'))
+            .map(|(id, _)| id.to_string())
+    }
+
+    fn query_queue(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>> {
+        Ok(run_bjobs(runner, user, false)?
+            .into_iter()
+            .filter(|job| matches!(job.state.as_str(), "PENDING" | "RUNNING" | "SUSPENDED"))
+            .collect())
+    }
+
+    /// `bjobs -a` 包含最近结束的作业；保留时长由 LSF 的 CLEAN_PERIOD 决定，`since` 不使用
+    fn query_history(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+        _since: &str,
+    ) -> Result<Vec<SchedulerJob>> {
+        run_bjobs(runner, user, true)
+    }
+}
+
+fn run_bjobs(
+    runner: &dyn CommandRunner,
+    user: Option<&str>,
+    all: bool,
+) -> Result<Vec<SchedulerJob>> {
+    let mut args = vec![
+        "-noheader".to_string(),
+        "-o".to_string(),
+        BJOBS_FORMAT.to_string(),
+    ];
+    if all {
+        args.push("-a".to_string());
+    }
+    if let Some(user) = user {
+        args.push("-u".to_string());
+        args.push(user.to_string());
+    }
+
+    Ok(parse_bjobs(&runner.run("bjobs", &args)?))
+}
+
+/// 解析 `ID|NAME|STAT` 行；数组作业名 `name[3]` 的任务号并入 ID（`123_3`）
+fn parse_bjobs(output: &str) -> Vec<SchedulerJob> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(3, '|');
+            let id = fields.next()?.trim();
+            let name = fields.next()?.trim();
+            let state = fields.next()?.trim();
+            if id.is_empty() || !id.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+
+            let (id, name) = match name.strip_suffix(']').and_then(|n| n.rsplit_once('[')) {
+                Some((base, task)) => (format!("{id}_{task}"), base.to_string()),
+                None => (id.to_string(), name.to_string()),
+            };
+            Some(SchedulerJob {
+                id,
+                name,
+                state: normalize_state(state).to_string(),
+            })
+        })
+        .collect()
+}
+
+/// LSF 状态转换为 Slurm 状态名
+fn normalize_state(state: &str) -> &'static str {
+    match state {
+        "PEND" | "PSUSP" | "WAIT" => "PENDING",
+        "RUN" | "PROV" => "RUNNING",
+        "USUSP" | "SSUSP" => "SUSPENDED",
+        "DONE" => "COMPLETED",
+        "EXIT" | "ZOMBI" => "FAILED",
+        _ => "UNKNOWN",
+    }
+}
+
+/// `bsub -W` 的 `[HH:]MM` 格式（只有一个字段时为分钟）
+fn run_limit_seconds(limit: &str) -> Option<u64> {
+    let minutes = match limit.split_once(':') {
+        Some((hours, minutes)) => hours.parse::<u64>().ok()? * 60 + minutes.parse::<u64>().ok()?,
+        None => limit.parse::<u64>().ok()?,
+    };
+    Some(minutes * 60)
+}
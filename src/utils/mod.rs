@@ -1,11 +1,22 @@
 //! # 工具函数模块
 //!
-//! 提供美化输出、进度条、Slurm 脚本生成等工具。
+//! 提供用户配置与集群配置档案、美化输出、结构化记录输出、进度条、并行度配置、外部命令执行、作业调度器（Slurm、PBS/Torque、LSF、SGE）脚本模板渲染、提交与队列查询等工具。
 //!
 //! ## 依赖关系
 //! - 被 `commands/` 模块使用
-//! - 子模块: output, progress, slurm
+//! - 子模块: command, config, lsf, output, parallel, pbs, progress, scheduler, sge, slurm, structured, template；测试辅助 test_support 仅在测试时编译
 
+pub mod command;
+pub mod config;
+pub mod lsf;
 pub mod output;
+pub mod parallel;
+pub mod pbs;
 pub mod progress;
+pub mod scheduler;
+pub mod sge;
 pub mod slurm;
+pub mod structured;
+pub mod template;
+#[cfg(test)]
+pub mod test_support;
@@ -1,16 +1,35 @@
 //! # 美化输出工具
 //!
-//! 提供统一的终端输出样式。
+//! 提供统一的终端输出样式与字节数格式化；结构化输出模式下提示信息与表格改写到 stderr。
 //!
 //! ## 依赖关系
 //! - 被所有 `commands/` 模块使用
 //! - 使用 `colored` crate
 
 use colored::Colorize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 结构化输出模式下为 true：提示信息与表格改写到 stderr
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if TO_STDERR.load(Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// 之后的提示信息与表格改写到 stderr，使 stdout 只保留结构化数据
+pub fn redirect_to_stderr() {
+    TO_STDERR.store(true, Ordering::Relaxed);
+}
 
 /// 打印成功消息
 pub fn print_success(msg: &str) {
-    println!("{} {}", "[OK]".green().bold(), msg);
+    say!("{} {}", "[OK]".green().bold(), msg);
 }
 
 /// 打印错误消息
@@ -20,27 +39,27 @@ pub fn print_error(msg: &str) {
 
 /// 打印警告消息
 pub fn print_warning(msg: &str) {
-    println!("{} {}", "[WARN]".yellow().bold(), msg);
+    say!("{} {}", "[WARN]".yellow().bold(), msg);
 }
 
 /// 打印信息消息
 pub fn print_info(msg: &str) {
-    println!("{} {}", "[*]".blue().bold(), msg);
+    say!("{} {}", "[*]".blue().bold(), msg);
 }
 
 /// 打印跳过消息
 pub fn print_skip(msg: &str) {
-    println!("{} {}", "[SKIP]".dimmed(), msg);
+    say!("{} {}", "[SKIP]".dimmed(), msg);
 }
 
 /// 打印完成消息
 pub fn print_done(msg: &str) {
-    println!("{} {}", "[DONE]".green().bold(), msg);
+    say!("{} {}", "[DONE]".green().bold(), msg);
 }
 
 /// 打印转换成功消息
 pub fn print_conversion(from: &str, to: &str) {
-    println!(
+    say!(
         "{} {} {} {}",
         "[OK]".green().bold(),
         from.dimmed(),
@@ -52,12 +71,34 @@ pub fn print_conversion(from: &str, to: &str) {
 /// 打印标题栏
 pub fn print_header(title: &str) {
     let line = "─".repeat(60);
-    println!("\n{}", line.dimmed());
-    println!("  {}", title.bold());
-    println!("{}\n", line.dimmed());
+    say!("\n{}", line.dimmed());
+    say!("  {}", title.bold());
+    say!("{}\n", line.dimmed());
 }
 
 /// 打印分隔线
 pub fn print_separator() {
-    println!("{}", "─".repeat(60).dimmed());
+    say!("{}", "─".repeat(60).dimmed());
+}
+
+/// 打印表格
+pub fn print_table(table: impl std::fmt::Display) {
+    say!("{table}");
+}
+
+/// 按二进制单位格式化字节数（B/KiB/MiB/GiB/TiB）
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
 }
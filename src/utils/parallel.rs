@@ -0,0 +1,17 @@
+//! # 并行度配置
+//!
+//! 按 `-j/--jobs` 参数配置 rayon 全局线程池，0 表示使用全部 CPU 核心。
+//!
+//! ## 依赖关系
+//! - 被 `commands/convert.rs`、`commands/collect.rs` 与 `commands/analyze/` 的 DFT 扫描类命令使用
+//! - 使用 `rayon` 与 `num_cpus`
+
+/// 配置全局线程池；全局线程池已初始化时保持原设置
+pub fn configure_threads(jobs: usize) {
+    let num_threads = if jobs == 0 { num_cpus::get() } else { jobs };
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build_global()
+        .ok();
+}
@@ -0,0 +1,191 @@
+//! # PBS/Torque 调度器后端
+//!
+//! 实现 `Scheduler`：生成 `#PBS` 脚本头，通过 `qsub` 提交，并解析 `qstat -f`（历史作业用 `qstat -x -f`）的输出。
+//! PBS 单字母状态归一化为 Slurm 状态名；已结束作业按 `Exit_status` 区分完成、超时与内存不足。
+//!
+//! ## 依赖关系
+//! - 实现 `utils/scheduler.rs` 的 `Scheduler`
+//! - 使用 `utils/command.rs` 执行调度器命令
+
+use crate::error::Result;
+use crate::utils::command::CommandRunner;
+use crate::utils::scheduler::{
+    directives, hms, log_path, memory_mib, require_job_id, time_limit_seconds, JobConfig,
+    Scheduler, SchedulerJob, ScriptResources,
+};
+
+use std::path::Path;
+
+/// PBS Pro / Torque 后端
+pub struct Pbs;
+
+impl Scheduler for Pbs {
+    fn name(&self) -> &'static str {
+        "PBS"
+    }
+
+    fn script_name(&self) -> &'static str {
+        "submit.pbs"
+    }
+
+    fn script_header(&self, config: &JobConfig, workdir: &Path) -> String {
+        let mut lines = vec![format!("#PBS -N {}", config.job_name)];
+        if !config.partition.is_empty() {
+            lines.push(format!("#PBS -q {}", config.partition));
+        }
+        let mut select = format!("nodes={}:ppn={}", config.nodes, config.cores_per_node());
+        if !config.constraint.is_empty() {
+            select.push_str(&format!(":{}", config.constraint));
+        }
+        lines.push(format!("#PBS -l {select}"));
+        lines.push(format!("#PBS -l walltime={}", hms(&config.time_limit)));
+        if let Some(mib) = memory_mib(&config.mem_per_cpu) {
+            lines.push(format!("#PBS -l pmem={mib}mb"));
+        }
+        lines.push(format!(
+            "#PBS -o {}",
+            log_path(workdir, &config.job_name, "out")
+        ));
+        lines.push(format!(
+            "#PBS -e {}",
+            log_path(workdir, &config.job_name, "err")
+        ));
+        lines.join("\n") + "\n"
+    }
+
+    fn submit(&self, runner: &dyn CommandRunner, script: &Path, _workdir: &Path) -> Result<String> {
+        let stdout = runner.run("qsub", &[script.display().to_string()])?;
+        require_job_id(self, "qsub", &stdout)
+    }
+
+    /// 读取 `-l` 资源表中的 `nodes=N:ppn=P`（或 PBS Pro 的 `select=N:ncpus=P`、`ncpus=P`）与 `walltime`
+    fn script_resources(&self, script: &str) -> ScriptResources {
+        let mut resources = ScriptResources::default();
+        for args in directives(script, "#PBS") {
+            let ["-l", list, ..] = args.as_slice() else {
+                continue;
+            };
+            for resource in list.split(',') {
+                if let Some(walltime) = resource.strip_prefix("walltime=") {
+                    resources.time_limit_sec = time_limit_seconds(walltime);
+                    continue;
+                }
+
+                let (mut units, mut per_unit) = (None, None);
+                for field in resource.split(':') {
+                    match field.split_once('=') {
+                        Some(("nodes" | "select", value)) => units = value.parse::<usize>().ok(),
+                        Some(("ppn" | "ncpus", value)) => per_unit = value.parse::<usize>().ok(),
+                        _ => {}
+                    }
+                }
+                if units.is_some() || per_unit.is_some() {
+                    resources.cores = Some(units.unwrap_or(1) * per_unit.unwrap_or(1));
+                }
+            }
+        }
+        resources
+    }
+
+    /// `=>> PBS: job killed: walltime 86412 exceeded limit 86400`
+    fn time_limit_exceeded(&self, log: &str) -> bool {
+        log.contains("walltime") && log.contains("exceeded limit")
+    }
+
+    /// `qsub` 只输出作业 ID，如 "4242.pbs01"
+    fn parse_job_id(&self, stdout: &str) -> Option<String> {
+        stdout
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+            .and_then(|line| line.split_whitespace().next())
+            .map(str::to_string)
+    }
+
+    fn query_queue(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>> {
+        let output = runner.run("qstat", &["-f".to_string()])?;
+        Ok(parse_full_status(&output, user)
+            .into_iter()
+            .filter(|job| matches!(job.state.as_str(), "PENDING" | "RUNNING" | "SUSPENDED"))
+            .collect())
+    }
+
+    /// `qstat -x` 列出保留期内的已结束作业；`since` 由服务器的作业保留时间决定，此处不使用
+    fn query_history(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+        _since: &str,
+    ) -> Result<Vec<SchedulerJob>> {
+        let output = runner.run("qstat", &["-x".to_string(), "-f".to_string()])?;
+        Ok(parse_full_status(&output, user))
+    }
+}
+
+/// 解析 `qstat -f` 的分块输出（"Job Id:" 开头，`key = value` 属性行）
+fn parse_full_status(output: &str, user: Option<&str>) -> Vec<SchedulerJob> {
+    struct Block {
+        id: String,
+        name: String,
+        owner: String,
+        state: String,
+        exit_status: Option<i32>,
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for line in output.lines() {
+        if let Some(id) = line.trim().strip_prefix("Job Id:") {
+            blocks.push(Block {
+                id: id.trim().to_string(),
+                name: String::new(),
+                owner: String::new(),
+                state: String::new(),
+                exit_status: None,
+            });
+            continue;
+        }
+        let (Some(block), Some((key, value))) = (blocks.last_mut(), line.split_once('=')) else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Job_Name" => block.name = value.to_string(),
+            "Job_Owner" => block.owner = value.to_string(),
+            "job_state" => block.state = value.to_string(),
+            "Exit_status" => block.exit_status = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    blocks
+        .into_iter()
+        .filter(|block| user.is_none_or(|user| block.owner.split('@').next() == Some(user)))
+        .map(|block| SchedulerJob {
+            state: normalize_state(&block.state, block.exit_status).to_string(),
+            id: block.id,
+            name: block.name,
+        })
+        .collect()
+}
+
+/// PBS 状态字母转换为 Slurm 状态名；Torque 用 `C`、PBS Pro 用 `F`（数组子作业为 `X`）表示已结束
+fn normalize_state(state: &str, exit_status: Option<i32>) -> &'static str {
+    match state {
+        "Q" | "H" | "W" | "T" => "PENDING",
+        "R" | "E" | "B" => "RUNNING",
+        "S" | "U" => "SUSPENDED",
+        "C" | "F" | "X" => match exit_status {
+            Some(0) => "COMPLETED",
+            // PBS 的作业资源超限退出码：-29 walltime，-27 内存
+            Some(-29) => "TIMEOUT",
+            Some(-27) => "OUT_OF_MEMORY",
+            Some(code) if code > 256 => "CANCELLED",
+            _ => "FAILED",
+        },
+        _ => "UNKNOWN",
+    }
+}
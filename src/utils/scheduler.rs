@@ -0,0 +1,739 @@
+//! # 作业调度器抽象
+//!
+//! 以 `Scheduler` trait 统一作业脚本头生成、提交命令、作业 ID 解析与队列查询，
+//! 作业脚本按模板（默认为内置模板）渲染，调度器指令、环境设置与资源参数作为占位符提供；
+//! 由 `utils/slurm.rs`、`utils/pbs.rs`、`utils/lsf.rs` 与 `utils/sge.rs` 分别实现。
+//! 队列记录的状态统一归一化为 Slurm 的状态名（`RUNNING`、`TIMEOUT` 等），供 `dft/queue.rs` 细化作业状态；
+//! 各调度器的作业日志都写入作业目录下的 `slurm_logs/`；各后端也负责从自己格式的作业脚本中读回申请的核数与时间上限，
+//! 并识别日志中的超时终止记录。
+//!
+//! ## 依赖关系
+//! - 被 `commands/submit.rs`、`commands/clean.rs`、`commands/analyze/dft_status.rs` 与 `dft/usage.rs` 使用
+//! - 使用 `utils/command.rs` 执行调度器命令，`utils/template.rs` 渲染作业脚本
+
+use crate::error::{QutilityError, Result};
+use crate::utils::command::CommandRunner;
+use crate::utils::template::ScriptTemplate;
+use crate::utils::{lsf, pbs, sge, slurm};
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 提交成功后在作业目录中记录作业 ID 的文件名（沿用旧文件名以兼容已有作业目录）
+pub const JOB_ID_FILE: &str = ".slurm_job_id";
+
+/// 作业日志目录（相对作业目录）
+pub const LOG_DIR: &str = "slurm_logs";
+
+/// 各调度器后端调用的外部命令，均可用 `--scheduler-cmd NAME=PATH` 替换
+pub const SCHEDULER_COMMANDS: &[&str] = &[
+    "sbatch", "squeue", "sacct", "qsub", "qstat", "bsub", "bjobs",
+];
+
+/// 支持的调度器；配置档案中写作 `slurm`、`pbs`（或 `torque`）、`lsf`、`sge`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerKind {
+    Slurm,
+    /// PBS Pro 与 Torque
+    #[serde(alias = "torque")]
+    Pbs,
+    Lsf,
+    Sge,
+}
+
+impl SchedulerKind {
+    /// 全部调度器，按查找作业脚本的顺序排列
+    pub const ALL: [SchedulerKind; 4] = [
+        SchedulerKind::Slurm,
+        SchedulerKind::Pbs,
+        SchedulerKind::Lsf,
+        SchedulerKind::Sge,
+    ];
+
+    pub fn scheduler(self) -> &'static dyn Scheduler {
+        match self {
+            SchedulerKind::Slurm => &slurm::Slurm,
+            SchedulerKind::Pbs => &pbs::Pbs,
+            SchedulerKind::Lsf => &lsf::Lsf,
+            SchedulerKind::Sge => &sge::Sge,
+        }
+    }
+}
+
+/// 调度器返回的作业条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerJob {
+    /// 作业 ID（Slurm 数组作业形如 `123_4`）
+    pub id: String,
+    /// 作业名（即结构名）
+    pub name: String,
+    /// 归一化为 Slurm 名称的大写状态，如 `RUNNING`、`TIMEOUT`
+    pub state: String,
+}
+
+/// 作业脚本中申请的资源；脚本未写明的项为 `None`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScriptResources {
+    /// 总核数
+    pub cores: Option<usize>,
+    /// 时间上限 (s)
+    pub time_limit_sec: Option<u64>,
+}
+
+/// 作业资源与环境配置
+pub struct JobConfig {
+    pub job_name: String,
+    /// 分区（Slurm）或队列（PBS/LSF/SGE）；为空时使用调度器默认队列
+    pub partition: String,
+    /// 节点特性约束；为空时不设置，SGE 不使用
+    pub constraint: String,
+    pub nodes: u32,
+    pub ntasks: u32,
+    pub cpus_per_task: u32,
+    /// 每核内存；为空时不设置
+    pub mem_per_cpu: String,
+    pub time_limit: String,
+    /// SGE 并行环境名
+    pub parallel_env: String,
+    pub modules: Vec<String>,
+    /// 导出的环境变量
+    pub env: Vec<(String, String)>,
+    /// 加载模块前原样写入的命令行
+    pub preamble: Vec<String>,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        JobConfig {
+            job_name: "job".to_string(),
+            partition: String::new(),
+            constraint: String::new(),
+            nodes: 1,
+            ntasks: 32,
+            cpus_per_task: 1,
+            mem_per_cpu: String::new(),
+            time_limit: "24:00:00".to_string(),
+            parallel_env: "mpi".to_string(),
+            modules: vec![],
+            env: vec![],
+            preamble: vec![],
+        }
+    }
+}
+
+impl JobConfig {
+    /// 申请的总核数
+    pub fn cores(&self) -> u32 {
+        self.ntasks * self.cpus_per_task.max(1)
+    }
+
+    /// 每个节点的核数（向上取整）
+    pub fn cores_per_node(&self) -> u32 {
+        self.cores().div_ceil(self.nodes.max(1))
+    }
+}
+
+/// 调度器后端
+pub trait Scheduler: Sync {
+    /// 显示名称
+    fn name(&self) -> &'static str;
+
+    /// 作业目录中作业脚本的文件名
+    fn script_name(&self) -> &'static str;
+
+    /// 作业脚本中 shebang 之后的调度器指令行；`workdir` 为绝对路径
+    fn script_header(&self, config: &JobConfig, workdir: &Path) -> String;
+
+    /// 作业命令之后追加的内容（如 Slurm 的 sacct 计时）
+    fn script_footer(&self) -> String {
+        String::new()
+    }
+
+    /// 提交作业脚本并返回作业 ID；`script` 与 `workdir` 为绝对路径
+    fn submit(&self, runner: &dyn CommandRunner, script: &Path, workdir: &Path) -> Result<String>;
+
+    /// 从提交命令的输出中解析作业 ID
+    fn parse_job_id(&self, stdout: &str) -> Option<String>;
+
+    /// 从本调度器格式的作业脚本中读取申请的总核数与时间上限
+    fn script_resources(&self, script: &str) -> ScriptResources;
+
+    /// 作业日志是否记录了因超出时间上限而被终止
+    fn time_limit_exceeded(&self, log: &str) -> bool;
+
+    /// 查询当前排队与运行中的作业
+    fn query_queue(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>>;
+
+    /// 查询 `since` 以来已结束作业的记录；不支持时返回空列表
+    fn query_history(
+        &self,
+        _runner: &dyn CommandRunner,
+        _user: Option<&str>,
+        _since: &str,
+    ) -> Result<Vec<SchedulerJob>> {
+        Ok(Vec::new())
+    }
+}
+
+/// 作业脚本模板中由调度器与作业配置提供的占位符
+pub const SCRIPT_PLACEHOLDERS: &[&str] = &[
+    "scheduler",
+    "scheduler_header",
+    "scheduler_footer",
+    "environment",
+    "exports",
+    "preamble",
+    "module_loads",
+    "modules",
+    "job_name",
+    "workdir",
+    "log_dir",
+    "partition",
+    "constraint",
+    "nodes",
+    "ntasks",
+    "cpus_per_task",
+    "cores",
+    "mem_per_cpu",
+    "time",
+    "exec_cmd",
+];
+
+/// 按模板渲染作业脚本；`extra` 为调用方附加的占位符（如结构元数据），与 `SCRIPT_PLACEHOLDERS` 同名时覆盖之
+pub fn generate_job_script(
+    scheduler: &dyn Scheduler,
+    template: &ScriptTemplate,
+    config: &JobConfig,
+    workdir: &Path,
+    exec_cmd: &str,
+    extra: &BTreeMap<&str, String>,
+) -> Result<String> {
+    let workdir = absolute_path(workdir);
+
+    let mut variables: BTreeMap<&str, String> = BTreeMap::from([
+        ("scheduler", scheduler.name().to_lowercase()),
+        (
+            "scheduler_header",
+            scheduler.script_header(config, &workdir),
+        ),
+        ("scheduler_footer", scheduler.script_footer()),
+        ("environment", script_environment(config)),
+        ("exports", export_lines(config).join("\n")),
+        ("preamble", config.preamble.join("\n")),
+        ("module_loads", module_lines(config).join("\n")),
+        ("modules", config.modules.join(" ")),
+        ("job_name", config.job_name.clone()),
+        ("workdir", workdir.display().to_string()),
+        ("log_dir", LOG_DIR.to_string()),
+        ("partition", config.partition.clone()),
+        ("constraint", config.constraint.clone()),
+        ("nodes", config.nodes.to_string()),
+        ("ntasks", config.ntasks.to_string()),
+        ("cpus_per_task", config.cpus_per_task.to_string()),
+        ("cores", config.cores().to_string()),
+        ("mem_per_cpu", config.mem_per_cpu.clone()),
+        ("time", config.time_limit.clone()),
+        ("exec_cmd", exec_cmd.to_string()),
+    ]);
+    variables.extend(extra.iter().map(|(key, value)| (*key, value.clone())));
+
+    template.render(&variables)
+}
+
+/// 调度器指令之后的公共环境设置：严格模式、栈大小、环境变量、前导行与模块加载
+pub(crate) fn script_environment(config: &JobConfig) -> String {
+    let mut lines = vec![
+        "set -euo pipefail".to_string(),
+        String::new(),
+        "if ! ulimit -s unlimited; then".to_string(),
+        "    echo \"Warning: failed to set stack size to unlimited\" >&2".to_string(),
+        "fi".to_string(),
+        String::new(),
+    ];
+    lines.extend(export_lines(config));
+    lines.extend(config.preamble.iter().cloned());
+    lines.extend(module_lines(config));
+    lines.join("\n") + "\n"
+}
+
+fn export_lines(config: &JobConfig) -> Vec<String> {
+    config
+        .env
+        .iter()
+        .map(|(key, value)| format!("export {key}=\"{value}\""))
+        .collect()
+}
+
+fn module_lines(config: &JobConfig) -> Vec<String> {
+    if config.modules.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec!["module purge 2>&1".to_string()];
+    lines.extend(config.modules.iter().map(|m| format!("module load {m}")));
+    lines.push("echo \"Loaded modules\"".to_string());
+    lines
+}
+
+/// 提交作业目录中的作业脚本并在目录中记录作业 ID
+pub fn submit_job_dir(
+    scheduler: &dyn Scheduler,
+    runner: &dyn CommandRunner,
+    job_dir: &Path,
+) -> Result<String> {
+    let job_dir = std::path::absolute(job_dir).map_err(|e| QutilityError::FileReadError {
+        path: job_dir.display().to_string(),
+        source: e,
+    })?;
+    let script = job_dir.join(scheduler.script_name());
+    if !script.exists() {
+        return Err(QutilityError::FileNotFound {
+            path: script.display().to_string(),
+        });
+    }
+
+    let job_id = scheduler.submit(runner, &script, &job_dir)?;
+    let id_file = job_dir.join(JOB_ID_FILE);
+    fs::write(&id_file, format!("{job_id}\n")).map_err(|e| QutilityError::FileWriteError {
+        path: id_file.display().to_string(),
+        source: e,
+    })?;
+
+    Ok(job_id)
+}
+
+/// 提交后解析作业 ID，无法解析时报错并附上提交命令输出
+pub(crate) fn require_job_id(
+    scheduler: &dyn Scheduler,
+    command: &str,
+    stdout: &str,
+) -> Result<String> {
+    scheduler.parse_job_id(stdout).ok_or_else(|| {
+        QutilityError::Other(format!(
+            "Cannot parse job ID from {command} output: {}",
+            stdout.trim()
+        ))
+    })
+}
+
+/// 以当前工作目录补全相对路径
+pub(crate) fn absolute_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 作业当前这次提交的日志：`slurm_logs/` 下扩展名为 `extension` 的文件，按修改时间从新到旧排列。
+///
+/// 修改时间早于作业 ID 文件的日志属于之前的提交（作业重新提交后尚未开始运行），不返回
+pub fn job_logs(job_dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let submitted = modified(&job_dir.join(JOB_ID_FILE));
+
+    let Ok(entries) = fs::read_dir(job_dir.join(LOG_DIR)) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(Option<std::time::SystemTime>, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some(extension))
+        .map(|path| (modified(&path), path))
+        .filter(|(time, _)| match (time, submitted) {
+            (Some(time), Some(submitted)) => *time >= submitted,
+            _ => true,
+        })
+        .collect();
+    logs.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    logs.into_iter().map(|(_, path)| path).collect()
+}
+
+/// 作业脚本中以 `prefix`（如 `#PBS`）开头的指令行，按空白切分为参数
+pub(crate) fn directives<'a>(
+    script: &'a str,
+    prefix: &'a str,
+) -> impl Iterator<Item = Vec<&'a str>> + 'a {
+    script
+        .lines()
+        .filter_map(move |line| line.trim_start().strip_prefix(prefix))
+        .map(|args| args.split_whitespace().collect())
+}
+
+/// Slurm 数组任务的调度器日志：作业目录记录的 ID 形如 `<作业ID>_<任务号>` 时，
+/// 为作业根目录（作业目录的上一级）下的 `slurm_logs/<作业ID>_<任务号>.<extension>`。
+/// 超时、内存不足等调度器消息只写在这里，作业目录中的日志只有程序自身的输出；
+/// 作业单独重新提交后记录的是普通作业 ID，不再对应数组日志
+pub fn array_task_log(job_dir: &Path, extension: &str) -> Option<PathBuf> {
+    let content = fs::read_to_string(job_dir.join(JOB_ID_FILE)).ok()?;
+    let id = content.trim();
+    if !id.contains('_') {
+        return None;
+    }
+    let path = job_dir
+        .parent()?
+        .join(LOG_DIR)
+        .join(format!("{id}.{extension}"));
+    path.is_file().then_some(path)
+}
+
+/// 作业日志路径 `<workdir>/slurm_logs/<name>.<extension>`
+pub(crate) fn log_path(workdir: &Path, name: &str, extension: &str) -> String {
+    workdir
+        .join(LOG_DIR)
+        .join(format!("{name}.{extension}"))
+        .display()
+        .to_string()
+}
+
+/// 解析 `[D-]HH:MM:SS`、`HH:MM` 或分钟数形式的时间上限，返回秒数
+pub(crate) fn time_limit_seconds(limit: &str) -> Option<u64> {
+    let (days, clock) = match limit.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, limit),
+    };
+    let fields: Vec<u64> = clock
+        .split(':')
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    let seconds = match fields.as_slice() {
+        [minutes] if days == 0 => minutes * 60,
+        [hours] => hours * 3600,
+        [hours, minutes] if days > 0 => hours * 3600 + minutes * 60,
+        [minutes, seconds] => minutes * 60 + seconds,
+        [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+        _ => return None,
+    };
+    Some(days * 86_400 + seconds)
+}
+
+/// `HH:MM:SS` 形式的时间上限；无法解析时原样返回
+pub(crate) fn hms(limit: &str) -> String {
+    match time_limit_seconds(limit) {
+        Some(secs) => format!(
+            "{:02}:{:02}:{:02}",
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60
+        ),
+        None => limit.to_string(),
+    }
+}
+
+/// 解析 `3G`、`500MB` 等内存量，返回 MiB
+pub(crate) fn memory_mib(memory: &str) -> Option<u64> {
+    let memory = memory.trim();
+    let split = memory
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(memory.len());
+    let (value, unit) = memory.split_at(split);
+    let value: f64 = value.parse().ok()?;
+    let scale = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "K" => 1.0 / 1024.0,
+        "" | "M" => 1.0,
+        "G" => 1024.0,
+        "T" => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((value * scale).ceil() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::command::SystemCommandRunner;
+    use crate::utils::test_support::unique_test_dir;
+
+    #[test]
+    fn parses_time_limits_and_memory() {
+        assert_eq!(time_limit_seconds("24:00:00"), Some(86_400));
+        assert_eq!(time_limit_seconds("1-02:00:00"), Some(93_600));
+        assert_eq!(time_limit_seconds("90"), Some(5400));
+        assert_eq!(hms("1-00:30"), "24:30:00");
+        assert_eq!(memory_mib("3G"), Some(3072));
+        assert_eq!(memory_mib("512MB"), Some(512));
+        assert_eq!(memory_mib("lots"), None);
+    }
+
+    #[test]
+    fn every_backend_writes_its_own_directives() {
+        let config = JobConfig {
+            job_name: "Si8".to_string(),
+            ..JobConfig::default()
+        };
+        let workdir = Path::new("/scratch/jobs/Si8");
+        for (kind, directive) in [
+            (SchedulerKind::Slurm, "#SBATCH -J Si8"),
+            (SchedulerKind::Pbs, "#PBS -N Si8"),
+            (SchedulerKind::Lsf, "#BSUB -J Si8"),
+            (SchedulerKind::Sge, "#$ -N Si8"),
+        ] {
+            let script = generate_job_script(
+                kind.scheduler(),
+                &ScriptTemplate::default(),
+                &config,
+                workdir,
+                "vasp_std",
+                &BTreeMap::new(),
+            )
+            .expect("render");
+            assert!(script.starts_with("#!/bin/bash\n"), "{kind:?}");
+            assert!(script.contains(directive), "{kind:?}");
+            assert!(script.contains("cd \"/scratch/jobs/Si8\""), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn backends_read_back_their_script_resources() {
+        let config = JobConfig {
+            job_name: "Si8".to_string(),
+            nodes: 2,
+            ntasks: 16,
+            cpus_per_task: 2,
+            time_limit: "1-12:00:00".to_string(),
+            ..JobConfig::default()
+        };
+        let workdir = Path::new("/scratch/jobs/Si8");
+        for kind in SchedulerKind::ALL {
+            let scheduler = kind.scheduler();
+            let script = generate_job_script(
+                scheduler,
+                &ScriptTemplate::default(),
+                &config,
+                workdir,
+                "vasp_std",
+                &BTreeMap::new(),
+            )
+            .expect("render");
+            assert_eq!(
+                scheduler.script_resources(&script),
+                ScriptResources {
+                    cores: Some(32),
+                    time_limit_sec: Some(36 * 3600),
+                },
+                "{kind:?}"
+            );
+        }
+
+        let pbs_pro = "#PBS -l select=2:ncpus=24:mpiprocs=24\n#PBS -l walltime=02:00:00\n";
+        assert_eq!(
+            SchedulerKind::Pbs.scheduler().script_resources(pbs_pro),
+            ScriptResources {
+                cores: Some(48),
+                time_limit_sec: Some(7200),
+            }
+        );
+        let sge = "#$ -pe orte 8\n#$ -l h_rt=5400,h_vmem=2G\n";
+        assert_eq!(
+            SchedulerKind::Sge.scheduler().script_resources(sge),
+            ScriptResources {
+                cores: Some(8),
+                time_limit_sec: Some(5400),
+            }
+        );
+
+        for (kind, log) in [
+            (
+                SchedulerKind::Slurm,
+                "slurmstepd: error: *** JOB 7 ON n01 CANCELLED AT 2024-01-02T10:00:00 DUE TO TIME LIMIT ***",
+            ),
+            (
+                SchedulerKind::Pbs,
+                "=>> PBS: job killed: walltime 7215 exceeded limit 7200",
+            ),
+            (
+                SchedulerKind::Lsf,
+                "TERM_RUNLIMIT: job killed after reaching LSF run time limit.",
+            ),
+            (
+                SchedulerKind::Sge,
+                "job 7.1 exceeded hard wallclock time - initiate terminate method",
+            ),
+        ] {
+            assert!(kind.scheduler().time_limit_exceeded(log), "{kind:?}");
+            assert!(!kind.scheduler().time_limit_exceeded("Done"), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn environment_comes_from_config() {
+        let config = JobConfig {
+            env: vec![("OMP_NUM_THREADS".to_string(), "1".to_string())],
+            preamble: vec!["source /etc/profile.d/modules.sh".to_string()],
+            ..JobConfig::default()
+        };
+        let script = generate_job_script(
+            &slurm::Slurm,
+            &ScriptTemplate::default(),
+            &config,
+            Path::new("/x"),
+            "vasp_std",
+            &BTreeMap::new(),
+        )
+        .expect("render");
+        assert!(script.contains("export OMP_NUM_THREADS=\"1\"\nsource /etc/profile.d/modules.sh\n"));
+        assert!(!script.contains("module "));
+        assert!(!script.contains("MODULEPATH"));
+        assert!(!script.contains("--partition"));
+
+        let config = JobConfig {
+            modules: vec!["vasp/6.4".to_string()],
+            ..JobConfig::default()
+        };
+        let script = generate_job_script(
+            &slurm::Slurm,
+            &ScriptTemplate::default(),
+            &config,
+            Path::new("/x"),
+            "vasp_std",
+            &BTreeMap::new(),
+        )
+        .expect("render");
+        assert!(script.contains("module purge 2>&1\nmodule load vasp/6.4\n"));
+    }
+
+    #[test]
+    fn custom_template_replaces_the_default_layout() {
+        let template = ScriptTemplate::parse(
+            "#!/bin/bash\n#SBATCH -J {{job_name}}\n#SBATCH -n {{ntasks}}\n{{exports}}\n{{module_loads}}\n\
+             cp -r {{workdir}} $TMPDIR/{{seed}}\ncd $TMPDIR/{{seed}}\n{{exec_cmd}}\n",
+            "gpu-less.tmpl",
+        )
+        .expect("parse");
+        let config = JobConfig {
+            job_name: "Si8".to_string(),
+            ntasks: 64,
+            modules: vec!["castep/25.12".to_string()],
+            env: vec![("OMP_NUM_THREADS".to_string(), "2".to_string())],
+            ..JobConfig::default()
+        };
+
+        let err = generate_job_script(
+            &slurm::Slurm,
+            &template,
+            &config,
+            Path::new("/x/Si8"),
+            "srun castep.mpi Si8",
+            &BTreeMap::new(),
+        )
+        .expect_err("seed is not provided");
+        assert!(err.to_string().contains("{{seed}} (line 6)"));
+
+        let extra = BTreeMap::from([("seed", "Si8".to_string())]);
+        let script = generate_job_script(
+            &slurm::Slurm,
+            &template,
+            &config,
+            Path::new("/x/Si8"),
+            "srun castep.mpi Si8",
+            &extra,
+        )
+        .expect("render");
+        assert_eq!(
+            script,
+            "#!/bin/bash\n#SBATCH -J Si8\n#SBATCH -n 64\nexport OMP_NUM_THREADS=\"2\"\n\
+             module purge 2>&1\nmodule load castep/25.12\necho \"Loaded modules\"\n\
+             cp -r /x/Si8 $TMPDIR/Si8\ncd $TMPDIR/Si8\nsrun castep.mpi Si8\n"
+        );
+    }
+
+    #[cfg(unix)]
+    fn write_stub(dir: &Path, name: &str, body: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{body}")).expect("write stub");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+    }
+
+    /// 以桩脚本替换调度器命令并提交、查询；PBS 与 SGE 共用 `qsub`/`qstat`，因此各用一个桩脚本目录
+    #[cfg(unix)]
+    #[test]
+    fn stub_qsub_and_bsub_submit_and_query() {
+        let root = unique_test_dir("scheduler-stubs");
+        let job_dir = root.join("jobs").join("Si8");
+        fs::create_dir_all(&job_dir).expect("create job dir");
+        let script = job_dir.join("submit.sh");
+        fs::write(&script, "#!/bin/bash\n").expect("write script");
+
+        let pbs_bin = root.join("pbs");
+        let lsf_bin = root.join("lsf");
+        let sge_bin = root.join("sge");
+        for dir in [&pbs_bin, &lsf_bin, &sge_bin] {
+            fs::create_dir_all(dir).expect("create bin");
+        }
+        write_stub(&pbs_bin, "qsub", "echo 4242.pbs01\n");
+        write_stub(
+            &pbs_bin,
+            "qstat",
+            "cat <<'EOF'\nJob Id: 4242.pbs01\n    Job_Name = Si8\n    Job_Owner = alice@login1\n    job_state = R\n\nJob Id: 4100.pbs01\n    Job_Name = Si4\n    Job_Owner = alice@login1\n    job_state = F\n    Exit_status = -29\nEOF\n",
+        );
+        // bsub 从标准输入读取脚本，桩脚本检查收到的内容
+        write_stub(
+            &lsf_bin,
+            "bsub",
+            "grep -q '^#!/bin/bash' && echo 'Job <777> is submitted to queue <normal>.'\n",
+        );
+        write_stub(
+            &lsf_bin,
+            "bjobs",
+            "echo '777|Si8|PEND'\necho '700|Si4|EXIT'\n",
+        );
+        write_stub(
+            &sge_bin,
+            "qsub",
+            "echo 'Your job 55 (\"Si8\") has been submitted'\n",
+        );
+        write_stub(
+            &sge_bin,
+            "qstat",
+            "cat <<'EOF'\njob-ID  prior   name       user         state submit/start at     queue                          slots ja-task-ID\n-----------------------------------------------------------------------------------------------------------------\n     55 0.55500 Si8_long_n alice        qw    03/01/2024 10:00:00                                   32\n       Full jobname:     Si8_long_name\nEOF\n",
+        );
+
+        let run = |kind: SchedulerKind, bin: &Path| {
+            let runner = SystemCommandRunner::new().with_overrides(
+                SCHEDULER_COMMANDS
+                    .iter()
+                    .map(|&name| (name, bin.join(name).display().to_string())),
+            );
+            let scheduler = kind.scheduler();
+            let id = scheduler.submit(&runner, &script, &job_dir);
+            let queue = scheduler.query_queue(&runner, Some("alice"));
+            let history = scheduler.query_history(&runner, Some("alice"), "now-7days");
+            (id, queue, history)
+        };
+
+        let pbs = run(SchedulerKind::Pbs, &pbs_bin);
+        let lsf = run(SchedulerKind::Lsf, &lsf_bin);
+        let sge = run(SchedulerKind::Sge, &sge_bin);
+
+        let (id, queue, history) = pbs;
+        assert_eq!(id.expect("qsub"), "4242.pbs01");
+        let queue = queue.expect("qstat");
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].state, "RUNNING");
+        assert_eq!(history.expect("qstat -x")[1].state, "TIMEOUT");
+
+        let (id, queue, history) = lsf;
+        assert_eq!(id.expect("bsub"), "777");
+        assert_eq!(queue.expect("bjobs")[0].state, "PENDING");
+        assert_eq!(history.expect("bjobs -a")[1].state, "FAILED");
+
+        let (id, queue, history) = sge;
+        assert_eq!(id.expect("qsub"), "55");
+        let queue = queue.expect("qstat");
+        assert_eq!(queue[0].name, "Si8_long_name");
+        assert_eq!(queue[0].state, "PENDING");
+        assert!(history.expect("no SGE history").is_empty());
+
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
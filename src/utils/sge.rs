@@ -0,0 +1,160 @@
+//! # SGE 调度器后端
+//!
+//! 实现 `Scheduler`：生成 `#$` 脚本头（并行环境由 `JobConfig::parallel_env` 指定），通过 `qsub` 提交，
+//! 并解析 `qstat -r` 的表格输出。SGE 不保留已结束作业的队列记录，因此没有历史查询。
+//!
+//! ## 依赖关系
+//! - 实现 `utils/scheduler.rs` 的 `Scheduler`
+//! - 使用 `utils/command.rs` 执行调度器命令
+
+use crate::error::Result;
+use crate::utils::command::CommandRunner;
+use crate::utils::scheduler::{
+    directives, hms, log_path, memory_mib, require_job_id, time_limit_seconds, JobConfig,
+    Scheduler, SchedulerJob, ScriptResources,
+};
+
+use std::path::Path;
+
+/// Sun/Univa/Son of Grid Engine 后端
+pub struct Sge;
+
+impl Scheduler for Sge {
+    fn name(&self) -> &'static str {
+        "SGE"
+    }
+
+    fn script_name(&self) -> &'static str {
+        "submit.sge"
+    }
+
+    fn script_header(&self, config: &JobConfig, workdir: &Path) -> String {
+        let mut lines = vec![
+            format!("#$ -N {}", config.job_name),
+            "#$ -S /bin/bash".to_string(),
+            format!("#$ -wd {}", workdir.display()),
+        ];
+        if !config.partition.is_empty() {
+            lines.push(format!("#$ -q {}", config.partition));
+        }
+        lines.push(format!("#$ -pe {} {}", config.parallel_env, config.cores()));
+        lines.push(format!("#$ -l h_rt={}", hms(&config.time_limit)));
+        if let Some(mib) = memory_mib(&config.mem_per_cpu) {
+            lines.push(format!("#$ -l h_vmem={mib}M"));
+        }
+        lines.push(format!(
+            "#$ -o {}",
+            log_path(workdir, &config.job_name, "out")
+        ));
+        lines.push(format!(
+            "#$ -e {}",
+            log_path(workdir, &config.job_name, "err")
+        ));
+        lines.join("\n") + "\n"
+    }
+
+    fn submit(&self, runner: &dyn CommandRunner, script: &Path, _workdir: &Path) -> Result<String> {
+        let stdout = runner.run("qsub", &[script.display().to_string()])?;
+        require_job_id(self, "qsub", &stdout)
+    }
+
+    /// 读取 `-pe <环境> N` 与 `-l h_rt=...`（`[[HH:]MM:]SS`）
+    fn script_resources(&self, script: &str) -> ScriptResources {
+        let mut resources = ScriptResources::default();
+        for args in directives(script, "#$") {
+            match args.as_slice() {
+                ["-pe", _, slots, ..] => resources.cores = slots.parse().ok(),
+                ["-l", list, ..] => {
+                    if let Some(h_rt) = list.split(',').find_map(|r| r.strip_prefix("h_rt=")) {
+                        resources.time_limit_sec = if h_rt.contains(':') {
+                            time_limit_seconds(h_rt)
+                        } else {
+                            h_rt.parse().ok()
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+        resources
+    }
+
+    /// 超出 `h_rt` 时的 `job ... exceeded hard wallclock time`
+    fn time_limit_exceeded(&self, log: &str) -> bool {
+        log.contains("exceeded hard wallclock time")
+    }
+
+    /// 解析 "Your job 123 (...)" 与数组作业的 "Your job-array 123.1-10:1 (...)"
+    fn parse_job_id(&self, stdout: &str) -> Option<String> {
+        stdout.lines().find_map(|line| {
+            let rest = line
+                .trim()
+                .strip_prefix("Your job-array ")
+                .or_else(|| line.trim().strip_prefix("Your job "))?;
+            let id = rest.split_whitespace().next()?;
+            let id = id.split('.').next()?;
+            id.chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| id.to_string())
+        })
+    }
+
+    fn query_queue(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>> {
+        let mut args = vec!["-r".to_string()];
+        if let Some(user) = user {
+            args.push("-u".to_string());
+            args.push(user.to_string());
+        }
+
+        Ok(parse_qstat(&runner.run("qstat", &args)?))
+    }
+}
+
+/// 解析 `qstat -r`：作业行给出 ID、截断的作业名与状态，其后的 "Full jobname:" 行给出完整作业名
+fn parse_qstat(output: &str) -> Vec<SchedulerJob> {
+    let mut jobs: Vec<SchedulerJob> = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix("Full jobname:") {
+            if let Some(job) = jobs.last_mut() {
+                job.name = name.trim().to_string();
+            }
+            continue;
+        }
+
+        let fields: Vec<&str> = trimmed.split_whitespace().collect();
+        let [id, _prior, name, _user, state, ..] = fields.as_slice() else {
+            continue;
+        };
+        if !id.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        jobs.push(SchedulerJob {
+            id: id.to_string(),
+            name: name.to_string(),
+            state: normalize_state(state).to_string(),
+        });
+    }
+    jobs
+}
+
+/// SGE 组合状态码转换为 Slurm 状态名：`E` 错误与 `d` 删除优先，其次是挂起与运行
+fn normalize_state(state: &str) -> &'static str {
+    if state.contains('E') {
+        "FAILED"
+    } else if state.contains('d') {
+        "CANCELLED"
+    } else if state.contains(['s', 'S', 'T']) {
+        "SUSPENDED"
+    } else if state.contains(['r', 't']) {
+        "RUNNING"
+    } else if state.contains(['q', 'h', 'w']) {
+        "PENDING"
+    } else {
+        "UNKNOWN"
+    }
+}
@@ -1,110 +1,255 @@
-//! # Slurm 脚本生成工具
+//! # Slurm 调度器后端
 //!
-//! 生成 sbatch 提交脚本。
+//! 实现 `Scheduler`：生成 `#SBATCH` 脚本头与 sacct 计时尾，通过 `sbatch` 提交，
+//! 并通过 `squeue`/`sacct` 查询作业队列状态；另提供数组作业脚本与任务映射文件的生成，以及 CASTEP 外压块的写入。
 //!
 //! ## 依赖关系
-//! - 被 `commands/submit.rs` 使用
-//! - 无外部模块依赖
+//! - 实现 `utils/scheduler.rs` 的 `Scheduler`
+//! - 数组作业生成与 `upsert_external_pressure_block` 被 `commands/submit.rs` 使用
+//! - 使用 `utils/command.rs` 执行调度器命令
 
+use crate::error::Result;
+use crate::utils::command::CommandRunner;
+use crate::utils::scheduler::{
+    absolute_path, directives, require_job_id, script_environment, time_limit_seconds, JobConfig,
+    Scheduler, SchedulerJob, ScriptResources,
+};
+
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 
-/// Slurm 作业配置
-pub struct SlurmConfig {
-    pub job_name: String,
-    pub partition: String,
-    pub constraint: String,
-    pub nodes: u32,
-    pub ntasks: u32,
-    pub cpus_per_task: u32,
-    pub mem_per_cpu: String,
-    pub time_limit: String,
-    pub modules: Vec<String>,
+/// 某一 DFT 代码的数组作业脚本文件名（位于作业根目录），如 `submit_array_vasp.sbatch`
+pub fn array_script_name(dft: &str) -> String {
+    format!("submit_array_{dft}.sbatch")
 }
 
-impl Default for SlurmConfig {
-    fn default() -> Self {
-        SlurmConfig {
-            job_name: "job".to_string(),
-            partition: "arm".to_string(),
-            constraint: "neoverse_v2".to_string(),
-            nodes: 1,
-            ntasks: 32,
-            cpus_per_task: 1,
-            mem_per_cpu: "3G".to_string(),
-            time_limit: "24:00:00".to_string(),
-            modules: vec![],
+/// 数组任务号到作业目录的映射文件名（位于作业根目录）
+pub const ARRAY_MAP_FILE: &str = "array_map.tsv";
+
+/// Slurm 后端
+pub struct Slurm;
+
+impl Scheduler for Slurm {
+    fn name(&self) -> &'static str {
+        "Slurm"
+    }
+
+    fn script_name(&self) -> &'static str {
+        "submit.sbatch"
+    }
+
+    fn script_header(&self, config: &JobConfig, _workdir: &Path) -> String {
+        sbatch_header(config, "%x")
+    }
+
+    fn script_footer(&self) -> String {
+        r#"
+echo "Timings:"
+sacct -o JobID,Submit,Start,End,CPUTime,State -j $SLURM_JOBID
+echo "Resources:"
+sacct -o JobID,JobName,Partition,ReqMem,MaxRSS,MaxVMSize -j $SLURM_JOBID
+"#
+        .to_string()
+    }
+
+    fn submit(&self, runner: &dyn CommandRunner, script: &Path, workdir: &Path) -> Result<String> {
+        let args = vec![
+            format!("--chdir={}", workdir.display()),
+            script.display().to_string(),
+        ];
+        let stdout = runner.run("sbatch", &args)?;
+        require_job_id(self, "sbatch", &stdout)
+    }
+
+    /// 读取 `-n/--ntasks`、`-c/--cpus-per-task` 与 `-t/--time`（空格或 `=` 分隔）
+    fn script_resources(&self, script: &str) -> ScriptResources {
+        let (mut ntasks, mut cpus_per_task, mut time_limit_sec) = (None, None, None);
+        for args in directives(script, "#SBATCH") {
+            let mut args = args.into_iter();
+            let Some(flag) = args.next() else {
+                continue;
+            };
+            let (flag, value) = match flag.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (flag, args.next()),
+            };
+            let Some(value) = value.map(|value| value.trim_matches('"')) else {
+                continue;
+            };
+            match flag {
+                "-n" | "--ntasks" => ntasks = value.parse::<usize>().ok(),
+                "-c" | "--cpus-per-task" => cpus_per_task = value.parse::<usize>().ok(),
+                "-t" | "--time" => time_limit_sec = time_limit_seconds(value),
+                _ => {}
+            }
+        }
+
+        ScriptResources {
+            cores: ntasks.map(|ntasks| ntasks * cpus_per_task.unwrap_or(1)),
+            time_limit_sec,
+        }
+    }
+
+    /// `slurmstepd: error: *** JOB 123 ON node01 CANCELLED AT ... DUE TO TIME LIMIT ***`
+    fn time_limit_exceeded(&self, log: &str) -> bool {
+        log.to_ascii_lowercase().contains("due to time limit")
+    }
+
+    /// 解析 "Submitted batch job 123"
+    fn parse_job_id(&self, stdout: &str) -> Option<String> {
+        stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Submitted batch job"))
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(str::to_string)
+    }
+
+    fn query_queue(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+    ) -> Result<Vec<SchedulerJob>> {
+        let mut args = vec!["--noheader".to_string(), "--format=%i|%j|%T".to_string()];
+        if let Some(user) = user {
+            args.push("-u".to_string());
+            args.push(user.to_string());
+        }
+
+        Ok(parse_job_table(&runner.run("squeue", &args)?))
+    }
+
+    fn query_history(
+        &self,
+        runner: &dyn CommandRunner,
+        user: Option<&str>,
+        since: &str,
+    ) -> Result<Vec<SchedulerJob>> {
+        let mut args = vec![
+            "--noheader".to_string(),
+            "--parsable2".to_string(),
+            "--format=JobID,JobName,State".to_string(),
+            "--starttime".to_string(),
+            since.to_string(),
+        ];
+        if let Some(user) = user {
+            args.push("-u".to_string());
+            args.push(user.to_string());
         }
+
+        Ok(parse_job_table(&runner.run("sacct", &args)?))
     }
 }
 
-/// 生成 sbatch 脚本内容
-pub fn generate_sbatch_script(config: &SlurmConfig, workdir: &Path, exec_cmd: &str) -> String {
-    let workdir = absolute_workdir(workdir);
-    let module_loads = config
-        .modules
-        .iter()
-        .map(|m| format!("module load {}", m))
-        .collect::<Vec<_>>()
-        .join("\n");
+/// `#SBATCH` 指令；日志写入提交目录下的 `slurm_logs/<log_stem>.out/.err`，空的分区、约束与内存不写出
+fn sbatch_header(config: &JobConfig, log_stem: &str) -> String {
+    let mut lines = Vec::new();
+    if !config.constraint.is_empty() {
+        lines.push(format!("#SBATCH --constraint \"{}\"", config.constraint));
+    }
+    if !config.partition.is_empty() {
+        lines.push(format!("#SBATCH --partition {}", config.partition));
+    }
+    lines.push("#SBATCH --switches=1".to_string());
+    lines.push(format!("#SBATCH --nodes={}", config.nodes));
+    if !config.mem_per_cpu.is_empty() {
+        lines.push(format!("#SBATCH --mem-per-cpu {}", config.mem_per_cpu));
+    }
+    lines.push(format!("#SBATCH --time {}", config.time_limit));
+    lines.push(format!("#SBATCH -c {}", config.cpus_per_task));
+    lines.push(format!("#SBATCH -n {}", config.ntasks));
+    lines.push(format!("#SBATCH -J {}", config.job_name));
+    lines.push(format!("#SBATCH -o slurm_logs/{log_stem}.out"));
+    lines.push(format!("#SBATCH -e slurm_logs/{log_stem}.err"));
+    lines.join("\n") + "\n"
+}
+
+/// 数组作业中的一个任务
+pub struct ArrayTask {
+    pub structure: String,
+    /// 作业目录（写入映射文件前转换为绝对路径）
+    pub job_dir: PathBuf,
+    pub exec_cmd: String,
+}
+
+/// 数组任务号 → 作业目录的映射文件内容（TSV，任务号从 1 开始，多个数组共用同一编号）
+pub fn array_map<'a>(tasks: impl IntoIterator<Item = &'a ArrayTask>) -> String {
+    let mut content = String::from("task\tstructure\tjob_dir\tcommand\n");
+    for (i, task) in tasks.into_iter().enumerate() {
+        content.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            i + 1,
+            task.structure,
+            absolute_path(&task.job_dir).display(),
+            task.exec_cmd
+        ));
+    }
+    content
+}
+
+/// 生成覆盖映射文件中 `tasks` 编号的数组作业脚本：每个任务从映射文件中取出自己的作业目录与命令，
+/// 在作业目录中运行并把输出写入该目录的 `slurm_logs/<结构名>.out/.err`；
+/// 调度器自身的消息（超时、内存不足等）写入作业根目录的 `slurm_logs/<作业ID>_<任务号>.out/.err`，
+/// 与作业目录记录的 `<作业ID>_<任务号>` 同名，见 `scheduler::array_task_log`
+pub fn generate_array_script(
+    config: &JobConfig,
+    workdir: &Path,
+    tasks: RangeInclusive<usize>,
+    throttle: Option<u32>,
+) -> String {
+    let workdir = absolute_path(workdir);
+    let throttle = throttle.map(|n| format!("%{n}")).unwrap_or_default();
 
     format!(
         r#"#!/bin/bash
-#SBATCH --constraint "{}"
-#SBATCH --partition {}
-#SBATCH --switches=1
-#SBATCH --nodes={}
-#SBATCH --mem-per-cpu {}
-#SBATCH --time {}
-#SBATCH -c {}
-#SBATCH -n {}
-#SBATCH -J {}
-#SBATCH -o slurm_logs/%x.out
-#SBATCH -e slurm_logs/%x.err
-
-set -euo pipefail
-
-if ! ulimit -s unlimited; then
-    echo "Warning: failed to set stack size to unlimited" >&2
-fi
+{}#SBATCH --array={}-{}{}
 
-export MODULEPATH="/home/changjiangwu_umass_edu/Modulefiles:$MODULEPATH"
-module purge 2>&1
 {}
-echo "Loaded modules"
+MAP="{}"
+TASK_LINE=$(awk -F'\t' -v id="$SLURM_ARRAY_TASK_ID" '$1 == id' "$MAP")
+if [ -z "$TASK_LINE" ]; then
+    echo "No entry for array task $SLURM_ARRAY_TASK_ID in $MAP" >&2
+    exit 1
+fi
+IFS=$'\t' read -r TASK_ID STRUCTURE JOB_DIR EXEC_CMD <<< "$TASK_LINE"
 
-cd "{}"
+cd "$JOB_DIR"
+exec > "slurm_logs/$STRUCTURE.out" 2> "slurm_logs/$STRUCTURE.err"
+echo "Array task $TASK_ID: $STRUCTURE"
 echo "PWD=$(pwd)"
-echo "Running: {}"
-{}
-
-echo "Timings:"
-sacct -o JobID,Submit,Start,End,CPUTime,State -j $SLURM_JOBID
-echo "Resources:"
-sacct -o JobID,JobName,Partition,ReqMem,MaxRSS,MaxVMSize -j $SLURM_JOBID
-"#,
-        config.constraint,
-        config.partition,
-        config.nodes,
-        config.mem_per_cpu,
-        config.time_limit,
-        config.cpus_per_task,
-        config.ntasks,
-        config.job_name,
-        module_loads,
-        workdir.display(),
-        exec_cmd,
-        exec_cmd,
+echo "Running: $EXEC_CMD"
+eval "$EXEC_CMD"
+{}"#,
+        sbatch_header(config, "%A_%a"),
+        tasks.start(),
+        tasks.end(),
+        throttle,
+        script_environment(config),
+        workdir.join(ARRAY_MAP_FILE).display(),
+        Slurm.script_footer(),
     )
 }
 
-fn absolute_workdir(workdir: &Path) -> PathBuf {
-    if workdir.is_absolute() {
-        return workdir.to_path_buf();
-    }
+/// 解析 `ID|NAME|STATE` 形式的输出；跳过作业步（`123.batch`），状态只保留首个单词
+fn parse_job_table(output: &str) -> Vec<SchedulerJob> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(3, '|');
+            let id = fields.next()?.trim();
+            let name = fields.next()?.trim();
+            let state = fields.next()?.split_whitespace().next()?;
 
-    std::env::current_dir()
-        .map(|cwd| cwd.join(workdir))
-        .unwrap_or_else(|_| workdir.to_path_buf())
+            if id.is_empty() || id.contains('.') {
+                return None;
+            }
+
+            Some(SchedulerJob {
+                id: id.to_string(),
+                name: name.to_string(),
+                state: state.trim_end_matches('+').to_ascii_uppercase(),
+            })
+        })
+        .collect()
 }
 
 /// 插入或替换 CASTEP .cell 文件中的 EXTERNAL_PRESSURE 块
@@ -133,10 +278,13 @@ GPa
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::scheduler::generate_job_script;
+    use crate::utils::template::ScriptTemplate;
+    use std::collections::BTreeMap;
 
     #[test]
-    fn generate_sbatch_script_uses_absolute_workdir() {
-        let config = SlurmConfig::default();
+    fn generate_job_script_uses_absolute_workdir() {
+        let config = JobConfig::default();
         let relative = Path::new("jobs/test-job");
         let expected = std::env::current_dir()
             .expect("current dir")
@@ -144,17 +292,97 @@ mod tests {
             .display()
             .to_string();
 
-        let script = generate_sbatch_script(&config, relative, "echo hello");
+        let script = generate_job_script(
+            &Slurm,
+            &ScriptTemplate::default(),
+            &config,
+            relative,
+            "echo hello",
+            &BTreeMap::new(),
+        )
+        .expect("render");
 
         assert!(script.contains(&format!("cd \"{}\"", expected)));
     }
 
     #[test]
-    fn generate_sbatch_script_sets_unlimited_stack_size() {
-        let config = SlurmConfig::default();
+    fn generate_job_script_sets_unlimited_stack_size() {
+        let config = JobConfig::default();
 
-        let script = generate_sbatch_script(&config, Path::new("jobs/test-job"), "echo hello");
+        let script = generate_job_script(
+            &Slurm,
+            &ScriptTemplate::default(),
+            &config,
+            Path::new("jobs/test-job"),
+            "echo hello",
+            &BTreeMap::new(),
+        )
+        .expect("render");
 
         assert!(script.contains("ulimit -s unlimited"));
     }
+
+    #[test]
+    fn parse_job_id_reads_submission_line() {
+        assert_eq!(
+            Slurm.parse_job_id("Submitted batch job 4242\n"),
+            Some("4242".to_string())
+        );
+        assert_eq!(Slurm.parse_job_id("sbatch: error"), None);
+    }
+
+    #[test]
+    fn parse_job_table_skips_steps_and_normalizes_state() {
+        let output = "\
+101|alpha|RUNNING
+102|beta|CANCELLED by 1234
+102.batch|batch|CANCELLED
+103_2|gamma|TIMEOUT
+";
+
+        let jobs = parse_job_table(output);
+
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[1].state, "CANCELLED");
+        assert_eq!(jobs[2].id, "103_2");
+        assert_eq!(jobs[2].state, "TIMEOUT");
+    }
+
+    #[test]
+    fn array_script_reads_its_task_from_the_map() {
+        let tasks = vec![
+            ArrayTask {
+                structure: "Si8".to_string(),
+                job_dir: PathBuf::from("/scratch/jobs/Si8"),
+                exec_cmd: "mpirun -np 32 castep.mpi \"Si8\"".to_string(),
+            },
+            ArrayTask {
+                structure: "Ge8".to_string(),
+                job_dir: PathBuf::from("/scratch/jobs/Ge8"),
+                exec_cmd: "mpirun -np 32 vasp_std".to_string(),
+            },
+        ];
+
+        let map = array_map(&tasks);
+        let lines: Vec<&str> = map.lines().collect();
+        assert_eq!(lines[0], "task\tstructure\tjob_dir\tcommand");
+        assert_eq!(
+            lines[2],
+            "2\tGe8\t/scratch/jobs/Ge8\tmpirun -np 32 vasp_std"
+        );
+
+        let script = generate_array_script(
+            &JobConfig::default(),
+            Path::new("/scratch/jobs"),
+            1..=2,
+            Some(20),
+        );
+        assert!(script.contains("#SBATCH --array=1-2%20\n"));
+        assert!(script.contains("#SBATCH -o slurm_logs/%A_%a.out"));
+        assert!(script.contains("MAP=\"/scratch/jobs/array_map.tsv\""));
+        assert!(script.contains("exec > \"slurm_logs/$STRUCTURE.out\""));
+        let unthrottled =
+            generate_array_script(&JobConfig::default(), Path::new("/x"), 3..=5, None);
+        assert!(unthrottled.contains("#SBATCH --array=3-5\n"));
+    }
 }
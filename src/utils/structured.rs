@@ -0,0 +1,135 @@
+//! # 结构化输出
+//!
+//! 全局 `--output-format json|jsonl|csv` 的实现：支持的命令把逐条记录（扁平结构，字段名即稳定模式）
+//! 写到 stdout，JSON 为对象数组，JSONL 每行一个对象，CSV 首行为字段名；
+//! 此时提示信息与表格改写到 stderr，stdout 只保留数据。默认 `table` 时不输出记录。
+//!
+//! ## 依赖关系
+//! - 由 `main.rs` 按命令行参数初始化，被 `commands/` 中支持结构化输出的命令调用
+//! - 使用 `utils/output.rs` 切换提示信息的输出流
+//! - 使用 `serde`、`serde_json` 与 `csv`
+
+use crate::error::{QutilityError, Result};
+use crate::utils::output;
+
+use serde::Serialize;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+/// 机器可读记录格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Json,
+    Jsonl,
+    Csv,
+}
+
+static FORMAT: OnceLock<RecordFormat> = OnceLock::new();
+
+/// 设置进程级记录格式；`None`（表格输出）时保持默认行为
+pub fn init(format: Option<RecordFormat>) {
+    if let Some(format) = format {
+        let _ = FORMAT.set(format);
+        output::redirect_to_stderr();
+    }
+}
+
+/// 当前记录格式；表格输出时为 `None`
+pub fn format() -> Option<RecordFormat> {
+    FORMAT.get().copied()
+}
+
+/// 结构化输出模式下把记录写到 stdout；表格输出时不做任何事
+pub fn emit<T: Serialize>(records: &[T]) -> Result<()> {
+    let Some(format) = format() else {
+        return Ok(());
+    };
+
+    let stdout = io::stdout();
+    write_records(stdout.lock(), format, records)
+}
+
+/// 按格式写出记录
+pub fn write_records<T: Serialize>(
+    mut writer: impl Write,
+    format: RecordFormat,
+    records: &[T],
+) -> Result<()> {
+    let write_error = |e| QutilityError::FileWriteError {
+        path: "<stdout>".to_string(),
+        source: e,
+    };
+    let json_error = |e: serde_json::Error| QutilityError::Other(format!("JSON output: {e}"));
+
+    match format {
+        RecordFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, records).map_err(json_error)?;
+            writeln!(writer).map_err(write_error)?;
+        }
+        RecordFormat::Jsonl => {
+            for record in records {
+                serde_json::to_writer(&mut writer, record).map_err(json_error)?;
+                writeln!(writer).map_err(write_error)?;
+            }
+        }
+        RecordFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(&mut writer);
+            for record in records {
+                csv_writer
+                    .serialize(record)
+                    .map_err(QutilityError::CsvError)?;
+            }
+            csv_writer.flush().map_err(write_error)?;
+        }
+    }
+
+    writer.flush().map_err(write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        structure: &'static str,
+        enthalpy_ev: Option<f64>,
+        converged: bool,
+    }
+
+    #[test]
+    fn writes_json_jsonl_and_csv_with_same_fields() {
+        let rows = [
+            Row {
+                structure: "alpha",
+                enthalpy_ev: Some(-1.5),
+                converged: true,
+            },
+            Row {
+                structure: "beta",
+                enthalpy_ev: None,
+                converged: false,
+            },
+        ];
+        let render = |format| {
+            let mut buffer = Vec::new();
+            write_records(&mut buffer, format, &rows).expect("write records");
+            String::from_utf8(buffer).expect("utf-8")
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(RecordFormat::Json)).expect("parse json");
+        assert_eq!(json[0]["structure"], "alpha");
+        assert!(json[1]["enthalpy_ev"].is_null());
+
+        assert_eq!(
+            render(RecordFormat::Jsonl),
+            "{\"structure\":\"alpha\",\"enthalpy_ev\":-1.5,\"converged\":true}\n\
+             {\"structure\":\"beta\",\"enthalpy_ev\":null,\"converged\":false}\n"
+        );
+        assert_eq!(
+            render(RecordFormat::Csv),
+            "structure,enthalpy_ev,converged\nalpha,-1.5,true\nbeta,,false\n"
+        );
+    }
+}
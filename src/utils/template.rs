@@ -0,0 +1,188 @@
+//! # 作业脚本模板
+//!
+//! 极简模板引擎：`{{ name }}` 占位符替换为变量值，其余文本（包括 shell 的 `${VAR}`、awk 的 `{ print }` 等单花括号）原样保留。
+//! 解析时记录每个占位符所在行；未知占位符一次性全部列出，并给出可用的占位符名。
+//! 内置默认模板 `job_script.tmpl` 即原先固定的作业脚本布局。
+//!
+//! ## 依赖关系
+//! - 被 `utils/scheduler.rs` 用于渲染作业脚本，被 `commands/submit.rs` 用于加载 `--script-template`
+//! - 使用 `error.rs`
+
+use crate::error::{QutilityError, Result};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 内置作业脚本模板
+pub const DEFAULT_JOB_TEMPLATE: &str = include_str!("job_script.tmpl");
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder { name: String, line: usize },
+}
+
+/// 已解析的作业脚本模板
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptTemplate {
+    /// 模板来源（文件路径或 "built-in job template"），用于错误信息
+    source: String,
+    parts: Vec<Part>,
+}
+
+impl ScriptTemplate {
+    /// 解析模板文本；占位符名只能由字母、数字与下划线组成，`{{` 未闭合时报错
+    pub fn parse(text: &str, source: &str) -> Result<Self> {
+        let invalid = |line: usize, reason: String| QutilityError::ParseError {
+            format: "job script template".to_string(),
+            path: source.to_string(),
+            reason: format!("line {line}: {reason}"),
+        };
+
+        let mut parts = Vec::new();
+        let mut rest = text;
+        let mut line = 1;
+        while let Some(start) = rest.find("{{") {
+            let (literal, tail) = rest.split_at(start);
+            line += literal.matches('\n').count();
+            if !literal.is_empty() {
+                parts.push(Part::Text(literal.to_string()));
+            }
+
+            let tail = &tail[2..];
+            let end = tail
+                .find("}}")
+                .filter(|&end| !tail[..end].contains('\n'))
+                .ok_or_else(|| invalid(line, "unclosed '{{'".to_string()))?;
+            let name = tail[..end].trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(invalid(
+                    line,
+                    format!("invalid placeholder '{{{{{}}}}}'", &tail[..end]),
+                ));
+            }
+            parts.push(Part::Placeholder {
+                name: name.to_string(),
+                line,
+            });
+            rest = &tail[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            parts,
+        })
+    }
+
+    /// 读取并解析模板文件
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| QutilityError::FileReadError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        Self::parse(&text, &path.display().to_string())
+    }
+
+    /// 模板中出现的占位符名（按出现顺序，可能重复）
+    pub fn placeholders(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Placeholder { name, .. } => Some(name.as_str()),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// 检查所有占位符都在 `known` 中；否则报错并列出未知占位符的行号与可用占位符
+    pub fn check<'a>(&self, known: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let mut known: Vec<&str> = known.into_iter().collect();
+        known.sort_unstable();
+        known.dedup();
+
+        let unknown: Vec<String> = self
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Placeholder { name, line }
+                    if known.binary_search(&name.as_str()).is_err() =>
+                {
+                    Some(format!("{{{{{name}}}}} (line {line})"))
+                }
+                _ => None,
+            })
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        Err(QutilityError::InvalidArgument(format!(
+            "Unknown placeholder{} in {}: {}. Available placeholders: {}",
+            if unknown.len() == 1 { "" } else { "s" },
+            self.source,
+            unknown.join(", "),
+            known.join(", ")
+        )))
+    }
+
+    /// 用 `variables` 替换占位符
+    pub fn render(&self, variables: &BTreeMap<&str, String>) -> Result<String> {
+        self.check(variables.keys().copied())?;
+
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => rendered.push_str(text),
+                Part::Placeholder { name, .. } => rendered.push_str(&variables[name.as_str()]),
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+impl Default for ScriptTemplate {
+    /// 内置默认模板
+    fn default() -> Self {
+        Self::parse(DEFAULT_JOB_TEMPLATE, "built-in job template")
+            .expect("built-in job template is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders_and_reports_unknown_ones() {
+        let template = ScriptTemplate::parse(
+            "#!/bin/bash\nexport OMP_NUM_THREADS=${OMP:-1}\ncd {{ workdir }}\nawk '{ print $1 }' {{seed}}.castep\n{{srun_opts}} {{exec}}\n",
+            "custom.tmpl",
+        )
+        .expect("parse");
+
+        let mut variables = BTreeMap::new();
+        variables.insert("workdir", "/scratch/Si8".to_string());
+        variables.insert("seed", "Si8".to_string());
+        let err = template
+            .render(&variables)
+            .expect_err("unknown placeholders");
+        let message = err.to_string();
+        assert!(message.contains("custom.tmpl"));
+        assert!(message.contains("{{srun_opts}} (line 5), {{exec}} (line 5)"));
+        assert!(message.contains("Available placeholders: seed, workdir"));
+
+        variables.insert("srun_opts", "srun --cpu-bind=cores".to_string());
+        variables.insert("exec", "castep.mpi Si8".to_string());
+        let script = template.render(&variables).expect("render");
+        assert!(script.contains("export OMP_NUM_THREADS=${OMP:-1}\ncd /scratch/Si8\n"));
+        assert!(script.contains("awk '{ print $1 }' Si8.castep\n"));
+        assert!(script.ends_with("srun --cpu-bind=cores castep.mpi Si8\n"));
+
+        assert!(ScriptTemplate::parse("a\n{{ job_name\n}}", "bad.tmpl")
+            .expect_err("unclosed")
+            .to_string()
+            .contains("line 2: unclosed '{{'"));
+        assert!(ScriptTemplate::parse("{{job-name}}", "bad.tmpl").is_err());
+    }
+}
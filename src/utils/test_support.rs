@@ -0,0 +1,25 @@
+//! # 测试辅助
+//!
+//! 单元测试共用的临时目录命名：进程号、进程内计数与纳秒时间戳共同保证并行测试之间互不冲突。
+//!
+//! ## 依赖关系
+//! - 仅在 `cfg(test)` 下编译，被各模块的 `#[cfg(test)] mod tests` 使用
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// 系统临时目录下唯一的测试目录路径（不创建目录）
+pub fn unique_test_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "qutility-{name}-{}-{count}-{nanos}",
+        std::process::id()
+    ))
+}
@@ -15,11 +15,11 @@
 //!
 //! ## 依赖关系
 //! - 被 `commands/analyze/xrd.rs` 调用
-//! - 使用 `models/structure.rs` 的 Crystal, Lattice 结构
+//! - 使用 `models/structure.rs` 的 Crystal, Lattice 结构（倒格矢见 `Lattice::reciprocal`）
 //! - 使用 `xrd/scattering.rs` 获取原子散射因子
 
 use crate::error::{QutilityError, Result};
-use crate::models::{Crystal, Lattice};
+use crate::models::Crystal;
 use crate::xrd::scattering;
 
 use std::f64::consts::PI;
@@ -77,7 +77,7 @@ impl XrdCalculator {
         }
 
         // 计算倒格矢矩阵
-        let recip_lattice = self.reciprocal_lattice(&crystal.lattice);
+        let recip_lattice = crystal.lattice.reciprocal();
 
         // 计算限制球半径: |G| <= 2/λ 对应 2θ = 180°
         // 对于给定的 2θ_max，有 sin(θ_max) = λ|G|/2
@@ -178,48 +178,6 @@ impl XrdCalculator {
         })
     }
 
-    /// 计算倒格矢矩阵
-    fn reciprocal_lattice(&self, lattice: &Lattice) -> [[f64; 3]; 3] {
-        let m = lattice.matrix;
-
-        // a, b, c 向量
-        let a = [m[0][0], m[0][1], m[0][2]];
-        let b = [m[1][0], m[1][1], m[1][2]];
-        let c = [m[2][0], m[2][1], m[2][2]];
-
-        // 体积 V = a · (b × c)
-        let b_cross_c = cross(&b, &c);
-        let volume = dot(&a, &b_cross_c);
-
-        if volume.abs() < 1e-10 {
-            return [[0.0; 3]; 3];
-        }
-
-        // 倒格矢：b1 = 2π(b×c)/V, b2 = 2π(c×a)/V, b3 = 2π(a×b)/V
-        let c_cross_a = cross(&c, &a);
-        let a_cross_b = cross(&a, &b);
-
-        let factor = 2.0 * PI / volume;
-
-        [
-            [
-                b_cross_c[0] * factor,
-                b_cross_c[1] * factor,
-                b_cross_c[2] * factor,
-            ],
-            [
-                c_cross_a[0] * factor,
-                c_cross_a[1] * factor,
-                c_cross_a[2] * factor,
-            ],
-            [
-                a_cross_b[0] * factor,
-                a_cross_b[1] * factor,
-                a_cross_b[2] * factor,
-            ],
-        ]
-    }
-
     /// 计算倒格矢 G
     fn calculate_g(&self, recip: &[[f64; 3]; 3], h: i32, k: i32, l: i32) -> [f64; 3] {
         let hf = h as f64;
@@ -308,20 +266,6 @@ impl XrdCalculator {
     }
 }
 
-/// 向量叉积
-fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
-    [
-        a[1] * b[2] - a[2] * b[1],
-        a[2] * b[0] - a[0] * b[2],
-        a[0] * b[1] - a[1] * b[0],
-    ]
-}
-
-/// 向量点积
-fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
-    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
-}
-
 /// 分数坐标转笛卡尔坐标
 fn frac_to_cart(frac: &[f64; 3], matrix: &[[f64; 3]; 3]) -> [f64; 3] {
     [
@@ -334,7 +278,7 @@ fn frac_to_cart(frac: &[f64; 3], matrix: &[[f64; 3]; 3]) -> [f64; 3] {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Atom;
+    use crate::models::{Atom, Lattice};
 
     #[test]
     fn test_xrd_nacl() {